@@ -14,8 +14,11 @@ use slashing_protection::{SlashingDatabase, SLASHING_PROTECTION_FILENAME};
 use std::ffi::OsStr;
 use std::fs;
 use std::path::{Path, PathBuf};
-use types::EthSpec;
-use validator_dir::Builder as ValidatorDirBuilder;
+use types::{ChainSpec, EthSpec};
+use validator_dir::{
+    write_standard_deposit_data_json, Builder as ValidatorDirBuilder, StandardDepositDataJson,
+    ValidatorDir, DEPOSIT_DATA_JSON_PREFIX,
+};
 
 pub const CMD: &str = "create";
 pub const WALLET_NAME_FLAG: &str = "wallet-name";
@@ -192,6 +195,8 @@ pub fn cli_run<T: EthSpec>(
             )
         })?;
 
+    let mut deposits = Vec::with_capacity(n);
+
     for i in 0..n {
         let voting_password = random_password();
         let withdrawal_password = random_password();
@@ -221,7 +226,7 @@ pub fn cli_run<T: EthSpec>(
                 )
             })?;
 
-        ValidatorDirBuilder::new(validator_dir.clone())
+        let new_validator_dir = ValidatorDirBuilder::new(validator_dir.clone())
             .password_dir(secrets_dir.clone())
             .voting_keystore(keystores.voting, voting_password.as_bytes())
             .withdrawal_keystore(keystores.withdrawal, withdrawal_password.as_bytes())
@@ -230,19 +235,53 @@ pub fn cli_run<T: EthSpec>(
             .build()
             .map_err(|e| format!("Unable to build validator directory: {:?}", e))?;
 
+        deposits.push(standard_deposit_data_json(&new_validator_dir, &spec)?);
+
         println!("{}/{}\t{}", i + 1, n, voting_pubkey.to_hex_string());
 
         println!("{}", MAINNET_WARNING);
     }
 
+    write_deposit_data_json(&validator_dir, &deposits)?;
+
+    Ok(())
+}
+
+/// Returns the launchpad-compatible deposit data for a freshly built `validator_dir`.
+pub fn standard_deposit_data_json(
+    validator_dir: &ValidatorDir,
+    spec: &ChainSpec,
+) -> Result<StandardDepositDataJson, String> {
+    let eth1_deposit_data = validator_dir
+        .eth1_deposit_data()
+        .map_err(|e| format!("Unable to read eth1 deposit data: {:?}", e))?
+        .ok_or_else(|| "Validator directory is missing eth1 deposit data".to_string())?;
+
+    Ok(StandardDepositDataJson::new(
+        &eth1_deposit_data.deposit_data,
+        spec,
+    ))
+}
+
+/// Writes `deposits` to a `deposit_data-<timestamp>.json` file in `validator_dir`, for use with
+/// the Eth2 launchpad.
+pub fn write_deposit_data_json(
+    validator_dir: &Path,
+    deposits: &[StandardDepositDataJson],
+) -> Result<(), String> {
+    let path = write_standard_deposit_data_json(validator_dir, deposits)
+        .map_err(|e| format!("Unable to write deposit data JSON: {:?}", e))?;
+
+    eprintln!("deposit data JSON path: {:?}", path);
+
     Ok(())
 }
 
 /// Returns the number of validators that exist in the given `validator_dir`.
 ///
-/// This function just assumes all files and directories, excluding the validator definitions YAML
-/// and slashing protection database are validator directories, making it likely to return a higher
-/// number than accurate but never a lower one.
+/// This function just assumes all files and directories, excluding the validator definitions YAML,
+/// slashing protection database and deposit data JSON files are validator directories, making it
+/// likely to return a higher number than accurate but never a lower one.
 fn existing_validator_count<P: AsRef<Path>>(validator_dir: P) -> Result<usize, String> {
     fs::read_dir(validator_dir.as_ref())
         .map(|iter| {
@@ -251,6 +290,10 @@ fn existing_validator_count<P: AsRef<Path>>(validator_dir: P) -> Result<usize, S
                     e.file_name() != OsStr::new(validator_definitions::CONFIG_FILENAME)
                         && e.file_name()
                             != OsStr::new(slashing_protection::SLASHING_PROTECTION_FILENAME)
+                        && !e
+                            .file_name()
+                            .to_string_lossy()
+                            .starts_with(DEPOSIT_DATA_JSON_PREFIX)
                 })
                 .count()
         })
@@ -10,14 +10,19 @@ use eth2_keystore::Keystore;
 use eth2_testnet_config::Eth2TestnetConfig;
 use safe_arith::SafeArith;
 use slot_clock::{SlotClock, SystemTimeSlotClock};
-use std::path::PathBuf;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
-use types::{ChainSpec, Epoch, EthSpec, Fork, VoluntaryExit};
+use types::{ChainSpec, Epoch, EthSpec, Fork, Hash256, SignedVoluntaryExit, VoluntaryExit};
 
 pub const CMD: &str = "exit";
 pub const KEYSTORE_FLAG: &str = "keystore";
 pub const PASSWORD_FILE_FLAG: &str = "password-file";
 pub const BEACON_SERVER_FLAG: &str = "beacon-node";
+pub const OFFLINE_FLAG: &str = "offline";
+pub const VALIDATOR_INDEX_FLAG: &str = "validator-index";
+pub const EPOCH_FLAG: &str = "epoch";
+pub const OUTPUT_PATH_FLAG: &str = "output-path";
 pub const PASSWORD_PROMPT: &str = "Enter the keystore password";
 
 pub const DEFAULT_BEACON_NODE: &str = "http://localhost:5052/";
@@ -27,7 +32,11 @@ pub const PROMPT: &str = "WARNING: WITHDRAWING STAKED ETH IS NOT CURRENTLY POSSI
 
 pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
     App::new("exit")
-        .about("Submits a VoluntaryExit to the beacon chain for a given validator keystore.")
+        .about(
+            "Submits a VoluntaryExit to the beacon chain for a given validator keystore. With \
+            --offline, signs the VoluntaryExit without contacting a beacon node and writes it to \
+            a file for later publication with the `publish-exit` command.",
+        )
         .arg(
             Arg::with_name(KEYSTORE_FLAG)
                 .long(KEYSTORE_FLAG)
@@ -51,6 +60,45 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .default_value(&DEFAULT_BEACON_NODE)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name(OFFLINE_FLAG)
+                .long(OFFLINE_FLAG)
+                .help(
+                    "If present, sign the exit without contacting a beacon node. The fork and \
+                    genesis validators root are read from the genesis state of the testnet.",
+                )
+                .requires_all(&[VALIDATOR_INDEX_FLAG, OUTPUT_PATH_FLAG]),
+        )
+        .arg(
+            Arg::with_name(VALIDATOR_INDEX_FLAG)
+                .long(VALIDATOR_INDEX_FLAG)
+                .value_name("VALIDATOR_INDEX")
+                .help("The index of the validator being exited. Only used with --offline.")
+                .requires(OFFLINE_FLAG)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(EPOCH_FLAG)
+                .long(EPOCH_FLAG)
+                .value_name("EPOCH")
+                .help(
+                    "The earliest epoch at which the exit may be included on chain. Defaults \
+                    to the current epoch. Only used with --offline.",
+                )
+                .requires(OFFLINE_FLAG)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(OUTPUT_PATH_FLAG)
+                .long(OUTPUT_PATH_FLAG)
+                .value_name("OUTPUT_PATH")
+                .help(
+                    "The path of the file to which the signed exit will be written. Must not \
+                    already exist. Only used with --offline.",
+                )
+                .requires(OFFLINE_FLAG)
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name(STDIN_INPUTS_FLAG)
                 .long(STDIN_INPUTS_FLAG)
@@ -65,6 +113,29 @@ pub fn cli_run<E: EthSpec>(matches: &ArgMatches, mut env: Environment<E>) -> Res
     let stdin_inputs = matches.is_present(STDIN_INPUTS_FLAG);
 
     let spec = env.eth2_config().spec.clone();
+
+    if matches.is_present(OFFLINE_FLAG) {
+        let validator_index: u64 = clap_utils::parse_required(matches, VALIDATOR_INDEX_FLAG)?;
+        let epoch: Option<Epoch> = clap_utils::parse_optional(matches, EPOCH_FLAG)?;
+        let output_path: PathBuf = clap_utils::parse_required(matches, OUTPUT_PATH_FLAG)?;
+
+        let testnet_config = env
+            .testnet
+            .as_ref()
+            .ok_or_else(|| "Unable to sign an offline exit without a testnet config")?;
+
+        return sign_offline_voluntary_exit::<E>(
+            &keystore_path,
+            password_file_path.as_ref(),
+            validator_index,
+            epoch,
+            &output_path,
+            &spec,
+            stdin_inputs,
+            testnet_config,
+        );
+    }
+
     let server_url: String = clap_utils::parse_required(matches, BEACON_SERVER_FLAG)?;
     let client = BeaconNodeHttpClient::new(
         Url::parse(&server_url)
@@ -74,7 +145,7 @@ pub fn cli_run<E: EthSpec>(matches: &ArgMatches, mut env: Environment<E>) -> Res
     let testnet_config = env
         .testnet
         .clone()
-        .expect("network should have a valid config");
+        .ok_or_else(|| "Unable to publish an exit without a testnet config")?;
 
     env.runtime().block_on(publish_voluntary_exit::<E>(
         &keystore_path,
@@ -97,11 +168,43 @@ async fn publish_voluntary_exit<E: EthSpec>(
     stdin_inputs: bool,
     testnet_config: &Eth2TestnetConfig,
 ) -> Result<(), String> {
+    let genesis_data = check_beacon_node::<E>(client, testnet_config).await?;
+
+    let keypair = load_voting_keypair(keystore_path, password_file_path, stdin_inputs)?;
+
+    let epoch = get_current_epoch::<E>(genesis_data.genesis_time, spec)
+        .ok_or_else(|| "Failed to get current epoch. Please check your system time".to_string())?;
+    let validator_index = get_validator_index_for_exit(client, &keypair.pk, epoch, spec).await?;
+
+    let fork = get_beacon_state_fork(client).await?;
+    let signed_voluntary_exit = sign_voluntary_exit(
+        &keypair,
+        validator_index,
+        epoch,
+        &fork,
+        genesis_data.genesis_validators_root,
+        spec,
+    );
+
+    confirm_and_publish_voluntary_exit(
+        client,
+        &signed_voluntary_exit,
+        &format!("validator {}", keypair.pk),
+        stdin_inputs,
+    )
+    .await
+}
+
+/// Checks that the beacon node is on the same network as `testnet_config` and is synced, returning
+/// its genesis data.
+pub(crate) async fn check_beacon_node<E: EthSpec>(
+    client: &BeaconNodeHttpClient,
+    testnet_config: &Eth2TestnetConfig,
+) -> Result<GenesisData, String> {
     let genesis_data = get_geneisis_data(client).await?;
     let testnet_genesis_root = testnet_config
         .beacon_state::<E>()
-        .as_ref()
-        .expect("network should have valid genesis state")
+        .map_err(|e| format!("Unable to load the genesis state of the testnet: {}", e))?
         .genesis_validators_root;
 
     // Verify that the beacon node and validator being exited are on the same network.
@@ -118,21 +221,20 @@ async fn publish_voluntary_exit<E: EthSpec>(
         return Err("Beacon node is still syncing".to_string());
     }
 
-    let keypair = load_voting_keypair(keystore_path, password_file_path, stdin_inputs)?;
-
-    let epoch = get_current_epoch::<E>(genesis_data.genesis_time, spec)
-        .ok_or_else(|| "Failed to get current epoch. Please check your system time".to_string())?;
-    let validator_index = get_validator_index_for_exit(client, &keypair.pk, epoch, spec).await?;
-
-    let fork = get_beacon_state_fork(client).await?;
-    let voluntary_exit = VoluntaryExit {
-        epoch,
-        validator_index,
-    };
+    Ok(genesis_data)
+}
 
+/// Asks the user to confirm the exit of `validator` (a description of the validator for display),
+/// then publishes `signed_voluntary_exit` if they do.
+pub(crate) async fn confirm_and_publish_voluntary_exit(
+    client: &BeaconNodeHttpClient,
+    signed_voluntary_exit: &SignedVoluntaryExit,
+    validator: &str,
+    stdin_inputs: bool,
+) -> Result<(), String> {
     eprintln!(
-        "Publishing a voluntary exit for validator: {} \n",
-        keypair.pk
+        "Publishing a voluntary exit for {} at epoch {}\n",
+        validator, signed_voluntary_exit.message.epoch
     );
     eprintln!("WARNING: THIS IS AN IRREVERSIBLE OPERATION\n");
     eprintln!("{}\n", PROMPT);
@@ -144,32 +246,114 @@ async fn publish_voluntary_exit<E: EthSpec>(
 
     let confirmation = account_utils::read_input_from_user(stdin_inputs)?;
     if confirmation == CONFIRMATION_PHRASE {
-        // Sign and publish the voluntary exit to network
-        let signed_voluntary_exit = voluntary_exit.sign(
-            &keypair.sk,
-            &fork,
-            genesis_data.genesis_validators_root,
-            spec,
-        );
         client
-            .post_beacon_pool_voluntary_exits(&signed_voluntary_exit)
+            .post_beacon_pool_voluntary_exits(signed_voluntary_exit)
             .await
             .map_err(|e| format!("Failed to publish voluntary exit: {}", e))?;
         tokio::time::delay_for(std::time::Duration::from_secs(1)).await; // Provides nicer UX.
         eprintln!(
-            "Successfully validated and published voluntary exit for validator {}",
-            keypair.pk
+            "Successfully validated and published voluntary exit for {}",
+            validator
         );
     } else {
         eprintln!(
-            "Did not publish voluntary exit for validator {}. Please check that you entered the correct exit phrase.",
-            keypair.pk
+            "Did not publish voluntary exit for {}. Please check that you entered the correct exit phrase.",
+            validator
         );
     }
 
     Ok(())
 }
 
+/// Signs a voluntary exit for `validator_index` without contacting a beacon node, writing it to
+/// `output_path`.
+///
+/// The fork and genesis validators root are read from the genesis state in `testnet_config`.
+#[allow(clippy::too_many_arguments)]
+fn sign_offline_voluntary_exit<E: EthSpec>(
+    keystore_path: &PathBuf,
+    password_file_path: Option<&PathBuf>,
+    validator_index: u64,
+    epoch: Option<Epoch>,
+    output_path: &Path,
+    spec: &ChainSpec,
+    stdin_inputs: bool,
+    testnet_config: &Eth2TestnetConfig,
+) -> Result<(), String> {
+    let genesis_state = testnet_config.beacon_state::<E>()?;
+
+    let epoch = match epoch {
+        Some(epoch) => epoch,
+        None => get_current_epoch::<E>(genesis_state.genesis_time, spec).ok_or_else(|| {
+            "Failed to get current epoch. Please check your system time".to_string()
+        })?,
+    };
+
+    let keypair = load_voting_keypair(keystore_path, password_file_path, stdin_inputs)?;
+
+    let signed_voluntary_exit = sign_voluntary_exit(
+        &keypair,
+        validator_index,
+        epoch,
+        &genesis_state.fork,
+        genesis_state.genesis_validators_root,
+        spec,
+    );
+
+    write_signed_voluntary_exit(output_path, &signed_voluntary_exit)?;
+
+    eprintln!(
+        "Signed a voluntary exit for validator {} (index {}) at epoch {}",
+        keypair.pk, validator_index, epoch
+    );
+    eprintln!("Exit written to {:?}", output_path);
+    eprintln!(
+        "WARNING: ANYONE WITH ACCESS TO THIS FILE CAN EXIT YOUR VALIDATOR. STORE IT SECURELY."
+    );
+
+    Ok(())
+}
+
+/// Sign a `VoluntaryExit` for the validator with the given `keypair` and `validator_index`.
+fn sign_voluntary_exit(
+    keypair: &Keypair,
+    validator_index: u64,
+    epoch: Epoch,
+    fork: &Fork,
+    genesis_validators_root: Hash256,
+    spec: &ChainSpec,
+) -> SignedVoluntaryExit {
+    VoluntaryExit {
+        epoch,
+        validator_index,
+    }
+    .sign(&keypair.sk, fork, genesis_validators_root, spec)
+}
+
+/// Writes `signed_voluntary_exit` to a new JSON file at `path`, refusing to overwrite an existing
+/// file.
+fn write_signed_voluntary_exit(
+    path: &Path,
+    signed_voluntary_exit: &SignedVoluntaryExit,
+) -> Result<(), String> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| format!("Unable to create {:?}: {}", path, e))?;
+
+    serde_json::to_writer_pretty(file, signed_voluntary_exit)
+        .map_err(|e| format!("Unable to write signed exit to {:?}: {}", path, e))
+}
+
+/// Reads a `SignedVoluntaryExit` from a JSON file created by `write_signed_voluntary_exit`.
+pub(crate) fn read_signed_voluntary_exit(path: &Path) -> Result<SignedVoluntaryExit, String> {
+    let file = File::open(path).map_err(|e| format!("Unable to open {:?}: {}", path, e))?;
+
+    serde_json::from_reader(file)
+        .map_err(|e| format!("Unable to parse signed exit from {:?}: {}", path, e))
+}
+
 /// Get the validator index of a given the validator public key by querying the beacon node endpoint.
 ///
 /// Returns an error if the beacon endpoint returns an error or given validator is not eligible for an exit.
@@ -307,6 +491,7 @@ mod tests {
     use std::fs::File;
     use std::io::Write;
     use tempfile::{tempdir, TempDir};
+    use types::{Domain, MainnetEthSpec, SignedRoot};
 
     const PASSWORD: &str = "cats";
     const KEYSTORE_NAME: &str = "keystore-m_12381_3600_0_0_0-1595406747.json";
@@ -345,4 +530,44 @@ mod tests {
 
         assert_eq!(expected_pk, kp.pk.into());
     }
+
+    #[test]
+    fn test_offline_exit_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("exit.json");
+        let spec = MainnetEthSpec::default_spec();
+        let keypair = Keypair::random();
+        let fork = Fork {
+            previous_version: spec.genesis_fork_version,
+            current_version: spec.genesis_fork_version,
+            epoch: Epoch::new(0),
+        };
+        let genesis_validators_root = Hash256::repeat_byte(42);
+
+        let signed_exit = sign_voluntary_exit(
+            &keypair,
+            7,
+            Epoch::new(1024),
+            &fork,
+            genesis_validators_root,
+            &spec,
+        );
+
+        write_signed_voluntary_exit(&path, &signed_exit).unwrap();
+        // Existing exits must not be overwritten.
+        write_signed_voluntary_exit(&path, &signed_exit).unwrap_err();
+
+        let read_exit = read_signed_voluntary_exit(&path).unwrap();
+        assert_eq!(read_exit, signed_exit);
+
+        let domain = spec.get_domain(
+            read_exit.message.epoch,
+            Domain::VoluntaryExit,
+            &fork,
+            genesis_validators_root,
+        );
+        assert!(read_exit
+            .signature
+            .verify(&keypair.pk, read_exit.message.signing_root(domain)));
+    }
 }
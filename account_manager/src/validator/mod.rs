@@ -2,6 +2,7 @@ pub mod create;
 pub mod exit;
 pub mod import;
 pub mod list;
+pub mod publish_exit;
 pub mod recover;
 pub mod slashing_protection;
 
@@ -34,6 +35,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
         .subcommand(recover::cli_app())
         .subcommand(slashing_protection::cli_app())
         .subcommand(exit::cli_app())
+        .subcommand(publish_exit::cli_app())
 }
 
 pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
@@ -49,11 +51,12 @@ pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<
         (create::CMD, Some(matches)) => create::cli_run::<T>(matches, env, validator_base_dir),
         (import::CMD, Some(matches)) => import::cli_run(matches, validator_base_dir),
         (list::CMD, Some(_)) => list::cli_run(validator_base_dir),
-        (recover::CMD, Some(matches)) => recover::cli_run(matches, validator_base_dir),
+        (recover::CMD, Some(matches)) => recover::cli_run::<T>(matches, env, validator_base_dir),
         (slashing_protection::CMD, Some(matches)) => {
             slashing_protection::cli_run(matches, env, validator_base_dir)
         }
         (exit::CMD, Some(matches)) => exit::cli_run(matches, env),
+        (publish_exit::CMD, Some(matches)) => publish_exit::cli_run(matches, env),
         (unknown, _) => Err(format!(
             "{} does not have a {} command. See --help",
             CMD, unknown
@@ -0,0 +1,89 @@
+use super::exit::{
+    check_beacon_node, confirm_and_publish_voluntary_exit, read_signed_voluntary_exit,
+    BEACON_SERVER_FLAG, DEFAULT_BEACON_NODE,
+};
+use crate::wallet::create::STDIN_INPUTS_FLAG;
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use eth2::{BeaconNodeHttpClient, Url};
+use eth2_testnet_config::Eth2TestnetConfig;
+use std::path::PathBuf;
+use types::{EthSpec, SignedVoluntaryExit};
+
+pub const CMD: &str = "publish-exit";
+pub const EXIT_FILE_FLAG: &str = "exit-file";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Publishes a SignedVoluntaryExit which was previously created with \
+            `exit --offline` to the beacon chain.",
+        )
+        .arg(
+            Arg::with_name(EXIT_FILE_FLAG)
+                .long(EXIT_FILE_FLAG)
+                .value_name("EXIT_FILE_PATH")
+                .help("The path to the JSON file containing the signed voluntary exit")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name(BEACON_SERVER_FLAG)
+                .long(BEACON_SERVER_FLAG)
+                .value_name("NETWORK_ADDRESS")
+                .help("Address to a beacon node HTTP API")
+                .default_value(&DEFAULT_BEACON_NODE)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(STDIN_INPUTS_FLAG)
+                .long(STDIN_INPUTS_FLAG)
+                .help("If present, read all user inputs from stdin instead of tty."),
+        )
+}
+
+pub fn cli_run<E: EthSpec>(matches: &ArgMatches, mut env: Environment<E>) -> Result<(), String> {
+    let exit_file_path: PathBuf = clap_utils::parse_required(matches, EXIT_FILE_FLAG)?;
+    let stdin_inputs = matches.is_present(STDIN_INPUTS_FLAG);
+
+    let server_url: String = clap_utils::parse_required(matches, BEACON_SERVER_FLAG)?;
+    let client = BeaconNodeHttpClient::new(
+        Url::parse(&server_url)
+            .map_err(|e| format!("Failed to parse beacon http server: {:?}", e))?,
+    );
+
+    let testnet_config = env
+        .testnet
+        .clone()
+        .ok_or_else(|| "Unable to publish an exit without a testnet config")?;
+
+    let signed_voluntary_exit = read_signed_voluntary_exit(&exit_file_path)?;
+
+    env.runtime().block_on(publish_signed_voluntary_exit::<E>(
+        signed_voluntary_exit,
+        &client,
+        stdin_inputs,
+        &testnet_config,
+    ))
+}
+
+/// Publishes a previously signed voluntary exit, after confirming with the user.
+async fn publish_signed_voluntary_exit<E: EthSpec>(
+    signed_voluntary_exit: SignedVoluntaryExit,
+    client: &BeaconNodeHttpClient,
+    stdin_inputs: bool,
+    testnet_config: &Eth2TestnetConfig,
+) -> Result<(), String> {
+    check_beacon_node::<E>(client, testnet_config).await?;
+
+    confirm_and_publish_voluntary_exit(
+        client,
+        &signed_voluntary_exit,
+        &format!(
+            "validator index {}",
+            signed_voluntary_exit.message.validator_index
+        ),
+        stdin_inputs,
+    )
+    .await
+}
@@ -25,7 +25,14 @@
 //!                                ▼
 //!                  impl SignatureVerifiedAttestation
 //! ```
+//!
+//! Internally, each attestation first passes through an "indexed" stage
+//! (`IndexedUnaggregatedAttestation`/`IndexedAggregatedAttestation`) where all checks *except*
+//! signature verification have been performed. This allows the signatures of many attestations to
+//! be verified at once (see `batch_verify_unaggregated_attestations` and
+//! `batch_verify_aggregated_attestations`).
 
+use crate::validator_pubkey_cache::ValidatorPubkeyCache;
 use crate::{
     beacon_chain::{
         HEAD_LOCK_TIMEOUT, MAXIMUM_GOSSIP_CLOCK_DISPARITY, VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT,
@@ -35,7 +42,7 @@ use crate::{
     observed_attesters::Error as ObservedAttestersError,
     BeaconChain, BeaconChainError, BeaconChainTypes,
 };
-use bls::verify_signature_sets;
+use bls::{verify_signature_sets, SignatureSet};
 use proto_array::Block as ProtoBlock;
 use slot_clock::SlotClock;
 use state_processing::{
@@ -49,8 +56,8 @@ use state_processing::{
 use std::borrow::Cow;
 use tree_hash::TreeHash;
 use types::{
-    Attestation, BeaconCommittee, CommitteeIndex, Epoch, EthSpec, Hash256, IndexedAttestation,
-    SelectionProof, SignedAggregateAndProof, Slot, SubnetId,
+    Attestation, BeaconCommittee, CommitteeIndex, Epoch, EthSpec, Fork, Hash256,
+    IndexedAttestation, SelectionProof, SignedAggregateAndProof, Slot, SubnetId,
 };
 
 /// Returned when an attestation was not successfully verified. It might not have been verified for
@@ -254,6 +261,34 @@ impl From<BeaconChainError> for Error {
     }
 }
 
+/// Used to avoid double-checking signatures.
+#[derive(Copy, Clone)]
+enum CheckAttestationSignature {
+    Yes,
+    No,
+}
+
+/// Wraps a `SignedAggregateAndProof` that has been verified up until the point that an
+/// `IndexedAttestation` can be derived.
+///
+/// These attestations have *not* undergone signature verification.
+struct IndexedAggregatedAttestation<T: BeaconChainTypes> {
+    signed_aggregate: SignedAggregateAndProof<T::EthSpec>,
+    indexed_attestation: IndexedAttestation<T::EthSpec>,
+    attestation_root: Hash256,
+}
+
+/// Wraps an `Attestation` that has been verified up until the point that an `IndexedAttestation`
+/// can be derived.
+///
+/// These attestations have *not* undergone signature verification.
+struct IndexedUnaggregatedAttestation<T: BeaconChainTypes> {
+    attestation: Attestation<T::EthSpec>,
+    indexed_attestation: IndexedAttestation<T::EthSpec>,
+    subnet_id: SubnetId,
+    validator_index: u64,
+}
+
 /// Wraps a `SignedAggregateAndProof` that has been verified for propagation on the gossip network.
 pub struct VerifiedAggregatedAttestation<T: BeaconChainTypes> {
     signed_aggregate: SignedAggregateAndProof<T::EthSpec>,
@@ -297,10 +332,10 @@ impl<T: BeaconChainTypes> SignatureVerifiedAttestation<T> for VerifiedUnaggregat
     }
 }
 
-impl<T: BeaconChainTypes> VerifiedAggregatedAttestation<T> {
-    /// Returns `Ok(Self)` if the `signed_aggregate` is valid to be (re)published on the gossip
-    /// network.
-    pub fn verify(
+impl<T: BeaconChainTypes> IndexedAggregatedAttestation<T> {
+    /// Returns `Ok(Self)` if the `signed_aggregate` passes all the gossip propagation checks
+    /// except for signature verification.
+    fn verify(
         signed_aggregate: SignedAggregateAndProof<T::EthSpec>,
         chain: &BeaconChain<T>,
     ) -> Result<Self, Error> {
@@ -389,11 +424,49 @@ impl<T: BeaconChainTypes> VerifiedAggregatedAttestation<T> {
                     .map_err(|e| BeaconChainError::from(e).into())
             })?;
 
+        Ok(IndexedAggregatedAttestation {
+            signed_aggregate,
+            indexed_attestation,
+            attestation_root,
+        })
+    }
+}
+
+impl<T: BeaconChainTypes> VerifiedAggregatedAttestation<T> {
+    /// Returns `Ok(Self)` if the `signed_aggregate` is valid to be (re)published on the gossip
+    /// network.
+    pub fn verify(
+        signed_aggregate: SignedAggregateAndProof<T::EthSpec>,
+        chain: &BeaconChain<T>,
+    ) -> Result<Self, Error> {
+        let indexed = IndexedAggregatedAttestation::verify(signed_aggregate, chain)?;
+        Self::from_indexed(indexed, chain, CheckAttestationSignature::Yes)
+    }
+
+    /// Complete the verification of an indexed attestation, optionally skipping signature
+    /// verification (e.g., if the signature has already been verified in a batch).
+    fn from_indexed(
+        indexed: IndexedAggregatedAttestation<T>,
+        chain: &BeaconChain<T>,
+        check_signature: CheckAttestationSignature,
+    ) -> Result<Self, Error> {
+        let IndexedAggregatedAttestation {
+            signed_aggregate,
+            indexed_attestation,
+            attestation_root,
+        } = indexed;
+
         // Ensure that all signatures are valid.
-        if !verify_signed_aggregate_signatures(chain, &signed_aggregate, &indexed_attestation)? {
-            return Err(Error::InvalidSignature);
+        if let CheckAttestationSignature::Yes = check_signature {
+            if !verify_signed_aggregate_signatures(chain, &signed_aggregate, &indexed_attestation)?
+            {
+                return Err(Error::InvalidSignature);
+            }
         }
 
+        let attestation = &signed_aggregate.message.aggregate;
+        let aggregator_index = signed_aggregate.message.aggregator_index;
+
         // Observe the valid attestation so we do not re-process it.
         //
         // It's important to double check that the attestation is not already known, otherwise two
@@ -443,13 +516,13 @@ impl<T: BeaconChainTypes> VerifiedAggregatedAttestation<T> {
     }
 }
 
-impl<T: BeaconChainTypes> VerifiedUnaggregatedAttestation<T> {
-    /// Returns `Ok(Self)` if the `attestation` is valid to be (re)published on the gossip
-    /// network.
+impl<T: BeaconChainTypes> IndexedUnaggregatedAttestation<T> {
+    /// Returns `Ok(Self)` if the `attestation` passes all the gossip propagation checks except for
+    /// signature verification.
     ///
     /// `subnet_id` is the subnet from which we received this attestation. This function will
     /// verify that it was received on the correct subnet.
-    pub fn verify(
+    fn verify(
         attestation: Attestation<T::EthSpec>,
         subnet_id: Option<SubnetId>,
         chain: &BeaconChain<T>,
@@ -527,8 +600,48 @@ impl<T: BeaconChainTypes> VerifiedUnaggregatedAttestation<T> {
             });
         }
 
+        Ok(Self {
+            attestation,
+            indexed_attestation,
+            subnet_id: expected_subnet_id,
+            validator_index,
+        })
+    }
+}
+
+impl<T: BeaconChainTypes> VerifiedUnaggregatedAttestation<T> {
+    /// Returns `Ok(Self)` if the `attestation` is valid to be (re)published on the gossip
+    /// network.
+    ///
+    /// `subnet_id` is the subnet from which we received this attestation. This function will
+    /// verify that it was received on the correct subnet.
+    pub fn verify(
+        attestation: Attestation<T::EthSpec>,
+        subnet_id: Option<SubnetId>,
+        chain: &BeaconChain<T>,
+    ) -> Result<Self, Error> {
+        let indexed = IndexedUnaggregatedAttestation::verify(attestation, subnet_id, chain)?;
+        Self::from_indexed(indexed, chain, CheckAttestationSignature::Yes)
+    }
+
+    /// Complete the verification of an indexed attestation, optionally skipping signature
+    /// verification (e.g., if the signature has already been verified in a batch).
+    fn from_indexed(
+        indexed: IndexedUnaggregatedAttestation<T>,
+        chain: &BeaconChain<T>,
+        check_signature: CheckAttestationSignature,
+    ) -> Result<Self, Error> {
+        let IndexedUnaggregatedAttestation {
+            attestation,
+            indexed_attestation,
+            subnet_id,
+            validator_index,
+        } = indexed;
+
         // The aggregate signature of the attestation is valid.
-        verify_attestation_signature(chain, &indexed_attestation)?;
+        if let CheckAttestationSignature::Yes = check_signature {
+            verify_attestation_signature(chain, &indexed_attestation)?;
+        }
 
         // Now that the attestation has been fully verified, store that we have received a valid
         // attestation from this validator.
@@ -550,7 +663,7 @@ impl<T: BeaconChainTypes> VerifiedUnaggregatedAttestation<T> {
         Ok(Self {
             attestation,
             indexed_attestation,
-            subnet_id: expected_subnet_id,
+            subnet_id,
         })
     }
 
@@ -768,30 +881,50 @@ pub fn verify_signed_aggregate_signatures<T: BeaconChainTypes>(
         .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
         .ok_or_else(|| BeaconChainError::ValidatorPubkeyCacheLockTimeout)?;
 
-    let aggregator_index = signed_aggregate.message.aggregator_index;
-    if aggregator_index >= pubkey_cache.len() as u64 {
-        return Err(Error::AggregatorPubkeyUnknown(aggregator_index));
-    }
-
     let fork = chain
         .canonical_head
         .try_read_for(HEAD_LOCK_TIMEOUT)
         .ok_or_else(|| BeaconChainError::CanonicalHeadLockTimeout)
         .map(|head| head.beacon_state.fork)?;
 
-    let signature_sets = vec![
+    let signature_sets = signed_aggregate_signature_sets(
+        chain,
+        &pubkey_cache,
+        signed_aggregate,
+        indexed_attestation,
+        &fork,
+    )?;
+
+    Ok(verify_signature_sets(signature_sets.iter()))
+}
+
+/// Returns the three signature sets in a `SignedAggregateAndProof` (see
+/// `verify_signed_aggregate_signatures`), ready to be verified.
+fn signed_aggregate_signature_sets<'a, T: BeaconChainTypes>(
+    chain: &'a BeaconChain<T>,
+    pubkey_cache: &'a ValidatorPubkeyCache,
+    signed_aggregate: &'a SignedAggregateAndProof<T::EthSpec>,
+    indexed_attestation: &'a IndexedAttestation<T::EthSpec>,
+    fork: &Fork,
+) -> Result<Vec<SignatureSet<'a>>, Error> {
+    let aggregator_index = signed_aggregate.message.aggregator_index;
+    if aggregator_index >= pubkey_cache.len() as u64 {
+        return Err(Error::AggregatorPubkeyUnknown(aggregator_index));
+    }
+
+    Ok(vec![
         signed_aggregate_selection_proof_signature_set(
             |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
-            &signed_aggregate,
-            &fork,
+            signed_aggregate,
+            fork,
             chain.genesis_validators_root,
             &chain.spec,
         )
         .map_err(BeaconChainError::SignatureSetError)?,
         signed_aggregate_signature_set(
             |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
-            &signed_aggregate,
-            &fork,
+            signed_aggregate,
+            fork,
             chain.genesis_validators_root,
             &chain.spec,
         )
@@ -799,15 +932,187 @@ pub fn verify_signed_aggregate_signatures<T: BeaconChainTypes>(
         indexed_attestation_signature_set_from_pubkeys(
             |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
             &indexed_attestation.signature,
-            &indexed_attestation,
-            &fork,
+            indexed_attestation,
+            fork,
             chain.genesis_validators_root,
             &chain.spec,
         )
         .map_err(BeaconChainError::SignatureSetError)?,
-    ];
+    ])
+}
 
-    Ok(verify_signature_sets(signature_sets.iter()))
+/// Verify a batch of unaggregated attestations, using a single batch signature verification for
+/// all attestations that pass the preliminary (non-signature) checks.
+///
+/// Returns a result for each of the given `attestations`, in the same order. If the batch
+/// signature verification fails then each attestation is verified individually so that the
+/// invalid attestation(s) can be identified.
+///
+/// The outer `Result` is only `Err` if there was an internal error which prevented any
+/// verification from taking place.
+pub fn batch_verify_unaggregated_attestations<T, I>(
+    attestations: I,
+    chain: &BeaconChain<T>,
+) -> Result<Vec<Result<VerifiedUnaggregatedAttestation<T>, Error>>, Error>
+where
+    T: BeaconChainTypes,
+    I: Iterator<Item = (Attestation<T::EthSpec>, Option<SubnetId>)>,
+{
+    let indexed_results = attestations
+        .map(|(attestation, subnet_id)| {
+            IndexedUnaggregatedAttestation::verify(attestation, subnet_id, chain)
+        })
+        .collect::<Vec<_>>();
+
+    let check_signature = {
+        let signature_setup_timer = metrics::start_timer(
+            &metrics::ATTESTATION_PROCESSING_BATCH_UNAGG_SIGNATURE_SETUP_TIMES,
+        );
+
+        let pubkey_cache = chain
+            .validator_pubkey_cache
+            .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
+            .ok_or_else(|| BeaconChainError::ValidatorPubkeyCacheLockTimeout)?;
+
+        let fork = chain
+            .canonical_head
+            .try_read_for(HEAD_LOCK_TIMEOUT)
+            .ok_or_else(|| BeaconChainError::CanonicalHeadLockTimeout)
+            .map(|head| head.beacon_state.fork)?;
+
+        let mut signature_sets = Vec::with_capacity(indexed_results.len());
+        let mut all_sets_valid = true;
+        for indexed in indexed_results.iter().flatten() {
+            match indexed_attestation_signature_set_from_pubkeys(
+                |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
+                &indexed.indexed_attestation.signature,
+                &indexed.indexed_attestation,
+                &fork,
+                chain.genesis_validators_root,
+                &chain.spec,
+            ) {
+                Ok(set) => signature_sets.push(set),
+                // An attestation without a valid signature set cannot be batched. Fall back to
+                // individual verification so that the error is attributed to that attestation.
+                Err(_) => all_sets_valid = false,
+            }
+        }
+
+        metrics::stop_timer(signature_setup_timer);
+
+        let _signature_verification_timer =
+            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_BATCH_UNAGG_SIGNATURE_TIMES);
+
+        if all_sets_valid {
+            batch_check_signature(&signature_sets)
+        } else {
+            CheckAttestationSignature::Yes
+        }
+    };
+
+    Ok(indexed_results
+        .into_iter()
+        .map(|result| {
+            result.and_then(|indexed| {
+                VerifiedUnaggregatedAttestation::from_indexed(indexed, chain, check_signature)
+            })
+        })
+        .collect())
+}
+
+/// Verify a batch of aggregated attestations, using a single batch signature verification for
+/// all aggregates that pass the preliminary (non-signature) checks.
+///
+/// Returns a result for each of the given `aggregates`, in the same order. If the batch signature
+/// verification fails then each aggregate is verified individually so that the invalid
+/// aggregate(s) can be identified.
+///
+/// The outer `Result` is only `Err` if there was an internal error which prevented any
+/// verification from taking place.
+pub fn batch_verify_aggregated_attestations<T, I>(
+    aggregates: I,
+    chain: &BeaconChain<T>,
+) -> Result<Vec<Result<VerifiedAggregatedAttestation<T>, Error>>, Error>
+where
+    T: BeaconChainTypes,
+    I: Iterator<Item = SignedAggregateAndProof<T::EthSpec>>,
+{
+    let indexed_results = aggregates
+        .map(|aggregate| IndexedAggregatedAttestation::verify(aggregate, chain))
+        .collect::<Vec<_>>();
+
+    let check_signature = {
+        let signature_setup_timer =
+            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_BATCH_AGG_SIGNATURE_SETUP_TIMES);
+
+        let pubkey_cache = chain
+            .validator_pubkey_cache
+            .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
+            .ok_or_else(|| BeaconChainError::ValidatorPubkeyCacheLockTimeout)?;
+
+        let fork = chain
+            .canonical_head
+            .try_read_for(HEAD_LOCK_TIMEOUT)
+            .ok_or_else(|| BeaconChainError::CanonicalHeadLockTimeout)
+            .map(|head| head.beacon_state.fork)?;
+
+        let mut signature_sets = Vec::with_capacity(indexed_results.len() * 3);
+        let mut all_sets_valid = true;
+        for indexed in indexed_results.iter().flatten() {
+            match signed_aggregate_signature_sets(
+                chain,
+                &pubkey_cache,
+                &indexed.signed_aggregate,
+                &indexed.indexed_attestation,
+                &fork,
+            ) {
+                Ok(sets) => signature_sets.extend(sets),
+                // An aggregate with an unknown aggregator cannot be batched. Fall back to
+                // individual verification so that the error is attributed to that aggregate.
+                Err(Error::AggregatorPubkeyUnknown(_)) => all_sets_valid = false,
+                Err(e) => return Err(e),
+            }
+        }
+
+        metrics::stop_timer(signature_setup_timer);
+
+        let _signature_verification_timer =
+            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_BATCH_AGG_SIGNATURE_TIMES);
+
+        if all_sets_valid {
+            batch_check_signature(&signature_sets)
+        } else {
+            CheckAttestationSignature::Yes
+        }
+    };
+
+    Ok(indexed_results
+        .into_iter()
+        .map(|result| {
+            result.and_then(|indexed| {
+                VerifiedAggregatedAttestation::from_indexed(indexed, chain, check_signature)
+            })
+        })
+        .collect())
+}
+
+/// Verifies all `signature_sets` in a single batch, returning `CheckAttestationSignature::No` if
+/// they are all valid.
+///
+/// If any signature is invalid (or there are no signatures to verify), returns
+/// `CheckAttestationSignature::Yes` to indicate that each signature should be checked
+/// individually.
+fn batch_check_signature(signature_sets: &[SignatureSet]) -> CheckAttestationSignature {
+    if signature_sets.is_empty() {
+        return CheckAttestationSignature::Yes;
+    }
+
+    if verify_signature_sets(signature_sets.iter()) {
+        CheckAttestationSignature::No
+    } else {
+        metrics::inc_counter(&metrics::ATTESTATION_PROCESSING_BATCH_SIGNATURE_FAILURES);
+        CheckAttestationSignature::Yes
+    }
 }
 
 /// Assists in readability.
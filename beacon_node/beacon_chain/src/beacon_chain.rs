@@ -1,4 +1,5 @@
 use crate::attestation_verification::{
+    batch_verify_aggregated_attestations, batch_verify_unaggregated_attestations,
     Error as AttestationError, SignatureVerifiedAttestation, VerifiedAggregatedAttestation,
     VerifiedUnaggregatedAttestation,
 };
@@ -19,8 +20,10 @@ use crate::observed_attesters::{ObservedAggregators, ObservedAttesters};
 use crate::observed_block_producers::ObservedBlockProducers;
 use crate::observed_operations::{ObservationOutcome, ObservedOperations};
 use crate::persisted_beacon_chain::{PersistedBeaconChain, DUMMY_CANONICAL_HEAD_BLOCK_ROOT};
+use crate::persisted_caches::PersistedCaches;
 use crate::persisted_fork_choice::PersistedForkChoice;
 use crate::shuffling_cache::{BlockShufflingIds, ShufflingCache};
+use crate::slot_hook::{SlotHook, SlotOffset};
 use crate::snapshot_cache::SnapshotCache;
 use crate::timeout_rw_lock::TimeoutRwLock;
 use crate::validator_pubkey_cache::ValidatorPubkeyCache;
@@ -30,7 +33,7 @@ use crate::{metrics, BeaconChainError};
 use fork_choice::ForkChoice;
 use futures::channel::mpsc::Sender;
 use itertools::process_results;
-use operation_pool::{OperationPool, PersistedOperationPool};
+use operation_pool::{OperationPool, PackingStrategy, PersistedOperationPool};
 use parking_lot::RwLock;
 use slog::{crit, debug, error, info, trace, warn, Logger};
 use slot_clock::SlotClock;
@@ -72,6 +75,7 @@ pub const BEACON_CHAIN_DB_KEY: Hash256 = Hash256::zero();
 pub const OP_POOL_DB_KEY: Hash256 = Hash256::zero();
 pub const ETH1_CACHE_DB_KEY: Hash256 = Hash256::zero();
 pub const FORK_CHOICE_DB_KEY: Hash256 = Hash256::zero();
+pub const CACHES_DB_KEY: Hash256 = Hash256::zero();
 
 /// The result of a chain segment processing.
 pub enum ChainSegmentResult<T: EthSpec> {
@@ -296,6 +300,22 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         Ok(())
     }
 
+    /// Persists the naive aggregation pool and the caches of observed attesters, aggregators and
+    /// block producers to disk.
+    pub fn persist_caches(&self) -> Result<(), Error> {
+        let _timer = metrics::start_timer(&metrics::PERSIST_CACHES);
+
+        let persisted_caches = PersistedCaches {
+            naive_aggregation_pool: self.naive_aggregation_pool.read().iter().cloned().collect(),
+            observed_attesters: self.observed_attesters.to_persisted(),
+            observed_aggregators: self.observed_aggregators.to_persisted(),
+            observed_block_producers: self.observed_block_producers.to_persisted(),
+        };
+        self.store.put_item(&CACHES_DB_KEY, &persisted_caches)?;
+
+        Ok(())
+    }
+
     /// Persists `self.eth1_chain` and its caches to disk.
     pub fn persist_eth1_cache(&self) -> Result<(), Error> {
         let _timer = metrics::start_timer(&metrics::PERSIST_OP_POOL);
@@ -961,6 +981,33 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         })
     }
 
+    /// Performs the same validation as `Self::verify_unaggregated_attestation_for_gossip`, but
+    /// for a batch of attestations, using batch signature verification where possible.
+    ///
+    /// Returns a result for each attestation, in the same order as `attestations`.
+    pub fn batch_verify_unaggregated_attestations_for_gossip<I>(
+        &self,
+        attestations: I,
+    ) -> Result<Vec<Result<VerifiedUnaggregatedAttestation<T>, AttestationError>>, AttestationError>
+    where
+        I: Iterator<Item = (Attestation<T::EthSpec>, Option<SubnetId>)>,
+    {
+        let _timer = metrics::start_timer(
+            &metrics::UNAGGREGATED_ATTESTATION_GOSSIP_BATCH_VERIFICATION_TIMES,
+        );
+
+        let results = batch_verify_unaggregated_attestations(attestations, self)?;
+
+        for result in &results {
+            metrics::inc_counter(&metrics::UNAGGREGATED_ATTESTATION_PROCESSING_REQUESTS);
+            if result.is_ok() {
+                metrics::inc_counter(&metrics::UNAGGREGATED_ATTESTATION_PROCESSING_SUCCESSES);
+            }
+        }
+
+        Ok(results)
+    }
+
     /// Accepts some `SignedAggregateAndProof` from the network and attempts to verify it,
     /// returning `Ok(_)` if it is valid to be (re)broadcast on the gossip network.
     pub fn verify_aggregated_attestation_for_gossip(
@@ -977,6 +1024,32 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         })
     }
 
+    /// Performs the same validation as `Self::verify_aggregated_attestation_for_gossip`, but for
+    /// a batch of aggregates, using batch signature verification where possible.
+    ///
+    /// Returns a result for each aggregate, in the same order as `aggregates`.
+    pub fn batch_verify_aggregated_attestations_for_gossip<I>(
+        &self,
+        aggregates: I,
+    ) -> Result<Vec<Result<VerifiedAggregatedAttestation<T>, AttestationError>>, AttestationError>
+    where
+        I: Iterator<Item = SignedAggregateAndProof<T::EthSpec>>,
+    {
+        let _timer =
+            metrics::start_timer(&metrics::AGGREGATED_ATTESTATION_GOSSIP_BATCH_VERIFICATION_TIMES);
+
+        let results = batch_verify_aggregated_attestations(aggregates, self)?;
+
+        for result in &results {
+            metrics::inc_counter(&metrics::AGGREGATED_ATTESTATION_PROCESSING_REQUESTS);
+            if result.is_ok() {
+                metrics::inc_counter(&metrics::AGGREGATED_ATTESTATION_PROCESSING_SUCCESSES);
+            }
+        }
+
+        Ok(results)
+    }
+
     /// Accepts some attestation-type object and attempts to verify it in the context of fork
     /// choice. If it is valid it is applied to `self.fork_choice`.
     ///
@@ -1414,6 +1487,20 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     pub fn process_block<B: IntoFullyVerifiedBlock<T>>(
         &self,
         unverified_block: B,
+    ) -> Result<Hash256, BlockError<T::EthSpec>> {
+        self.process_block_seen_at(unverified_block, None)
+    }
+
+    /// As `process_block`, but with the time at which the block was first seen (e.g., when it
+    /// arrived on gossip), as a duration since the UNIX epoch.
+    ///
+    /// The block's eligibility for the proposer score boost is judged from `seen_timestamp`, so
+    /// that slow verification can't make a block that arrived on time late. If `seen_timestamp` is
+    /// `None` the block is treated as seen when it is imported.
+    pub fn process_block_seen_at<B: IntoFullyVerifiedBlock<T>>(
+        &self,
+        unverified_block: B,
+        seen_timestamp: Option<Duration>,
     ) -> Result<Hash256, BlockError<T::EthSpec>> {
         // Start the Prometheus timer.
         let _full_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_TIMES);
@@ -1427,7 +1514,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         // A small closure to group the verification and import errors.
         let import_block = |unverified_block: B| -> Result<Hash256, BlockError<T::EthSpec>> {
             let fully_verified = unverified_block.into_fully_verified_block(self)?;
-            self.import_block(fully_verified)
+            self.import_block(fully_verified, seen_timestamp)
         };
 
         // Verify and import the block.
@@ -1493,6 +1580,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     fn import_block(
         &self,
         fully_verified_block: FullyVerifiedBlock<T>,
+        seen_timestamp: Option<Duration>,
     ) -> Result<Hash256, BlockError<T::EthSpec>> {
         let signed_block = fully_verified_block.block;
         let block_root = fully_verified_block.block_root;
@@ -1592,8 +1680,28 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         {
             let _fork_choice_block_timer =
                 metrics::start_timer(&metrics::FORK_CHOICE_PROCESS_BLOCK_TIMES);
+
+            // The time between the start of the block's slot and when the block was seen, used to
+            // determine if the block is eligible for the proposer score boost. A block seen before
+            // its slot started is timely. If the slot clock cannot be read, treat the block as
+            // late.
+            let block_delay = seen_timestamp
+                .or_else(|| self.slot_clock.now_duration())
+                .and_then(|seen| {
+                    let slot_start = self.slot_clock.start_of(block.slot)?;
+                    Some(seen.checked_sub(slot_start).unwrap_or_default())
+                })
+                .unwrap_or_else(|| self.slot_clock.slot_duration());
+
             fork_choice
-                .on_block(current_slot, block, block_root, &state)
+                .on_block(
+                    current_slot,
+                    block,
+                    block_root,
+                    block_delay,
+                    &state,
+                    &self.spec,
+                )
                 .map_err(|e| BlockError::BeaconChainError(e.into()))?;
         }
 
@@ -1679,12 +1787,37 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         randao_reveal: Signature,
         slot: Slot,
         validator_graffiti: Option<Graffiti>,
+    ) -> Result<BeaconBlockAndState<T::EthSpec>, BlockProductionError> {
+        self.produce_block_with_strategy(
+            randao_reveal,
+            slot,
+            validator_graffiti,
+            PackingStrategy::default(),
+        )
+    }
+
+    /// Produce a new block at the given `slot`, choosing attestations with the given
+    /// `packing_strategy`.
+    ///
+    /// See `Self::produce_block`.
+    pub fn produce_block_with_strategy(
+        &self,
+        randao_reveal: Signature,
+        slot: Slot,
+        validator_graffiti: Option<Graffiti>,
+        packing_strategy: PackingStrategy,
     ) -> Result<BeaconBlockAndState<T::EthSpec>, BlockProductionError> {
         let state = self
             .state_at_slot(slot - 1, StateSkipConfig::WithStateRoots)
             .map_err(|_| BlockProductionError::UnableToProduceAtSlot(slot))?;
 
-        self.produce_block_on_state(state, slot, randao_reveal, validator_graffiti)
+        self.produce_block_on_state(
+            state,
+            slot,
+            randao_reveal,
+            validator_graffiti,
+            packing_strategy,
+        )
     }
 
     /// Produce a block for some `slot` upon the given `state`.
@@ -1701,6 +1834,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         produce_at_slot: Slot,
         randao_reveal: Signature,
         validator_graffiti: Option<Graffiti>,
+        packing_strategy: PackingStrategy,
     ) -> Result<BeaconBlockAndState<T::EthSpec>, BlockProductionError> {
         metrics::inc_counter(&metrics::BLOCK_PRODUCTION_REQUESTS);
         let timer = metrics::start_timer(&metrics::BLOCK_PRODUCTION_TIMES);
@@ -1769,6 +1903,37 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             }
         }
 
+        let packing_timer = metrics::start_timer_vec(
+            &metrics::BLOCK_PRODUCTION_PACKING_TIMES,
+            &[packing_strategy.name()],
+        );
+        let packed = self
+            .op_pool
+            .get_attestations_with_strategy(
+                &state,
+                attestation_filter,
+                packing_strategy,
+                &self.spec,
+            )
+            .map_err(BlockProductionError::OpPoolError)?;
+        metrics::stop_timer(packing_timer);
+        metrics::inc_counter_vec(
+            &metrics::BLOCK_PRODUCTION_PACKINGS,
+            &[packing_strategy.name()],
+        );
+        metrics::inc_counter_vec_by(
+            &metrics::BLOCK_PRODUCTION_PACKING_REWARD,
+            &[packing_strategy.name()],
+            packed.reward as i64,
+        );
+        // Recording what the default strategy would have packed allows the strategies to be
+        // compared on the same attestations.
+        metrics::inc_counter_vec_by(
+            &metrics::BLOCK_PRODUCTION_PACKING_MAX_REWARD,
+            &[packing_strategy.name()],
+            packed.max_reward as i64,
+        );
+
         // Override the beacon node's graffiti with graffiti from the validator, if present.
         let graffiti = match validator_graffiti {
             Some(graffiti) => graffiti,
@@ -1787,11 +1952,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                     graffiti,
                     proposer_slashings: proposer_slashings.into(),
                     attester_slashings: attester_slashings.into(),
-                    attestations: self
-                        .op_pool
-                        .get_attestations(&state, attestation_filter, &self.spec)
-                        .map_err(BlockProductionError::OpPoolError)?
-                        .into(),
+                    attestations: packed.attestations.into(),
                     deposits,
                     voluntary_exits: self.op_pool.get_voluntary_exits(&state, &self.spec).into(),
                 },
@@ -1842,7 +2003,10 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
     fn fork_choice_internal(&self) -> Result<(), Error> {
         // Determine the root of the block that is the head of the chain.
-        let beacon_block_root = self.fork_choice.write().get_head(self.slot()?)?;
+        let beacon_block_root = self
+            .fork_choice
+            .write()
+            .get_head(self.slot()?, self.config.proposer_score_boost)?;
 
         let current_head = self.head_info()?;
         let old_finalized_checkpoint = current_head.finalized_checkpoint;
@@ -1943,7 +2107,6 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             || is_reorg
         {
             self.persist_head_and_fork_choice()?;
-            self.op_pool.prune_attestations(self.epoch()?);
         }
 
         let update_head_timer = metrics::start_timer(&metrics::UPDATE_HEAD_TIMES);
@@ -2080,6 +2243,52 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         }
     }
 
+    /// Returns the hooks which the timer service runs within every slot, in addition to
+    /// `per_slot_task`.
+    ///
+    /// Maintenance that doesn't need to happen at the start of the slot should be registered here.
+    pub fn slot_hooks(self: &Arc<Self>) -> Vec<SlotHook> {
+        let chain = self.clone();
+        vec![SlotHook::new(
+            "beacon_chain_late_slot",
+            SlotOffset::Fraction {
+                numerator: 3,
+                denominator: 4,
+            },
+            move || chain.late_slot_task(),
+        )]
+    }
+
+    /// Called by the timer late in every slot, after attestations and aggregates for the slot
+    /// have been produced.
+    ///
+    /// Performs maintenance which does not need to happen at the start of the slot:
+    ///
+    /// - Pruning old attestations from the op pool.
+    /// - Persisting the op pool to disk, during the last slot of each epoch.
+    ///
+    /// This function may block on disk I/O, so it must not be called from an async context.
+    pub fn late_slot_task(&self) {
+        trace!(self.log, "Running beacon chain late slot tasks");
+        let slot = match self.slot_clock.now() {
+            Some(slot) => slot,
+            None => return,
+        };
+        let slots_per_epoch = T::EthSpec::slots_per_epoch();
+
+        self.op_pool.prune_attestations(slot.epoch(slots_per_epoch));
+
+        if (slot + 1) % slots_per_epoch == 0 {
+            if let Err(e) = self.persist_op_pool() {
+                error!(
+                    self.log,
+                    "Failed to persist op pool";
+                    "error" => format!("{:?}", e)
+                );
+            }
+        }
+    }
+
     /// Called after `self` has had a new block finalized.
     ///
     /// Performs pruning and finality-based optimizations.
@@ -2439,6 +2648,7 @@ impl<T: BeaconChainTypes> Drop for BeaconChain<T> {
         let drop = || -> Result<(), Error> {
             self.persist_head_and_fork_choice()?;
             self.persist_op_pool()?;
+            self.persist_caches()?;
             self.persist_eth1_cache()
         };
 
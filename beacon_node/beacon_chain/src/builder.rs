@@ -1,11 +1,15 @@
 use crate::beacon_chain::{
-    BEACON_CHAIN_DB_KEY, ETH1_CACHE_DB_KEY, FORK_CHOICE_DB_KEY, OP_POOL_DB_KEY,
+    BEACON_CHAIN_DB_KEY, CACHES_DB_KEY, ETH1_CACHE_DB_KEY, FORK_CHOICE_DB_KEY, OP_POOL_DB_KEY,
 };
 use crate::eth1_chain::{CachingEth1Backend, SszEth1};
 use crate::events::NullEventHandler;
 use crate::head_tracker::HeadTracker;
 use crate::migrate::{BackgroundMigrator, MigratorConfig};
+use crate::naive_aggregation_pool::NaiveAggregationPool;
+use crate::observed_attesters::{ObservedAggregators, ObservedAttesters};
+use crate::observed_block_producers::ObservedBlockProducers;
 use crate::persisted_beacon_chain::PersistedBeaconChain;
+use crate::persisted_caches::PersistedCaches;
 use crate::persisted_fork_choice::PersistedForkChoice;
 use crate::shuffling_cache::ShufflingCache;
 use crate::snapshot_cache::{SnapshotCache, DEFAULT_SNAPSHOT_CACHE_SIZE};
@@ -86,6 +90,7 @@ pub struct BeaconChainBuilder<T: BeaconChainTypes> {
         ForkChoice<BeaconForkChoiceStore<T::EthSpec, T::HotStore, T::ColdStore>, T::EthSpec>,
     >,
     op_pool: Option<OperationPool<T::EthSpec>>,
+    persisted_caches: Option<PersistedCaches<T::EthSpec>>,
     eth1_chain: Option<Eth1Chain<T::Eth1Chain, T::EthSpec>>,
     event_handler: Option<T::EventHandler>,
     slot_clock: Option<T::SlotClock>,
@@ -126,6 +131,7 @@ where
             genesis_state_root: None,
             fork_choice: None,
             op_pool: None,
+            persisted_caches: None,
             eth1_chain: None,
             event_handler: None,
             slot_clock: None,
@@ -289,6 +295,10 @@ where
                 .unwrap_or_else(OperationPool::new),
         );
 
+        self.persisted_caches = store
+            .get_item::<PersistedCaches<TEthSpec>>(&CACHES_DB_KEY)
+            .map_err(|e| format!("DB error whilst reading persisted caches: {:?}", e))?;
+
         let pubkey_cache = ValidatorPubkeyCache::load_from_file(pubkey_cache_path)
             .map_err(|e| format!("Unable to open persisted pubkey cache: {:?}", e))?;
 
@@ -459,7 +469,7 @@ where
         };
 
         let head_block_root = fork_choice
-            .get_head(current_slot)
+            .get_head(current_slot, self.chain_config.proposer_score_boost)
             .map_err(|e| format!("Unable to get fork choice head: {:?}", e))?;
 
         let head_block = store
@@ -526,6 +536,11 @@ where
             log.clone(),
         );
 
+        // Restore the gossip caches that are still relevant to the current slot.
+        let persisted_caches = self.persisted_caches.unwrap_or_default();
+        let current_epoch = current_slot.epoch(TEthSpec::slots_per_epoch());
+        let finalized_slot = fc_finalized.epoch.start_slot(TEthSpec::slots_per_epoch());
+
         let beacon_chain = BeaconChain {
             spec: self.spec,
             config: self.chain_config,
@@ -535,16 +550,25 @@ where
             op_pool: self
                 .op_pool
                 .ok_or_else(|| "Cannot build without op pool".to_string())?,
-            // TODO: allow for persisting and loading the pool from disk.
-            naive_aggregation_pool: <_>::default(),
+            naive_aggregation_pool: RwLock::new(NaiveAggregationPool::from_persisted(
+                persisted_caches.naive_aggregation_pool,
+                current_slot,
+                &log,
+            )),
             // TODO: allow for persisting and loading the pool from disk.
             observed_attestations: <_>::default(),
-            // TODO: allow for persisting and loading the pool from disk.
-            observed_attesters: <_>::default(),
-            // TODO: allow for persisting and loading the pool from disk.
-            observed_aggregators: <_>::default(),
-            // TODO: allow for persisting and loading the pool from disk.
-            observed_block_producers: <_>::default(),
+            observed_attesters: ObservedAttesters::from_persisted(
+                persisted_caches.observed_attesters,
+                current_epoch,
+            ),
+            observed_aggregators: ObservedAggregators::from_persisted(
+                persisted_caches.observed_aggregators,
+                current_epoch,
+            ),
+            observed_block_producers: ObservedBlockProducers::from_persisted(
+                persisted_caches.observed_block_producers,
+                finalized_slot,
+            ),
             // TODO: allow for persisting and loading the pool from disk.
             observed_voluntary_exits: <_>::default(),
             observed_proposer_slashings: <_>::default(),
@@ -12,6 +12,11 @@ pub struct ChainConfig {
     ///
     /// If `None`, there is no weak subjectivity verification.
     pub weak_subjectivity_checkpoint: Option<Checkpoint>,
+    /// The percentage of the committee weight which is added to a timely block from the current
+    /// slot when running fork choice.
+    ///
+    /// If `None`, there is no proposer score boost.
+    pub proposer_score_boost: Option<u64>,
 }
 
 impl Default for ChainConfig {
@@ -19,6 +24,7 @@ impl Default for ChainConfig {
         Self {
             import_max_skip_slots: None,
             weak_subjectivity_checkpoint: None,
+            proposer_score_boost: None,
         }
     }
 }
@@ -18,8 +18,10 @@ mod observed_attesters;
 mod observed_block_producers;
 pub mod observed_operations;
 mod persisted_beacon_chain;
+mod persisted_caches;
 mod persisted_fork_choice;
 mod shuffling_cache;
+pub mod slot_hook;
 mod snapshot_cache;
 pub mod test_utils;
 mod timeout_rw_lock;
@@ -27,17 +29,19 @@ mod validator_pubkey_cache;
 
 pub use self::beacon_chain::{
     AttestationProcessingOutcome, BeaconChain, BeaconChainTypes, ChainSegmentResult,
-    ForkChoiceError, StateSkipConfig,
+    ForkChoiceError, StateSkipConfig, MAXIMUM_GOSSIP_CLOCK_DISPARITY,
 };
 pub use self::beacon_snapshot::BeaconSnapshot;
 pub use self::chain_config::ChainConfig;
 pub use self::errors::{BeaconChainError, BlockProductionError};
+pub use self::slot_hook::{SlotHook, SlotOffset};
 pub use attestation_verification::Error as AttestationError;
 pub use beacon_fork_choice_store::{BeaconForkChoiceStore, Error as ForkChoiceStoreError};
 pub use block_verification::{BlockError, GossipVerifiedBlock};
 pub use eth1_chain::{Eth1Chain, Eth1ChainBackend};
 pub use events::EventHandler;
 pub use metrics::scrape_for_metrics;
+pub use operation_pool::{PackedAttestations, PackingStrategy};
 pub use parking_lot;
 pub use slot_clock;
 pub use state_processing::per_block_processing::errors::{
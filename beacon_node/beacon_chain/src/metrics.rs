@@ -68,6 +68,26 @@ lazy_static! {
     );
     pub static ref BLOCK_PRODUCTION_TIMES: Result<Histogram> =
         try_create_histogram("beacon_block_production_seconds", "Full runtime of block production");
+    pub static ref BLOCK_PRODUCTION_PACKING_TIMES: Result<HistogramVec> = try_create_histogram_vec(
+        "beacon_block_production_packing_seconds",
+        "Time spent packing attestations into a block, by packing strategy",
+        &["strategy"]
+    );
+    pub static ref BLOCK_PRODUCTION_PACKINGS: Result<IntCounterVec> = try_create_int_counter_vec(
+        "beacon_block_production_packings_total",
+        "Count of attestation packings for block production, by packing strategy",
+        &["strategy"]
+    );
+    pub static ref BLOCK_PRODUCTION_PACKING_REWARD: Result<IntCounterVec> = try_create_int_counter_vec(
+        "beacon_block_production_packing_reward_gwei_total",
+        "Sum of the expected proposer rewards of packed attestations, by packing strategy",
+        &["strategy"]
+    );
+    pub static ref BLOCK_PRODUCTION_PACKING_MAX_REWARD: Result<IntCounterVec> = try_create_int_counter_vec(
+        "beacon_block_production_packing_max_reward_gwei_total",
+        "Sum of the expected proposer rewards of the max_reward packing of the same attestations, by packing strategy",
+        &["strategy"]
+    );
 
     /*
      * Block Statistics
@@ -92,6 +112,10 @@ lazy_static! {
         "beacon_unaggregated_attestation_gossip_verification_seconds",
         "Full runtime of aggregated attestation gossip verification"
     );
+    pub static ref UNAGGREGATED_ATTESTATION_GOSSIP_BATCH_VERIFICATION_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_unaggregated_attestation_gossip_batch_verification_seconds",
+        "Full runtime of batched unaggregated attestation gossip verification"
+    );
 
     /*
      * Aggregated Attestation Verification
@@ -108,6 +132,10 @@ lazy_static! {
         "beacon_aggregated_attestation_gossip_verification_seconds",
         "Full runtime of aggregated attestation gossip verification"
     );
+    pub static ref AGGREGATED_ATTESTATION_GOSSIP_BATCH_VERIFICATION_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_aggregated_attestation_gossip_batch_verification_seconds",
+        "Full runtime of batched aggregated attestation gossip verification"
+    );
 
     /*
      * General Attestation Processing
@@ -172,6 +200,26 @@ lazy_static! {
         "beacon_attestation_processing_signature_seconds",
         "Time spent on the signature verification of attestation processing"
     );
+    pub static ref ATTESTATION_PROCESSING_BATCH_UNAGG_SIGNATURE_SETUP_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_attestation_processing_batch_unagg_signature_setup_seconds",
+        "Time spent on setting up for the signature verification of batch unaggregated attestation processing"
+    );
+    pub static ref ATTESTATION_PROCESSING_BATCH_UNAGG_SIGNATURE_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_attestation_processing_batch_unagg_signature_seconds",
+        "Time spent on the batch signature verification of unaggregated attestations"
+    );
+    pub static ref ATTESTATION_PROCESSING_BATCH_AGG_SIGNATURE_SETUP_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_attestation_processing_batch_agg_signature_setup_seconds",
+        "Time spent on setting up for the signature verification of batch aggregate processing"
+    );
+    pub static ref ATTESTATION_PROCESSING_BATCH_AGG_SIGNATURE_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_attestation_processing_batch_agg_signature_seconds",
+        "Time spent on the batch signature verification of aggregated attestations"
+    );
+    pub static ref ATTESTATION_PROCESSING_BATCH_SIGNATURE_FAILURES: Result<IntCounter> = try_create_int_counter(
+        "beacon_attestation_processing_batch_signature_failures_total",
+        "Count of attestation batches which failed signature verification and fell back to individual verification"
+    );
 
     /*
      * Shuffling cache
@@ -245,6 +293,8 @@ lazy_static! {
         try_create_histogram("beacon_persist_op_pool", "Time taken to persist the operations pool");
     pub static ref PERSIST_ETH1_CACHE: Result<Histogram> =
         try_create_histogram("beacon_persist_eth1_cache", "Time taken to persist the eth1 caches");
+    pub static ref PERSIST_CACHES: Result<Histogram> =
+        try_create_histogram("beacon_persist_caches", "Time taken to persist the gossip caches");
     pub static ref PERSIST_FORK_CHOICE: Result<Histogram> =
         try_create_histogram("beacon_persist_fork_choice", "Time taken to persist the fork choice struct");
 
@@ -1,4 +1,5 @@
 use crate::metrics;
+use slog::{debug, Logger};
 use std::collections::HashMap;
 use tree_hash::TreeHash;
 use types::{Attestation, AttestationData, EthSpec, Hash256, Slot};
@@ -16,6 +17,11 @@ const SLOTS_RETAINED: usize = 3;
 /// This is a DoS protection measure.
 const MAX_ATTESTATIONS_PER_SLOT: usize = 16_384;
 
+/// The initial capacity of the map for a slot, when it can't be estimated from earlier slots.
+///
+/// This is the mainnet default committee size.
+const DEFAULT_MAP_CAPACITY: usize = 128;
+
 /// Returned upon successfully inserting an attestation into the pool.
 #[derive(Debug, PartialEq)]
 pub enum InsertOutcome {
@@ -213,8 +219,8 @@ impl<E: EthSpec> NaiveAggregationPool<E> {
                 .map(|(_slot, map)| map.len())
                 .fold((0, 0), |(count, sum), len| (count + 1, sum + len));
 
-            // Use the mainnet default committee size if we can't determine an average.
-            let initial_capacity = sum.checked_div(count).unwrap_or(128);
+            // Use the default capacity if we can't determine an average.
+            let initial_capacity = sum.checked_div(count).unwrap_or(DEFAULT_MAP_CAPACITY);
 
             let mut item = AggregatedAttestationMap::new(initial_capacity);
             let outcome = item.insert(attestation);
@@ -249,6 +255,51 @@ impl<E: EthSpec> NaiveAggregationPool<E> {
         self.maps.iter().map(|(_slot, map)| map.iter()).flatten()
     }
 
+    /// Restores a pool from the aggregated attestations returned by `Self::iter`, dropping any
+    /// which are too old to be stored at `current_slot`.
+    pub fn from_persisted(
+        attestations: Vec<Attestation<E>>,
+        current_slot: Slot,
+        log: &Logger,
+    ) -> Self {
+        let mut pool = Self::default();
+        pool.prune(current_slot);
+
+        let mut dropped = 0_usize;
+        for attestation in attestations {
+            let slot = attestation.data.slot;
+            if slot < pool.lowest_permissible_slot {
+                continue;
+            }
+
+            let map = pool
+                .maps
+                .entry(slot)
+                .or_insert_with(|| AggregatedAttestationMap::new(DEFAULT_MAP_CAPACITY));
+            if map.len() < MAX_ATTESTATIONS_PER_SLOT {
+                map.map
+                    .insert(attestation.data.tree_hash_root(), attestation);
+            } else {
+                dropped += 1;
+            }
+        }
+
+        if dropped > 0 {
+            debug!(
+                log,
+                "Dropped persisted attestations";
+                "reason" => "reached max attestations per slot",
+                "max_attestations_per_slot" => MAX_ATTESTATIONS_PER_SLOT,
+                "count" => dropped,
+            );
+        }
+
+        // Restoring attestations from the future could leave too many slots in the pool.
+        pool.prune(current_slot);
+
+        pool
+    }
+
     /// Removes any attestations with a slot lower than `current_slot` and bars any future
     /// attestations with a slot lower than `current_slot - SLOTS_RETAINED`.
     pub fn prune(&mut self, current_slot: Slot) {
@@ -47,6 +47,9 @@ pub trait Item {
 
     /// Returns `true` if `validator_index` has been stored in `self`.
     fn contains(&self, validator_index: usize) -> bool;
+
+    /// Returns all the validator indices that have been stored in `self`.
+    fn validator_indices(&self) -> Vec<usize>;
 }
 
 /// Stores a `BitVec` that represents which validator indices have attested during an epoch.
@@ -98,6 +101,15 @@ impl Item for EpochBitfield {
     fn contains(&self, validator_index: usize) -> bool {
         self.bitfield.get(validator_index).map_or(false, |bit| *bit)
     }
+
+    fn validator_indices(&self) -> Vec<usize> {
+        self.bitfield
+            .iter()
+            .enumerate()
+            .filter(|(_, bit)| **bit)
+            .map(|(validator_index, _)| validator_index)
+            .collect()
+    }
 }
 
 /// Stores a `HashSet` of which validator indices have created an aggregate attestation during an
@@ -137,6 +149,10 @@ impl Item for EpochHashSet {
     fn contains(&self, validator_index: usize) -> bool {
         self.set.contains(&validator_index)
     }
+
+    fn validator_indices(&self) -> Vec<usize> {
+        self.set.iter().copied().collect()
+    }
 }
 
 /// A container that stores some number of `T` items.
@@ -256,6 +272,45 @@ impl<T: Item, E: EthSpec> AutoPruningContainer<T, E> {
         Ok(())
     }
 
+    /// Returns the validator indices observed in each epoch, for persisting to disk.
+    pub fn to_persisted(&self) -> Vec<(Epoch, Vec<u64>)> {
+        self.items
+            .read()
+            .iter()
+            .map(|(epoch, item)| {
+                let validator_indices = item
+                    .validator_indices()
+                    .into_iter()
+                    .map(|i| i as u64)
+                    .collect();
+                (*epoch, validator_indices)
+            })
+            .collect()
+    }
+
+    /// Restores `self` from the output of `Self::to_persisted`, dropping any epochs which are no
+    /// longer relevant at `current_epoch`.
+    pub fn from_persisted(persisted: Vec<(Epoch, Vec<u64>)>, current_epoch: Epoch) -> Self {
+        let container = Self::default();
+        container.prune(current_epoch);
+        let lowest_permissible_epoch = *container.lowest_permissible_epoch.read();
+
+        container.items.write().extend(
+            persisted
+                .into_iter()
+                .filter(|(epoch, _)| *epoch >= lowest_permissible_epoch)
+                .map(|(epoch, validator_indices)| {
+                    let mut item = T::with_capacity(validator_indices.len());
+                    for validator_index in validator_indices {
+                        item.insert(validator_index as usize);
+                    }
+                    (epoch, item)
+                }),
+        );
+
+        container
+    }
+
     /// The maximum number of epochs stored in `self`.
     fn max_capacity(&self) -> u64 {
         // The current epoch and the previous epoch. This is sufficient whilst
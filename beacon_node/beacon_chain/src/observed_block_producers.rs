@@ -103,6 +103,28 @@ impl<E: EthSpec> ObservedBlockProducers<E> {
         Ok(())
     }
 
+    /// Returns the proposers observed at each slot, for persisting to disk.
+    pub fn to_persisted(&self) -> Vec<(Slot, Vec<u64>)> {
+        self.items
+            .read()
+            .iter()
+            .map(|(slot, set)| (*slot, set.iter().copied().collect()))
+            .collect()
+    }
+
+    /// Restores `self` from the output of `Self::to_persisted`, dropping any observations of
+    /// blocks equal to or earlier than `finalized_slot`.
+    pub fn from_persisted(persisted: Vec<(Slot, Vec<u64>)>, finalized_slot: Slot) -> Self {
+        let observed = Self::default();
+        observed.items.write().extend(
+            persisted
+                .into_iter()
+                .map(|(slot, proposers)| (slot, proposers.into_iter().collect())),
+        );
+        observed.prune(finalized_slot);
+        observed
+    }
+
     /// Removes all observations of blocks equal to or earlier than `finalized_slot`.
     ///
     /// Stores `finalized_slot` in `self`, so that `self` will reject any block that has a slot
@@ -0,0 +1,34 @@
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use store::{DBColumn, Error as StoreError, StoreItem};
+use types::{Attestation, Epoch, EthSpec, Slot};
+
+/// SSZ-serializable version of the caches that the `BeaconChain` uses to process gossip.
+///
+/// These caches are only useful for a few epochs, so they're restored on a best-effort basis:
+/// anything that is no longer relevant to the current slot is dropped when they're loaded.
+#[derive(Debug, Default, Encode, Decode)]
+pub struct PersistedCaches<E: EthSpec> {
+    /// The aggregated attestations in the `NaiveAggregationPool`.
+    pub naive_aggregation_pool: Vec<Attestation<E>>,
+    /// The validators that have been observed attesting in each epoch.
+    pub observed_attesters: Vec<(Epoch, Vec<u64>)>,
+    /// The validators that have been observed aggregating in each epoch.
+    pub observed_aggregators: Vec<(Epoch, Vec<u64>)>,
+    /// The validators that have been observed proposing in each slot.
+    pub observed_block_producers: Vec<(Slot, Vec<u64>)>,
+}
+
+impl<E: EthSpec> StoreItem for PersistedCaches<E> {
+    fn db_column() -> DBColumn {
+        DBColumn::GossipCaches
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Self::from_ssz_bytes(bytes).map_err(Into::into)
+    }
+}
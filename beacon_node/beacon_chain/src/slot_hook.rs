@@ -0,0 +1,128 @@
+//! Defines hooks which run at a fixed point within every slot.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A point within a slot, relative to the start of the slot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlotOffset {
+    /// At `numerator / denominator` of the way through the slot (e.g., `1/3`).
+    Fraction { numerator: u32, denominator: u32 },
+    /// At the given duration before the start of the next slot.
+    BeforeNextSlot(Duration),
+}
+
+impl SlotOffset {
+    /// Returns the duration from the start of a slot until `self`.
+    ///
+    /// The result is never greater than `slot_duration`.
+    pub fn duration_into_slot(&self, slot_duration: Duration) -> Duration {
+        let offset = match self {
+            SlotOffset::Fraction {
+                numerator,
+                denominator,
+            } => {
+                if *denominator == 0 {
+                    slot_duration
+                } else {
+                    slot_duration * *numerator / *denominator
+                }
+            }
+            SlotOffset::BeforeNextSlot(duration) => slot_duration
+                .checked_sub(*duration)
+                .unwrap_or_else(|| Duration::from_secs(0)),
+        };
+
+        std::cmp::min(offset, slot_duration)
+    }
+}
+
+/// A task which is run once per slot, at `offset` into the slot.
+///
+/// Hooks are run by the timer service on the blocking thread pool, so the task may perform
+/// blocking work such as disk writes. A hook is not run again until its previous run has
+/// completed.
+#[derive(Clone)]
+pub struct SlotHook {
+    name: &'static str,
+    offset: SlotOffset,
+    task: Arc<dyn Fn() + Send + Sync>,
+}
+
+impl SlotHook {
+    /// Create a new hook which runs `task` at `offset` into each slot.
+    ///
+    /// The `name` is used for logging and as the label for the hook metrics.
+    pub fn new<F>(name: &'static str, offset: SlotOffset, task: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        Self {
+            name,
+            offset,
+            task: Arc::new(task),
+        }
+    }
+
+    /// The name of the hook, for logging and metrics.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The point within each slot at which the hook runs.
+    pub fn offset(&self) -> SlotOffset {
+        self.offset
+    }
+
+    /// Runs the hook's task, blocking until it completes.
+    pub fn run(&self) {
+        (self.task)()
+    }
+}
+
+impl fmt::Debug for SlotHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlotHook")
+            .field("name", &self.name)
+            .field("offset", &self.offset)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SLOT: Duration = Duration::from_secs(12);
+
+    #[test]
+    fn offsets() {
+        let third = SlotOffset::Fraction {
+            numerator: 1,
+            denominator: 3,
+        };
+        assert_eq!(third.duration_into_slot(SLOT), Duration::from_secs(4));
+
+        let two_thirds = SlotOffset::Fraction {
+            numerator: 2,
+            denominator: 3,
+        };
+        assert_eq!(two_thirds.duration_into_slot(SLOT), Duration::from_secs(8));
+
+        let too_large = SlotOffset::Fraction {
+            numerator: 4,
+            denominator: 3,
+        };
+        assert_eq!(too_large.duration_into_slot(SLOT), SLOT);
+
+        let before = SlotOffset::BeforeNextSlot(Duration::from_secs(2));
+        assert_eq!(before.duration_into_slot(SLOT), Duration::from_secs(10));
+
+        let before_too_large = SlotOffset::BeforeNextSlot(Duration::from_secs(13));
+        assert_eq!(
+            before_too_large.duration_into_slot(SLOT),
+            Duration::from_secs(0)
+        );
+    }
+}
@@ -8,7 +8,7 @@ use crate::{
     builder::{BeaconChainBuilder, Witness},
     eth1_chain::CachingEth1Backend,
     events::NullEventHandler,
-    BeaconChain, BeaconChainTypes, BlockError, ChainConfig, StateSkipConfig,
+    BeaconChain, BeaconChainTypes, BlockError, ChainConfig, PackingStrategy, StateSkipConfig,
 };
 use futures::channel::mpsc::Receiver;
 use genesis::interop_genesis_state;
@@ -410,7 +410,13 @@ where
 
         let (block, state) = self
             .chain
-            .produce_block_on_state(state, slot, randao_reveal, Some(graffiti))
+            .produce_block_on_state(
+                state,
+                slot,
+                randao_reveal,
+                Some(graffiti),
+                PackingStrategy::default(),
+            )
             .unwrap();
 
         let signed_block = block.sign(
@@ -52,6 +52,17 @@ fn get_harness(validator_count: usize) -> BeaconChainHarness<EphemeralHarnessTyp
 /// Also returns some info about who created it.
 fn get_valid_unaggregated_attestation<T: BeaconChainTypes>(
     chain: &BeaconChain<T>,
+) -> (Attestation<T::EthSpec>, usize, usize, SecretKey, SubnetId) {
+    get_valid_unaggregated_attestation_at_position(chain, 0)
+}
+
+/// Returns an attestation that is valid for some slot in the given `chain`, signed by the member
+/// of the committee at `validator_committee_index`.
+///
+/// Also returns some info about who created it.
+fn get_valid_unaggregated_attestation_at_position<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    validator_committee_index: usize,
 ) -> (Attestation<T::EthSpec>, usize, usize, SecretKey, SubnetId) {
     let head = chain.head().expect("should get head");
     let current_slot = chain.slot().expect("should get slot");
@@ -60,7 +71,6 @@ fn get_valid_unaggregated_attestation<T: BeaconChainTypes>(
         .produce_unaggregated_attestation(current_slot, 0)
         .expect("should not error while producing attestation");
 
-    let validator_committee_index = 0;
     let validator_index = *head
         .beacon_state
         .get_beacon_committee(current_slot, valid_attestation.data.index)
@@ -942,3 +952,131 @@ fn attestation_that_skips_epochs() {
         .verify_unaggregated_attestation_for_gossip(attestation, Some(subnet_id))
         .expect("should gossip verify attestation that skips slots");
 }
+
+/// Tests that a batch of valid unaggregated attestations is verified, and that the valid
+/// attestations in a batch with an invalid signature are still accepted.
+#[test]
+fn batch_unaggregated_gossip_verification() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        MainnetEthSpec::slots_per_epoch() as usize * 3 - 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    // Advance into a slot where there have not been blocks or attestations produced.
+    harness.advance_slot();
+
+    let attestations = (0..6)
+        .map(|position| {
+            let (attestation, _, _, _, subnet_id) =
+                get_valid_unaggregated_attestation_at_position(&harness.chain, position);
+            (attestation, Some(subnet_id))
+        })
+        .collect::<Vec<_>>();
+
+    /*
+     * A batch of valid attestations.
+     */
+
+    let results = harness
+        .chain
+        .batch_verify_unaggregated_attestations_for_gossip(attestations[0..3].to_vec().into_iter())
+        .expect("should verify batch");
+
+    assert_eq!(results.len(), 3);
+    for (result, (attestation, _)) in results.iter().zip(&attestations[0..3]) {
+        assert_eq!(
+            result.as_ref().expect("should be valid").attestation(),
+            attestation
+        );
+    }
+
+    /*
+     * A batch containing an invalid signature and an already-seen attestation. The remaining
+     * attestations are verified individually.
+     */
+
+    let mut batch = attestations[3..6].to_vec();
+    batch[1].0.signature = batch[0].0.signature.clone();
+    batch.push(attestations[0].clone());
+
+    let results = harness
+        .chain
+        .batch_verify_unaggregated_attestations_for_gossip(batch.into_iter())
+        .expect("should verify batch");
+
+    assert_eq!(results.len(), 4);
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(AttnError::InvalidSignature)));
+    assert!(results[2].is_ok());
+    assert!(matches!(
+        results[3],
+        Err(AttnError::PriorAttestationKnown { .. })
+    ));
+}
+
+/// Tests that a batch of valid aggregates is verified, and that the valid aggregates in a batch
+/// with an invalid signature are still accepted.
+#[test]
+fn batch_aggregated_gossip_verification() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        MainnetEthSpec::slots_per_epoch() as usize * 3 - 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    // Advance into a slot where there have not been blocks or attestations produced.
+    harness.advance_slot();
+
+    /*
+     * A batch of valid aggregates.
+     */
+
+    let (attestation, _, _, _, _) = get_valid_unaggregated_attestation(&harness.chain);
+    let (valid_aggregate, _, _) = get_valid_aggregated_attestation(&harness.chain, attestation);
+
+    let results = harness
+        .chain
+        .batch_verify_aggregated_attestations_for_gossip(vec![valid_aggregate.clone()].into_iter())
+        .expect("should verify batch");
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(
+        results[0].as_ref().expect("should be valid").aggregate(),
+        &valid_aggregate
+    );
+
+    /*
+     * A batch containing an invalid signature and an already-seen aggregate. The remaining
+     * aggregate is verified individually.
+     */
+
+    harness.advance_slot();
+
+    let (attestation, _, _, _, _) = get_valid_unaggregated_attestation(&harness.chain);
+    let (new_aggregate, _, _) = get_valid_aggregated_attestation(&harness.chain, attestation);
+    let mut invalid_aggregate = new_aggregate.clone();
+    invalid_aggregate.signature = valid_aggregate.signature.clone();
+
+    let results = harness
+        .chain
+        .batch_verify_aggregated_attestations_for_gossip(
+            vec![invalid_aggregate, new_aggregate.clone(), valid_aggregate].into_iter(),
+        )
+        .expect("should verify batch");
+
+    assert_eq!(results.len(), 3);
+    assert!(matches!(results[0], Err(AttnError::InvalidSignature)));
+    assert_eq!(
+        results[1].as_ref().expect("should be valid").aggregate(),
+        &new_aggregate
+    );
+    assert!(matches!(
+        results[2],
+        Err(AttnError::AttestationAlreadyKnown(_))
+    ));
+}
@@ -4,14 +4,18 @@
 extern crate lazy_static;
 
 use beacon_chain::{
-    test_utils::{AttestationStrategy, BeaconChainHarness, BlockStrategy},
+    builder::BeaconChainBuilder,
+    migrate::MigratorConfig,
+    slot_clock::{SlotClock, TestingSlotClock},
+    test_utils::{AttestationStrategy, BeaconChainHarness, BlockStrategy, DiskHarnessType},
     BeaconChain, BeaconChainTypes,
 };
 use sloggers::{null::NullLoggerBuilder, Build};
 use std::sync::Arc;
+use std::time::Duration;
 use store::{HotColdDB, LevelDB, StoreConfig};
 use tempfile::{tempdir, TempDir};
-use types::{EthSpec, Keypair, MinimalEthSpec};
+use types::{EthSpec, Keypair, MinimalEthSpec, Slot};
 
 type E = MinimalEthSpec;
 
@@ -137,6 +141,185 @@ fn finalizes_after_resuming_from_db() {
     );
 }
 
+/// Builds a harness and imports a few blocks and attestations, populating the gossip caches.
+///
+/// The head block's proposer is also marked as observed, since the harness doesn't import blocks
+/// via gossip.
+fn harness_with_gossip_caches(
+    store: Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>>,
+) -> BeaconChainHarness<DiskHarnessType<E>> {
+    let harness = BeaconChainHarness::new_with_disk_store(
+        MinimalEthSpec,
+        store,
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+    );
+
+    harness.advance_slot();
+    harness.extend_chain(
+        E::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head_block = harness.chain.head().unwrap().beacon_block;
+    harness
+        .chain
+        .observed_block_producers
+        .observe_proposer(&head_block.message)
+        .unwrap();
+
+    harness
+        .chain
+        .persist_head_and_fork_choice()
+        .expect("should persist the head and fork choice");
+    harness
+        .chain
+        .persist_op_pool()
+        .expect("should persist the op pool");
+    harness
+        .chain
+        .persist_caches()
+        .expect("should persist the caches");
+
+    harness
+}
+
+#[test]
+fn restores_caches_after_resuming_from_db() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+
+    let harness = harness_with_gossip_caches(store.clone());
+    let data_dir = harness.data_dir;
+    let original_chain = harness.chain;
+    let epoch = original_chain.epoch().unwrap();
+    let head_block = original_chain.head().unwrap().beacon_block;
+    let naive_aggregates = original_chain
+        .naive_aggregation_pool
+        .read()
+        .iter()
+        .cloned()
+        .collect::<Vec<_>>();
+
+    assert!(
+        !naive_aggregates.is_empty(),
+        "the naive aggregation pool should not be empty"
+    );
+    assert!(
+        original_chain
+            .observed_attesters
+            .observed_validator_count(epoch)
+            .unwrap_or(0)
+            > 0,
+        "attesters should have been observed"
+    );
+
+    let resumed_harness = BeaconChainHarness::resume_from_disk_store(
+        MinimalEthSpec,
+        store,
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+        data_dir,
+    );
+    let resumed_chain = &resumed_harness.chain;
+
+    for aggregate in &naive_aggregates {
+        assert_eq!(
+            resumed_chain
+                .naive_aggregation_pool
+                .read()
+                .get(&aggregate.data),
+            Some(aggregate.clone()),
+            "the naive aggregation pool should be restored"
+        );
+    }
+    assert_eq!(
+        resumed_chain
+            .observed_attesters
+            .observed_validator_count(epoch),
+        original_chain
+            .observed_attesters
+            .observed_validator_count(epoch),
+        "the observed attesters should be restored"
+    );
+    assert_eq!(
+        resumed_chain
+            .observed_aggregators
+            .observed_validator_count(epoch),
+        original_chain
+            .observed_aggregators
+            .observed_validator_count(epoch),
+        "the observed aggregators should be restored"
+    );
+    assert!(
+        resumed_chain
+            .observed_block_producers
+            .proposer_has_been_observed(&head_block.message)
+            .unwrap(),
+        "the observed block producers should be restored"
+    );
+}
+
+#[test]
+fn drops_stale_caches_after_resuming_from_db() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+
+    let harness = harness_with_gossip_caches(store.clone());
+    let genesis_time = harness.chain.head().unwrap().beacon_state.genesis_time;
+    let epoch = harness.chain.epoch().unwrap();
+    let head_block = harness.chain.head().unwrap().beacon_block;
+
+    // Resume several epochs later, when none of the caches are relevant any more.
+    let slot_clock = TestingSlotClock::new(
+        Slot::new(0),
+        Duration::from_secs(genesis_time),
+        Duration::from_secs(1),
+    );
+    slot_clock.set_slot((epoch + 4).start_slot(E::slots_per_epoch()).as_u64());
+
+    let (shutdown_tx, _shutdown_rx) = futures::channel::mpsc::channel(1);
+    let resumed_chain = BeaconChainBuilder::new(MinimalEthSpec)
+        .logger(NullLoggerBuilder.build().unwrap())
+        .custom_spec(E::default_spec())
+        .store(store)
+        .store_migrator_config(MigratorConfig::default().blocking())
+        .data_dir(harness.data_dir.path().to_path_buf())
+        .resume_from_db()
+        .expect("should resume beacon chain from db")
+        .dummy_eth1_backend()
+        .expect("should build dummy backend")
+        .null_event_handler()
+        .slot_clock(slot_clock)
+        .shutdown_sender(shutdown_tx)
+        .build()
+        .expect("should build");
+
+    assert_eq!(
+        resumed_chain.naive_aggregation_pool.read().iter().count(),
+        0,
+        "stale naive aggregates should be dropped"
+    );
+    assert_eq!(
+        resumed_chain
+            .observed_attesters
+            .observed_validator_count(epoch),
+        None,
+        "stale observed attesters should be dropped"
+    );
+    assert_eq!(
+        resumed_chain
+            .observed_aggregators
+            .observed_validator_count(epoch),
+        None,
+        "stale observed aggregators should be dropped"
+    );
+    // Block producers are only pruned by finality.
+    assert!(resumed_chain
+        .observed_block_producers
+        .proposer_has_been_observed(&head_block.message)
+        .unwrap());
+}
+
 /// Checks that two chains are the same, for the purpose of this tests.
 ///
 /// Several fields that are hard/impossible to check are ignored (e.g., the store).
@@ -156,8 +339,8 @@ fn assert_chains_pretty_much_the_same<T: BeaconChainTypes>(a: &BeaconChain<T>, b
 
     let slot = a.slot().unwrap();
     assert!(
-        a.fork_choice.write().get_head(slot).unwrap()
-            == b.fork_choice.write().get_head(slot).unwrap(),
+        a.fork_choice.write().get_head(slot, None).unwrap()
+            == b.fork_choice.write().get_head(slot, None).unwrap(),
         "fork_choice heads should be equal"
     );
 }
@@ -1,13 +1,14 @@
+use crate::error;
 use crate::types::{GossipEncoding, GossipKind, GossipTopic};
-use crate::{error, TopicHash};
 use libp2p::gossipsub::{
     GenericGossipsubConfig, IdentTopic as Topic, PeerScoreParams, PeerScoreThresholds,
     TopicScoreParams,
 };
+use serde::Serialize;
 use std::cmp::max;
 use std::collections::HashMap;
 use std::marker::PhantomData;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use types::{ChainSpec, EnrForkId, EthSpec, Slot, SubnetId};
 
 const MAX_IN_MESH_SCORE: f64 = 10.0;
@@ -18,6 +19,173 @@ const VOLUNTARY_EXIT_WEIGHT: f64 = 0.05;
 const PROPOSER_SLASHING_WEIGHT: f64 = 0.05;
 const ATTESTER_SLASHING_WEIGHT: f64 = 0.05;
 
+/// Counts the gossip messages received on each kind of topic, to measure their message rates.
+pub struct MessageRates {
+    slot: Duration,
+    counts: HashMap<GossipKind, u64>,
+    since: Instant,
+}
+
+impl MessageRates {
+    pub fn new(slot: Duration) -> Self {
+        MessageRates {
+            slot,
+            counts: HashMap::new(),
+            since: Instant::now(),
+        }
+    }
+
+    /// Records a message received on a topic of the given kind.
+    pub fn observe(&mut self, kind: GossipKind) {
+        *self.counts.entry(kind).or_insert(0) += 1;
+    }
+
+    /// Returns the average number of messages per slot received on each of the `kinds` that
+    /// received any messages since the last reset.
+    ///
+    /// Returns `None` if no messages were received or less than a slot has passed.
+    pub fn message_rate(&self, kinds: &[GossipKind]) -> Option<f64> {
+        let slots = self.since.elapsed().as_secs_f64() / self.slot.as_secs_f64();
+        let counts = kinds
+            .iter()
+            .filter_map(|kind| self.counts.get(kind))
+            .collect::<Vec<_>>();
+        if slots < 1.0 || counts.is_empty() {
+            return None;
+        }
+        Some(counts.iter().copied().sum::<u64>() as f64 / counts.len() as f64 / slots)
+    }
+
+    /// Clears all counts, starting a new measurement period.
+    pub fn reset(&mut self) {
+        self.counts.clear();
+        self.since = Instant::now();
+    }
+}
+
+/// The score parameters shared by one or more kinds of topics.
+pub struct KindScoreParams {
+    /// A name for the kinds of topics, e.g. `beacon_attestation` for all attestation subnets.
+    pub name: &'static str,
+    pub kinds: Vec<GossipKind>,
+    /// The number of messages per slot that the parameters were computed for.
+    pub expected_message_rate: f64,
+    /// The number of messages per slot observed since the last update, if any were received.
+    pub observed_message_rate: Option<f64>,
+    pub params: TopicScoreParams,
+}
+
+/// The current score parameters of a kind of topic, as exposed by the HTTP API.
+#[derive(Clone, Debug, Serialize)]
+pub struct TopicScoreParamsSummary {
+    pub name: String,
+    pub expected_message_rate: f64,
+    pub observed_message_rate: Option<f64>,
+    pub topic_weight: f64,
+    pub time_in_mesh_weight: f64,
+    pub time_in_mesh_quantum_millis: u64,
+    pub time_in_mesh_cap: f64,
+    pub first_message_deliveries_weight: f64,
+    pub first_message_deliveries_decay: f64,
+    pub first_message_deliveries_cap: f64,
+    pub mesh_message_deliveries_weight: f64,
+    pub mesh_message_deliveries_decay: f64,
+    pub mesh_message_deliveries_threshold: f64,
+    pub mesh_message_deliveries_cap: f64,
+    pub mesh_message_deliveries_activation_millis: u64,
+    pub mesh_message_deliveries_window_millis: u64,
+    pub mesh_failure_penalty_weight: f64,
+    pub mesh_failure_penalty_decay: f64,
+    pub invalid_message_deliveries_weight: f64,
+    pub invalid_message_deliveries_decay: f64,
+}
+
+impl From<&KindScoreParams> for TopicScoreParamsSummary {
+    fn from(kind_params: &KindScoreParams) -> Self {
+        let params = &kind_params.params;
+        TopicScoreParamsSummary {
+            name: kind_params.name.to_string(),
+            expected_message_rate: kind_params.expected_message_rate,
+            observed_message_rate: kind_params.observed_message_rate,
+            topic_weight: params.topic_weight,
+            time_in_mesh_weight: params.time_in_mesh_weight,
+            time_in_mesh_quantum_millis: params.time_in_mesh_quantum.as_millis() as u64,
+            time_in_mesh_cap: params.time_in_mesh_cap,
+            first_message_deliveries_weight: params.first_message_deliveries_weight,
+            first_message_deliveries_decay: params.first_message_deliveries_decay,
+            first_message_deliveries_cap: params.first_message_deliveries_cap,
+            mesh_message_deliveries_weight: params.mesh_message_deliveries_weight,
+            mesh_message_deliveries_decay: params.mesh_message_deliveries_decay,
+            mesh_message_deliveries_threshold: params.mesh_message_deliveries_threshold,
+            mesh_message_deliveries_cap: params.mesh_message_deliveries_cap,
+            mesh_message_deliveries_activation_millis: params
+                .mesh_message_deliveries_activation
+                .as_millis() as u64,
+            mesh_message_deliveries_window_millis: params.mesh_message_deliveries_window.as_millis()
+                as u64,
+            mesh_failure_penalty_weight: params.mesh_failure_penalty_weight,
+            mesh_failure_penalty_decay: params.mesh_failure_penalty_decay,
+            invalid_message_deliveries_weight: params.invalid_message_deliveries_weight,
+            invalid_message_deliveries_decay: params.invalid_message_deliveries_decay,
+        }
+    }
+}
+
+/// The current peer score parameters, as exposed by the HTTP API.
+#[derive(Clone, Debug, Serialize)]
+pub struct PeerScoreParamsSummary {
+    /// The number of active validators the topic parameters were computed for.
+    pub active_validators: usize,
+    /// The slot at which the topic parameters were computed.
+    pub current_slot: Slot,
+    pub topic_score_cap: f64,
+    pub app_specific_weight: f64,
+    pub ip_colocation_factor_weight: f64,
+    pub ip_colocation_factor_threshold: f64,
+    pub behaviour_penalty_weight: f64,
+    pub behaviour_penalty_threshold: f64,
+    pub behaviour_penalty_decay: f64,
+    pub decay_interval_millis: u64,
+    pub decay_to_zero: f64,
+    pub topics: Vec<TopicScoreParamsSummary>,
+}
+
+impl PeerScoreParamsSummary {
+    pub fn new(
+        params: &PeerScoreParams,
+        active_validators: usize,
+        current_slot: Slot,
+        topic_params: &[KindScoreParams],
+    ) -> Self {
+        PeerScoreParamsSummary {
+            active_validators,
+            current_slot,
+            topic_score_cap: params.topic_score_cap,
+            app_specific_weight: params.app_specific_weight,
+            ip_colocation_factor_weight: params.ip_colocation_factor_weight,
+            ip_colocation_factor_threshold: params.ip_colocation_factor_threshold,
+            behaviour_penalty_weight: params.behaviour_penalty_weight,
+            behaviour_penalty_threshold: params.behaviour_penalty_threshold,
+            behaviour_penalty_decay: params.behaviour_penalty_decay,
+            decay_interval_millis: params.decay_interval.as_millis() as u64,
+            decay_to_zero: params.decay_to_zero,
+            topics: topic_params.iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Replaces the topic parameters after they have been recomputed.
+    pub fn update_topics(
+        &mut self,
+        active_validators: usize,
+        current_slot: Slot,
+        topic_params: &[KindScoreParams],
+    ) {
+        self.active_validators = active_validators;
+        self.current_slot = current_slot;
+        self.topics = topic_params.iter().map(Into::into).collect();
+    }
+}
+
 pub struct PeerScoreSettings<TSpec: EthSpec> {
     slot: Duration,
     epoch: Duration,
@@ -67,13 +235,14 @@ impl<TSpec: EthSpec> PeerScoreSettings<TSpec> {
         }
     }
 
+    /// Returns the peer score parameters, using `topic_params` from `Self::get_topic_params` for
+    /// the topics.
     pub fn get_peer_score_params(
         &self,
-        active_validators: usize,
         thresholds: &PeerScoreThresholds,
         enr_fork_id: &EnrForkId,
-        current_slot: Slot,
-    ) -> error::Result<PeerScoreParams> {
+        topic_params: &[KindScoreParams],
+    ) -> PeerScoreParams {
         let mut params = PeerScoreParams::default();
 
         params.decay_interval = self.decay_interval;
@@ -95,127 +264,151 @@ impl<TSpec: EthSpec> PeerScoreSettings<TSpec> {
         params.ip_colocation_factor_weight = -params.topic_score_cap;
 
         params.topics = HashMap::new();
+        for kind_params in topic_params {
+            for kind in &kind_params.kinds {
+                let topic: Topic = GossipTopic::new(
+                    kind.clone(),
+                    GossipEncoding::default(),
+                    enr_fork_id.fork_digest,
+                )
+                .into();
+                params
+                    .topics
+                    .insert(topic.hash(), kind_params.params.clone());
+            }
+        }
 
-        let get_hash = |kind: GossipKind| -> TopicHash {
-            let topic: Topic =
-                GossipTopic::new(kind, GossipEncoding::default(), enr_fork_id.fork_digest).into();
-            topic.hash()
+        params
+    }
+
+    /// Computes the score parameters of every kind of topic.
+    ///
+    /// The expected message rates of blocks, aggregates and attestations follow from the number of
+    /// active validators. The rates of the remaining topics can not be predicted, so the rate
+    /// observed since the last update is used whenever it exceeds the default rate.
+    pub fn get_topic_params(
+        &self,
+        active_validators: usize,
+        current_slot: Slot,
+        rates: &MessageRates,
+    ) -> error::Result<Vec<KindScoreParams>> {
+        let (aggregators_per_slot, committees_per_slot) =
+            self.expected_aggregator_count_per_slot(active_validators)?;
+        let multiple_bursts_per_subnet_per_epoch = committees_per_slot as u64
+            >= 2 * self.attestation_subnet_count / TSpec::slots_per_epoch();
+
+        let kind_params = |name: &'static str,
+                           kinds: Vec<GossipKind>,
+                           expected_message_rate: f64,
+                           params: TopicScoreParams| {
+            KindScoreParams {
+                name,
+                observed_message_rate: rates.message_rate(&kinds),
+                kinds,
+                expected_message_rate,
+                params,
+            }
         };
+        let observed_or_default = |kind: GossipKind, default_rate: f64| {
+            rates
+                .message_rate(&[kind])
+                .map_or(default_rate, |rate| rate.max(default_rate))
+        };
+
+        let mut topic_params = vec![];
 
-        //first all fixed topics
-        params.topics.insert(
-            get_hash(GossipKind::VoluntaryExit),
-            Self::get_topic_params(
-                self,
+        // The topics with unpredictable message rates.
+        for (name, kind, weight, default_rate) in &[
+            (
+                "voluntary_exit",
+                GossipKind::VoluntaryExit,
                 VOLUNTARY_EXIT_WEIGHT,
                 4.0 / TSpec::slots_per_epoch() as f64,
-                self.epoch * 100,
-                None,
             ),
-        );
-        params.topics.insert(
-            get_hash(GossipKind::AttesterSlashing),
-            Self::get_topic_params(
-                self,
+            (
+                "attester_slashing",
+                GossipKind::AttesterSlashing,
                 ATTESTER_SLASHING_WEIGHT,
                 1.0 / 5.0 / TSpec::slots_per_epoch() as f64,
-                self.epoch * 100,
-                None,
             ),
-        );
-        params.topics.insert(
-            get_hash(GossipKind::ProposerSlashing),
-            Self::get_topic_params(
-                self,
+            (
+                "proposer_slashing",
+                GossipKind::ProposerSlashing,
                 PROPOSER_SLASHING_WEIGHT,
                 1.0 / 5.0 / TSpec::slots_per_epoch() as f64,
-                self.epoch * 100,
-                None,
             ),
-        );
-
-        //dynamic topics
-        let (beacon_block_params, beacon_aggregate_proof_params, beacon_attestation_subnet_params) =
-            self.get_dynamic_topic_params(active_validators, current_slot)?;
-
-        params
-            .topics
-            .insert(get_hash(GossipKind::BeaconBlock), beacon_block_params);
-
-        params.topics.insert(
-            get_hash(GossipKind::BeaconAggregateAndProof),
-            beacon_aggregate_proof_params,
-        );
-
-        for i in 0..self.attestation_subnet_count {
-            params.topics.insert(
-                get_hash(GossipKind::Attestation(SubnetId::new(i))),
-                beacon_attestation_subnet_params.clone(),
-            );
+        ] {
+            let rate = observed_or_default(kind.clone(), *default_rate);
+            topic_params.push(kind_params(
+                *name,
+                vec![kind.clone()],
+                rate,
+                self.get_single_topic_params(*weight, rate, self.epoch * 100, None),
+            ));
         }
 
-        Ok(params)
-    }
-
-    pub fn get_dynamic_topic_params(
-        &self,
-        active_validators: usize,
-        current_slot: Slot,
-    ) -> error::Result<(TopicScoreParams, TopicScoreParams, TopicScoreParams)> {
-        let (aggregators_per_slot, committees_per_slot) =
-            self.expected_aggregator_count_per_slot(active_validators)?;
-        let multiple_bursts_per_subnet_per_epoch = committees_per_slot as u64
-            >= 2 * self.attestation_subnet_count / TSpec::slots_per_epoch();
-
-        let beacon_block_params = Self::get_topic_params(
-            self,
-            BEACON_BLOCK_WEIGHT,
+        // The topics with message rates that depend on the number of active validators.
+        topic_params.push(kind_params(
+            "beacon_block",
+            vec![GossipKind::BeaconBlock],
             1.0,
-            self.epoch * 20,
-            Some((TSpec::slots_per_epoch() * 5, 3.0, self.epoch, current_slot)),
-        );
+            self.get_single_topic_params(
+                BEACON_BLOCK_WEIGHT,
+                1.0,
+                self.epoch * 20,
+                Some((TSpec::slots_per_epoch() * 5, 3.0, self.epoch, current_slot)),
+            ),
+        ));
 
-        let beacon_aggregate_proof_params = Self::get_topic_params(
-            self,
-            BEACON_AGGREGATE_PROOF_WEIGHT,
+        topic_params.push(kind_params(
+            "beacon_aggregate_and_proof",
+            vec![GossipKind::BeaconAggregateAndProof],
             aggregators_per_slot,
-            self.epoch,
-            Some((TSpec::slots_per_epoch() * 2, 4.0, self.epoch, current_slot)),
-        );
-        let beacon_attestation_subnet_params = Self::get_topic_params(
-            self,
-            self.beacon_attestation_subnet_weight,
-            active_validators as f64
-                / self.attestation_subnet_count as f64
-                / TSpec::slots_per_epoch() as f64,
-            self.epoch
-                * (if multiple_bursts_per_subnet_per_epoch {
-                    1
-                } else {
-                    4
-                }),
-            Some((
-                TSpec::slots_per_epoch()
+            self.get_single_topic_params(
+                BEACON_AGGREGATE_PROOF_WEIGHT,
+                aggregators_per_slot,
+                self.epoch,
+                Some((TSpec::slots_per_epoch() * 2, 4.0, self.epoch, current_slot)),
+            ),
+        ));
+
+        let attestations_per_subnet_per_slot = active_validators as f64
+            / self.attestation_subnet_count as f64
+            / TSpec::slots_per_epoch() as f64;
+        topic_params.push(kind_params(
+            "beacon_attestation",
+            (0..self.attestation_subnet_count)
+                .map(|i| GossipKind::Attestation(SubnetId::new(i)))
+                .collect(),
+            attestations_per_subnet_per_slot,
+            self.get_single_topic_params(
+                self.beacon_attestation_subnet_weight,
+                attestations_per_subnet_per_slot,
+                self.epoch
                     * (if multiple_bursts_per_subnet_per_epoch {
-                        4
+                        1
                     } else {
-                        16
+                        4
                     }),
-                16.0,
-                if multiple_bursts_per_subnet_per_epoch {
-                    self.slot * (TSpec::slots_per_epoch() as u32 / 2 + 1)
-                } else {
-                    self.epoch * 3
-                },
-                current_slot,
-            )),
-        );
+                Some((
+                    TSpec::slots_per_epoch()
+                        * (if multiple_bursts_per_subnet_per_epoch {
+                            4
+                        } else {
+                            16
+                        }),
+                    16.0,
+                    if multiple_bursts_per_subnet_per_epoch {
+                        self.slot * (TSpec::slots_per_epoch() as u32 / 2 + 1)
+                    } else {
+                        self.epoch * 3
+                    },
+                    current_slot,
+                )),
+            ),
+        ));
 
-        Ok((
-            beacon_block_params,
-            beacon_aggregate_proof_params,
-            beacon_attestation_subnet_params,
-        ))
+        Ok(topic_params)
     }
 
     pub fn attestation_subnet_count(&self) -> u64 {
@@ -278,7 +471,7 @@ impl<TSpec: EthSpec> PeerScoreSettings<TSpec> {
         Self::score_parameter_decay_with_base(decay_time, self.decay_interval, self.decay_to_zero)
     }
 
-    fn get_topic_params(
+    fn get_single_topic_params(
         &self,
         topic_weight: f64,
         expected_message_rate: f64,
@@ -344,3 +537,38 @@ impl<TSpec: EthSpec> PeerScoreSettings<TSpec> {
         t_params
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_rates() {
+        let mut rates = MessageRates::new(Duration::from_secs(3600));
+        rates.observe(GossipKind::VoluntaryExit);
+        // Less than a slot has passed.
+        assert_eq!(rates.message_rate(&[GossipKind::VoluntaryExit]), None);
+
+        let mut rates = MessageRates::new(Duration::from_nanos(1));
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(rates.message_rate(&[GossipKind::VoluntaryExit]), None);
+
+        rates.observe(GossipKind::VoluntaryExit);
+        rates.observe(GossipKind::Attestation(SubnetId::new(0)));
+        rates.observe(GossipKind::Attestation(SubnetId::new(0)));
+        rates.observe(GossipKind::Attestation(SubnetId::new(1)));
+        assert!(rates.message_rate(&[GossipKind::VoluntaryExit]).unwrap() > 0.0);
+
+        // Subnets without messages do not lower the average.
+        let subnets = (0..4)
+            .map(|i| GossipKind::Attestation(SubnetId::new(i)))
+            .collect::<Vec<_>>();
+        let per_subnet = rates.message_rate(&subnets).unwrap();
+        let first_subnet = rates.message_rate(&subnets[..1]).unwrap();
+        assert!(per_subnet < first_subnet);
+        assert!(per_subnet > first_subnet / 2.0);
+
+        rates.reset();
+        assert_eq!(rates.message_rate(&subnets), None);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::behaviour::gossipsub_scoring_parameters::PeerScoreSettings;
+use crate::behaviour::gossipsub_scoring_parameters::{MessageRates, PeerScoreSettings};
 use crate::peer_manager::{score::PeerAction, ConnectionDirection, PeerManager, PeerManagerEvent};
 use crate::rpc::*;
 use crate::service::METADATA_FILENAME;
@@ -39,12 +39,15 @@ use std::{
     marker::PhantomData,
     sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 use types::{ChainSpec, EnrForkId, EthSpec, SignedBeaconBlock, Slot, SubnetId};
 
 mod gossipsub_scoring_parameters;
 mod handler;
 
+pub use gossipsub_scoring_parameters::{PeerScoreParamsSummary, TopicScoreParamsSummary};
+
 const MAX_IDENTIFY_ADDRESSES: usize = 10;
 pub const GOSSIPSUB_GREYLIST_THRESHOLD: f64 = -16000.0;
 
@@ -136,6 +139,9 @@ pub struct Behaviour<TSpec: EthSpec> {
 
     score_settings: PeerScoreSettings<TSpec>,
 
+    /// The gossip messages received since the score parameters were last updated.
+    message_rates: MessageRates,
+
     /// The interval for updating gossipsub scores
     update_gossipsub_scores: tokio::time::Interval,
 }
@@ -189,14 +195,19 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
         };
 
         let score_settings = PeerScoreSettings::new(chain_spec, &net_conf.gs_config);
+        let message_rates =
+            MessageRates::new(Duration::from_millis(chain_spec.milliseconds_per_slot));
 
         //Prepare scoring parameters
-        let params = score_settings.get_peer_score_params(
+        let topic_params =
+            score_settings.get_topic_params(active_validators, current_slot, &message_rates)?;
+        let params = score_settings.get_peer_score_params(&thresholds, &enr_fork_id, &topic_params);
+        *network_globals.gossipsub_score_params.write() = Some(PeerScoreParamsSummary::new(
+            &params,
             active_validators,
-            &thresholds,
-            &enr_fork_id,
             current_slot,
-        )?;
+            &topic_params,
+        ));
 
         trace!(behaviour_log, "Using peer score params"; "params" => format!("{:?}", params));
 
@@ -220,18 +231,24 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
             network_dir: net_conf.network_dir.clone(),
             log: behaviour_log,
             score_settings,
+            message_rates,
             update_gossipsub_scores,
         })
     }
 
+    /// Recomputes the topic score parameters from the number of active validators and the message
+    /// rates observed since the last update, and applies them to gossipsub.
     pub fn update_gossipsub_parameters(
         &mut self,
         active_validators: usize,
         current_slot: Slot,
     ) -> error::Result<()> {
-        let (beacon_block_params, beacon_aggregate_proof_params, beacon_attestation_subnet_params) =
-            self.score_settings
-                .get_dynamic_topic_params(active_validators, current_slot)?;
+        let topic_params = self.score_settings.get_topic_params(
+            active_validators,
+            current_slot,
+            &self.message_rates,
+        )?;
+        self.message_rates.reset();
 
         let fork_digest = self.enr_fork_id.fork_digest;
         let get_topic = |kind: GossipKind| -> Topic {
@@ -240,25 +257,22 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
 
         debug!(self.log, "Updating gossipsub score parameters";
             "active_validators" => active_validators);
-        trace!(self.log, "Updated gossipsub score parameters";
-            "beacon_block_params" => format!("{:?}", beacon_block_params),
-            "beacon_aggregate_proof_params" => format!("{:?}", beacon_aggregate_proof_params),
-            "beacon_attestation_subnet_params" => format!("{:?}", beacon_attestation_subnet_params),
-        );
 
-        self.gossipsub
-            .set_topic_params(get_topic(GossipKind::BeaconBlock), beacon_block_params)?;
-
-        self.gossipsub.set_topic_params(
-            get_topic(GossipKind::BeaconAggregateAndProof),
-            beacon_aggregate_proof_params,
-        )?;
+        for kind_params in &topic_params {
+            trace!(self.log, "Updated gossipsub score parameters";
+                "topic" => kind_params.name,
+                "expected_message_rate" => kind_params.expected_message_rate,
+                "observed_message_rate" => format!("{:?}", kind_params.observed_message_rate),
+                "params" => format!("{:?}", kind_params.params),
+            );
+            for kind in &kind_params.kinds {
+                self.gossipsub
+                    .set_topic_params(get_topic(kind.clone()), kind_params.params.clone())?;
+            }
+        }
 
-        for i in 0..self.score_settings.attestation_subnet_count() {
-            self.gossipsub.set_topic_params(
-                get_topic(GossipKind::Attestation(SubnetId::new(i))),
-                beacon_attestation_subnet_params.clone(),
-            )?;
+        if let Some(summary) = self.network_globals.gossipsub_score_params.write().as_mut() {
+            summary.update_topics(active_validators, current_slot, &topic_params);
         }
 
         Ok(())
@@ -493,6 +507,7 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
             .update_enr_bitfield(subnet_id, value)
         {
             crit!(self.log, "Could not update ENR bitfield"; "error" => e);
+            return;
         }
         // update the local meta data which informs our peers of the update during PINGS
         self.update_metadata();
@@ -549,6 +564,10 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
         {
             // write lock scope
             let mut meta_data = self.network_globals.local_metadata.write();
+            if meta_data.attnets == local_attnets {
+                // nothing has changed, there is no need to announce a new sequence number
+                return;
+            }
             meta_data.seq_number += 1;
             meta_data.attnets = local_attnets;
         }
@@ -624,6 +643,7 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
                         }
                     }
                     Ok(msg) => {
+                        self.message_rates.observe(msg.kind());
                         // Notify the network
                         self.add_event(BehaviourEvent::PubsubMessage {
                             id,
@@ -816,6 +836,9 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
                             condition: libp2p::swarm::DialPeerCondition::Disconnected,
                         });
                     }
+                    PeerManagerEvent::DialAddress(address) => {
+                        return Poll::Ready(NBAction::DialAddress { address });
+                    }
                     PeerManagerEvent::SocketUpdated(address) => {
                         return Poll::Ready(NBAction::ReportObservedAddr { address });
                     }
@@ -978,6 +1001,13 @@ impl<TSpec: EthSpec> NetworkBehaviour for Behaviour<TSpec> {
             // If we are at our peer limit and we don't need the peer for a future validator
             // duty, send goodbye with reason TooManyPeers
             Some(GoodbyeReason::TooManyPeers)
+        } else if self
+            .peer_manager
+            .ip_limit_reached(peer_id, endpoint.get_remote_address())
+        {
+            // If we already have the maximum number of peers from this peer's IP address, send
+            // goodbye with reason TooManyPeers
+            Some(GoodbyeReason::TooManyPeers)
         } else {
             None
         };
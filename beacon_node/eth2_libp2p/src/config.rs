@@ -12,6 +12,7 @@ use libp2p::Multiaddr;
 use serde_derive::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::time::Duration;
 
 pub const GOSSIP_MAX_SIZE: usize = 1_048_576;
@@ -23,6 +24,31 @@ pub type GossipsubConfig = GenericGossipsubConfig<MessageData>;
 pub type GossipsubConfigBuilder = GenericGossipsubConfigBuilder<MessageData>;
 pub type GossipsubMessage = GenericGossipsubMessage<MessageData>;
 
+/// The stream multiplexers that are negotiated on new libp2p connections.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Muxer {
+    /// Only support yamux.
+    Yamux,
+    /// Only support mplex.
+    Mplex,
+    /// Support both yamux and mplex, preferring yamux.
+    Both,
+}
+
+impl FromStr for Muxer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "yamux" => Ok(Muxer::Yamux),
+            "mplex" => Ok(Muxer::Mplex),
+            "both" => Ok(Muxer::Both),
+            other => Err(format!("Unknown muxer: {}", other)),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(default)]
 /// Network configuration for lighthouse.
@@ -52,6 +78,23 @@ pub struct Config {
     /// Target number of connected peers.
     pub target_peers: usize,
 
+    /// The maximum number of peers that may be connected from a single IP address. `None`
+    /// indicates that there is no limit.
+    pub max_peers_per_ip: Option<usize>,
+
+    /// The stream multiplexers to support on libp2p connections.
+    pub muxer: Muxer,
+
+    /// Use an in-process memory transport rather than TCP. The node listens on
+    /// `/memory/<libp2p_port>`. This is only useful for running many nodes in a single process,
+    /// such as in tests.
+    pub memory_transport: bool,
+
+    /// Artificial latency added to every write on libp2p connections. This is only useful for
+    /// simulating a slow network, such as in the simulator.
+    #[cfg(feature = "transport-latency")]
+    pub transport_latency: Option<Duration>,
+
     /// Gossipsub configuration parameters.
     #[serde(skip)]
     pub gs_config: GossipsubConfig,
@@ -175,6 +218,11 @@ impl Default for Config {
             enr_udp_port: None,
             enr_tcp_port: None,
             target_peers: 50,
+            max_peers_per_ip: None,
+            muxer: Muxer::Both,
+            memory_transport: false,
+            #[cfg(feature = "transport-latency")]
+            transport_latency: None,
             gs_config,
             discv5_config,
             boot_nodes_enr: vec![],
@@ -0,0 +1,149 @@
+//! A stream wrapper which adds artificial latency to writes, used to simulate slow networks.
+
+use futures::io::{AsyncRead, AsyncWrite};
+use futures::ready;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{delay_until, Delay, Instant};
+
+/// The maximum number of bytes held back by a `DelayedStream` before writes are refused.
+const MAX_PENDING_BYTES: usize = 4 * 1_048_576;
+
+/// Wraps a connection so that written bytes are only passed to the inner stream once `latency`
+/// has elapsed since they were written. Reads are unaffected.
+///
+/// Writes are accepted immediately and held in a queue, so the latency does not limit throughput.
+/// A flush completes once all of the queued bytes have been passed to the inner stream.
+pub struct DelayedStream<S> {
+    inner: S,
+    latency: Duration,
+    /// Written bytes, along with the time at which they may be passed to the inner stream.
+    pending: VecDeque<(Instant, Vec<u8>)>,
+    /// The number of bytes at the front of `pending` already passed to the inner stream.
+    front_offset: usize,
+    /// The total number of bytes in `pending`.
+    pending_bytes: usize,
+    /// Wakes the task once the front of `pending` is due.
+    delay: Option<Delay>,
+}
+
+impl<S> DelayedStream<S> {
+    pub fn new(inner: S, latency: Duration) -> Self {
+        Self {
+            inner,
+            latency,
+            pending: VecDeque::new(),
+            front_offset: 0,
+            pending_bytes: 0,
+            delay: None,
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> DelayedStream<S> {
+    /// Passes all the due bytes in `pending` to the inner stream, returning `Poll::Ready` once
+    /// `pending` is empty.
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while let Some((due, bytes)) = self.pending.front() {
+            let due = *due;
+            if due > Instant::now() {
+                let delay = self.delay.get_or_insert_with(|| delay_until(due));
+                if delay.deadline() != due {
+                    delay.reset(due);
+                }
+                ready!(Pin::new(delay).poll(cx));
+            }
+
+            let written =
+                ready!(Pin::new(&mut self.inner).poll_write(cx, &bytes[self.front_offset..]))?;
+            if written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+
+            self.front_offset += written;
+            if self.front_offset == bytes.len() {
+                self.pending_bytes -= bytes.len();
+                self.front_offset = 0;
+                self.pending.pop_front();
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for DelayedStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for DelayedStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        // Make progress on earlier writes without waiting for them.
+        if let Poll::Ready(Err(e)) = this.poll_pending(cx) {
+            return Poll::Ready(Err(e));
+        }
+
+        // `poll_pending` has registered a wake-up for when the queue can shrink.
+        if this.pending_bytes >= MAX_PENDING_BYTES {
+            return Poll::Pending;
+        }
+
+        this.pending
+            .push_back((Instant::now() + this.latency, buf.to_vec()));
+        this.pending_bytes += buf.len();
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        Pin::new(&mut this.inner).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::io::{AsyncWriteExt, Cursor};
+
+    #[tokio::test]
+    async fn writes_are_delayed_but_not_serialized() {
+        let latency = Duration::from_millis(100);
+        let mut stream = DelayedStream::new(Cursor::new(vec![]), latency);
+
+        let start = Instant::now();
+        for i in 0..10 {
+            stream.write_all(&[i]).await.unwrap();
+        }
+        assert!(stream.inner.get_ref().is_empty());
+
+        stream.flush().await.unwrap();
+        let elapsed = start.elapsed();
+
+        assert!(elapsed >= latency);
+        assert!(elapsed < latency * 10);
+        assert_eq!(stream.inner.into_inner(), (0..10).collect::<Vec<u8>>());
+    }
+}
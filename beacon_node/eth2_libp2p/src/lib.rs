@@ -7,6 +7,8 @@ extern crate lazy_static;
 
 pub mod behaviour;
 mod config;
+#[cfg(feature = "transport-latency")]
+mod delayed_stream;
 pub mod discovery;
 mod metrics;
 mod peer_manager;
@@ -58,10 +60,16 @@ impl<'de> Deserialize<'de> for PeerIdSerialized {
     }
 }
 
-pub use crate::types::{error, Enr, GossipTopic, NetworkGlobals, PubsubMessage, SubnetDiscovery};
-pub use behaviour::{BehaviourEvent, Gossipsub, PeerRequestId, Request, Response};
+pub use crate::types::{
+    error, AttestationSubnetsSummary, Enr, GossipTopic, NetworkGlobals, PubsubMessage,
+    SubnetDiscovery, SubnetSubscription, SubnetSubscriptionKind,
+};
+pub use behaviour::{
+    BehaviourEvent, Gossipsub, PeerRequestId, PeerScoreParamsSummary, Request, Response,
+    TopicScoreParamsSummary,
+};
 pub use config::Config as NetworkConfig;
-pub use config::{GossipsubConfig, GossipsubConfigBuilder, GossipsubMessage};
+pub use config::{GossipsubConfig, GossipsubConfigBuilder, GossipsubMessage, Muxer};
 pub use discovery::{CombinedKeyExt, EnrExt, Eth2Enr};
 pub use discv5;
 pub use libp2p::gossipsub::{MessageAcceptance, MessageId, Topic, TopicHash};
@@ -70,6 +78,6 @@ pub use libp2p::{multiaddr, Multiaddr};
 pub use metrics::scrape_discovery_metrics;
 pub use peer_manager::{
     client::Client, score::PeerAction, ConnectionDirection, PeerConnectionStatus, PeerDB, PeerInfo,
-    PeerSyncStatus, SyncInfo,
+    PeerSyncStatus, ScoreChange, ScoreChangeCause, SyncInfo,
 };
 pub use service::{load_private_key, Libp2pEvent, Service, NETWORK_KEY_FILENAME};
@@ -5,7 +5,7 @@ use crate::discovery::{subnet_predicate, Discovery, DiscoveryEvent, TARGET_SUBNE
 use crate::rpc::{GoodbyeReason, MetaData, Protocol, RPCError, RPCResponseErrorCode};
 use crate::types::SyncState;
 use crate::{error, metrics, Gossipsub};
-use crate::{EnrExt, NetworkConfig, NetworkGlobals, PeerId, SubnetDiscovery};
+use crate::{Enr, EnrExt, NetworkConfig, NetworkGlobals, PeerId, SubnetDiscovery};
 use futures::prelude::*;
 use futures::Stream;
 use hashset_delay::HashSetDelay;
@@ -14,7 +14,7 @@ use libp2p::identify::IdentifyInfo;
 use slog::{crit, debug, error, warn};
 use smallvec::SmallVec;
 use std::{
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
@@ -30,7 +30,10 @@ mod peer_sync_status;
 mod peerdb;
 pub(crate) mod score;
 
-pub use peer_info::{ConnectionDirection, PeerConnectionStatus, PeerConnectionStatus::*, PeerInfo};
+pub use peer_info::{
+    ConnectionDirection, PeerConnectionStatus, PeerConnectionStatus::*, PeerInfo, ScoreChange,
+    ScoreChangeCause,
+};
 pub use peer_sync_status::{PeerSyncStatus, SyncInfo};
 use score::{PeerAction, ScoreState};
 use std::cmp::Ordering;
@@ -75,6 +78,8 @@ pub struct PeerManager<TSpec: EthSpec> {
     target_peers: usize,
     /// The maximum number of peers we allow (exceptions for subnet peers)
     max_peers: usize,
+    /// The maximum number of peers we allow from a single IP address, if any.
+    max_peers_per_ip: Option<usize>,
     /// The discovery service.
     discovery: Discovery<TSpec>,
     /// The heartbeat interval to perform routine maintenance.
@@ -87,6 +92,8 @@ pub struct PeerManager<TSpec: EthSpec> {
 pub enum PeerManagerEvent {
     /// Dial a PeerId.
     Dial(PeerId),
+    /// Dial a multiaddr which may not belong to a known peer.
+    DialAddress(Multiaddr),
     /// Inform libp2p that our external socket addr has been updated.
     SocketUpdated(Multiaddr),
     /// Sends a STATUS to a peer.
@@ -123,6 +130,7 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             status_peers: HashSetDelay::new(Duration::from_secs(STATUS_INTERVAL)),
             target_peers: config.target_peers,
             max_peers: (config.target_peers as f32 * (1.0 + PEER_EXCESS_FACTOR)).ceil() as usize,
+            max_peers_per_ip: config.max_peers_per_ip,
             discovery,
             heartbeat,
             log: log.clone(),
@@ -200,6 +208,88 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         self.ban_and_unban_peers(to_ban_peers, to_unban_peers);
     }
 
+    /* Manual Peer Management */
+
+    /// Marks a peer as trusted, unbanning it if required.
+    pub fn add_trusted_peer(&mut self, peer_id: &PeerId) {
+        let is_banned = {
+            let mut peer_db = self.network_globals.peers.write();
+            peer_db.add_trusted_peer(peer_id.clone());
+            peer_db
+                .peer_info(peer_id)
+                .map_or(false, |info| info.is_banned())
+        };
+        debug!(self.log, "Added trusted peer"; "peer_id" => %peer_id);
+
+        if is_banned {
+            if let Err(e) = self.unban_peer(peer_id) {
+                error!(self.log, "Failed to unban trusted peer"; "peer_id" => %peer_id, "error" => e);
+            }
+        }
+    }
+
+    /// Removes the trusted status of a peer. Returns false if the peer is not trusted.
+    pub fn remove_trusted_peer(&mut self, peer_id: &PeerId) -> bool {
+        let removed = self
+            .network_globals
+            .peers
+            .write()
+            .remove_trusted_peer(peer_id);
+        if removed {
+            debug!(self.log, "Removed trusted peer"; "peer_id" => %peer_id);
+        }
+        removed
+    }
+
+    /// Disconnects a peer without banning it. The peer is free to reconnect.
+    pub fn disconnect_peer(&mut self, peer_id: &PeerId, reason: GoodbyeReason) {
+        if self.network_globals.peers.read().is_connected(peer_id) {
+            debug!(self.log, "Disconnecting peer"; "peer_id" => %peer_id, "reason" => %reason);
+            self.network_globals
+                .peers
+                .write()
+                .notify_disconnecting(peer_id);
+            self.events
+                .push(PeerManagerEvent::DisconnectPeer(peer_id.clone(), reason));
+        }
+    }
+
+    /// Resets the score of a banned peer and unbans it.
+    pub fn reset_and_unban_peer(&mut self, peer_id: &PeerId) -> Result<(), &'static str> {
+        {
+            let mut peer_db = self.network_globals.peers.write();
+            let info = peer_db
+                .peer_info_mut(peer_id)
+                .ok_or("Unbanning unknown peer")?;
+            if !info.is_banned() {
+                return Err("Unbanning peer that is not banned");
+            }
+            info.restore_default_score();
+        } // end write lock
+
+        debug!(self.log, "Unbanning peer"; "peer_id" => %peer_id);
+        self.unban_peer(peer_id)
+    }
+
+    /// Dials a multiaddr which may not be associated with a known peer.
+    pub fn dial_address(&mut self, address: Multiaddr) {
+        self.events.push(PeerManagerEvent::DialAddress(address));
+    }
+
+    /// Adds an ENR to the routing table and dials the peer it describes.
+    pub fn dial_enr(&mut self, enr: Enr) {
+        let peer_id = enr.peer_id();
+        self.discovery.add_enr(enr);
+        if !self
+            .network_globals
+            .peers
+            .read()
+            .is_connected_or_dialing(&peer_id)
+        {
+            self.dial_peer(&peer_id);
+        }
+    }
+
     /* Discovery Requests */
 
     /// Provides a reference to the underlying discovery service.
@@ -324,6 +414,41 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         self.network_globals.connected_or_dialing_peers() >= self.max_peers
     }
 
+    /// Returns true if connecting to `peer_id` at `address` would exceed the maximum number of
+    /// peers per IP address. Trusted peers and addresses without an IP (e.g., memory addresses)
+    /// are never limited.
+    pub fn ip_limit_reached(&self, peer_id: &PeerId, address: &Multiaddr) -> bool {
+        let max_peers_per_ip = match self.max_peers_per_ip {
+            Some(max_peers_per_ip) => max_peers_per_ip,
+            None => return false,
+        };
+
+        let ip: IpAddr = match address.iter().find_map(|protocol| match protocol {
+            MProtocol::Ip4(ip) => Some(ip.into()),
+            MProtocol::Ip6(ip) => Some(ip.into()),
+            _ => None,
+        }) {
+            Some(ip) => ip,
+            None => return false,
+        };
+
+        let peers = self.network_globals.peers.read();
+        if peers
+            .peer_info(peer_id)
+            .map_or(false, |info| info.is_trusted)
+        {
+            return false;
+        }
+
+        peers
+            .connected_peers()
+            .filter(|(id, info)| {
+                *id != peer_id && info.seen_addresses().any(|seen_ip| seen_ip == ip)
+            })
+            .count()
+            >= max_peers_per_ip
+    }
+
     /// Updates `PeerInfo` with `identify` information.
     pub fn identify(&mut self, peer_id: &PeerId, info: &IdentifyInfo) {
         if let Some(peer_info) = self.network_globals.peers.write().peer_info_mut(peer_id) {
@@ -1003,3 +1128,111 @@ enum ConnectingType {
         multiaddr: Multiaddr,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::{CombinedKey, CombinedKeyExt};
+    use discv5::enr::EnrBuilder;
+    use slog::{o, Drain};
+    use types::MinimalEthSpec;
+
+    type E = MinimalEthSpec;
+
+    pub fn build_log(level: slog::Level, enabled: bool) -> slog::Logger {
+        let decorator = slog_term::TermDecorator::new().build();
+        let drain = slog_term::FullFormat::new(decorator).build().fuse();
+        let drain = slog_async::Async::new(drain).build().fuse();
+
+        if enabled {
+            slog::Logger::root(drain.filter_level(level).fuse(), o!())
+        } else {
+            slog::Logger::root(drain.filter(|_| false).fuse(), o!())
+        }
+    }
+
+    async fn build_peer_manager(max_peers_per_ip: Option<usize>) -> PeerManager<E> {
+        let keypair = Keypair::generate_secp256k1();
+        let config = NetworkConfig {
+            disable_discovery: true,
+            max_peers_per_ip,
+            ..Default::default()
+        };
+        let log = build_log(slog::Level::Debug, false);
+
+        let enr_key = CombinedKey::from_libp2p(&keypair).unwrap();
+        let enr = EnrBuilder::new("v4").build(&enr_key).unwrap();
+        let meta_data = MetaData {
+            seq_number: 0,
+            attnets: Default::default(),
+        };
+        let globals = NetworkGlobals::new(enr, 9000, 9000, meta_data, vec![], &log);
+
+        PeerManager::new(&keypair, &config, Arc::new(globals), &log)
+            .await
+            .unwrap()
+    }
+
+    /// Connects `peer_id` from `address` in the same way as the behaviour does, returning false
+    /// if the connection is rejected by the per-IP limit.
+    fn connect_from(peer_manager: &mut PeerManager<E>, peer_id: &PeerId, address: &str) -> bool {
+        let multiaddr: Multiaddr = address.parse().unwrap();
+        if peer_manager.ip_limit_reached(peer_id, &multiaddr) {
+            return false;
+        }
+        peer_manager.connect_ingoing(peer_id, multiaddr)
+    }
+
+    #[tokio::test]
+    async fn test_ip_limit_reached() {
+        let mut peer_manager = build_peer_manager(Some(2)).await;
+        let limited_ip = "/ip4/1.2.3.4/tcp/9000";
+
+        let peers = (0..4).map(|_| PeerId::random()).collect::<Vec<_>>();
+        assert!(connect_from(&mut peer_manager, &peers[0], limited_ip));
+        assert!(connect_from(&mut peer_manager, &peers[1], limited_ip));
+
+        // Any further peers from the same IP are rejected.
+        assert!(!connect_from(&mut peer_manager, &peers[2], limited_ip));
+        assert!(!connect_from(&mut peer_manager, &peers[3], limited_ip));
+        assert_eq!(peer_manager.network_globals.connected_peers(), 2);
+
+        // A connected peer does not count against itself.
+        assert!(!peer_manager.ip_limit_reached(&peers[0], &limited_ip.parse().unwrap()));
+
+        // Peers from other IPs are still accepted.
+        assert!(connect_from(
+            &mut peer_manager,
+            &PeerId::random(),
+            "/ip4/5.6.7.8/tcp/9000"
+        ));
+        assert!(connect_from(
+            &mut peer_manager,
+            &PeerId::random(),
+            "/ip6/::1/tcp/9000"
+        ));
+        assert_eq!(peer_manager.network_globals.connected_peers(), 4);
+
+        // Trusted peers are never limited.
+        let trusted_peer = PeerId::random();
+        peer_manager
+            .network_globals
+            .peers
+            .write()
+            .add_trusted_peer(trusted_peer.clone());
+        assert!(connect_from(&mut peer_manager, &trusted_peer, limited_ip));
+    }
+
+    #[tokio::test]
+    async fn test_no_ip_limit() {
+        let mut peer_manager = build_peer_manager(None).await;
+
+        for _ in 0..10 {
+            assert!(connect_from(
+                &mut peer_manager,
+                &PeerId::random(),
+                "/ip4/1.2.3.4/tcp/9000"
+            ));
+        }
+    }
+}
@@ -8,12 +8,15 @@ use serde::{
     ser::{SerializeStruct, Serializer},
     Serialize,
 };
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 use std::net::{IpAddr, SocketAddr};
-use std::time::Instant;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use types::{EthSpec, SubnetId};
 use PeerConnectionStatus::*;
 
+/// The maximum number of score changes retained in the score history of each peer.
+pub(crate) const MAX_SCORE_HISTORY: usize = 20;
+
 /// Information about a given connected peer.
 #[derive(Clone, Debug, Serialize)]
 #[serde(bound = "T: EthSpec")]
@@ -22,6 +25,8 @@ pub struct PeerInfo<T: EthSpec> {
     _status: PeerStatus,
     /// The peers reputation
     score: Score,
+    /// The most recent changes to the peer's reputation, oldest first.
+    score_history: VecDeque<ScoreChange>,
     /// Client managing this peer
     pub client: Client,
     /// Connection status of this peer
@@ -56,6 +61,7 @@ impl<TSpec: EthSpec> Default for PeerInfo<TSpec> {
         PeerInfo {
             _status: Default::default(),
             score: Score::default(),
+            score_history: VecDeque::new(),
             client: Client::default(),
             connection_status: Default::default(),
             listening_addresses: Vec::new(),
@@ -113,6 +119,11 @@ impl<T: EthSpec> PeerInfo<T> {
         &self.score
     }
 
+    /// Returns the most recent changes to the peer's score, oldest first.
+    pub fn score_history(&self) -> impl Iterator<Item = &ScoreChange> {
+        self.score_history.iter()
+    }
+
     /// Returns the state of the peer based on the score.
     pub(crate) fn score_state(&self) -> ScoreState {
         self.score.state()
@@ -128,8 +139,48 @@ impl<T: EthSpec> PeerInfo<T> {
     /// Apply peer action to a non-trusted peer's score.
     pub fn apply_peer_action_to_score(&mut self, peer_action: PeerAction) {
         if !self.is_trusted {
-            self.score.apply_peer_action(peer_action)
+            let score_before = self.score.score();
+            self.score.apply_peer_action(peer_action);
+            self.record_score_change(ScoreChangeCause::PeerAction(peer_action), score_before);
+        }
+    }
+
+    /// Resets a non-trusted peer's score to that of a new peer, e.g. when it is manually unbanned.
+    pub(crate) fn restore_default_score(&mut self) {
+        if !self.is_trusted {
+            let score_before = self.score.score();
+            self.score.reset();
+            self.record_score_change(ScoreChangeCause::Reset, score_before);
+        }
+    }
+
+    /// Marks the peer as trusted or untrusted.
+    ///
+    /// Trusted peers have the maximum score, untrusted peers start again from the default score.
+    pub(crate) fn set_trusted(&mut self, is_trusted: bool) {
+        let score_before = self.score.score();
+        self.is_trusted = is_trusted;
+        self.score = if is_trusted {
+            Score::max_score()
+        } else {
+            Score::default()
+        };
+        self.record_score_change(ScoreChangeCause::Trusted(is_trusted), score_before);
+    }
+
+    fn record_score_change(&mut self, cause: ScoreChangeCause, score_before: f64) {
+        if self.score_history.len() >= MAX_SCORE_HISTORY {
+            self.score_history.pop_front();
         }
+        self.score_history.push_back(ScoreChange {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0),
+            cause,
+            score_before,
+            score_after: self.score.score(),
+        });
     }
 
     pub(crate) fn update_gossipsub_score(&mut self, new_score: f64, ignore: bool) {
@@ -299,6 +350,27 @@ impl<T: EthSpec> PeerInfo<T> {
     }
 }
 
+/// The reason a peer's score was changed.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub enum ScoreChangeCause {
+    /// The peer was reported for an action.
+    PeerAction(PeerAction),
+    /// The score was reset to the default, e.g. because the peer was manually unbanned.
+    Reset,
+    /// The peer was marked as trusted (`true`) or had its trusted status removed (`false`).
+    Trusted(bool),
+}
+
+/// A single change to a peer's score.
+#[derive(Clone, Debug, Serialize)]
+pub struct ScoreChange {
+    /// The time of the change, in seconds since the UNIX epoch.
+    pub timestamp: u64,
+    pub cause: ScoreChangeCause,
+    pub score_before: f64,
+    pub score_after: f64,
+}
+
 #[derive(Clone, Debug, Serialize)]
 /// The current health status of the peer.
 pub enum PeerStatus {
@@ -507,6 +507,26 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
         }
     }
 
+    /// Marks a peer as trusted, inserting it into the DB if it is unknown.
+    ///
+    /// Trusted peers have the maximum score and are never disconnected or banned due to their
+    /// score.
+    pub fn add_trusted_peer(&mut self, peer_id: PeerId) {
+        self.peers.entry(peer_id).or_default().set_trusted(true);
+    }
+
+    /// Removes the trusted status of a peer, resetting it's score to the default score.
+    /// Returns false if the peer is not trusted.
+    pub fn remove_trusted_peer(&mut self, peer_id: &PeerId) -> bool {
+        match self.peers.get_mut(peer_id) {
+            Some(info) if info.is_trusted => {
+                info.set_trusted(false);
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Unbans a peer.
     /// This should only be called once a peer's score is no longer banned.
     /// If this is called for a banned peer, it will error.
@@ -611,6 +631,8 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
 
 #[cfg(test)]
 mod tests {
+    use super::super::peer_info::{ScoreChangeCause, MAX_SCORE_HISTORY};
+    use super::super::score::PeerAction;
     use super::*;
     use libp2p::core::Multiaddr;
     use slog::{o, Drain};
@@ -1047,4 +1069,72 @@ mod tests {
             Score::max_score().score()
         );
     }
+
+    #[test]
+    fn test_add_and_remove_trusted_peer() {
+        let mut pdb = get_db();
+        let peer = PeerId::random();
+
+        pdb.connect_ingoing(&peer, "/ip4/0.0.0.0".parse().unwrap(), None);
+        add_score(&mut pdb, &peer, -10.0);
+        assert!(!pdb.remove_trusted_peer(&peer));
+
+        pdb.add_trusted_peer(peer.clone());
+        assert!(pdb.peer_info(&peer).unwrap().is_trusted);
+        assert_eq!(pdb.score(&peer), Score::max_score().score());
+        let last = pdb
+            .peer_info(&peer)
+            .unwrap()
+            .score_history()
+            .last()
+            .unwrap();
+        assert!(matches!(last.cause, ScoreChangeCause::Trusted(true)));
+        assert_eq!(last.score_after, Score::max_score().score());
+
+        assert!(pdb.remove_trusted_peer(&peer));
+        assert!(!pdb.peer_info(&peer).unwrap().is_trusted);
+        assert_eq!(pdb.score(&peer), Score::default().score());
+        let last = pdb
+            .peer_info(&peer)
+            .unwrap()
+            .score_history()
+            .last()
+            .unwrap();
+        assert!(matches!(last.cause, ScoreChangeCause::Trusted(false)));
+        assert_eq!(last.score_before, Score::max_score().score());
+
+        // Unknown peers are inserted as trusted peers.
+        let unknown_peer = PeerId::random();
+        pdb.add_trusted_peer(unknown_peer.clone());
+        assert!(pdb.peer_info(&unknown_peer).unwrap().is_trusted);
+    }
+
+    #[test]
+    fn test_score_history() {
+        let mut pdb = get_db();
+        let peer = PeerId::random();
+
+        pdb.connect_ingoing(&peer, "/ip4/0.0.0.0".parse().unwrap(), None);
+        let info = pdb.peer_info_mut(&peer).unwrap();
+        for _ in 0..MAX_SCORE_HISTORY + 5 {
+            info.apply_peer_action_to_score(PeerAction::HighToleranceError);
+        }
+        info.apply_peer_action_to_score(PeerAction::Fatal);
+
+        let history = info.score_history().collect::<Vec<_>>();
+        assert_eq!(history.len(), MAX_SCORE_HISTORY);
+        let last = history.last().unwrap();
+        assert!(matches!(
+            last.cause,
+            ScoreChangeCause::PeerAction(PeerAction::Fatal)
+        ));
+        assert!(last.score_after < last.score_before);
+
+        info.restore_default_score();
+        assert!(matches!(
+            info.score_history().last().unwrap().cause,
+            ScoreChangeCause::Reset
+        ));
+        assert_eq!(pdb.score(&peer), Score::default().score());
+    }
 }
@@ -42,7 +42,7 @@ const GOSSIPSUB_POSITIVE_SCORE_WEIGHT: f64 = GOSSIPSUB_NEGATIVE_SCORE_WEIGHT;
 /// Each variant has an associated score change.
 // To easily assess the behaviour of scores changes the number of variants should stay low, and
 // somewhat generic.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize)]
 pub enum PeerAction {
     /// We should not communicate more with this peer.
     /// This action will cause the peer to get banned.
@@ -159,6 +159,11 @@ impl RealScore {
         }
     }
 
+    /// Resets the score to that of a newly discovered peer, discarding any ban period.
+    pub fn reset(&mut self) {
+        *self = RealScore::default();
+    }
+
     fn set_lighthouse_score(&mut self, new_score: f64) {
         self.lighthouse_score = new_score;
         self.update_state();
@@ -268,6 +273,7 @@ macro_rules! apply {
 
 apply!(apply_peer_action, peer_action: PeerAction);
 apply!(update);
+apply!(reset);
 apply!(update_gossipsub_score, new_score: f64, ignore: bool);
 #[cfg(test)]
 apply!(test_add, score: f64);
@@ -1,15 +1,20 @@
 use crate::behaviour::{
     save_metadata_to_disk, Behaviour, BehaviourEvent, PeerRequestId, Request, Response,
 };
+#[cfg(feature = "transport-latency")]
+use crate::delayed_stream::DelayedStream;
 use crate::discovery::enr;
 use crate::multiaddr::Protocol;
 use crate::rpc::{GoodbyeReason, MetaData, RPCResponseErrorCode, RequestId};
 use crate::types::{error, EnrBitfield, GossipKind};
 use crate::EnrExt;
-use crate::{NetworkConfig, NetworkGlobals, PeerAction};
+use crate::{Muxer, NetworkConfig, NetworkGlobals, PeerAction};
 use futures::prelude::*;
 use libp2p::core::{
-    identity::Keypair, multiaddr::Multiaddr, muxing::StreamMuxerBox, transport::Boxed,
+    identity::Keypair,
+    multiaddr::Multiaddr,
+    muxing::StreamMuxerBox,
+    transport::{Boxed, MemoryTransport},
 };
 use libp2p::{
     core, noise,
@@ -101,8 +106,8 @@ impl<TSpec: EthSpec> Service<TSpec> {
         debug!(log, "Attempting to open listening ports"; "address" => format!("{}", config.listen_address), "tcp_port" => config.libp2p_port, "udp_port" => discovery_string);
 
         let mut swarm = {
-            // Set up the transport - tcp/ws with noise and mplex
-            let transport = build_transport(local_keypair.clone())
+            // Set up the transport - tcp/ws (or memory) with noise and yamux/mplex
+            let transport = build_transport(local_keypair.clone(), config)
                 .map_err(|e| format!("Failed to build transport: {:?}", e))?;
             // Lighthouse network behaviour
             let behaviour = Behaviour::new(
@@ -132,7 +137,9 @@ impl<TSpec: EthSpec> Service<TSpec> {
         };
 
         // listen on the specified address
-        let listen_multiaddr = {
+        let listen_multiaddr = if config.memory_transport {
+            Multiaddr::empty().with(Protocol::Memory(config.libp2p_port as u64))
+        } else {
             let mut m = Multiaddr::from(config.listen_address);
             m.push(Protocol::Tcp(config.libp2p_port));
             m
@@ -330,8 +337,21 @@ impl<TSpec: EthSpec> Service<TSpec> {
 }
 
 /// The implementation supports TCP/IP, WebSockets over TCP/IP, noise as the encryption layer, and
-/// mplex as the multiplexing layer.
-fn build_transport(local_private_key: Keypair) -> std::io::Result<Boxed<(PeerId, StreamMuxerBox)>> {
+/// yamux and/or mplex as the multiplexing layer. If `config.memory_transport` is set, an in-process
+/// memory transport is used instead of TCP. With the `transport-latency` feature, if
+/// `config.transport_latency` is set, every write on a connection is delayed by that duration.
+fn build_transport(
+    local_private_key: Keypair,
+    config: &NetworkConfig,
+) -> std::io::Result<Boxed<(PeerId, StreamMuxerBox)>> {
+    if config.memory_transport {
+        return Ok(upgrade_transport_with_latency(
+            MemoryTransport::default(),
+            &local_private_key,
+            config,
+        ));
+    }
+
     let transport = libp2p::tcp::TokioTcpConfig::new().nodelay(true);
     let transport = libp2p::dns::DnsConfig::new(transport)?;
     #[cfg(feature = "libp2p-websocket")]
@@ -340,21 +360,99 @@ fn build_transport(local_private_key: Keypair) -> std::io::Result<Boxed<(PeerId,
         transport.or_transport(libp2p::websocket::WsConfig::new(trans_clone))
     };
 
+    Ok(upgrade_transport_with_latency(
+        transport,
+        &local_private_key,
+        config,
+    ))
+}
+
+/// Delays the connections of `transport` by `config.transport_latency` (if any), then upgrades it
+/// with `upgrade_transport`.
+#[cfg(feature = "transport-latency")]
+fn upgrade_transport_with_latency<T>(
+    transport: T,
+    local_private_key: &Keypair,
+    config: &NetworkConfig,
+) -> Boxed<(PeerId, StreamMuxerBox)>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Output: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    T::Error: Send + Sync + 'static,
+    T::Listener: Send + 'static,
+    T::ListenerUpgrade: Send + 'static,
+    T::Dial: Send + 'static,
+{
+    match config.transport_latency {
+        Some(latency) => upgrade_transport(
+            transport.map(move |stream, _| DelayedStream::new(stream, latency)),
+            local_private_key,
+            config.muxer,
+        ),
+        None => upgrade_transport(transport, local_private_key, config.muxer),
+    }
+}
+
+/// Upgrades `transport` with `upgrade_transport`. Latency can only be added with the
+/// `transport-latency` feature.
+#[cfg(not(feature = "transport-latency"))]
+fn upgrade_transport_with_latency<T>(
+    transport: T,
+    local_private_key: &Keypair,
+    config: &NetworkConfig,
+) -> Boxed<(PeerId, StreamMuxerBox)>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Output: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    T::Error: Send + Sync + 'static,
+    T::Listener: Send + 'static,
+    T::ListenerUpgrade: Send + 'static,
+    T::Dial: Send + 'static,
+{
+    upgrade_transport(transport, local_private_key, config.muxer)
+}
+
+/// Authenticates connections on `transport` with noise and multiplexes them with `muxer`.
+fn upgrade_transport<T>(
+    transport: T,
+    local_private_key: &Keypair,
+    muxer: Muxer,
+) -> Boxed<(PeerId, StreamMuxerBox)>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Output: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    T::Error: Send + Sync + 'static,
+    T::Listener: Send + 'static,
+    T::ListenerUpgrade: Send + 'static,
+    T::Dial: Send + 'static,
+{
     // mplex config
     let mut mplex_config = libp2p::mplex::MplexConfig::new();
     mplex_config.max_buffer_len(256);
     mplex_config.max_buffer_len_behaviour(libp2p::mplex::MaxBufferBehaviour::Block);
 
     // Authentication
-    Ok(transport
+    let transport = transport
         .upgrade(core::upgrade::Version::V1)
-        .authenticate(generate_noise_config(&local_private_key))
-        .multiplex(core::upgrade::SelectUpgrade::new(
-            libp2p::yamux::Config::default(),
-            mplex_config,
-        ))
-        .timeout(Duration::from_secs(10))
-        .boxed())
+        .authenticate(generate_noise_config(local_private_key));
+
+    match muxer {
+        Muxer::Yamux => transport
+            .multiplex(libp2p::yamux::Config::default())
+            .timeout(Duration::from_secs(10))
+            .boxed(),
+        Muxer::Mplex => transport
+            .multiplex(mplex_config)
+            .timeout(Duration::from_secs(10))
+            .boxed(),
+        Muxer::Both => transport
+            .multiplex(core::upgrade::SelectUpgrade::new(
+                libp2p::yamux::Config::default(),
+                mplex_config,
+            ))
+            .timeout(Duration::from_secs(10))
+            .boxed(),
+    }
 }
 
 // Useful helper functions for debugging. Currently not used in the client.
@@ -1,7 +1,8 @@
 //! A collection of variables that are accessible outside of the network thread itself.
+use crate::behaviour::PeerScoreParamsSummary;
 use crate::peer_manager::PeerDB;
 use crate::rpc::MetaData;
-use crate::types::SyncState;
+use crate::types::{AttestationSubnetsSummary, SyncState};
 use crate::Client;
 use crate::EnrExt;
 use crate::{Enr, GossipTopic, Multiaddr, PeerId};
@@ -29,6 +30,10 @@ pub struct NetworkGlobals<TSpec: EthSpec> {
     pub gossipsub_subscriptions: RwLock<HashSet<GossipTopic>>,
     /// The current sync status of the node.
     pub sync_state: RwLock<SyncState>,
+    /// The current gossipsub peer score parameters, once they have been computed.
+    pub gossipsub_score_params: RwLock<Option<PeerScoreParamsSummary>>,
+    /// The attestation subnets we are currently subscribed to.
+    pub attestation_subnets: RwLock<AttestationSubnetsSummary>,
 }
 
 impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
@@ -50,6 +55,8 @@ impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
             peers: RwLock::new(PeerDB::new(trusted_peers, log)),
             gossipsub_subscriptions: RwLock::new(HashSet::new()),
             sync_state: RwLock::new(SyncState::Stalled),
+            gossipsub_score_params: RwLock::new(None),
+            attestation_subnets: RwLock::new(AttestationSubnetsSummary::default()),
         }
     }
 
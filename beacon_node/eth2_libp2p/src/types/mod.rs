@@ -14,6 +14,8 @@ pub type Enr = discv5::enr::Enr<discv5::enr::CombinedKey>;
 
 pub use globals::NetworkGlobals;
 pub use pubsub::{MessageData, PubsubMessage};
-pub use subnet::SubnetDiscovery;
+pub use subnet::{
+    AttestationSubnetsSummary, SubnetDiscovery, SubnetSubscription, SubnetSubscriptionKind,
+};
 pub use sync_state::SyncState;
 pub use topics::{GossipEncoding, GossipKind, GossipTopic, CORE_TOPICS};
@@ -1,5 +1,6 @@
+use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
-use types::SubnetId;
+use types::{Slot, SubnetId};
 
 const DURATION_DIFFERENCE: Duration = Duration::from_millis(1);
 
@@ -26,3 +27,34 @@ impl PartialEq for SubnetDiscovery {
             }
     }
 }
+
+/// The reason we are subscribed to an attestation subnet.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubnetSubscriptionKind {
+    /// A random subnet that we stay subscribed to for many epochs on behalf of our validators.
+    LongLived,
+    /// A subnet that we are subscribed to for an upcoming aggregation duty.
+    ShortLived,
+}
+
+/// A single attestation subnet subscription along with the slot at which it is due to expire.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubnetSubscription {
+    pub subnet_id: SubnetId,
+    pub kind: SubnetSubscriptionKind,
+    /// `None` if the subscription does not expire (i.e. we are subscribed to all subnets).
+    pub expiry_slot: Option<Slot>,
+}
+
+/// A snapshot of the attestation subnets that the node is currently subscribed to.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AttestationSubnetsSummary {
+    /// The number of validators that have recently sent us a subscription.
+    pub known_validators: usize,
+    /// The number of long-lived subnets we aim to be subscribed to given `known_validators`.
+    pub long_lived_target: usize,
+    /// True if the node has been configured to subscribe to all subnets.
+    pub subscribe_all_subnets: bool,
+    pub subscriptions: Vec<SubnetSubscription>,
+}
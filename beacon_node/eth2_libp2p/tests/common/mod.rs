@@ -1,18 +1,25 @@
 #![cfg(test)]
+use eth2_libp2p::multiaddr::Protocol;
 use eth2_libp2p::Enr;
-use eth2_libp2p::EnrExt;
 use eth2_libp2p::Multiaddr;
 use eth2_libp2p::Service as LibP2PService;
 use eth2_libp2p::{GossipsubConfigBuilder, Libp2pEvent, NetworkConfig};
 use slog::{debug, error, o, Drain};
-use std::net::{TcpListener, UdpSocket};
+use std::sync::atomic::{AtomicU16, Ordering};
 use std::time::Duration;
 use types::{ChainSpec, EnrForkId, MinimalEthSpec};
 
 type E = MinimalEthSpec;
 use tempdir::TempDir;
 
-pub struct Libp2pInstance(LibP2PService<E>, exit_future::Signal);
+pub struct Libp2pInstance(LibP2PService<E>, exit_future::Signal, Multiaddr);
+
+impl Libp2pInstance {
+    /// The address that this node can be dialed on.
+    pub fn multiaddr(&self) -> Multiaddr {
+        self.2.clone()
+    }
+}
 
 impl std::ops::Deref for Libp2pInstance {
     type Target = LibP2PService<E>;
@@ -39,44 +46,24 @@ pub fn build_log(level: slog::Level, enabled: bool) -> slog::Logger {
     }
 }
 
-// A bit of hack to find an unused port.
-///
-/// Does not guarantee that the given port is unused after the function exists, just that it was
-/// unused before the function started (i.e., it does not reserve a port).
-pub fn unused_port(transport: &str) -> Result<u16, String> {
-    let local_addr = match transport {
-        "tcp" => {
-            let listener = TcpListener::bind("127.0.0.1:0").map_err(|e| {
-                format!("Failed to create TCP listener to find unused port: {:?}", e)
-            })?;
-            listener.local_addr().map_err(|e| {
-                format!(
-                    "Failed to read TCP listener local_addr to find unused port: {:?}",
-                    e
-                )
-            })?
-        }
-        "udp" => {
-            let socket = UdpSocket::bind("127.0.0.1:0")
-                .map_err(|e| format!("Failed to create UDP socket to find unused port: {:?}", e))?;
-            socket.local_addr().map_err(|e| {
-                format!(
-                    "Failed to read UDP socket local_addr to find unused port: {:?}",
-                    e
-                )
-            })?
-        }
-        _ => return Err("Invalid transport to find unused port".into()),
-    };
-    Ok(local_addr.port())
+/// The next port to use for the in-memory transport. Memory ports are local to the process, so
+/// handing them out in order is deterministic and never collides with real sockets.
+static NEXT_MEMORY_PORT: AtomicU16 = AtomicU16::new(1);
+
+/// Returns the address that a node listening on the memory `port` can be dialed on.
+pub fn memory_multiaddr(port: u16) -> Multiaddr {
+    Multiaddr::empty().with(Protocol::Memory(port as u64))
 }
 
 pub fn build_config(port: u16, mut boot_nodes: Vec<Enr>) -> NetworkConfig {
     let mut config = NetworkConfig::default();
     let path = TempDir::new(&format!("libp2p_test{}", port)).unwrap();
 
-    config.libp2p_port = port; // tcp port
+    config.libp2p_port = port; // memory port
     config.discovery_port = port; // udp port
+    config.memory_transport = true;
+    // Nodes are connected by dialing each other directly, without discovery.
+    config.disable_discovery = true;
     config.enr_tcp_port = Some(port);
     config.enr_udp_port = Some(port);
     config.enr_address = Some("127.0.0.1".parse().unwrap());
@@ -92,7 +79,7 @@ pub fn build_config(port: u16, mut boot_nodes: Vec<Enr>) -> NetworkConfig {
 }
 
 pub async fn build_libp2p_instance(boot_nodes: Vec<Enr>, log: slog::Logger) -> Libp2pInstance {
-    let port = unused_port("tcp").unwrap();
+    let port = NEXT_MEMORY_PORT.fetch_add(1, Ordering::SeqCst);
     let config = build_config(port, boot_nodes);
     // launch libp2p service
 
@@ -116,6 +103,7 @@ pub async fn build_libp2p_instance(boot_nodes: Vec<Enr>, log: slog::Logger) -> L
         .expect("should build libp2p instance")
         .1,
         signal,
+        memory_multiaddr(port),
     )
 }
 
@@ -132,10 +120,7 @@ pub async fn build_full_mesh(log: slog::Logger, n: usize) -> Vec<Libp2pInstance>
     for _ in 0..n {
         nodes.push(build_libp2p_instance(vec![], log.clone()).await);
     }
-    let multiaddrs: Vec<Multiaddr> = nodes
-        .iter()
-        .map(|x| get_enr(&x).multiaddr()[1].clone())
-        .collect();
+    let multiaddrs: Vec<Multiaddr> = nodes.iter().map(|x| x.multiaddr()).collect();
 
     for (i, node) in nodes.iter_mut().enumerate().take(n) {
         for (j, multiaddr) in multiaddrs.iter().enumerate().skip(i) {
@@ -160,7 +145,7 @@ pub async fn build_node_pair(log: &slog::Logger) -> (Libp2pInstance, Libp2pInsta
     let mut sender = build_libp2p_instance(vec![], sender_log).await;
     let mut receiver = build_libp2p_instance(vec![], receiver_log).await;
 
-    let receiver_multiaddr = receiver.swarm.local_enr().multiaddr()[1].clone();
+    let receiver_multiaddr = receiver.multiaddr();
 
     // let the two nodes set up listeners
     let sender_fut = async {
@@ -203,10 +188,7 @@ pub async fn build_linear(log: slog::Logger, n: usize) -> Vec<Libp2pInstance> {
         nodes.push(build_libp2p_instance(vec![], log.clone()).await);
     }
 
-    let multiaddrs: Vec<Multiaddr> = nodes
-        .iter()
-        .map(|x| get_enr(&x).multiaddr()[1].clone())
-        .collect();
+    let multiaddrs: Vec<Multiaddr> = nodes.iter().map(|x| x.multiaddr()).collect();
     for i in 0..n - 1 {
         match libp2p::Swarm::dial_addr(&mut nodes[i].swarm, multiaddrs[i + 1].clone()) {
             Ok(()) => debug!(log, "Connected"),
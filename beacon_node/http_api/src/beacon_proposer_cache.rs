@@ -1,10 +1,12 @@
 use crate::metrics;
 use beacon_chain::{BeaconChain, BeaconChainError, BeaconChainTypes};
-use eth2::types::ProposerData;
+use eth2::types::{DutiesResponse, ProposerData};
 use fork_choice::ProtoBlock;
+use parking_lot::Mutex;
 use slot_clock::SlotClock;
 use state_processing::per_slot_processing;
-use types::{BeaconState, Epoch, EthSpec, Hash256, PublicKeyBytes};
+use std::sync::Arc;
+use types::{BeaconState, CloneConfig, Epoch, EthSpec, Hash256, PublicKeyBytes};
 
 /// This sets a maximum bound on the number of epochs to skip whilst instantiating the cache for
 /// the first time.
@@ -12,14 +14,21 @@ const EPOCHS_TO_SKIP: u64 = 2;
 
 /// Caches the beacon block proposers for a given `epoch` and `epoch_boundary_root`.
 ///
-/// This cache is only able to contain a single set of proposers and is only
-/// intended to cache the proposers for the current epoch according to the head
-/// of the chain. A change in epoch or re-org to a different chain may cause a
-/// cache miss and rebuild.
+/// This cache is intended to cache the proposers for the current epoch according to the head
+/// of the chain, along with a look-ahead at the proposers for the next epoch. A change in epoch
+/// or re-org to a different chain may cause a cache miss and rebuild.
 pub struct BeaconProposerCache {
     epoch: Epoch,
     decision_block_root: Hash256,
     proposers: Vec<ProposerData>,
+    /// The proposers for the next epoch, keyed by epoch and decision block root.
+    ///
+    /// The decision block of the next epoch is usually the head block, so this is rebuilt each
+    /// time the head changes.
+    next_epoch_proposers: Option<(Epoch, Hash256, Vec<ProposerData>)>,
+    /// Held whilst computing `next_epoch_proposers`, which happens outside of the lock on the
+    /// cache.
+    next_epoch_lock: Arc<Mutex<()>>,
 }
 
 impl BeaconProposerCache {
@@ -76,10 +85,27 @@ impl BeaconProposerCache {
             per_slot_processing(&mut head_state, Some(Hash256::zero()), &chain.spec)?;
         }
 
-        let proposers = current_epoch
+        let proposers = Self::compute_proposers(chain, current_epoch, &head_state)?;
+
+        Ok(Self {
+            epoch: current_epoch,
+            decision_block_root,
+            proposers,
+            next_epoch_proposers: None,
+            next_epoch_lock: Arc::new(Mutex::new(())),
+        })
+    }
+
+    /// Computes the proposers for each slot of `epoch` from `state`, which must be in `epoch`.
+    pub fn compute_proposers<T: BeaconChainTypes>(
+        chain: &BeaconChain<T>,
+        epoch: Epoch,
+        state: &BeaconState<T::EthSpec>,
+    ) -> Result<Vec<ProposerData>, BeaconChainError> {
+        epoch
             .slot_iter(T::EthSpec::slots_per_epoch())
             .map(|slot| {
-                head_state
+                state
                     .get_beacon_proposer_index(slot, &chain.spec)
                     .map_err(BeaconChainError::from)
                     .and_then(|i| {
@@ -94,13 +120,7 @@ impl BeaconProposerCache {
                         })
                     })
             })
-            .collect::<Result<_, _>>()?;
-
-        Ok(Self {
-            epoch: current_epoch,
-            decision_block_root,
-            proposers,
-        })
+            .collect()
     }
 
     /// Returns a block root which can be used to key the shuffling obtained from the following
@@ -129,7 +149,8 @@ impl BeaconProposerCache {
         }
     }
 
-    /// Return the proposers for the given `Epoch`.
+    /// Return the proposers for the given `Epoch`, along with the block root on which they
+    /// depend.
     ///
     /// The cache may be rebuilt if:
     ///
@@ -139,7 +160,7 @@ impl BeaconProposerCache {
         &mut self,
         chain: &BeaconChain<T>,
         epoch: Epoch,
-    ) -> Result<Vec<ProposerData>, warp::Rejection> {
+    ) -> Result<DutiesResponse<Vec<ProposerData>>, warp::Rejection> {
         let current_epoch = chain
             .slot_clock
             .now_or_genesis()
@@ -175,12 +196,101 @@ impl BeaconProposerCache {
         if self.epoch != current_epoch || self.decision_block_root != head_decision_block_root {
             metrics::inc_counter(&metrics::HTTP_API_BEACON_PROPOSER_CACHE_MISSES_TOTAL);
 
-            *self = Self::for_head_block(chain, current_epoch, head_block_root, head_block)
+            let rebuilt = Self::for_head_block(chain, current_epoch, head_block_root, head_block)
                 .map_err(warp_utils::reject::beacon_chain_error)?;
+            *self = Self {
+                next_epoch_proposers: self.next_epoch_proposers.take(),
+                next_epoch_lock: self.next_epoch_lock.clone(),
+                ..rebuilt
+            };
         } else {
             metrics::inc_counter(&metrics::HTTP_API_BEACON_PROPOSER_CACHE_HITS_TOTAL);
         }
 
-        Ok(self.proposers.clone())
+        Ok(DutiesResponse {
+            dependent_root: self.decision_block_root,
+            data: self.proposers.clone(),
+        })
+    }
+
+    /// Return the proposers for `epoch`, which must be the epoch after the current epoch, along
+    /// with the block root on which they depend.
+    ///
+    /// The proposers are computed by skipping a copy of the head state into `epoch`. This is done
+    /// without holding the lock on `cache`, so that it does not block lookups for the current
+    /// epoch, and only once for each decision block root.
+    pub fn get_next_epoch_proposers<T: BeaconChainTypes>(
+        cache: &Mutex<Self>,
+        chain: &BeaconChain<T>,
+        epoch: Epoch,
+    ) -> Result<DutiesResponse<Vec<ProposerData>>, warp::Rejection> {
+        let decision_block_root = chain
+            .with_head(|head| {
+                Self::decision_block_root(epoch, head.beacon_block_root, &head.beacon_state)
+            })
+            .map_err(warp_utils::reject::beacon_chain_error)?;
+
+        // Only allow one request at a time to compute the look-ahead, so that concurrent requests
+        // for the same head wait for the first of them rather than repeating its work.
+        let next_epoch_lock = cache.lock().next_epoch_lock.clone();
+        let _next_epoch_guard = next_epoch_lock.lock();
+
+        if let Some(proposers) = cache
+            .lock()
+            .cached_next_epoch_proposers(epoch, decision_block_root)
+        {
+            metrics::inc_counter(&metrics::HTTP_API_BEACON_PROPOSER_CACHE_HITS_TOTAL);
+            return Ok(DutiesResponse {
+                dependent_root: decision_block_root,
+                data: proposers,
+            });
+        }
+
+        metrics::inc_counter(&metrics::HTTP_API_BEACON_PROPOSER_CACHE_MISSES_TOTAL);
+        let _timer = metrics::start_timer(&metrics::HTTP_API_BEACON_PROPOSER_CACHE_TIMES);
+
+        // The head may have changed since the decision block root was read, so read it again
+        // alongside the state.
+        let (decision_block_root, mut state) = chain
+            .with_head(|head| {
+                let decision_block_root =
+                    Self::decision_block_root(epoch, head.beacon_block_root, &head.beacon_state)?;
+                let state = head
+                    .beacon_state
+                    .clone_with(CloneConfig::committee_caches_only());
+                Ok((decision_block_root, state))
+            })
+            .map_err(warp_utils::reject::beacon_chain_error)?;
+
+        while state.current_epoch() < epoch {
+            // Don't calculate state roots since they aren't required for calculating
+            // proposers (achieved by providing Hash256::zero()).
+            per_slot_processing(&mut state, Some(Hash256::zero()), &chain.spec)
+                .map_err(warp_utils::reject::slot_processing_error)?;
+        }
+
+        let proposers = Self::compute_proposers(chain, epoch, &state)
+            .map_err(warp_utils::reject::beacon_chain_error)?;
+
+        cache.lock().next_epoch_proposers = Some((epoch, decision_block_root, proposers.clone()));
+
+        Ok(DutiesResponse {
+            dependent_root: decision_block_root,
+            data: proposers,
+        })
+    }
+
+    /// Returns the cached proposers for `epoch` if they were computed from `decision_block_root`.
+    fn cached_next_epoch_proposers(
+        &self,
+        epoch: Epoch,
+        decision_block_root: Hash256,
+    ) -> Option<Vec<ProposerData>> {
+        self.next_epoch_proposers
+            .as_ref()
+            .filter(|(cached_epoch, root, _)| {
+                *cached_epoch == epoch && *root == decision_block_root
+            })
+            .map(|(_, _, proposers)| proposers.clone())
     }
 }
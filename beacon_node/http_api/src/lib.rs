@@ -13,15 +13,20 @@ mod validator_inclusion;
 
 use beacon_chain::{
     observed_operations::ObservationOutcome, AttestationError as AttnError, BeaconChain,
-    BeaconChainError, BeaconChainTypes,
+    BeaconChainError, BeaconChainTypes, PackingStrategy,
 };
 use beacon_proposer_cache::BeaconProposerCache;
 use block_id::BlockId;
+use eth2::lighthouse::{
+    BlockPackingStrategy, LighthouseValidatorBlocksQuery, PeerManagementAction,
+};
 use eth2::{
     types::{self as api_types, ValidatorId},
     StatusCode,
 };
-use eth2_libp2p::{types::SyncState, EnrExt, NetworkGlobals, PeerId, PubsubMessage};
+use eth2_libp2p::{
+    types::SyncState, Enr, EnrExt, Multiaddr, NetworkGlobals, PeerAction, PeerId, PubsubMessage,
+};
 use lighthouse_version::version_with_platform;
 use network::NetworkMessage;
 use parking_lot::Mutex;
@@ -36,10 +41,11 @@ use std::convert::TryInto;
 use std::future::Future;
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc::UnboundedSender;
 use types::{
     Attestation, AttestationDuty, AttesterSlashing, CloneConfig, CommitteeCache, Epoch, EthSpec,
-    Hash256, ProposerSlashing, PublicKey, PublicKeyBytes, RelativeEpoch, SignedAggregateAndProof,
+    Hash256, ProposerSlashing, PublicKey, RelativeEpoch, SignedAggregateAndProof,
     SignedBeaconBlock, SignedVoluntaryExit, Slot, YamlConfig,
 };
 use warp::{http::Response, Filter};
@@ -55,6 +61,14 @@ const API_VERSION: &str = "v1";
 /// finalized head.
 const SYNC_TOLERANCE_EPOCHS: u64 = 8;
 
+/// The time budget used by the `time_budget` block packing strategy when none is requested.
+const DEFAULT_PACKING_TIME_BUDGET: Duration = Duration::from_millis(200);
+
+/// The greatest time budget that may be requested for the `time_budget` block packing strategy.
+///
+/// Block production must finish well within a slot for the block to propagate in time.
+const MAX_PACKING_TIME_BUDGET: Duration = Duration::from_millis(1_000);
+
 /// A wrapper around all the items required to spawn the HTTP server.
 ///
 /// The server will gracefully handle the case where any fields are `None`.
@@ -74,6 +88,7 @@ pub struct Config {
     pub listen_addr: Ipv4Addr,
     pub listen_port: u16,
     pub allow_origin: Option<String>,
+    pub allow_peer_management: bool,
 }
 
 impl Default for Config {
@@ -83,6 +98,7 @@ impl Default for Config {
             listen_addr: Ipv4Addr::new(127, 0, 0, 1),
             listen_port: 5052,
             allow_origin: None,
+            allow_peer_management: false,
         }
     }
 }
@@ -314,6 +330,22 @@ pub fn serve<T: BeaconChainTypes>(
             }
         });
 
+    // Create a `warp` filter that rejects requests which modify the peers of the node, unless
+    // they have been explicitly enabled.
+    let allow_peer_management = config.allow_peer_management;
+    let peer_management_filter = warp::any()
+        .and_then(move || async move {
+            if allow_peer_management {
+                Ok(())
+            } else {
+                Err(warp_utils::reject::custom_not_found(
+                    "Peer management is disabled. Use --http-allow-peer-management on the CLI."
+                        .to_string(),
+                ))
+            }
+        })
+        .untuple_one();
+
     // Create a `warp` filter that rejects request whilst the node is syncing.
     let not_while_syncing_filter = warp::any()
         .and(network_globals.clone())
@@ -1547,49 +1579,48 @@ pub fn serve<T: BeaconChainTypes>(
                         .epoch()
                         .map_err(warp_utils::reject::beacon_chain_error)?;
 
-                    if epoch > current_epoch {
+                    if epoch > current_epoch + 1 {
                         return Err(warp_utils::reject::custom_bad_request(format!(
-                            "request epoch {} is ahead of the current epoch {}",
+                            "request epoch {} is more than one epoch past the current epoch {}",
                             epoch, current_epoch
                         )));
                     }
 
                     if epoch == current_epoch {
-                        beacon_proposer_cache
-                            .lock()
-                            .get_proposers(&chain, epoch)
-                            .map(api_types::GenericResponse::from)
-                    } else {
-                        let state =
-                            StateId::slot(epoch.start_slot(T::EthSpec::slots_per_epoch()))
-                                .state(&chain)?;
-
-                        epoch
-                            .slot_iter(T::EthSpec::slots_per_epoch())
-                            .map(|slot| {
-                                state
-                                    .get_beacon_proposer_index(slot, &chain.spec)
-                                    .map_err(warp_utils::reject::beacon_state_error)
-                                    .and_then(|i| {
-                                        let pubkey =
-                                            chain.validator_pubkey(i)
-                                                .map_err(warp_utils::reject::beacon_chain_error)?
-                                                .ok_or_else(||
-                                                    warp_utils::reject::beacon_chain_error(
-                                                        BeaconChainError::ValidatorPubkeyCacheIncomplete(i)
-                                                    )
-                                                )?;
-
-                                        Ok(api_types::ProposerData {
-                                            pubkey: PublicKeyBytes::from(pubkey),
-                                            validator_index: i as u64,
-                                            slot,
-                                        })
-                                    })
-                            })
-                            .collect::<Result<Vec<api_types::ProposerData>, _>>()
-                            .map(api_types::GenericResponse::from)
+                        return beacon_proposer_cache.lock().get_proposers(&chain, epoch);
                     }
+
+                    // The proposers for the next epoch depend upon the last block of the current
+                    // epoch, so they are subject to change until the end of the current epoch.
+                    // They are cached until the head changes.
+                    if epoch > current_epoch {
+                        return BeaconProposerCache::get_next_epoch_proposers(
+                            &beacon_proposer_cache,
+                            &chain,
+                            epoch,
+                        );
+                    }
+
+                    // Historical requests load the state at the start of the request epoch from
+                    // disk.
+                    let state = StateId::slot(epoch.start_slot(T::EthSpec::slots_per_epoch()))
+                        .state(&chain)?;
+
+                    // The state is at the start of `epoch`, so it only falls back to the provided
+                    // root at genesis.
+                    let dependent_root = BeaconProposerCache::decision_block_root(
+                        epoch,
+                        chain.genesis_block_root,
+                        &state,
+                    )
+                    .map_err(warp_utils::reject::beacon_chain_error)?;
+
+                    BeaconProposerCache::compute_proposers(&chain, epoch, &state)
+                        .map(|data| api_types::DutiesResponse {
+                            dependent_root,
+                            data,
+                        })
+                        .map_err(warp_utils::reject::beacon_chain_error)
                 })
             },
         );
@@ -1757,9 +1788,23 @@ pub fn serve<T: BeaconChainTypes>(
                     //
                     // The idea is to stop historical requests from washing out the cache on the
                     // beacon chain, whilst allowing a VC to request duties quickly.
-                    let duties = if epoch == current_epoch {
+                    //
+                    // In both cases the duties depend upon the block at the last slot of
+                    // `epoch - 2`, which is the proposer shuffling decision block of `epoch - 1`.
+                    let dependent_epoch = epoch.saturating_sub(1_u64);
+                    let (dependent_root, duties) = if epoch == current_epoch {
                         // Fast path.
-                        pubkeys
+                        let dependent_root = chain
+                            .with_head(|head| {
+                                BeaconProposerCache::decision_block_root(
+                                    dependent_epoch,
+                                    head.beacon_block_root,
+                                    &head.beacon_state,
+                                )
+                            })
+                            .map_err(warp_utils::reject::beacon_chain_error)?;
+
+                        let duties = pubkeys
                             .into_iter()
                             // Exclude indices which do not represent a known public key and a
                             // validator duty.
@@ -1772,16 +1817,22 @@ pub fn serve<T: BeaconChainTypes>(
                                         .map(|duty| convert(i, pubkey, duty)),
                                 )
                             })
-                            .collect::<Result<Vec<_>, warp::Rejection>>()?
+                            .collect::<Result<Vec<_>, warp::Rejection>>()?;
+
+                        (dependent_root, duties)
                     } else {
                         // If the head state is equal to or earlier than the request epoch, use it.
-                        let mut state = chain
+                        //
+                        // Otherwise, load a state at the start of the request epoch. This state
+                        // is always past the decision slot, except at genesis.
+                        let (state_block_root, mut state) = chain
                             .with_head(|head| {
                                 if head.beacon_state.current_epoch() <= epoch {
-                                    Ok(Some(
+                                    Ok(Some((
+                                        head.beacon_block_root,
                                         head.beacon_state
                                             .clone_with(CloneConfig::committee_caches_only()),
-                                    ))
+                                    )))
                                 } else {
                                     Ok(None)
                                 }
@@ -1791,8 +1842,16 @@ pub fn serve<T: BeaconChainTypes>(
                             .unwrap_or_else(|| {
                                 StateId::slot(epoch.start_slot(T::EthSpec::slots_per_epoch()))
                                     .state(&chain)
+                                    .map(|state| (chain.genesis_block_root, state))
                             })?;
 
+                        let dependent_root = BeaconProposerCache::decision_block_root(
+                            dependent_epoch,
+                            state_block_root,
+                            &state,
+                        )
+                        .map_err(warp_utils::reject::beacon_chain_error)?;
+
                         // Only skip forward to the epoch prior to the request, since we have a
                         // one-epoch look-ahead on shuffling.
                         while state
@@ -1819,7 +1878,7 @@ pub fn serve<T: BeaconChainTypes>(
                         state
                             .build_committee_cache(relative_epoch, &chain.spec)
                             .map_err(warp_utils::reject::beacon_state_error)?;
-                        pubkeys
+                        let duties = pubkeys
                             .into_iter()
                             .filter_map(|(i, pubkey)| {
                                 Some(
@@ -1830,10 +1889,15 @@ pub fn serve<T: BeaconChainTypes>(
                                         .map(|duty| convert(i, pubkey, duty)),
                                 )
                             })
-                            .collect::<Result<Vec<_>, warp::Rejection>>()?
+                            .collect::<Result<Vec<_>, warp::Rejection>>()?;
+
+                        (dependent_root, duties)
                     };
 
-                    Ok(api_types::GenericResponse::from(duties))
+                    Ok(api_types::DutiesResponse {
+                        dependent_root,
+                        data: duties,
+                    })
                 })
             },
         );
@@ -1932,7 +1996,7 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("beacon_committee_subscriptions"))
         .and(warp::path::end())
         .and(warp::body::json())
-        .and(network_tx_filter)
+        .and(network_tx_filter.clone())
         .and_then(
             |subscriptions: Vec<api_types::BeaconCommitteeSubscription>,
              network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>| {
@@ -2008,7 +2072,7 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("peers"))
         .and(warp::path("connected"))
         .and(warp::path::end())
-        .and(network_globals)
+        .and(network_globals.clone())
         .and_then(|network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
             blocking_json_task(move || {
                 Ok(network_globals
@@ -2023,6 +2087,172 @@ pub fn serve<T: BeaconChainTypes>(
             })
         });
 
+    // GET lighthouse/gossipsub/score_params
+    let get_lighthouse_gossipsub_score_params = warp::path("lighthouse")
+        .and(warp::path("gossipsub"))
+        .and(warp::path("score_params"))
+        .and(warp::path::end())
+        .and(network_globals.clone())
+        .and_then(|network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
+            blocking_json_task(move || {
+                network_globals
+                    .gossipsub_score_params
+                    .read()
+                    .clone()
+                    .map(api_types::GenericResponse::from)
+                    .ok_or_else(|| {
+                        warp_utils::reject::custom_not_found(
+                            "gossipsub score parameters have not been computed.".to_string(),
+                        )
+                    })
+            })
+        });
+
+    // GET lighthouse/subnets
+    let get_lighthouse_subnets = warp::path("lighthouse")
+        .and(warp::path("subnets"))
+        .and(warp::path::end())
+        .and(network_globals.clone())
+        .and_then(|network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
+            blocking_json_task(move || {
+                Ok(api_types::GenericResponse::from(
+                    network_globals.attestation_subnets.read().clone(),
+                ))
+            })
+        });
+
+    // GET lighthouse/peers/{peer_id}
+    let get_lighthouse_peers_by_id = warp::path("lighthouse")
+        .and(warp::path("peers"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(network_globals.clone())
+        .and_then(
+            |requested_peer_id: String, network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
+                blocking_json_task(move || {
+                    let peer_id = parse_peer_id(&requested_peer_id)?;
+                    network_globals
+                        .peers
+                        .read()
+                        .peer_info(&peer_id)
+                        .map(|peer_info| eth2::lighthouse::Peer {
+                            peer_id: peer_id.to_string(),
+                            peer_info: peer_info.clone(),
+                        })
+                        .ok_or_else(|| {
+                            warp_utils::reject::custom_not_found("peer not found.".to_string())
+                        })
+                })
+            },
+        );
+
+    // POST lighthouse/peers/dial
+    let post_lighthouse_peers_dial = warp::path("lighthouse")
+        .and(warp::path("peers"))
+        .and(warp::path("dial"))
+        .and(warp::path::end())
+        .and(peer_management_filter.clone())
+        .and(warp::body::json())
+        .and(network_tx_filter.clone())
+        .and_then(
+            |request: eth2::lighthouse::PeerDialRequest,
+             network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>| {
+                blocking_json_task(move || {
+                    let message = if request.address.starts_with("enr:") {
+                        let enr = request.address.parse::<Enr>().map_err(|e| {
+                            warp_utils::reject::custom_bad_request(format!("invalid enr: {}", e))
+                        })?;
+                        NetworkMessage::DialEnr { enr }
+                    } else {
+                        let address = request.address.parse::<Multiaddr>().map_err(|e| {
+                            warp_utils::reject::custom_bad_request(format!(
+                                "invalid multiaddr: {}",
+                                e
+                            ))
+                        })?;
+                        NetworkMessage::DialAddress { address }
+                    };
+
+                    publish_network_message(&network_tx, message)
+                })
+            },
+        );
+
+    // POST lighthouse/peers/{peer_id}/{action}
+    let post_lighthouse_peers_action = warp::path("lighthouse")
+        .and(warp::path("peers"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::param::<PeerManagementAction>())
+        .and(warp::path::end())
+        .and(peer_management_filter)
+        .and(network_globals)
+        .and(network_tx_filter)
+        .and_then(
+            |requested_peer_id: String,
+             action: PeerManagementAction,
+             network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+             network_tx: UnboundedSender<NetworkMessage<T::EthSpec>>| {
+                blocking_json_task(move || {
+                    let peer_id = parse_peer_id(&requested_peer_id)?;
+
+                    let message = {
+                        let peers = network_globals.peers.read();
+                        let peer_info = peers.peer_info(&peer_id);
+
+                        // Any peer may be trusted, all other actions require a known peer.
+                        if action != PeerManagementAction::Trust && peer_info.is_none() {
+                            return Err(warp_utils::reject::custom_not_found(
+                                "peer not found.".to_string(),
+                            ));
+                        }
+                        let is_trusted = peer_info.map_or(false, |info| info.is_trusted);
+                        let bad_request = |msg: &str| {
+                            Err(warp_utils::reject::custom_bad_request(msg.to_string()))
+                        };
+
+                        match action {
+                            PeerManagementAction::Trust => NetworkMessage::SetTrustedPeer {
+                                peer_id,
+                                trusted: true,
+                            },
+                            PeerManagementAction::Untrust => {
+                                if !is_trusted {
+                                    return bad_request("peer is not trusted.");
+                                }
+                                NetworkMessage::SetTrustedPeer {
+                                    peer_id,
+                                    trusted: false,
+                                }
+                            }
+                            PeerManagementAction::Disconnect => {
+                                if !peers.is_connected(&peer_id) {
+                                    return bad_request("peer is not connected.");
+                                }
+                                NetworkMessage::DisconnectPeer { peer_id }
+                            }
+                            PeerManagementAction::Ban => {
+                                if is_trusted {
+                                    return bad_request("trusted peers cannot be banned.");
+                                }
+                                NetworkMessage::ReportPeer {
+                                    peer_id,
+                                    action: PeerAction::Fatal,
+                                }
+                            }
+                            PeerManagementAction::Unban => {
+                                if !peer_info.map_or(false, |info| info.is_banned()) {
+                                    return bad_request("peer is not banned.");
+                                }
+                                NetworkMessage::UnbanPeer { peer_id }
+                            }
+                        }
+                    };
+
+                    publish_network_message(&network_tx, message)
+                })
+            },
+        );
+
     // GET lighthouse/proto_array
     let get_lighthouse_proto_array = warp::path("lighthouse")
         .and(warp::path("proto_array"))
@@ -2066,6 +2296,57 @@ pub fn serve<T: BeaconChainTypes>(
             })
         });
 
+    // GET lighthouse/validator/blocks/{slot}
+    let get_lighthouse_validator_blocks = warp::path("lighthouse")
+        .and(warp::path("validator"))
+        .and(warp::path("blocks"))
+        .and(warp::path::param::<Slot>())
+        .and(warp::path::end())
+        .and(not_while_syncing_filter.clone())
+        .and(warp::query::<LighthouseValidatorBlocksQuery>())
+        .and(chain_filter.clone())
+        .and_then(
+            |slot: Slot, query: LighthouseValidatorBlocksQuery, chain: Arc<BeaconChain<T>>| {
+                blocking_json_task(move || {
+                    let randao_reveal = (&query.randao_reveal).try_into().map_err(|e| {
+                        warp_utils::reject::custom_bad_request(format!(
+                            "randao reveal is not valid BLS signature: {:?}",
+                            e
+                        ))
+                    })?;
+
+                    let time_budget = query
+                        .time_budget_ms
+                        .map_or(DEFAULT_PACKING_TIME_BUDGET, Duration::from_millis);
+                    if time_budget > MAX_PACKING_TIME_BUDGET {
+                        return Err(warp_utils::reject::custom_bad_request(format!(
+                            "time_budget_ms must not exceed {}",
+                            MAX_PACKING_TIME_BUDGET.as_millis()
+                        )));
+                    }
+
+                    let packing_strategy = match query.packing_strategy {
+                        None | Some(BlockPackingStrategy::MaxReward) => PackingStrategy::MaxReward,
+                        Some(BlockPackingStrategy::FreshFirst) => PackingStrategy::FreshFirst,
+                        Some(BlockPackingStrategy::TimeBudget) => {
+                            PackingStrategy::TimeBudget(time_budget)
+                        }
+                    };
+
+                    chain
+                        .produce_block_with_strategy(
+                            randao_reveal,
+                            slot,
+                            query.graffiti.map(Into::into),
+                            packing_strategy,
+                        )
+                        .map(|block_and_state| block_and_state.0)
+                        .map(api_types::GenericResponse::from)
+                        .map_err(warp_utils::reject::block_production_error)
+                })
+            },
+        );
+
     // GET lighthouse/eth1/syncing
     let get_lighthouse_eth1_syncing = warp::path("lighthouse")
         .and(warp::path("eth1"))
@@ -2205,9 +2486,13 @@ pub fn serve<T: BeaconChainTypes>(
                 .or(get_lighthouse_syncing.boxed())
                 .or(get_lighthouse_peers.boxed())
                 .or(get_lighthouse_peers_connected.boxed())
+                .or(get_lighthouse_peers_by_id.boxed())
+                .or(get_lighthouse_gossipsub_score_params.boxed())
+                .or(get_lighthouse_subnets.boxed())
                 .or(get_lighthouse_proto_array.boxed())
                 .or(get_lighthouse_validator_inclusion_global.boxed())
                 .or(get_lighthouse_validator_inclusion.boxed())
+                .or(get_lighthouse_validator_blocks.boxed())
                 .or(get_lighthouse_eth1_syncing.boxed())
                 .or(get_lighthouse_eth1_block_cache.boxed())
                 .or(get_lighthouse_eth1_deposit_cache.boxed())
@@ -2222,7 +2507,9 @@ pub fn serve<T: BeaconChainTypes>(
                 .or(post_beacon_pool_voluntary_exits.boxed())
                 .or(post_validator_duties_attester.boxed())
                 .or(post_validator_aggregate_and_proofs.boxed())
-                .or(post_validator_beacon_committee_subscriptions.boxed()),
+                .or(post_validator_beacon_committee_subscriptions.boxed())
+                .or(post_lighthouse_peers_dial.boxed())
+                .or(post_lighthouse_peers_action.boxed()),
         ))
         .recover(warp_utils::reject::handle_rejection)
         .with(slog_logging(log.clone()))
@@ -2247,6 +2534,15 @@ pub fn serve<T: BeaconChainTypes>(
     Ok((listening_socket, server))
 }
 
+/// Parse a base58 encoded peer id.
+fn parse_peer_id(peer_id: &str) -> Result<PeerId, warp::Rejection> {
+    let bytes = bs58::decode(peer_id)
+        .into_vec()
+        .map_err(|e| warp_utils::reject::custom_bad_request(format!("invalid peer id: {}", e)))?;
+    PeerId::from_bytes(bytes)
+        .map_err(|_| warp_utils::reject::custom_bad_request("invalid peer id.".to_string()))
+}
+
 /// Publish a message to the libp2p pubsub network.
 fn publish_pubsub_message<T: EthSpec>(
     network_tx: &UnboundedSender<NetworkMessage<T>>,
@@ -7,7 +7,14 @@ use beacon_chain::{
 use discv5::enr::{CombinedKey, EnrBuilder};
 use environment::null_logger;
 use eth2::Error;
-use eth2::{types::*, BeaconNodeHttpClient, Url};
+use eth2::{
+    lighthouse::{
+        AttestationSubnetsSummary, BlockPackingStrategy, PeerManagementAction, SubnetSubscription,
+        SubnetSubscriptionKind,
+    },
+    types::*,
+    BeaconNodeHttpClient, Url,
+};
 use eth2_libp2p::{
     rpc::methods::MetaData,
     types::{EnrBitfield, SyncState},
@@ -50,6 +57,27 @@ const SKIPPED_SLOTS: &[u64] = &[
     FINALIZED_EPOCH * SLOTS_PER_EPOCH,
 ];
 
+/// The subnet subscriptions reported by the network service in these tests.
+fn attestation_subnets_summary() -> AttestationSubnetsSummary {
+    AttestationSubnetsSummary {
+        known_validators: 1,
+        long_lived_target: 1,
+        subscribe_all_subnets: false,
+        subscriptions: vec![
+            SubnetSubscription {
+                subnet_id: SubnetId::new(3),
+                kind: SubnetSubscriptionKind::LongLived,
+                expiry_slot: Some(Slot::new(CHAIN_LENGTH + 256)),
+            },
+            SubnetSubscription {
+                subnet_id: SubnetId::new(7),
+                kind: SubnetSubscriptionKind::ShortLived,
+                expiry_slot: Some(Slot::new(CHAIN_LENGTH + 1)),
+            },
+        ],
+    }
+}
+
 struct ApiTester {
     chain: Arc<BeaconChain<EphemeralHarnessType<E>>>,
     client: BeaconNodeHttpClient,
@@ -165,6 +193,7 @@ impl ApiTester {
         );
 
         *network_globals.sync_state.write() = SyncState::Synced;
+        *network_globals.attestation_subnets.write() = attestation_subnets_summary();
 
         let eth1_service =
             eth1::Service::new(eth1::Config::default(), log.clone(), chain.spec.clone());
@@ -175,6 +204,7 @@ impl ApiTester {
                 listen_addr: Ipv4Addr::new(127, 0, 0, 1),
                 listen_port: 0,
                 allow_origin: None,
+                allow_peer_management: true,
             },
             chain: Some(chain.clone()),
             network_tx: Some(network_tx),
@@ -1371,12 +1401,18 @@ impl ApiTester {
                     continue;
                 }
 
-                let results = self
+                let response = self
                     .client
                     .post_validator_duties_attester(epoch, indices.as_slice())
                     .await
-                    .unwrap()
-                    .data;
+                    .unwrap();
+
+                assert_eq!(
+                    response.dependent_root,
+                    self.dependent_root(epoch.saturating_sub(1_u64))
+                );
+
+                let results = response.data;
 
                 let mut state = self
                     .chain
@@ -1434,84 +1470,121 @@ impl ApiTester {
         self
     }
 
+    /// Returns the root of the block that decides the proposer shuffling for `shuffling_epoch`,
+    /// according to the head of the chain.
+    fn dependent_root(&self, shuffling_epoch: Epoch) -> Hash256 {
+        let decision_slot = shuffling_epoch
+            .start_slot(E::slots_per_epoch())
+            .saturating_sub(1_u64);
+
+        self.chain
+            .block_root_at_slot(decision_slot)
+            .unwrap()
+            .unwrap_or_else(|| self.chain.head_beacon_block_root().unwrap())
+    }
+
     pub async fn test_get_validator_duties_proposer(self) -> Self {
         let current_epoch = self.chain.epoch().unwrap();
 
-        let result = self
-            .client
-            .get_validator_duties_proposer(current_epoch)
-            .await
-            .unwrap()
-            .data;
+        for epoch in &[current_epoch, current_epoch + 1] {
+            let epoch = *epoch;
 
-        let mut state = self.chain.head_beacon_state().unwrap();
+            let response = self
+                .client
+                .get_validator_duties_proposer(epoch)
+                .await
+                .unwrap();
 
-        while state.current_epoch() < current_epoch {
-            per_slot_processing(&mut state, None, &self.chain.spec).unwrap();
-        }
+            assert_eq!(response.dependent_root, self.dependent_root(epoch));
 
-        state
-            .build_committee_cache(RelativeEpoch::Current, &self.chain.spec)
-            .unwrap();
+            let mut state = self.chain.head_beacon_state().unwrap();
 
-        let expected = current_epoch
-            .slot_iter(E::slots_per_epoch())
-            .map(|slot| {
-                let index = state
-                    .get_beacon_proposer_index(slot, &self.chain.spec)
-                    .unwrap();
-                let pubkey = state.validators[index].pubkey.clone().into();
+            while state.current_epoch() < epoch {
+                per_slot_processing(&mut state, None, &self.chain.spec).unwrap();
+            }
 
-                ProposerData {
-                    pubkey,
-                    validator_index: index as u64,
-                    slot,
-                }
-            })
-            .collect::<Vec<_>>();
+            state
+                .build_committee_cache(RelativeEpoch::Current, &self.chain.spec)
+                .unwrap();
 
-        assert_eq!(result, expected);
+            let expected = epoch
+                .slot_iter(E::slots_per_epoch())
+                .map(|slot| {
+                    let index = state
+                        .get_beacon_proposer_index(slot, &self.chain.spec)
+                        .unwrap();
+                    let pubkey = state.validators[index].pubkey.clone().into();
+
+                    ProposerData {
+                        pubkey,
+                        validator_index: index as u64,
+                        slot,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(response.data, expected, "epoch: {}", epoch);
+        }
+
+        // The endpoint does not allow getting duties past the next epoch.
+        assert_eq!(
+            self.client
+                .get_validator_duties_proposer(current_epoch + 2)
+                .await
+                .unwrap_err()
+                .status()
+                .map(Into::into),
+            Some(400)
+        );
 
         self
     }
 
-    pub async fn test_block_production(self) -> Self {
+    /// Returns the secret key of the proposer at the current slot, along with its randao reveal.
+    async fn proposer_sk_and_randao_reveal(&self) -> (SecretKey, SignatureBytes) {
         let fork = self.chain.head_info().unwrap().fork;
         let genesis_validators_root = self.chain.genesis_validators_root;
+        let slot = self.chain.slot().unwrap();
+        let epoch = self.chain.epoch().unwrap();
 
-        for _ in 0..E::slots_per_epoch() * 3 {
-            let slot = self.chain.slot().unwrap();
-            let epoch = self.chain.epoch().unwrap();
+        let proposer_pubkey_bytes = self
+            .client
+            .get_validator_duties_proposer(epoch)
+            .await
+            .unwrap()
+            .data
+            .into_iter()
+            .find(|duty| duty.slot == slot)
+            .map(|duty| duty.pubkey)
+            .unwrap();
+        let proposer_pubkey = (&proposer_pubkey_bytes).try_into().unwrap();
 
-            let proposer_pubkey_bytes = self
-                .client
-                .get_validator_duties_proposer(epoch)
-                .await
-                .unwrap()
-                .data
-                .into_iter()
-                .find(|duty| duty.slot == slot)
-                .map(|duty| duty.pubkey)
-                .unwrap();
-            let proposer_pubkey = (&proposer_pubkey_bytes).try_into().unwrap();
+        let sk = self
+            .validator_keypairs
+            .iter()
+            .find(|kp| kp.pk == proposer_pubkey)
+            .map(|kp| kp.sk.clone())
+            .unwrap();
 
-            let sk = self
-                .validator_keypairs
-                .iter()
-                .find(|kp| kp.pk == proposer_pubkey)
-                .map(|kp| kp.sk.clone())
-                .unwrap();
+        let randao_reveal = {
+            let domain =
+                self.chain
+                    .spec
+                    .get_domain(epoch, Domain::Randao, &fork, genesis_validators_root);
+            let message = epoch.signing_root(domain);
+            sk.sign(message).into()
+        };
 
-            let randao_reveal = {
-                let domain = self.chain.spec.get_domain(
-                    epoch,
-                    Domain::Randao,
-                    &fork,
-                    genesis_validators_root,
-                );
-                let message = epoch.signing_root(domain);
-                sk.sign(message).into()
-            };
+        (sk, randao_reveal)
+    }
+
+    pub async fn test_block_production(self) -> Self {
+        let fork = self.chain.head_info().unwrap().fork;
+        let genesis_validators_root = self.chain.genesis_validators_root;
+
+        for _ in 0..E::slots_per_epoch() * 3 {
+            let slot = self.chain.slot().unwrap();
+            let (sk, randao_reveal) = self.proposer_sk_and_randao_reveal().await;
 
             let block = self
                 .client
@@ -1747,6 +1820,69 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_get_lighthouse_subnets(self) -> Self {
+        let result = self.client.get_lighthouse_subnets().await.unwrap().data;
+
+        assert_eq!(result, attestation_subnets_summary());
+
+        self
+    }
+
+    pub async fn test_post_lighthouse_peers_action(mut self) -> Self {
+        let peer_id = self.external_peer_id.to_string();
+
+        // The peer is connected, but neither trusted nor banned.
+        for action in &[PeerManagementAction::Untrust, PeerManagementAction::Unban] {
+            self.client
+                .post_lighthouse_peers_action(&peer_id, *action)
+                .await
+                .unwrap_err();
+            assert!(self.network_rx.try_recv().is_err());
+        }
+
+        for action in &[
+            PeerManagementAction::Trust,
+            PeerManagementAction::Disconnect,
+            PeerManagementAction::Ban,
+        ] {
+            self.client
+                .post_lighthouse_peers_action(&peer_id, *action)
+                .await
+                .unwrap();
+            assert!(self.network_rx.try_recv().is_ok());
+        }
+
+        // Unknown peers may only be trusted.
+        let unknown_peer_id = PeerId::random().to_string();
+        self.client
+            .post_lighthouse_peers_action(&unknown_peer_id, PeerManagementAction::Ban)
+            .await
+            .unwrap_err();
+        self.client
+            .post_lighthouse_peers_action(&unknown_peer_id, PeerManagementAction::Trust)
+            .await
+            .unwrap();
+        assert!(self.network_rx.try_recv().is_ok());
+
+        self
+    }
+
+    pub async fn test_post_lighthouse_peers_dial(mut self) -> Self {
+        self.client
+            .post_lighthouse_peers_dial("/ip4/127.0.0.1/tcp/9000".to_string())
+            .await
+            .unwrap();
+        assert!(self.network_rx.try_recv().is_ok());
+
+        self.client
+            .post_lighthouse_peers_dial("not an address".to_string())
+            .await
+            .unwrap_err();
+        assert!(self.network_rx.try_recv().is_err());
+
+        self
+    }
+
     pub async fn test_get_lighthouse_proto_array(self) -> Self {
         self.client.get_lighthouse_proto_array().await.unwrap();
 
@@ -1773,6 +1909,68 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_get_lighthouse_validator_blocks(self) -> Self {
+        let fork = self.chain.head_info().unwrap().fork;
+        let genesis_validators_root = self.chain.genesis_validators_root;
+        let strategies = [
+            None,
+            Some(BlockPackingStrategy::MaxReward),
+            Some(BlockPackingStrategy::FreshFirst),
+            Some(BlockPackingStrategy::TimeBudget),
+        ];
+
+        for strategy in strategies
+            .iter()
+            .cycle()
+            .take(E::slots_per_epoch() as usize * 3)
+        {
+            let slot = self.chain.slot().unwrap();
+            let (sk, randao_reveal) = self.proposer_sk_and_randao_reveal().await;
+
+            let block = self
+                .client
+                .get_lighthouse_validator_blocks::<E>(
+                    slot,
+                    randao_reveal,
+                    None,
+                    *strategy,
+                    Some(50),
+                )
+                .await
+                .unwrap()
+                .data;
+
+            let signed_block = block.sign(&sk, &fork, genesis_validators_root, &self.chain.spec);
+
+            self.client.post_beacon_blocks(&signed_block).await.unwrap();
+
+            assert_eq!(self.chain.head_beacon_block().unwrap(), signed_block);
+
+            self.chain.slot_clock.set_slot(slot.as_u64() + 1);
+        }
+
+        // The endpoint does not allow time budgets that would delay the block.
+        let slot = self.chain.slot().unwrap();
+        let (_, randao_reveal) = self.proposer_sk_and_randao_reveal().await;
+        assert_eq!(
+            self.client
+                .get_lighthouse_validator_blocks::<E>(
+                    slot,
+                    randao_reveal,
+                    None,
+                    Some(BlockPackingStrategy::TimeBudget),
+                    Some(60_000),
+                )
+                .await
+                .unwrap_err()
+                .status()
+                .map(Into::into),
+            Some(400)
+        );
+
+        self
+    }
+
     pub async fn test_get_lighthouse_eth1_syncing(self) -> Self {
         self.client.get_lighthouse_eth1_syncing().await.unwrap();
 
@@ -2002,6 +2200,13 @@ async fn block_production_with_skip_slots() {
         .await;
 }
 
+#[tokio::test(core_threads = 2)]
+async fn lighthouse_block_production() {
+    ApiTester::new()
+        .test_get_lighthouse_validator_blocks()
+        .await;
+}
+
 #[tokio::test(core_threads = 2)]
 async fn get_validator_attestation_data() {
     ApiTester::new().test_get_validator_attestation_data().await;
@@ -2074,8 +2279,14 @@ async fn lighthouse_endpoints() {
         .await
         .test_get_lighthouse_syncing()
         .await
+        .test_get_lighthouse_subnets()
+        .await
         .test_get_lighthouse_proto_array()
         .await
+        .test_post_lighthouse_peers_action()
+        .await
+        .test_post_lighthouse_peers_dial()
+        .await
         .test_get_lighthouse_validator_inclusion()
         .await
         .test_get_lighthouse_validator_inclusion_global()
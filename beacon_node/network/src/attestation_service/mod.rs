@@ -13,7 +13,10 @@ use rand::seq::SliceRandom;
 use slog::{debug, error, o, trace, warn};
 
 use beacon_chain::{BeaconChain, BeaconChainTypes};
-use eth2_libp2p::{NetworkConfig, SubnetDiscovery};
+use eth2_libp2p::{
+    AttestationSubnetsSummary, NetworkConfig, SubnetDiscovery, SubnetSubscription,
+    SubnetSubscriptionKind,
+};
 use hashset_delay::HashSetDelay;
 use slot_clock::SlotClock;
 use types::{Attestation, EthSpec, Slot, SubnetId, ValidatorSubscription};
@@ -140,6 +143,79 @@ impl<T: BeaconChainTypes> AttestationService<T> {
         }
     }
 
+    /// The number of long-lived random subnets we should be subscribed to.
+    ///
+    /// This scales with the number of validators that have recently sent us a subscription, up to
+    /// all subnets.
+    pub fn long_lived_subnet_target(&self) -> usize {
+        let spec = &self.beacon_chain.spec;
+        let subnet_count = spec.attestation_subnet_count as usize;
+        if self.subscribe_all_subnets {
+            return subnet_count;
+        }
+        self.known_validators
+            .len()
+            .saturating_mul(spec.random_subnets_per_validator as usize)
+            .min(subnet_count)
+    }
+
+    /// Returns a snapshot of the current subnet subscriptions and the slot at which each expires.
+    pub fn subnets_summary(&self) -> AttestationSubnetsSummary {
+        let long_lived_target = self.long_lived_subnet_target();
+        let known_validators = self.known_validators.len();
+
+        if self.subscribe_all_subnets {
+            return AttestationSubnetsSummary {
+                known_validators,
+                long_lived_target,
+                subscribe_all_subnets: true,
+                subscriptions: (0..self.beacon_chain.spec.attestation_subnet_count)
+                    .map(|subnet_id| SubnetSubscription {
+                        subnet_id: SubnetId::new(subnet_id),
+                        kind: SubnetSubscriptionKind::LongLived,
+                        expiry_slot: None,
+                    })
+                    .collect(),
+            };
+        }
+
+        let mut subscriptions = self
+            .subscriptions
+            .iter()
+            .map(|subnet_id| {
+                if let Some(expiry) = self.random_subnets.get(subnet_id) {
+                    SubnetSubscription {
+                        subnet_id: *subnet_id,
+                        kind: SubnetSubscriptionKind::LongLived,
+                        expiry_slot: self.instant_to_slot(*expiry),
+                    }
+                } else {
+                    // A short-lived subscription lasts until the latest pending unsubscription.
+                    let expiry_slot = self
+                        .unsubscriptions
+                        .keys()
+                        .filter(|exact_subnet| exact_subnet.subnet_id == *subnet_id)
+                        .filter_map(|exact_subnet| self.unsubscriptions.get(exact_subnet))
+                        .max()
+                        .and_then(|expiry| self.instant_to_slot(*expiry));
+                    SubnetSubscription {
+                        subnet_id: *subnet_id,
+                        kind: SubnetSubscriptionKind::ShortLived,
+                        expiry_slot,
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        subscriptions.sort_by_key(|subscription| *subscription.subnet_id);
+
+        AttestationSubnetsSummary {
+            known_validators,
+            long_lived_target,
+            subscribe_all_subnets: false,
+            subscriptions,
+        }
+    }
+
     /// Return count of all currently subscribed subnets (long-lived **and** short-lived).
     #[cfg(test)]
     pub fn subscription_count(&self) -> usize {
@@ -150,6 +226,12 @@ impl<T: BeaconChainTypes> AttestationService<T> {
         }
     }
 
+    /// Return count of the currently subscribed long-lived random subnets.
+    #[cfg(test)]
+    pub fn random_subnet_count(&self) -> usize {
+        self.random_subnets.len()
+    }
+
     /// Processes a list of validator subscriptions.
     ///
     /// This will:
@@ -269,6 +351,21 @@ impl<T: BeaconChainTypes> AttestationService<T> {
 
     /* Internal private functions */
 
+    /// Converts an expiry `Instant` into the slot during which it fires.
+    fn instant_to_slot(&self, instant: Instant) -> Option<Slot> {
+        let current_slot = self.beacon_chain.slot_clock.now()?;
+        let slot_duration = self.beacon_chain.slot_clock.slot_duration();
+        let duration_to_slot_start = self.beacon_chain.slot_clock.duration_to_next_slot()?;
+        let remaining = instant.saturating_duration_since(Instant::now());
+
+        if remaining < duration_to_slot_start {
+            return Some(current_slot);
+        }
+        let slots_after_next =
+            (remaining - duration_to_slot_start).as_millis() / slot_duration.as_millis().max(1);
+        Some(current_slot + 1 + slots_after_next as u64)
+    }
+
     /// Checks if there are currently queued discovery requests and the time required to make the
     /// request.
     ///
@@ -386,21 +483,36 @@ impl<T: BeaconChainTypes> AttestationService<T> {
     ///
     /// This also updates the ENR to indicate our long-lived subscription to the subnet
     fn add_known_validator(&mut self, validator_index: u64) {
-        if self.known_validators.get(&validator_index).is_none() && !self.subscribe_all_subnets {
+        let is_new_validator = self.known_validators.get(&validator_index).is_none();
+        // add the new validator or update the current timeout for a known validator
+        self.known_validators.insert(validator_index);
+
+        if is_new_validator {
             // New validator has subscribed
             // Subscribe to random topics and update the ENR if needed.
+            self.rebalance_random_subnets();
+        }
+    }
+
+    /// Subscribes to, or unsubscribes from, random subnets such that the number of long-lived
+    /// subnets matches `long_lived_subnet_target`.
+    fn rebalance_random_subnets(&mut self) {
+        if self.subscribe_all_subnets {
+            return;
+        }
 
-            let spec = &self.beacon_chain.spec;
+        let target = self.long_lived_subnet_target();
+        let current = self.random_subnets.len();
 
-            if self.random_subnets.len() < spec.attestation_subnet_count as usize {
-                // Still room for subscriptions
-                self.subscribe_to_random_subnets(
-                    self.beacon_chain.spec.random_subnets_per_validator as usize,
-                );
-            }
+        if current < target {
+            self.subscribe_to_random_subnets(target - current);
+        } else if current > target {
+            self.unsubscribe_from_random_subnets(current - target);
         }
-        // add the new validator or update the current timeout for a known validator
-        self.known_validators.insert(validator_index);
+        metrics::set_gauge(
+            &metrics::LONG_LIVED_SUBNETS,
+            self.random_subnets.len() as i64,
+        );
     }
 
     /// Subscribe to long-lived random subnets and update the local ENR bitfield.
@@ -457,6 +569,41 @@ impl<T: BeaconChainTypes> AttestationService<T> {
         }
     }
 
+    /// Removes a random selection of long-lived subnets and updates the local ENR bitfield.
+    fn unsubscribe_from_random_subnets(&mut self, no_subnets_to_unsubscribe: usize) {
+        let subscribed_subnets = self.random_subnets.keys().cloned().collect::<Vec<_>>();
+        let to_remove_subnets = subscribed_subnets
+            .choose_multiple(&mut rand::thread_rng(), no_subnets_to_unsubscribe)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for subnet_id in to_remove_subnets {
+            self.random_subnets.remove(&subnet_id);
+            self.remove_random_subnet(subnet_id);
+        }
+    }
+
+    /// Unsubscribes from a subnet that is no longer a long-lived random subnet, unless a
+    /// short-lived subscription still requires it, and removes it from the ENR bitfield.
+    fn remove_random_subnet(&mut self, subnet_id: SubnetId) {
+        // If there are no unsubscription events for `subnet_id`, we unsubscribe immediately.
+        if self
+            .unsubscriptions
+            .keys()
+            .find(|s| s.subnet_id == subnet_id)
+            .is_none()
+        {
+            debug!(self.log, "Unsubscribing from random subnet"; "subnet_id" => *subnet_id);
+            self.subscriptions.remove(&subnet_id);
+            self.events
+                .push_back(AttServiceMessage::Unsubscribe(subnet_id));
+        }
+        // as the long lasting subnet subscription is being removed, remove the subnet_id from
+        // the ENR bitfield
+        self.events
+            .push_back(AttServiceMessage::EnrRemove(subnet_id));
+    }
+
     /* A collection of functions that handle the various timeouts */
 
     /// A queued subscription is ready.
@@ -517,30 +664,17 @@ impl<T: BeaconChainTypes> AttestationService<T> {
     /// This function selects a new subnet to join, or extends the expiry if there are no more
     /// available subnets to choose from.
     fn handle_random_subnet_expiry(&mut self, subnet_id: SubnetId) {
-        let subnet_count = self.beacon_chain.spec.attestation_subnet_count;
-        if self.random_subnets.len() == (subnet_count - 1) as usize {
-            // We are at capacity, simply increase the timeout of the current subnet
+        let subnet_count = self.beacon_chain.spec.attestation_subnet_count as usize;
+        let target = self.long_lived_subnet_target();
+        if target >= subnet_count && self.random_subnets.len() + 1 >= subnet_count {
+            // We are subscribed to every subnet, simply increase the timeout of the current subnet
             self.random_subnets.insert(subnet_id);
             return;
         }
-        // If there are no unsubscription events for `subnet_id`, we unsubscribe immediately.
-        if self
-            .unsubscriptions
-            .keys()
-            .find(|s| s.subnet_id == subnet_id)
-            .is_none()
-        {
-            // we are not at capacity, unsubscribe from the current subnet.
-            debug!(self.log, "Unsubscribing from random subnet"; "subnet_id" => *subnet_id);
-            self.events
-                .push_back(AttServiceMessage::Unsubscribe(subnet_id));
-        }
 
-        // Remove the ENR bitfield bit and choose a new random on from the available subnets
-        self.events
-            .push_back(AttServiceMessage::EnrRemove(subnet_id));
-        // Subscribe to a new random subnet
-        self.subscribe_to_random_subnets(1);
+        // Remove the ENR bitfield bit and choose new random subnets from the available subnets
+        self.remove_random_subnet(subnet_id);
+        self.rebalance_random_subnets();
     }
 
     /// A known validator has not sent a subscription in a while. They are considered offline and the
@@ -549,39 +683,8 @@ impl<T: BeaconChainTypes> AttestationService<T> {
     /// We don't keep track of a specific validator to random subnet, rather the ratio of active
     /// validators to random subnets. So when a validator goes offline, we can simply remove the
     /// allocated amount of random subnets.
-    fn handle_known_validator_expiry(&mut self) -> Result<(), ()> {
-        let spec = &self.beacon_chain.spec;
-        let subnet_count = spec.attestation_subnet_count;
-        let random_subnets_per_validator = spec.random_subnets_per_validator;
-        if self.known_validators.len() as u64 * random_subnets_per_validator >= subnet_count {
-            // have too many validators, ignore
-            return Ok(());
-        }
-
-        let subscribed_subnets = self.random_subnets.keys().cloned().collect::<Vec<_>>();
-        let to_remove_subnets = subscribed_subnets.choose_multiple(
-            &mut rand::thread_rng(),
-            random_subnets_per_validator as usize,
-        );
-
-        for subnet_id in to_remove_subnets {
-            // If there are no unsubscription events for `subnet_id`, we unsubscribe immediately.
-            if self
-                .unsubscriptions
-                .keys()
-                .find(|s| s.subnet_id == *subnet_id)
-                .is_none()
-            {
-                self.events
-                    .push_back(AttServiceMessage::Unsubscribe(*subnet_id));
-            }
-            // as the long lasting subnet subscription is being removed, remove the subnet_id from
-            // the ENR bitfield
-            self.events
-                .push_back(AttServiceMessage::EnrRemove(*subnet_id));
-            self.random_subnets.remove(subnet_id);
-        }
-        Ok(())
+    fn handle_known_validator_expiry(&mut self) {
+        self.rebalance_random_subnets();
     }
 }
 
@@ -618,9 +721,7 @@ impl<T: BeaconChainTypes> Stream for AttestationService<T> {
 
         // process any known validator expiries
         match self.known_validators.poll_next_unpin(cx) {
-            Poll::Ready(Some(Ok(_validator_index))) => {
-                let _ = self.handle_known_validator_expiry();
-            }
+            Poll::Ready(Some(Ok(_validator_index))) => self.handle_known_validator_expiry(),
             Poll::Ready(Some(Err(e))) => {
                 error!(self.log, "Failed to check for random subnet cycles"; "error"=> e);
             }
@@ -431,4 +431,75 @@ mod tests {
         assert_eq!(enr_add_count, 64);
         assert_eq!(unexpected_msg_count, 0);
     }
+
+    #[tokio::test]
+    async fn long_lived_subnets_scale_with_validator_count() {
+        let spec = MinimalEthSpec::default_spec();
+        let validator_count = 4;
+        let expected_subnets = (validator_count * spec.random_subnets_per_validator) as usize;
+        let committee_count = 1;
+
+        // create the attestation service and subscriptions
+        let mut attestation_service = get_attestation_service();
+        let current_slot = attestation_service
+            .beacon_chain
+            .slot_clock
+            .now()
+            .expect("Could not get current slot");
+
+        let subscriptions = get_subscriptions(validator_count, current_slot, committee_count);
+
+        // submit the subscriptions
+        attestation_service
+            .validator_subscriptions(subscriptions)
+            .unwrap();
+
+        assert_eq!(
+            attestation_service.long_lived_subnet_target(),
+            expected_subnets
+        );
+        assert_eq!(attestation_service.random_subnet_count(), expected_subnets);
+
+        let summary = attestation_service.subnets_summary();
+        assert_eq!(summary.known_validators, validator_count as usize);
+        assert_eq!(summary.long_lived_target, expected_subnets);
+        assert!(!summary.subscribe_all_subnets);
+        let long_lived = summary
+            .subscriptions
+            .iter()
+            .filter(|subscription| subscription.kind == SubnetSubscriptionKind::LongLived)
+            .collect::<Vec<_>>();
+        assert_eq!(long_lived.len(), expected_subnets);
+        for subscription in long_lived {
+            let expiry_slot = subscription
+                .expiry_slot
+                .expect("long-lived subnets should expire");
+            assert!(expiry_slot > current_slot);
+        }
+
+        // Drop all but one of the validators, the long-lived subnets should follow.
+        for validator_index in 1..validator_count {
+            attestation_service
+                .known_validators
+                .remove(&validator_index);
+        }
+        attestation_service.handle_known_validator_expiry();
+
+        let expected_subnets = spec.random_subnets_per_validator as usize;
+        assert_eq!(
+            attestation_service.long_lived_subnet_target(),
+            expected_subnets
+        );
+        assert_eq!(attestation_service.random_subnet_count(), expected_subnets);
+
+        let events = get_events(&mut attestation_service, None, 1).await;
+        let enr_remove_count = events
+            .iter()
+            .filter(|event| matches!(event, AttServiceMessage::EnrRemove(_)))
+            .count();
+        assert_eq!(
+            enr_remove_count,
+            (validator_count as usize - 1) * expected_subnets
+        );
+    }
 }
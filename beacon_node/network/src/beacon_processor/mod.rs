@@ -34,11 +34,17 @@
 //! Whenever the manager receives a notification that a worker has finished a parcel of work, it
 //! checks the queues to see if there are more parcels of work that can be spawned in a new worker
 //! task.
+//!
+//! Some gossip messages cannot be verified at the time they arrive: attestations for a block we
+//! have not yet imported and blocks from a slot that has not yet started. Workers send these to the
+//! reprocessing queue (see `work_reprocessing_queue`), which sends them back to the manager once
+//! they are worth verifying again.
 
 use crate::{metrics, service::NetworkMessage, sync::SyncMessage};
 use beacon_chain::{BeaconChain, BeaconChainTypes, BlockError};
 use eth2_libp2p::{MessageId, NetworkGlobals, PeerId};
 use slog::{crit, debug, error, trace, warn, Logger};
+use std::cmp;
 use std::collections::VecDeque;
 use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
@@ -48,9 +54,14 @@ use types::{
     Attestation, AttesterSlashing, EthSpec, Hash256, ProposerSlashing, SignedAggregateAndProof,
     SignedBeaconBlock, SignedVoluntaryExit, SubnetId,
 };
+use work_reprocessing_queue::{
+    spawn_reprocess_scheduler, QueuedAggregate, QueuedBlock, QueuedUnaggregate, ReadyWork,
+    ReprocessQueueMessage,
+};
 use worker::Worker;
 
 mod chain_segment;
+mod work_reprocessing_queue;
 mod worker;
 
 pub use chain_segment::ProcessId;
@@ -60,6 +71,14 @@ pub use chain_segment::ProcessId;
 /// Setting this too low will cause consensus messages to be dropped.
 pub const MAX_WORK_EVENT_QUEUE_LEN: usize = 16_384;
 
+/// The maximum number of queued `Attestation` objects that will be verified together in a
+/// single batch.
+const MAX_GOSSIP_ATTESTATION_BATCH_SIZE: usize = 64;
+
+/// The maximum number of queued `SignedAggregateAndProof` objects that will be verified together
+/// in a single batch.
+const MAX_GOSSIP_AGGREGATE_BATCH_SIZE: usize = 64;
+
 /// The maximum size of the channel for idle events to the `BeaconProcessor`.
 ///
 /// Setting this too low will prevent new workers from being spawned. It *should* only need to be
@@ -98,6 +117,18 @@ const MAX_RPC_BLOCK_QUEUE_LEN: usize = 1_024;
 /// be stored before we start dropping them.
 const MAX_CHAIN_SEGMENT_QUEUE_LEN: usize = 64;
 
+/// The maximum number of queued `Attestation` objects released by the reprocessing queue that will
+/// be stored before we start dropping them.
+const MAX_UNKNOWN_BLOCK_ATTESTATION_QUEUE_LEN: usize = 8_192;
+
+/// The maximum number of queued `SignedAggregateAndProof` objects released by the reprocessing
+/// queue that will be stored before we start dropping them.
+const MAX_UNKNOWN_BLOCK_AGGREGATE_QUEUE_LEN: usize = 1_024;
+
+/// The maximum number of queued early `SignedBeaconBlock` objects released by the reprocessing
+/// queue that will be stored before we start dropping them.
+const MAX_DELAYED_BLOCK_QUEUE_LEN: usize = 1_024;
+
 /// The name of the manager tokio task.
 const MANAGER_TASK_NAME: &str = "beacon_gossip_processor_manager";
 /// The name of the worker tokio tasks.
@@ -238,11 +269,12 @@ impl<E: EthSpec> WorkEvent<E> {
         }
     }
 
-    /// Create a new `Work` event for some block.
+    /// Create a new `Work` event for some block, first seen at `seen_timestamp`.
     pub fn gossip_beacon_block(
         message_id: MessageId,
         peer_id: PeerId,
         block: Box<SignedBeaconBlock<E>>,
+        seen_timestamp: Option<Duration>,
     ) -> Self {
         Self {
             drop_during_sync: false,
@@ -250,6 +282,7 @@ impl<E: EthSpec> WorkEvent<E> {
                 message_id,
                 peer_id,
                 block,
+                seen_timestamp,
             },
         }
     }
@@ -322,6 +355,73 @@ impl<E: EthSpec> WorkEvent<E> {
     }
 }
 
+impl<E: EthSpec> From<ReadyWork<E>> for WorkEvent<E> {
+    fn from(ready_work: ReadyWork<E>) -> Self {
+        match ready_work {
+            ReadyWork::Block(QueuedBlock {
+                message_id,
+                peer_id,
+                block,
+                seen_timestamp,
+            }) => Self {
+                drop_during_sync: false,
+                work: Work::DelayedImportBlock {
+                    message_id,
+                    peer_id,
+                    block,
+                    seen_timestamp,
+                },
+            },
+            ReadyWork::Unaggregate(QueuedUnaggregate {
+                message_id,
+                peer_id,
+                attestation,
+                subnet_id,
+                should_import,
+            }) => Self {
+                drop_during_sync: true,
+                work: Work::UnknownBlockAttestation {
+                    message_id,
+                    peer_id,
+                    attestation,
+                    subnet_id,
+                    should_import,
+                },
+            },
+            ReadyWork::Aggregate(QueuedAggregate {
+                message_id,
+                peer_id,
+                aggregate,
+            }) => Self {
+                drop_during_sync: true,
+                work: Work::UnknownBlockAggregate {
+                    message_id,
+                    peer_id,
+                    aggregate,
+                },
+            },
+        }
+    }
+}
+
+/// Items required to verify an unaggregated gossip attestation as part of a batch.
+#[derive(Debug)]
+pub struct GossipAttestationPackage<E: EthSpec> {
+    message_id: MessageId,
+    peer_id: PeerId,
+    attestation: Box<Attestation<E>>,
+    subnet_id: SubnetId,
+    should_import: bool,
+}
+
+/// Items required to verify an aggregated gossip attestation as part of a batch.
+#[derive(Debug)]
+pub struct GossipAggregatePackage<E: EthSpec> {
+    message_id: MessageId,
+    peer_id: PeerId,
+    aggregate: Box<SignedAggregateAndProof<E>>,
+}
+
 /// A consensus message (or multiple) from the network that requires processing.
 #[derive(Debug)]
 pub enum Work<E: EthSpec> {
@@ -332,15 +432,43 @@ pub enum Work<E: EthSpec> {
         subnet_id: SubnetId,
         should_import: bool,
     },
+    GossipAttestationBatch {
+        packages: Vec<GossipAttestationPackage<E>>,
+    },
     GossipAggregate {
         message_id: MessageId,
         peer_id: PeerId,
         aggregate: Box<SignedAggregateAndProof<E>>,
     },
+    GossipAggregateBatch {
+        packages: Vec<GossipAggregatePackage<E>>,
+    },
     GossipBlock {
         message_id: MessageId,
         peer_id: PeerId,
         block: Box<SignedBeaconBlock<E>>,
+        seen_timestamp: Option<Duration>,
+    },
+    /// A gossip block that arrived before its slot, released by the reprocessing queue.
+    DelayedImportBlock {
+        message_id: MessageId,
+        peer_id: PeerId,
+        block: Box<SignedBeaconBlock<E>>,
+        seen_timestamp: Option<Duration>,
+    },
+    /// An attestation for an unknown block, released by the reprocessing queue.
+    UnknownBlockAttestation {
+        message_id: MessageId,
+        peer_id: PeerId,
+        attestation: Box<Attestation<E>>,
+        subnet_id: SubnetId,
+        should_import: bool,
+    },
+    /// An aggregate for an unknown block, released by the reprocessing queue.
+    UnknownBlockAggregate {
+        message_id: MessageId,
+        peer_id: PeerId,
+        aggregate: Box<SignedAggregateAndProof<E>>,
     },
     GossipVoluntaryExit {
         message_id: MessageId,
@@ -372,8 +500,13 @@ impl<E: EthSpec> Work<E> {
     fn str_id(&self) -> &'static str {
         match self {
             Work::GossipAttestation { .. } => "gossip_attestation",
+            Work::GossipAttestationBatch { .. } => "gossip_attestation_batch",
             Work::GossipAggregate { .. } => "gossip_aggregate",
+            Work::GossipAggregateBatch { .. } => "gossip_aggregate_batch",
             Work::GossipBlock { .. } => "gossip_block",
+            Work::DelayedImportBlock { .. } => "delayed_import_block",
+            Work::UnknownBlockAttestation { .. } => "unknown_block_attestation",
+            Work::UnknownBlockAggregate { .. } => "unknown_block_aggregate",
             Work::GossipVoluntaryExit { .. } => "gossip_voluntary_exit",
             Work::GossipProposerSlashing { .. } => "gossip_proposer_slashing",
             Work::GossipAttesterSlashing { .. } => "gossip_attester_slashing",
@@ -452,9 +585,22 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
         let mut rpc_block_queue = FifoQueue::new(MAX_RPC_BLOCK_QUEUE_LEN);
         let mut chain_segment_queue = FifoQueue::new(MAX_CHAIN_SEGMENT_QUEUE_LEN);
         let mut gossip_block_queue = FifoQueue::new(MAX_GOSSIP_BLOCK_QUEUE_LEN);
+        let mut delayed_block_queue = FifoQueue::new(MAX_DELAYED_BLOCK_QUEUE_LEN);
+
+        // Using FIFO queues for released attestations so that the ones that have waited longest
+        // are verified first.
+        let mut unknown_block_aggregate_queue =
+            FifoQueue::new(MAX_UNKNOWN_BLOCK_AGGREGATE_QUEUE_LEN);
+        let mut unknown_block_attestation_queue =
+            FifoQueue::new(MAX_UNKNOWN_BLOCK_ATTESTATION_QUEUE_LEN);
 
         let executor = self.executor.clone();
 
+        // Work that cannot be verified yet is sent to the reprocessing queue by the workers, which
+        // sends it back on `ready_work_rx` when it should be verified again.
+        let (ready_work_tx, mut ready_work_rx) = mpsc::unbounded_channel::<ReadyWork<T::EthSpec>>();
+        let reprocess_tx = spawn_reprocess_scheduler(ready_work_tx, &executor, self.log.clone());
+
         // The manager future will run on the core executor and delegate tasks to worker
         // threads on the blocking executor.
         let manager_future = async move {
@@ -482,6 +628,10 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                             break
                         }
                     },
+                    // Some work has been released by the reprocessing queue.
+                    Some(ready_work) = ready_work_rx.recv() => {
+                        Some(WorkEvent::from(ready_work))
+                    },
                     // There is a new piece of work to be handled.
                     new_work_event_opt = event_rx.recv() => {
                         if let Some(new_work_event) = new_work_event_opt {
@@ -525,35 +675,124 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                         // Check for chain segments first, they're the most efficient way to get
                         // blocks into the system.
                         if let Some(item) = chain_segment_queue.pop() {
-                            self.spawn_worker(idle_tx.clone(), item);
+                            self.spawn_worker(idle_tx.clone(), reprocess_tx.clone(), item);
                         // Check sync blocks before gossip blocks, since we've already explicitly
                         // requested these blocks.
                         } else if let Some(item) = rpc_block_queue.pop() {
-                            self.spawn_worker(idle_tx.clone(), item);
+                            self.spawn_worker(idle_tx.clone(), reprocess_tx.clone(), item);
                         // Check gossip blocks before gossip attestations, since a block might be
-                        // required to verify some attestations.
+                        // required to verify some attestations. Delayed blocks are for the
+                        // current slot, so they come first.
+                        } else if let Some(item) = delayed_block_queue.pop() {
+                            self.spawn_worker(idle_tx.clone(), reprocess_tx.clone(), item);
                         } else if let Some(item) = gossip_block_queue.pop() {
-                            self.spawn_worker(idle_tx.clone(), item);
+                            self.spawn_worker(idle_tx.clone(), reprocess_tx.clone(), item);
                         // Check the aggregates, *then* the unaggregates
                         // since we assume that aggregates are more valuable to local validators
                         // and effectively give us more information with less signature
                         // verification time.
-                        } else if let Some(item) = aggregate_queue.pop() {
-                            self.spawn_worker(idle_tx.clone(), item);
-                        } else if let Some(item) = attestation_queue.pop() {
-                            self.spawn_worker(idle_tx.clone(), item);
+                        //
+                        // If there are multiple items in either queue, verify them as a batch so
+                        // that we can benefit from batch signature verification.
+                        } else if aggregate_queue.len() > 0 {
+                            let batch_size =
+                                cmp::min(aggregate_queue.len(), MAX_GOSSIP_AGGREGATE_BATCH_SIZE);
+
+                            if batch_size < 2 {
+                                // There is only one aggregate in the queue, process it
+                                // individually.
+                                if let Some(item) = aggregate_queue.pop() {
+                                    self.spawn_worker(idle_tx.clone(), reprocess_tx.clone(), item);
+                                }
+                            } else {
+                                let mut packages = Vec::with_capacity(batch_size);
+                                for _ in 0..batch_size {
+                                    match aggregate_queue.pop() {
+                                        Some(Work::GossipAggregate {
+                                            message_id,
+                                            peer_id,
+                                            aggregate,
+                                        }) => packages.push(GossipAggregatePackage {
+                                            message_id,
+                                            peer_id,
+                                            aggregate,
+                                        }),
+                                        Some(_) => error!(
+                                            self.log,
+                                            "Invalid item in aggregate queue";
+                                        ),
+                                        None => break,
+                                    }
+                                }
+
+                                self.spawn_worker(
+                                    idle_tx.clone(),
+                                    reprocess_tx.clone(),
+                                    Work::GossipAggregateBatch { packages },
+                                );
+                            }
+                        } else if attestation_queue.len() > 0 {
+                            let batch_size = cmp::min(
+                                attestation_queue.len(),
+                                MAX_GOSSIP_ATTESTATION_BATCH_SIZE,
+                            );
+
+                            if batch_size < 2 {
+                                // There is only one attestation in the queue, process it
+                                // individually.
+                                if let Some(item) = attestation_queue.pop() {
+                                    self.spawn_worker(idle_tx.clone(), reprocess_tx.clone(), item);
+                                }
+                            } else {
+                                let mut packages = Vec::with_capacity(batch_size);
+                                for _ in 0..batch_size {
+                                    match attestation_queue.pop() {
+                                        Some(Work::GossipAttestation {
+                                            message_id,
+                                            peer_id,
+                                            attestation,
+                                            subnet_id,
+                                            should_import,
+                                        }) => packages.push(GossipAttestationPackage {
+                                            message_id,
+                                            peer_id,
+                                            attestation,
+                                            subnet_id,
+                                            should_import,
+                                        }),
+                                        Some(_) => error!(
+                                            self.log,
+                                            "Invalid item in attestation queue";
+                                        ),
+                                        None => break,
+                                    }
+                                }
+
+                                self.spawn_worker(
+                                    idle_tx.clone(),
+                                    reprocess_tx.clone(),
+                                    Work::GossipAttestationBatch { packages },
+                                );
+                            }
+                        // Check the attestations released by the reprocessing queue after the
+                        // fresh ones, since they have already waited for their block and are
+                        // verified individually.
+                        } else if let Some(item) = unknown_block_aggregate_queue.pop() {
+                            self.spawn_worker(idle_tx.clone(), reprocess_tx.clone(), item);
+                        } else if let Some(item) = unknown_block_attestation_queue.pop() {
+                            self.spawn_worker(idle_tx.clone(), reprocess_tx.clone(), item);
                         // Check slashings after all other consensus messages so we prioritize
                         // following head.
                         //
                         // Check attester slashings before proposer slashings since they have the
                         // potential to slash multiple validators at once.
                         } else if let Some(item) = gossip_attester_slashing_queue.pop() {
-                            self.spawn_worker(idle_tx.clone(), item);
+                            self.spawn_worker(idle_tx.clone(), reprocess_tx.clone(), item);
                         } else if let Some(item) = gossip_proposer_slashing_queue.pop() {
-                            self.spawn_worker(idle_tx.clone(), item);
+                            self.spawn_worker(idle_tx.clone(), reprocess_tx.clone(), item);
                         // Check exits last since our validators don't get rewards from them.
                         } else if let Some(item) = gossip_voluntary_exit_queue.pop() {
-                            self.spawn_worker(idle_tx.clone(), item);
+                            self.spawn_worker(idle_tx.clone(), reprocess_tx.clone(), item);
                         }
                     }
                     // There is no new work event and we are unable to spawn a new worker.
@@ -587,12 +826,31 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                     Some(WorkEvent { work, .. }) => {
                         let work_id = work.str_id();
                         match work {
-                            _ if can_spawn => self.spawn_worker(idle_tx.clone(), work),
+                            _ if can_spawn => {
+                                self.spawn_worker(idle_tx.clone(), reprocess_tx.clone(), work)
+                            }
                             Work::GossipAttestation { .. } => attestation_queue.push(work),
                             Work::GossipAggregate { .. } => aggregate_queue.push(work),
+                            // Batches are only ever created by the manager, after items have been
+                            // taken from the queues.
+                            Work::GossipAttestationBatch { .. }
+                            | Work::GossipAggregateBatch { .. } => error!(
+                                self.log,
+                                "Unexpected batch work event";
+                                "work_id" => work_id
+                            ),
                             Work::GossipBlock { .. } => {
                                 gossip_block_queue.push(work, work_id, &self.log)
                             }
+                            Work::DelayedImportBlock { .. } => {
+                                delayed_block_queue.push(work, work_id, &self.log)
+                            }
+                            Work::UnknownBlockAttestation { .. } => {
+                                unknown_block_attestation_queue.push(work, work_id, &self.log)
+                            }
+                            Work::UnknownBlockAggregate { .. } => {
+                                unknown_block_aggregate_queue.push(work, work_id, &self.log)
+                            }
                             Work::GossipVoluntaryExit { .. } => {
                                 gossip_voluntary_exit_queue.push(work, work_id, &self.log)
                             }
@@ -626,6 +884,15 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                     &metrics::BEACON_PROCESSOR_GOSSIP_BLOCK_QUEUE_TOTAL,
                     gossip_block_queue.len() as i64,
                 );
+                metrics::set_gauge(
+                    &metrics::BEACON_PROCESSOR_DELAYED_BLOCK_QUEUE_TOTAL,
+                    delayed_block_queue.len() as i64,
+                );
+                metrics::set_gauge(
+                    &metrics::BEACON_PROCESSOR_UNKNOWN_BLOCK_ATTESTATION_QUEUE_TOTAL,
+                    (unknown_block_attestation_queue.len() + unknown_block_aggregate_queue.len())
+                        as i64,
+                );
                 metrics::set_gauge(
                     &metrics::BEACON_PROCESSOR_RPC_BLOCK_QUEUE_TOTAL,
                     rpc_block_queue.len() as i64,
@@ -674,7 +941,12 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
     /// Spawns a blocking worker thread to process some `Work`.
     ///
     /// Sends an message on `idle_tx` when the work is complete and the task is stopping.
-    fn spawn_worker(&mut self, idle_tx: mpsc::Sender<()>, work: Work<T::EthSpec>) {
+    fn spawn_worker(
+        &mut self,
+        idle_tx: mpsc::Sender<()>,
+        reprocess_tx: mpsc::UnboundedSender<ReprocessQueueMessage<T::EthSpec>>,
+        work: Work<T::EthSpec>,
+    ) {
         // Wrap the `idle_tx` in a struct that will fire the idle message whenever it is dropped.
         //
         // This helps ensure that the worker is always freed in the case of an early exit or panic.
@@ -713,6 +985,7 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
             chain,
             network_tx: self.network_tx.clone(),
             sync_tx: self.sync_tx.clone(),
+            reprocess_tx,
             log: self.log.clone(),
         };
 
@@ -740,10 +1013,17 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                     } => worker.process_gossip_attestation(
                         message_id,
                         peer_id,
-                        *attestation,
+                        attestation,
                         subnet_id,
                         should_import,
+                        true,
                     ),
+                    /*
+                     * Batched unaggregated attestation verification.
+                     */
+                    Work::GossipAttestationBatch { packages } => {
+                        worker.process_gossip_attestation_batch(packages)
+                    }
                     /*
                      * Aggregated attestation verification.
                      */
@@ -751,7 +1031,13 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                         message_id,
                         peer_id,
                         aggregate,
-                    } => worker.process_gossip_aggregate(message_id, peer_id, *aggregate),
+                    } => worker.process_gossip_aggregate(message_id, peer_id, aggregate, true),
+                    /*
+                     * Batched aggregated attestation verification.
+                     */
+                    Work::GossipAggregateBatch { packages } => {
+                        worker.process_gossip_aggregate_batch(packages)
+                    }
                     /*
                      * Verification for beacon blocks received on gossip.
                      */
@@ -759,7 +1045,52 @@ impl<T: BeaconChainTypes> BeaconProcessor<T> {
                         message_id,
                         peer_id,
                         block,
-                    } => worker.process_gossip_block(message_id, peer_id, *block),
+                        seen_timestamp,
+                    } => worker.process_gossip_block(
+                        message_id,
+                        peer_id,
+                        *block,
+                        seen_timestamp,
+                        true,
+                    ),
+                    /*
+                     * Gossip blocks that arrived early and have been held until their slot.
+                     */
+                    Work::DelayedImportBlock {
+                        message_id,
+                        peer_id,
+                        block,
+                        seen_timestamp,
+                    } => worker.process_gossip_block(
+                        message_id,
+                        peer_id,
+                        *block,
+                        seen_timestamp,
+                        false,
+                    ),
+                    /*
+                     * Attestations and aggregates that have been held whilst waiting for their
+                     * block. They are not queued a second time.
+                     */
+                    Work::UnknownBlockAttestation {
+                        message_id,
+                        peer_id,
+                        attestation,
+                        subnet_id,
+                        should_import,
+                    } => worker.process_gossip_attestation(
+                        message_id,
+                        peer_id,
+                        attestation,
+                        subnet_id,
+                        should_import,
+                        false,
+                    ),
+                    Work::UnknownBlockAggregate {
+                        message_id,
+                        peer_id,
+                        aggregate,
+                    } => worker.process_gossip_aggregate(message_id, peer_id, aggregate, false),
                     /*
                      * Voluntary exits received on gossip.
                      */
@@ -0,0 +1,593 @@
+//! Provides a queue which holds gossip messages that cannot be verified *yet*, releasing them back
+//! to the `BeaconProcessor` once they might succeed.
+//!
+//! Items are queued for two reasons:
+//!
+//! - An attestation or aggregate references a block that we have not imported. It is released as
+//!   soon as that block is imported, or after `QUEUED_ATTESTATION_DELAY` if it never arrives.
+//! - A gossip block arrived before the start of its slot. It is released when the slot starts.
+//!
+//! The queue is bounded. When it is full, new items are released straight away so they are
+//! processed (and most likely ignored) in the usual way rather than silently dropped.
+
+use crate::metrics;
+use eth2_libp2p::{MessageId, PeerId};
+use futures::prelude::*;
+use slog::{crit, debug, error, Logger};
+use std::collections::HashMap;
+use std::time::Duration;
+use task_executor::TaskExecutor;
+use tokio::sync::mpsc;
+use tokio::time::delay_queue::{DelayQueue, Key};
+use types::{Attestation, EthSpec, Hash256, SignedAggregateAndProof, SignedBeaconBlock, SubnetId};
+
+/// The name of the reprocessing queue tokio task.
+const TASK_NAME: &str = "beacon_processor_reprocess_queue";
+
+/// The maximum time an attestation or aggregate is held whilst waiting for its block.
+const QUEUED_ATTESTATION_DELAY: Duration = Duration::from_secs(12);
+
+/// The maximum number of attestations and aggregates (combined) held by the queue.
+const MAXIMUM_QUEUED_ATTESTATIONS: usize = 16_384;
+
+/// The maximum number of early blocks held by the queue.
+const MAXIMUM_QUEUED_BLOCKS: usize = 16;
+
+/// An unaggregated attestation that references an unknown block.
+#[derive(Debug)]
+pub struct QueuedUnaggregate<E: EthSpec> {
+    pub message_id: MessageId,
+    pub peer_id: PeerId,
+    pub attestation: Box<Attestation<E>>,
+    pub subnet_id: SubnetId,
+    pub should_import: bool,
+}
+
+/// An aggregated attestation that references an unknown block.
+#[derive(Debug)]
+pub struct QueuedAggregate<E: EthSpec> {
+    pub message_id: MessageId,
+    pub peer_id: PeerId,
+    pub aggregate: Box<SignedAggregateAndProof<E>>,
+}
+
+/// A gossip block that arrived before the start of its slot.
+#[derive(Debug)]
+pub struct QueuedBlock<E: EthSpec> {
+    pub message_id: MessageId,
+    pub peer_id: PeerId,
+    pub block: Box<SignedBeaconBlock<E>>,
+    /// When the block arrived, as a duration since the UNIX epoch.
+    pub seen_timestamp: Option<Duration>,
+}
+
+/// Messages sent to the reprocessing queue by the `BeaconProcessor` workers.
+#[derive(Debug)]
+pub enum ReprocessQueueMessage<E: EthSpec> {
+    /// A gossip block that should be released once `delay` has passed (i.e. its slot starts).
+    EarlyBlock {
+        block: QueuedBlock<E>,
+        delay: Duration,
+    },
+    /// A block has been imported, release any attestations that were waiting for it.
+    BlockImported(Hash256),
+    /// An unaggregated attestation that references an unknown block.
+    UnknownBlockUnaggregate(QueuedUnaggregate<E>),
+    /// An aggregated attestation that references an unknown block.
+    UnknownBlockAggregate(QueuedAggregate<E>),
+}
+
+/// Work released by the reprocessing queue, to be sent back to the `BeaconProcessor`.
+#[derive(Debug)]
+pub enum ReadyWork<E: EthSpec> {
+    Block(QueuedBlock<E>),
+    Unaggregate(QueuedUnaggregate<E>),
+    Aggregate(QueuedAggregate<E>),
+}
+
+/// Identifies an attestation or aggregate held in the queue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum QueuedAttestationId {
+    Unaggregate(usize),
+    Aggregate(usize),
+}
+
+/// Events processed by the `ReprocessQueue` task.
+enum InboundEvent<E: EthSpec> {
+    Msg(ReprocessQueueMessage<E>),
+    ReadyBlock(QueuedBlock<E>),
+    ReadyAttestation(QueuedAttestationId),
+}
+
+struct ReprocessQueue<E: EthSpec> {
+    /// Receives new items and block import notifications from the workers.
+    work_reprocessing_rx: mpsc::UnboundedReceiver<ReprocessQueueMessage<E>>,
+    /// Sends released work back to the `BeaconProcessor` manager.
+    ready_work_tx: mpsc::UnboundedSender<ReadyWork<E>>,
+    /// Early blocks, released at the start of their slot.
+    early_blocks: DelayQueue<QueuedBlock<E>>,
+    /// Expiry timeouts for the attestations and aggregates held in the queue.
+    attestations_delay_queue: DelayQueue<QueuedAttestationId>,
+    queued_unaggregates: HashMap<usize, (QueuedUnaggregate<E>, Key)>,
+    queued_aggregates: HashMap<usize, (QueuedAggregate<E>, Key)>,
+    /// The attestations and aggregates waiting for each unknown block root.
+    awaiting_attestations_per_root: HashMap<Hash256, Vec<QueuedAttestationId>>,
+    next_attestation: usize,
+    log: Logger,
+}
+
+/// Spawns the reprocessing queue on `executor`, returning the sender used to submit work to it.
+///
+/// Released work is sent on `ready_work_tx`.
+pub fn spawn_reprocess_scheduler<E: EthSpec>(
+    ready_work_tx: mpsc::UnboundedSender<ReadyWork<E>>,
+    executor: &TaskExecutor,
+    log: Logger,
+) -> mpsc::UnboundedSender<ReprocessQueueMessage<E>> {
+    let (work_reprocessing_tx, work_reprocessing_rx) = mpsc::unbounded_channel();
+
+    let queue = ReprocessQueue::new(work_reprocessing_rx, ready_work_tx, log);
+    executor.spawn(queue.run(), TASK_NAME);
+
+    work_reprocessing_tx
+}
+
+impl<E: EthSpec> ReprocessQueue<E> {
+    fn new(
+        work_reprocessing_rx: mpsc::UnboundedReceiver<ReprocessQueueMessage<E>>,
+        ready_work_tx: mpsc::UnboundedSender<ReadyWork<E>>,
+        log: Logger,
+    ) -> Self {
+        Self {
+            work_reprocessing_rx,
+            ready_work_tx,
+            early_blocks: DelayQueue::new(),
+            attestations_delay_queue: DelayQueue::new(),
+            queued_unaggregates: HashMap::new(),
+            queued_aggregates: HashMap::new(),
+            awaiting_attestations_per_root: HashMap::new(),
+            next_attestation: 0,
+            log,
+        }
+    }
+
+    /// Processes events until all the senders have been dropped.
+    async fn run(mut self) {
+        while let Some(event) = self.next_event().await {
+            self.handle_event(event);
+        }
+        debug!(self.log, "Reprocessing queue stopped");
+    }
+
+    /// Waits for the next inbound message or expired item.
+    ///
+    /// Returns `None` once all the senders have been dropped.
+    async fn next_event(&mut self) -> Option<InboundEvent<E>> {
+        loop {
+            // An empty `DelayQueue` resolves to `None` immediately, so those branches are
+            // disabled until there is something to wait on.
+            let has_blocks = !self.early_blocks.is_empty();
+            let has_attestations = !self.attestations_delay_queue.is_empty();
+
+            tokio::select! {
+                msg = self.work_reprocessing_rx.recv() => {
+                    return msg.map(InboundEvent::Msg);
+                }
+                Some(expired) = self.early_blocks.next(), if has_blocks => {
+                    match expired {
+                        Ok(expired) => return Some(InboundEvent::ReadyBlock(expired.into_inner())),
+                        Err(e) => error!(
+                            self.log,
+                            "Failed to poll early block delay queue";
+                            "error" => format!("{:?}", e)
+                        ),
+                    }
+                }
+                Some(expired) = self.attestations_delay_queue.next(), if has_attestations => {
+                    match expired {
+                        Ok(expired) => {
+                            return Some(InboundEvent::ReadyAttestation(expired.into_inner()))
+                        }
+                        Err(e) => error!(
+                            self.log,
+                            "Failed to poll queued attestation delay queue";
+                            "error" => format!("{:?}", e)
+                        ),
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: InboundEvent<E>) {
+        match event {
+            InboundEvent::Msg(ReprocessQueueMessage::EarlyBlock { block, delay }) => {
+                if self.early_blocks.len() >= MAXIMUM_QUEUED_BLOCKS {
+                    debug!(
+                        self.log,
+                        "Early block queue is full";
+                        "queue_size" => MAXIMUM_QUEUED_BLOCKS,
+                    );
+                    self.send_ready_work(ReadyWork::Block(block));
+                    return;
+                }
+                self.early_blocks.insert(block, delay);
+            }
+            InboundEvent::Msg(ReprocessQueueMessage::UnknownBlockUnaggregate(queued)) => {
+                if self.is_attestation_queue_full() {
+                    self.send_ready_work(ReadyWork::Unaggregate(queued));
+                    return;
+                }
+                let id = QueuedAttestationId::Unaggregate(self.next_attestation);
+                let root = queued.attestation.data.beacon_block_root;
+                let key = self
+                    .attestations_delay_queue
+                    .insert(id, QUEUED_ATTESTATION_DELAY);
+                self.queued_unaggregates
+                    .insert(self.next_attestation, (queued, key));
+                self.register_attestation(root, id);
+            }
+            InboundEvent::Msg(ReprocessQueueMessage::UnknownBlockAggregate(queued)) => {
+                if self.is_attestation_queue_full() {
+                    self.send_ready_work(ReadyWork::Aggregate(queued));
+                    return;
+                }
+                let id = QueuedAttestationId::Aggregate(self.next_attestation);
+                let root = queued.aggregate.message.aggregate.data.beacon_block_root;
+                let key = self
+                    .attestations_delay_queue
+                    .insert(id, QUEUED_ATTESTATION_DELAY);
+                self.queued_aggregates
+                    .insert(self.next_attestation, (queued, key));
+                self.register_attestation(root, id);
+            }
+            InboundEvent::Msg(ReprocessQueueMessage::BlockImported(block_root)) => {
+                if let Some(queued_ids) = self.awaiting_attestations_per_root.remove(&block_root) {
+                    for id in queued_ids {
+                        if let Some(work) = self.remove_attestation(id) {
+                            metrics::inc_counter(
+                                &metrics::BEACON_PROCESSOR_REPROCESSING_QUEUE_MATCHED_ATTESTATIONS,
+                            );
+                            self.send_ready_work(work);
+                        }
+                    }
+                }
+            }
+            InboundEvent::ReadyBlock(block) => {
+                metrics::inc_counter(&metrics::BEACON_PROCESSOR_REPROCESSING_QUEUE_RELEASED_BLOCKS);
+                self.send_ready_work(ReadyWork::Block(block));
+            }
+            InboundEvent::ReadyAttestation(id) => {
+                // The delay queue entry has already fired, only the stored item remains.
+                let work = match id {
+                    QueuedAttestationId::Unaggregate(index) => {
+                        self.queued_unaggregates.remove(&index).map(|(queued, _)| {
+                            (
+                                queued.attestation.data.beacon_block_root,
+                                ReadyWork::Unaggregate(queued),
+                            )
+                        })
+                    }
+                    QueuedAttestationId::Aggregate(index) => {
+                        self.queued_aggregates.remove(&index).map(|(queued, _)| {
+                            (
+                                queued.aggregate.message.aggregate.data.beacon_block_root,
+                                ReadyWork::Aggregate(queued),
+                            )
+                        })
+                    }
+                };
+
+                if let Some((root, work)) = work {
+                    if let Some(queued_ids) = self.awaiting_attestations_per_root.get_mut(&root) {
+                        queued_ids.retain(|queued_id| *queued_id != id);
+                        if queued_ids.is_empty() {
+                            self.awaiting_attestations_per_root.remove(&root);
+                        }
+                    }
+                    metrics::inc_counter(
+                        &metrics::BEACON_PROCESSOR_REPROCESSING_QUEUE_EXPIRED_ATTESTATIONS,
+                    );
+                    self.send_ready_work(work);
+                } else {
+                    crit!(
+                        self.log,
+                        "Queued attestation missing from the reprocessing queue";
+                        "id" => format!("{:?}", id),
+                    );
+                }
+            }
+        }
+
+        metrics::set_gauge(
+            &metrics::BEACON_PROCESSOR_REPROCESSING_QUEUE_TOTAL,
+            (self.queued_unaggregates.len() + self.queued_aggregates.len()) as i64,
+        );
+    }
+
+    fn is_attestation_queue_full(&self) -> bool {
+        let full = self.attestations_delay_queue.len() >= MAXIMUM_QUEUED_ATTESTATIONS;
+        if full {
+            debug!(
+                self.log,
+                "Attestation reprocessing queue is full";
+                "queue_size" => MAXIMUM_QUEUED_ATTESTATIONS,
+            );
+        }
+        full
+    }
+
+    /// Records that the attestation `id` is waiting for `root`.
+    fn register_attestation(&mut self, root: Hash256, id: QueuedAttestationId) {
+        self.awaiting_attestations_per_root
+            .entry(root)
+            .or_default()
+            .push(id);
+        self.next_attestation = self.next_attestation.wrapping_add(1);
+    }
+
+    /// Removes the attestation `id` and its expiry from the queue.
+    fn remove_attestation(&mut self, id: QueuedAttestationId) -> Option<ReadyWork<E>> {
+        match id {
+            QueuedAttestationId::Unaggregate(index) => {
+                let (queued, key) = self.queued_unaggregates.remove(&index)?;
+                self.attestations_delay_queue.remove(&key);
+                Some(ReadyWork::Unaggregate(queued))
+            }
+            QueuedAttestationId::Aggregate(index) => {
+                let (queued, key) = self.queued_aggregates.remove(&index)?;
+                self.attestations_delay_queue.remove(&key);
+                Some(ReadyWork::Aggregate(queued))
+            }
+        }
+    }
+
+    fn send_ready_work(&self, work: ReadyWork<E>) {
+        if self.ready_work_tx.send(work).is_err() {
+            debug!(
+                self.log,
+                "Failed to send reprocessed work";
+                "msg" => "beacon processor stopped, shutdown may be underway"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sloggers::{null::NullLoggerBuilder, Build};
+    use tokio::sync::mpsc::error::TryRecvError;
+    use tokio::time;
+    use types::test_utils::{SeedableRng, TestRandom, XorShiftRng};
+    use types::{BeaconBlock, MainnetEthSpec, Signature, Slot};
+
+    type E = MainnetEthSpec;
+
+    /// Spawns a `ReprocessQueue` on the current runtime, returning its input and output channels.
+    fn spawn_queue() -> (
+        mpsc::UnboundedSender<ReprocessQueueMessage<E>>,
+        mpsc::UnboundedReceiver<ReadyWork<E>>,
+    ) {
+        let (work_reprocessing_tx, work_reprocessing_rx) = mpsc::unbounded_channel();
+        let (ready_work_tx, ready_work_rx) = mpsc::unbounded_channel();
+        let log = NullLoggerBuilder.build().expect("logger should build");
+
+        tokio::spawn(ReprocessQueue::new(work_reprocessing_rx, ready_work_tx, log).run());
+
+        (work_reprocessing_tx, ready_work_rx)
+    }
+
+    /// Allows the queue task to handle all the messages sent to it so far.
+    async fn settle() {
+        for _ in 0..8 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    fn counter(counter: &lighthouse_metrics::Result<lighthouse_metrics::IntCounter>) -> i64 {
+        counter.as_ref().map(|counter| counter.get()).unwrap_or(0)
+    }
+
+    fn message_id(i: u8) -> MessageId {
+        MessageId::from(&[i][..])
+    }
+
+    fn unaggregate(block_root: Hash256) -> QueuedUnaggregate<E> {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let mut attestation = Attestation::random_for_test(&mut rng);
+        attestation.data.beacon_block_root = block_root;
+
+        QueuedUnaggregate {
+            message_id: message_id(0),
+            peer_id: PeerId::random(),
+            attestation: Box::new(attestation),
+            subnet_id: SubnetId::new(0),
+            should_import: true,
+        }
+    }
+
+    fn aggregate(block_root: Hash256) -> QueuedAggregate<E> {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let mut aggregate = SignedAggregateAndProof::random_for_test(&mut rng);
+        aggregate.message.aggregate.data.beacon_block_root = block_root;
+
+        QueuedAggregate {
+            message_id: message_id(1),
+            peer_id: PeerId::random(),
+            aggregate: Box::new(aggregate),
+        }
+    }
+
+    fn block(slot: u64) -> QueuedBlock<E> {
+        let mut block = BeaconBlock::empty(&E::default_spec());
+        block.slot = Slot::new(slot);
+
+        QueuedBlock {
+            message_id: message_id(2),
+            peer_id: PeerId::random(),
+            block: Box::new(SignedBeaconBlock {
+                message: block,
+                signature: Signature::empty(),
+            }),
+            seen_timestamp: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn attestations_released_on_block_import() {
+        time::pause();
+        let (tx, mut rx) = spawn_queue();
+        let matched = counter(&metrics::BEACON_PROCESSOR_REPROCESSING_QUEUE_MATCHED_ATTESTATIONS);
+
+        let root = Hash256::repeat_byte(1);
+        let other_root = Hash256::repeat_byte(2);
+        tx.send(ReprocessQueueMessage::UnknownBlockUnaggregate(unaggregate(
+            root,
+        )))
+        .unwrap();
+        tx.send(ReprocessQueueMessage::UnknownBlockAggregate(aggregate(
+            root,
+        )))
+        .unwrap();
+        tx.send(ReprocessQueueMessage::UnknownBlockUnaggregate(unaggregate(
+            other_root,
+        )))
+        .unwrap();
+        settle().await;
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+
+        tx.send(ReprocessQueueMessage::BlockImported(root)).unwrap();
+        settle().await;
+
+        match rx.try_recv() {
+            Ok(ReadyWork::Unaggregate(queued)) => {
+                assert_eq!(queued.attestation.data.beacon_block_root, root)
+            }
+            other => panic!("expected the unaggregate, got {:?}", other),
+        }
+        match rx.try_recv() {
+            Ok(ReadyWork::Aggregate(queued)) => assert_eq!(
+                queued.aggregate.message.aggregate.data.beacon_block_root,
+                root
+            ),
+            other => panic!("expected the aggregate, got {:?}", other),
+        }
+        // The attestation for the other block is still held.
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+        assert_eq!(
+            counter(&metrics::BEACON_PROCESSOR_REPROCESSING_QUEUE_MATCHED_ATTESTATIONS),
+            matched + 2
+        );
+    }
+
+    #[tokio::test]
+    async fn attestations_expire() {
+        time::pause();
+        let (tx, mut rx) = spawn_queue();
+        let expired = counter(&metrics::BEACON_PROCESSOR_REPROCESSING_QUEUE_EXPIRED_ATTESTATIONS);
+
+        let root = Hash256::repeat_byte(3);
+        tx.send(ReprocessQueueMessage::UnknownBlockAggregate(aggregate(
+            root,
+        )))
+        .unwrap();
+        settle().await;
+
+        time::advance(QUEUED_ATTESTATION_DELAY - Duration::from_millis(1)).await;
+        settle().await;
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+
+        time::advance(Duration::from_millis(1)).await;
+        match rx.recv().await {
+            Some(ReadyWork::Aggregate(queued)) => assert_eq!(
+                queued.aggregate.message.aggregate.data.beacon_block_root,
+                root
+            ),
+            other => panic!("expected the aggregate, got {:?}", other),
+        }
+        assert_eq!(
+            counter(&metrics::BEACON_PROCESSOR_REPROCESSING_QUEUE_EXPIRED_ATTESTATIONS),
+            expired + 1
+        );
+
+        // The expired aggregate is no longer matched by its block.
+        tx.send(ReprocessQueueMessage::BlockImported(root)).unwrap();
+        settle().await;
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+    }
+
+    #[tokio::test]
+    async fn early_block_released_at_slot() {
+        time::pause();
+        let (tx, mut rx) = spawn_queue();
+
+        let delay = Duration::from_secs(3);
+        tx.send(ReprocessQueueMessage::EarlyBlock {
+            block: block(1),
+            delay,
+        })
+        .unwrap();
+        settle().await;
+
+        time::advance(delay - Duration::from_millis(1)).await;
+        settle().await;
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+
+        time::advance(Duration::from_millis(1)).await;
+        match rx.recv().await {
+            Some(ReadyWork::Block(queued)) => assert_eq!(queued.block.slot(), Slot::new(1)),
+            other => panic!("expected the block, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn full_queue_passes_items_through() {
+        time::pause();
+        let (tx, mut rx) = spawn_queue();
+
+        for slot in 0..MAXIMUM_QUEUED_BLOCKS as u64 {
+            tx.send(ReprocessQueueMessage::EarlyBlock {
+                block: block(slot),
+                delay: Duration::from_secs(12),
+            })
+            .unwrap();
+        }
+        let queued = unaggregate(Hash256::repeat_byte(4));
+        for _ in 0..MAXIMUM_QUEUED_ATTESTATIONS {
+            tx.send(ReprocessQueueMessage::UnknownBlockUnaggregate(
+                QueuedUnaggregate {
+                    message_id: queued.message_id.clone(),
+                    peer_id: queued.peer_id.clone(),
+                    attestation: queued.attestation.clone(),
+                    subnet_id: queued.subnet_id,
+                    should_import: queued.should_import,
+                },
+            ))
+            .unwrap();
+        }
+
+        // Messages are handled in order and nothing queued so far is due, so the first work
+        // released is the item that did not fit.
+        tx.send(ReprocessQueueMessage::EarlyBlock {
+            block: block(MAXIMUM_QUEUED_BLOCKS as u64),
+            delay: Duration::from_secs(12),
+        })
+        .unwrap();
+        match rx.recv().await {
+            Some(ReadyWork::Block(queued)) => {
+                assert_eq!(queued.block.slot(), Slot::new(MAXIMUM_QUEUED_BLOCKS as u64))
+            }
+            other => panic!("expected the block, got {:?}", other),
+        }
+
+        tx.send(ReprocessQueueMessage::UnknownBlockAggregate(aggregate(
+            Hash256::repeat_byte(5),
+        )))
+        .unwrap();
+        match rx.recv().await {
+            Some(ReadyWork::Aggregate(_)) => {}
+            other => panic!("expected the aggregate, got {:?}", other),
+        }
+    }
+}
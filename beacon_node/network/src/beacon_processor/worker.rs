@@ -1,27 +1,68 @@
 use super::{
     chain_segment::{handle_chain_segment, ProcessId},
-    BlockResultSender,
+    work_reprocessing_queue::{
+        QueuedAggregate, QueuedBlock, QueuedUnaggregate, ReprocessQueueMessage,
+    },
+    BlockResultSender, GossipAggregatePackage, GossipAttestationPackage,
 };
 use crate::{metrics, service::NetworkMessage, sync::SyncMessage};
 use beacon_chain::{
-    attestation_verification::Error as AttnError, observed_operations::ObservationOutcome,
+    attestation_verification::{
+        Error as AttnError, VerifiedAggregatedAttestation, VerifiedUnaggregatedAttestation,
+    },
+    observed_operations::ObservationOutcome,
     BeaconChain, BeaconChainError, BeaconChainTypes, BlockError, ForkChoiceError,
+    MAXIMUM_GOSSIP_CLOCK_DISPARITY,
 };
 use eth2_libp2p::{MessageAcceptance, MessageId, PeerAction, PeerId};
 use slog::{crit, debug, error, info, trace, warn, Logger};
+use slot_clock::SlotClock;
 use ssz::Encode;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use types::{
-    Attestation, AttesterSlashing, Hash256, ProposerSlashing, SignedAggregateAndProof,
+    Attestation, AttesterSlashing, EthSpec, Hash256, ProposerSlashing, SignedAggregateAndProof,
     SignedBeaconBlock, SignedVoluntaryExit, SubnetId,
 };
 
+/// An attestation or aggregate that failed verification, along with the data required to queue it
+/// for reprocessing.
+pub enum FailedAtt<E: EthSpec> {
+    Unaggregate {
+        attestation: Box<Attestation<E>>,
+        subnet_id: SubnetId,
+        should_import: bool,
+    },
+    Aggregate {
+        aggregate: Box<SignedAggregateAndProof<E>>,
+    },
+}
+
+impl<E: EthSpec> FailedAtt<E> {
+    pub fn beacon_block_root(&self) -> &Hash256 {
+        match self {
+            FailedAtt::Unaggregate { attestation, .. } => &attestation.data.beacon_block_root,
+            FailedAtt::Aggregate { aggregate } => {
+                &aggregate.message.aggregate.data.beacon_block_root
+            }
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FailedAtt::Unaggregate { .. } => "unaggregated",
+            FailedAtt::Aggregate { .. } => "aggregated",
+        }
+    }
+}
+
 /// Contains the context necessary to import blocks, attestations, etc to the beacon chain.
 pub struct Worker<T: BeaconChainTypes> {
     pub chain: Arc<BeaconChain<T>>,
     pub network_tx: mpsc::UnboundedSender<NetworkMessage<T::EthSpec>>,
     pub sync_tx: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+    pub reprocess_tx: mpsc::UnboundedSender<ReprocessQueueMessage<T::EthSpec>>,
     pub log: Logger,
 }
 
@@ -32,28 +73,142 @@ impl<T: BeaconChainTypes> Worker<T> {
     /// - Attempt to apply it to fork choice.
     /// - Attempt to add it to the naive aggregation pool.
     ///
+    /// If the attestation references an unknown block and `allow_reprocess` is `true`, it is sent
+    /// to the reprocessing queue to wait for the block.
+    ///
     /// Raises a log if there are errors.
     pub fn process_gossip_attestation(
         self,
         message_id: MessageId,
         peer_id: PeerId,
-        attestation: Attestation<T::EthSpec>,
+        attestation: Box<Attestation<T::EthSpec>>,
         subnet_id: SubnetId,
         should_import: bool,
+        allow_reprocess: bool,
     ) {
-        let beacon_block_root = attestation.data.beacon_block_root;
+        let result = self
+            .chain
+            .verify_unaggregated_attestation_for_gossip((*attestation).clone(), Some(subnet_id));
+
+        self.process_gossip_attestation_result(
+            result,
+            message_id,
+            peer_id,
+            attestation,
+            subnet_id,
+            should_import,
+            allow_reprocess,
+        );
+    }
+
+    /// Process a batch of unaggregated attestations received from the gossip network, using
+    /// batch signature verification.
+    ///
+    /// Each attestation is then handled in the same way as `Self::process_gossip_attestation`.
+    pub fn process_gossip_attestation_batch(
+        self,
+        packages: Vec<GossipAttestationPackage<T::EthSpec>>,
+    ) {
+        // Separate the attestations from the metadata required to report on them, so the
+        // attestations can be passed by value to the beacon chain.
+        let (attestations, metadata): (Vec<_>, Vec<_>) = packages
+            .into_iter()
+            .map(|package| {
+                (
+                    ((*package.attestation).clone(), Some(package.subnet_id)),
+                    (
+                        package.message_id,
+                        package.peer_id,
+                        package.attestation,
+                        package.subnet_id,
+                        package.should_import,
+                    ),
+                )
+            })
+            .unzip();
 
-        let attestation = match self
+        let results = match self
             .chain
-            .verify_unaggregated_attestation_for_gossip(attestation, Some(subnet_id))
+            .batch_verify_unaggregated_attestations_for_gossip(attestations.into_iter())
         {
-            Ok(attestation) => attestation,
+            Ok(results) => results,
+            Err(e) => {
+                // Every gossip message must receive a validation result, so verify each
+                // attestation individually.
+                error!(
+                    self.log,
+                    "Batch unagg. attn verification failed";
+                    "error" => format!("{:?}", e),
+                    "info" => "verifying attestations individually",
+                );
+                metadata
+                    .iter()
+                    .map(|(_, _, attestation, subnet_id, _)| {
+                        self.chain.verify_unaggregated_attestation_for_gossip(
+                            (**attestation).clone(),
+                            Some(*subnet_id),
+                        )
+                    })
+                    .collect()
+            }
+        };
+
+        // Sanity check.
+        if results.len() != metadata.len() {
+            // The log is `crit` since in this scenario we might be penalizing/rewarding the wrong
+            // peer.
+            crit!(
+                self.log,
+                "Batch attestation result mismatch";
+                "results" => results.len(),
+                "packages" => metadata.len(),
+            )
+        }
+
+        metrics::inc_counter(&metrics::BEACON_PROCESSOR_UNAGGREGATED_ATTESTATION_BATCHES_TOTAL);
+
+        for (result, (message_id, peer_id, attestation, subnet_id, should_import)) in
+            results.into_iter().zip(metadata.into_iter())
+        {
+            self.process_gossip_attestation_result(
+                result,
+                message_id,
+                peer_id,
+                attestation,
+                subnet_id,
+                should_import,
+                true,
+            );
+        }
+    }
+
+    /// Handles the result of verifying an unaggregated attestation received from the gossip
+    /// network, either individually or as part of a batch.
+    #[allow(clippy::too_many_arguments)]
+    fn process_gossip_attestation_result(
+        &self,
+        result: Result<VerifiedUnaggregatedAttestation<T>, AttnError>,
+        message_id: MessageId,
+        peer_id: PeerId,
+        attestation: Box<Attestation<T::EthSpec>>,
+        subnet_id: SubnetId,
+        should_import: bool,
+        allow_reprocess: bool,
+    ) {
+        let beacon_block_root = attestation.data.beacon_block_root;
+
+        let attestation = match result {
+            Ok(verified_attestation) => verified_attestation,
             Err(e) => {
                 self.handle_attestation_verification_failure(
                     peer_id,
                     message_id,
-                    beacon_block_root,
-                    "unaggregated",
+                    FailedAtt::Unaggregate {
+                        attestation,
+                        subnet_id,
+                        should_import,
+                    },
+                    allow_reprocess,
                     e,
                 );
                 return;
@@ -110,27 +265,113 @@ impl<T: BeaconChainTypes> Worker<T> {
     /// - Attempt to apply it to fork choice.
     /// - Attempt to add it to the block inclusion pool.
     ///
+    /// If the aggregate references an unknown block and `allow_reprocess` is `true`, it is sent to
+    /// the reprocessing queue to wait for the block.
+    ///
     /// Raises a log if there are errors.
     pub fn process_gossip_aggregate(
         self,
         message_id: MessageId,
         peer_id: PeerId,
-        aggregate: SignedAggregateAndProof<T::EthSpec>,
+        aggregate: Box<SignedAggregateAndProof<T::EthSpec>>,
+        allow_reprocess: bool,
     ) {
-        let beacon_block_root = aggregate.message.aggregate.data.beacon_block_root;
+        let result = self
+            .chain
+            .verify_aggregated_attestation_for_gossip((*aggregate).clone());
 
-        let aggregate = match self
+        self.process_gossip_aggregate_result(
+            result,
+            message_id,
+            peer_id,
+            aggregate,
+            allow_reprocess,
+        );
+    }
+
+    /// Process a batch of aggregated attestations received from the gossip network, using batch
+    /// signature verification.
+    ///
+    /// Each aggregate is then handled in the same way as `Self::process_gossip_aggregate`.
+    pub fn process_gossip_aggregate_batch(self, packages: Vec<GossipAggregatePackage<T::EthSpec>>) {
+        // Separate the aggregates from the metadata required to report on them, so the
+        // aggregates can be passed by value to the beacon chain.
+        let (aggregates, metadata): (Vec<_>, Vec<_>) = packages
+            .into_iter()
+            .map(|package| {
+                (
+                    (*package.aggregate).clone(),
+                    (package.message_id, package.peer_id, package.aggregate),
+                )
+            })
+            .unzip();
+
+        let results = match self
             .chain
-            .verify_aggregated_attestation_for_gossip(aggregate)
+            .batch_verify_aggregated_attestations_for_gossip(aggregates.into_iter())
+        {
+            Ok(results) => results,
+            Err(e) => {
+                // Every gossip message must receive a validation result, so verify each
+                // aggregate individually.
+                error!(
+                    self.log,
+                    "Batch agg. attn verification failed";
+                    "error" => format!("{:?}", e),
+                    "info" => "verifying aggregates individually",
+                );
+                metadata
+                    .iter()
+                    .map(|(_, _, aggregate)| {
+                        self.chain
+                            .verify_aggregated_attestation_for_gossip((**aggregate).clone())
+                    })
+                    .collect()
+            }
+        };
+
+        // Sanity check.
+        if results.len() != metadata.len() {
+            // The log is `crit` since in this scenario we might be penalizing/rewarding the wrong
+            // peer.
+            crit!(
+                self.log,
+                "Batch agg. attestation result mismatch";
+                "results" => results.len(),
+                "packages" => metadata.len(),
+            )
+        }
+
+        metrics::inc_counter(&metrics::BEACON_PROCESSOR_AGGREGATED_ATTESTATION_BATCHES_TOTAL);
+
+        for (result, (message_id, peer_id, aggregate)) in
+            results.into_iter().zip(metadata.into_iter())
         {
-            Ok(aggregate) => aggregate,
+            self.process_gossip_aggregate_result(result, message_id, peer_id, aggregate, true);
+        }
+    }
+
+    /// Handles the result of verifying an aggregated attestation received from the gossip
+    /// network, either individually or as part of a batch.
+    fn process_gossip_aggregate_result(
+        &self,
+        result: Result<VerifiedAggregatedAttestation<T>, AttnError>,
+        message_id: MessageId,
+        peer_id: PeerId,
+        aggregate: Box<SignedAggregateAndProof<T::EthSpec>>,
+        allow_reprocess: bool,
+    ) {
+        let beacon_block_root = aggregate.message.aggregate.data.beacon_block_root;
+
+        let aggregate = match result {
+            Ok(verified_aggregate) => verified_aggregate,
             Err(e) => {
                 // Report the failure to gossipsub
                 self.handle_attestation_verification_failure(
                     peer_id,
                     message_id,
-                    beacon_block_root,
-                    "aggregated",
+                    FailedAtt::Aggregate { aggregate },
+                    allow_reprocess,
                     e,
                 );
                 return;
@@ -183,13 +424,43 @@ impl<T: BeaconChainTypes> Worker<T> {
     /// - Attempt to add it to the beacon chain, informing the sync thread if more blocks need to
     ///   be downloaded.
     ///
+    /// If the block is from the next slot and `allow_reprocess` is `true`, it is sent to the
+    /// reprocessing queue to be verified once its slot starts.
+    ///
     /// Raises a log if there are errors.
     pub fn process_gossip_block(
         self,
         message_id: MessageId,
         peer_id: PeerId,
         block: SignedBeaconBlock<T::EthSpec>,
+        seen_timestamp: Option<Duration>,
+        allow_reprocess: bool,
     ) {
+        if allow_reprocess {
+            if let Some(delay) = self.early_block_delay(&block) {
+                debug!(
+                    self.log,
+                    "Delaying verification of early block";
+                    "slot" => block.slot(),
+                    "delay_ms" => delay.as_millis() as u64,
+                    "peer_id" => peer_id.to_string(),
+                );
+                let msg = ReprocessQueueMessage::EarlyBlock {
+                    block: QueuedBlock {
+                        message_id,
+                        peer_id,
+                        block: Box::new(block),
+                        seen_timestamp,
+                    },
+                    delay,
+                };
+                if self.reprocess_tx.send(msg).is_err() {
+                    error!(self.log, "Failed to send early block for reprocessing");
+                }
+                return;
+            }
+        }
+
         let verified_block = match self.chain.verify_block_for_gossip(block) {
             Ok(verified_block) => {
                 info!(
@@ -248,10 +519,16 @@ impl<T: BeaconChainTypes> Worker<T> {
         metrics::inc_counter(&metrics::BEACON_PROCESSOR_GOSSIP_BLOCK_VERIFIED_TOTAL);
 
         let block = Box::new(verified_block.block.clone());
-        match self.chain.process_block(verified_block) {
-            Ok(_block_root) => {
+        match self
+            .chain
+            .process_block_seen_at(verified_block, seen_timestamp)
+        {
+            Ok(block_root) => {
                 metrics::inc_counter(&metrics::BEACON_PROCESSOR_GOSSIP_BLOCK_IMPORTED_TOTAL);
 
+                // Release any attestations that were waiting for this block.
+                self.notify_block_imported(block_root);
+
                 trace!(
                     self.log,
                     "Gossipsub block processed";
@@ -479,6 +756,10 @@ impl<T: BeaconChainTypes> Worker<T> {
 
         metrics::inc_counter(&metrics::BEACON_PROCESSOR_RPC_BLOCK_IMPORTED_TOTAL);
 
+        if let Ok(block_root) = &block_result {
+            self.notify_block_imported(*block_root);
+        }
+
         if result_tx.send(block_result).is_err() {
             crit!(self.log, "Failed return sync block result");
         }
@@ -491,7 +772,54 @@ impl<T: BeaconChainTypes> Worker<T> {
         process_id: ProcessId,
         blocks: Vec<SignedBeaconBlock<T::EthSpec>>,
     ) {
-        handle_chain_segment(self.chain, process_id, blocks, self.sync_tx, self.log)
+        // Parent lookups are triggered by gossip, so there may be attestations waiting for these
+        // blocks. Range sync blocks are too old to be of interest.
+        let parent_lookup_roots = if let ProcessId::ParentLookup(..) = process_id {
+            blocks.iter().map(|block| block.canonical_root()).collect()
+        } else {
+            vec![]
+        };
+
+        handle_chain_segment(
+            self.chain.clone(),
+            process_id,
+            blocks,
+            self.sync_tx.clone(),
+            self.log.clone(),
+        );
+
+        for block_root in parent_lookup_roots {
+            if self.chain.fork_choice.read().contains_block(&block_root) {
+                self.notify_block_imported(block_root);
+            }
+        }
+    }
+
+    /// Returns the time until the start of the block's slot, if the block is from the next slot
+    /// and has arrived too early to pass gossip verification.
+    ///
+    /// Blocks from any later slot are left to fail verification as usual.
+    fn early_block_delay(&self, block: &SignedBeaconBlock<T::EthSpec>) -> Option<Duration> {
+        let slot_clock = &self.chain.slot_clock;
+        let tolerant_slot = slot_clock.now_with_future_tolerance(MAXIMUM_GOSSIP_CLOCK_DISPARITY)?;
+
+        if block.slot() == tolerant_slot + 1 {
+            slot_clock.duration_to_slot(block.slot())
+        } else {
+            None
+        }
+    }
+
+    /// Informs the reprocessing queue that `block_root` has been imported.
+    fn notify_block_imported(&self, block_root: Hash256) {
+        self.reprocess_tx
+            .send(ReprocessQueueMessage::BlockImported(block_root))
+            .unwrap_or_else(|_| {
+                warn!(
+                    self.log,
+                    "Could not send block import to the reprocessing queue"
+                )
+            });
     }
 
     /// Send a message on `message_tx` that the `message_id` sent by `peer_id` should be propagated on
@@ -544,14 +872,20 @@ impl<T: BeaconChainTypes> Worker<T> {
 
     /// Handle an error whilst verifying an `Attestation` or `SignedAggregateAndProof` from the
     /// network.
+    ///
+    /// If the attestation references an unknown block and `allow_reprocess` is `true`, it is sent to
+    /// the reprocessing queue and its validation result is reported once it has been processed
+    /// again.
     pub fn handle_attestation_verification_failure(
         &self,
         peer_id: PeerId,
         message_id: MessageId,
-        beacon_block_root: Hash256,
-        attestation_type: &str,
+        failed_att: FailedAtt<T::EthSpec>,
+        allow_reprocess: bool,
         error: AttnError,
     ) {
+        let beacon_block_root = *failed_att.beacon_block_root();
+        let attestation_type = failed_att.kind();
         metrics::register_attestation_error(&error);
         match &error {
             AttnError::FutureEpoch { .. }
@@ -716,13 +1050,12 @@ impl<T: BeaconChainTypes> Worker<T> {
                 // just old. See:
                 //
                 // https://github.com/sigp/lighthouse/issues/1039
-
-                // TODO: Maintain this attestation and re-process once sync completes
                 debug!(
                     self.log,
                     "Attestation for unknown block";
                     "peer_id" => peer_id.to_string(),
-                    "block" => format!("{}", beacon_block_root)
+                    "block" => format!("{}", beacon_block_root),
+                    "reprocess" => allow_reprocess,
                 );
                 // we don't know the block, get the sync manager to handle the block lookup
                 self.sync_tx
@@ -737,7 +1070,40 @@ impl<T: BeaconChainTypes> Worker<T> {
                             "msg" => "UnknownBlockHash"
                         )
                     });
-                self.propagate_validation_result(message_id, peer_id, MessageAcceptance::Ignore);
+
+                if allow_reprocess {
+                    // Hold the attestation until the block is imported. The validation result is
+                    // reported when it is processed again.
+                    let msg = match failed_att {
+                        FailedAtt::Unaggregate {
+                            attestation,
+                            subnet_id,
+                            should_import,
+                        } => ReprocessQueueMessage::UnknownBlockUnaggregate(QueuedUnaggregate {
+                            message_id,
+                            peer_id,
+                            attestation,
+                            subnet_id,
+                            should_import,
+                        }),
+                        FailedAtt::Aggregate { aggregate } => {
+                            ReprocessQueueMessage::UnknownBlockAggregate(QueuedAggregate {
+                                message_id,
+                                peer_id,
+                                aggregate,
+                            })
+                        }
+                    };
+                    if self.reprocess_tx.send(msg).is_err() {
+                        error!(self.log, "Failed to send attestation for reprocessing");
+                    }
+                } else {
+                    self.propagate_validation_result(
+                        message_id,
+                        peer_id,
+                        MessageAcceptance::Ignore,
+                    );
+                }
                 return;
             }
             AttnError::UnknownTargetRoot(_) => {
@@ -154,6 +154,10 @@ lazy_static! {
         "gossipsub_subnet_subscriptions_aggregator_total",
         "Count of validator subscription requests where the subscriber is an aggregator."
     );
+    pub static ref LONG_LIVED_SUBNETS: Result<IntGauge> = try_create_int_gauge(
+        "gossipsub_long_lived_subnets",
+        "Count of long-lived random attestation subnets that the node is subscribed to."
+    );
 
     /*
      * Gossip processor
@@ -285,6 +289,10 @@ lazy_static! {
         "beacon_processor_unaggregated_attestation_imported_total",
         "Total number of unaggregated attestations imported to fork choice, etc."
     );
+    pub static ref BEACON_PROCESSOR_UNAGGREGATED_ATTESTATION_BATCHES_TOTAL: Result<IntCounter> = try_create_int_counter(
+        "beacon_processor_unaggregated_attestation_batches_total",
+        "Total number of batches of unaggregated attestations verified for gossip."
+    );
     // Aggregated attestations.
     pub static ref BEACON_PROCESSOR_AGGREGATED_ATTESTATION_QUEUE_TOTAL: Result<IntGauge> = try_create_int_gauge(
         "beacon_processor_aggregated_attestation_queue_total",
@@ -298,6 +306,35 @@ lazy_static! {
         "beacon_processor_aggregated_attestation_imported_total",
         "Total number of aggregated attestations imported to fork choice, etc."
     );
+    pub static ref BEACON_PROCESSOR_AGGREGATED_ATTESTATION_BATCHES_TOTAL: Result<IntCounter> = try_create_int_counter(
+        "beacon_processor_aggregated_attestation_batches_total",
+        "Total number of batches of aggregated attestations verified for gossip."
+    );
+    // Reprocessing queue.
+    pub static ref BEACON_PROCESSOR_REPROCESSING_QUEUE_TOTAL: Result<IntGauge> = try_create_int_gauge(
+        "beacon_processor_reprocessing_queue_total",
+        "Count of attestations and aggregates waiting for an unknown block."
+    );
+    pub static ref BEACON_PROCESSOR_REPROCESSING_QUEUE_MATCHED_ATTESTATIONS: Result<IntCounter> = try_create_int_counter(
+        "beacon_processor_reprocessing_queue_matched_attestations",
+        "Number of queued attestations released because their block was imported."
+    );
+    pub static ref BEACON_PROCESSOR_REPROCESSING_QUEUE_EXPIRED_ATTESTATIONS: Result<IntCounter> = try_create_int_counter(
+        "beacon_processor_reprocessing_queue_expired_attestations",
+        "Number of queued attestations released because their block did not arrive in time."
+    );
+    pub static ref BEACON_PROCESSOR_REPROCESSING_QUEUE_RELEASED_BLOCKS: Result<IntCounter> = try_create_int_counter(
+        "beacon_processor_reprocessing_queue_released_blocks",
+        "Number of early gossip blocks released at the start of their slot."
+    );
+    pub static ref BEACON_PROCESSOR_UNKNOWN_BLOCK_ATTESTATION_QUEUE_TOTAL: Result<IntGauge> = try_create_int_gauge(
+        "beacon_processor_unknown_block_attestation_queue_total",
+        "Count of released attestations and aggregates waiting to be processed again."
+    );
+    pub static ref BEACON_PROCESSOR_DELAYED_BLOCK_QUEUE_TOTAL: Result<IntGauge> = try_create_int_gauge(
+        "beacon_processor_delayed_block_queue_total",
+        "Count of released early blocks waiting to be processed."
+    );
 }
 
 lazy_static! {
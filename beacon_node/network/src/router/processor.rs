@@ -478,9 +478,13 @@ impl<T: BeaconChainTypes> Processor<T> {
         peer_id: PeerId,
         block: Box<SignedBeaconBlock<T::EthSpec>>,
     ) {
+        let seen_timestamp = self.chain.slot_clock.now_duration();
         self.beacon_processor_send
             .try_send(BeaconWorkEvent::gossip_beacon_block(
-                message_id, peer_id, block,
+                message_id,
+                peer_id,
+                block,
+                seen_timestamp,
             ))
             .unwrap_or_else(|e| {
                 error!(
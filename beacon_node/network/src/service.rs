@@ -13,9 +13,10 @@ use eth2_libp2p::{
 use eth2_libp2p::{
     types::GossipKind, BehaviourEvent, GossipTopic, MessageId, NetworkGlobals, PeerId, TopicHash,
 };
-use eth2_libp2p::{MessageAcceptance, Service as LibP2PService};
+use eth2_libp2p::{Enr, EnrExt, MessageAcceptance, Multiaddr, Service as LibP2PService};
 use futures::prelude::*;
 use slog::{debug, error, info, o, trace, warn};
+use slot_clock::SlotClock;
 use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
 use store::HotColdDB;
 use tokio::sync::mpsc;
@@ -84,6 +85,16 @@ pub enum NetworkMessage<T: EthSpec> {
         peer_id: PeerId,
         reason: GoodbyeReason,
     },
+    /// Marks a peer as trusted, or removes its trusted status.
+    SetTrustedPeer { peer_id: PeerId, trusted: bool },
+    /// Disconnects a peer without banning it.
+    DisconnectPeer { peer_id: PeerId },
+    /// Resets the score of a banned peer and unbans it.
+    UnbanPeer { peer_id: PeerId },
+    /// Dials a multiaddr.
+    DialAddress { address: Multiaddr },
+    /// Adds an ENR to the routing table and dials the peer.
+    DialEnr { enr: Enr },
 }
 
 /// Service that handles communication between internal services and the `eth2_libp2p` network service.
@@ -115,7 +126,7 @@ pub struct NetworkService<T: BeaconChainTypes> {
     subscribe_all_subnets: bool,
     /// A timer for updating various network metrics.
     metrics_update: tokio::time::Interval,
-    /// gossipsub_parameter_update timer
+    /// A timer for recomputing the gossipsub score parameters each epoch.
     gossipsub_parameter_update: tokio::time::Interval,
     /// The logger for the network service.
     log: slog::Logger,
@@ -135,11 +146,12 @@ impl<T: BeaconChainTypes> NetworkService<T> {
         // build the network channel
         let (network_send, network_recv) = mpsc::unbounded_channel::<NetworkMessage<T::EthSpec>>();
 
-        // try and construct UPnP port mappings if required.
+        // try and construct UPnP port mappings if required. There is nothing to map when using the
+        // memory transport.
         let upnp_config = crate::nat::UPnPConfig::from(config);
         let upnp_log = network_log.new(o!("service" => "UPnP"));
         let upnp_network_send = network_send.clone();
-        if config.upnp_enabled {
+        if config.upnp_enabled && !config.memory_transport {
             executor.spawn_blocking(
                 move || {
                     crate::nat::construct_upnp_mappings(upnp_config, upnp_network_send, upnp_log)
@@ -195,8 +207,10 @@ impl<T: BeaconChainTypes> NetworkService<T> {
         // create a timer for updating network metrics
         let metrics_update = tokio::time::interval(Duration::from_secs(METRIC_UPDATE_INTERVAL));
 
-        // create a timer for updating gossipsub parameters
-        let gossipsub_parameter_update = tokio::time::interval(Duration::from_secs(60));
+        // create a timer for updating gossipsub parameters once per epoch
+        let gossipsub_parameter_update = tokio::time::interval(
+            beacon_chain.slot_clock.slot_duration() * T::EthSpec::slots_per_epoch() as u32,
+        );
 
         // create the network service and spawn the task
         let network_log = network_log.new(o!("service" => "network"));
@@ -277,6 +291,9 @@ fn spawn_service<T: BeaconChainTypes>(
                         &service.libp2p.swarm.gs(),
                         &service.network_globals,
                     );
+                    // keep the view of our subnet subscriptions up to date for the HTTP API
+                    *service.network_globals.attestation_subnets.write() =
+                        service.attestation_service.subnets_summary();
                 }
                 _ = service.gossipsub_parameter_update.next() => {
                     if let Ok(slot) = service.beacon_chain.slot() {
@@ -383,12 +400,38 @@ fn spawn_service<T: BeaconChainTypes>(
                         }
                         NetworkMessage::ReportPeer { peer_id, action } => service.libp2p.report_peer(&peer_id, action),
                         NetworkMessage::GoodbyePeer { peer_id, reason } => service.libp2p.goodbye_peer(&peer_id, reason),
+                        NetworkMessage::SetTrustedPeer { peer_id, trusted } => {
+                            let peer_manager = service.libp2p.swarm.peer_manager();
+                            if trusted {
+                                peer_manager.add_trusted_peer(&peer_id);
+                            } else if !peer_manager.remove_trusted_peer(&peer_id) {
+                                debug!(service.log, "Peer is not trusted"; "peer_id" => %peer_id);
+                            }
+                        }
+                        NetworkMessage::DisconnectPeer { peer_id } => {
+                            service.libp2p.swarm.peer_manager().disconnect_peer(&peer_id, GoodbyeReason::Unknown);
+                        }
+                        NetworkMessage::UnbanPeer { peer_id } => {
+                            if let Err(e) = service.libp2p.swarm.peer_manager().reset_and_unban_peer(&peer_id) {
+                                warn!(service.log, "Failed to unban peer"; "peer_id" => %peer_id, "error" => e);
+                            }
+                        }
+                        NetworkMessage::DialAddress { address } => {
+                            debug!(service.log, "Dialing address"; "address" => %address);
+                            service.libp2p.swarm.peer_manager().dial_address(address);
+                        }
+                        NetworkMessage::DialEnr { enr } => {
+                            debug!(service.log, "Dialing ENR"; "peer_id" => %enr.peer_id());
+                            service.libp2p.swarm.peer_manager().dial_enr(enr);
+                        }
                         NetworkMessage::Subscribe { subscriptions } => {
                             if let Err(e) = service
                                 .attestation_service
                                 .validator_subscriptions(subscriptions) {
                                     warn!(service.log, "Validator subscription failed"; "error" => e);
                                 }
+                            *service.network_globals.attestation_subnets.write() =
+                                service.attestation_service.subnets_summary();
                         }
                         NetworkMessage::SubscribeCoreTopics => {
                             let mut subscribed_topics: Vec<GossipKind> = vec![];
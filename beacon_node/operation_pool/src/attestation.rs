@@ -3,6 +3,7 @@ use state_processing::common::{get_attesting_indices, get_base_reward};
 use std::collections::HashMap;
 use types::{Attestation, BeaconState, BitList, ChainSpec, EthSpec};
 
+#[derive(Clone)]
 pub struct AttMaxCover<'a, T: EthSpec> {
     /// Underlying attestation.
     att: &'a Attestation<T>,
@@ -38,14 +39,25 @@ impl<'a, T: EthSpec> AttMaxCover<'a, T> {
             fresh_validators_rewards,
         })
     }
+
+    /// The underlying attestation.
+    pub fn attestation(&self) -> &'a Attestation<T> {
+        self.att
+    }
+
+    /// The proposer reward for each validator that this attestation would include for the first
+    /// time, ignoring any other attestations in the block.
+    pub fn fresh_validators_rewards(&self) -> &HashMap<u64, u64> {
+        &self.fresh_validators_rewards
+    }
 }
 
 impl<'a, T: EthSpec> MaxCover for AttMaxCover<'a, T> {
-    type Object = Attestation<T>;
+    type Object = &'a Attestation<T>;
     type Set = HashMap<u64, u64>;
 
-    fn object(&self) -> Attestation<T> {
-        self.att.clone()
+    fn object(&self) -> &'a Attestation<T> {
+        self.att
     }
 
     fn covering_set(&self) -> &HashMap<u64, u64> {
@@ -59,7 +71,7 @@ impl<'a, T: EthSpec> MaxCover for AttMaxCover<'a, T> {
     /// that a slot and index uniquely identify a committee.
     fn update_covering_set(
         &mut self,
-        best_att: &Attestation<T>,
+        best_att: &&'a Attestation<T>,
         covered_validators: &HashMap<u64, u64>,
     ) {
         if self.att.data.slot == best_att.data.slot && self.att.data.index == best_att.data.index {
@@ -113,3 +125,50 @@ pub fn earliest_attestation_validators<T: EthSpec>(
 
     new_validators
 }
+
+/// Combine the `partials`, which must all share the same `AttestationData`, into as few
+/// aggregates as possible whilst including every validator that signed any of them.
+///
+/// Only attestations with disjoint signers can be aggregated, so this greedily covers the
+/// aggregation bitfields: each aggregate starts from the partial with the most signers not yet
+/// covered, then absorbs the disjoint partial that adds the most uncovered signers until none
+/// remain. Aggregates that would not cover any new signers are not returned.
+pub fn aggregate_partials<T: EthSpec>(partials: &[Attestation<T>]) -> Vec<Attestation<T>> {
+    let mut covered: Option<BitList<T::MaxValidatorsPerCommittee>> = None;
+    let mut aggregates = vec![];
+
+    loop {
+        let uncovered = |att: &Attestation<T>| match &covered {
+            Some(covered) => att.aggregation_bits.difference(covered).num_set_bits(),
+            None => att.aggregation_bits.num_set_bits(),
+        };
+
+        let seed = partials
+            .iter()
+            .map(|att| (uncovered(att), att))
+            .filter(|(count, _)| *count > 0)
+            .max_by_key(|(count, _)| *count);
+        let mut aggregate = match seed {
+            Some((_, att)) => att.clone(),
+            None => break,
+        };
+
+        while let Some((_, att)) = partials
+            .iter()
+            .filter(|att| aggregate.signers_disjoint_from(att))
+            .map(|att| (uncovered(att), att))
+            .filter(|(count, _)| *count > 0)
+            .max_by_key(|(count, _)| *count)
+        {
+            aggregate.aggregate(att);
+        }
+
+        covered = Some(match covered {
+            Some(covered) => covered.union(&aggregate.aggregation_bits),
+            None => aggregate.aggregation_bits.clone(),
+        });
+        aggregates.push(aggregate);
+    }
+
+    aggregates
+}
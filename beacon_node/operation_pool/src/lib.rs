@@ -2,11 +2,13 @@ mod attestation;
 mod attestation_id;
 mod attester_slashing;
 mod max_cover;
+mod packing;
 mod persistence;
 
+pub use packing::{PackedAttestations, PackingStrategy};
 pub use persistence::PersistedOperationPool;
 
-use attestation::AttMaxCover;
+use attestation::{aggregate_partials, AttMaxCover};
 use attestation_id::AttestationId;
 use attester_slashing::AttesterSlashingMaxCover;
 use max_cover::maximum_cover;
@@ -49,7 +51,12 @@ impl<T: EthSpec> OperationPool<T> {
         Self::default()
     }
 
-    /// Insert an attestation into the pool, aggregating it with existing attestations if possible.
+    /// Insert an attestation into the pool.
+    ///
+    /// Attestations are kept as partial aggregates, which are only combined when packing a block
+    /// (see `Self::get_attestations`). An attestation is ignored if its signers are already
+    /// covered by some existing attestation, and existing attestations whose signers are covered
+    /// by the new one are removed.
     ///
     /// ## Note
     ///
@@ -74,40 +81,58 @@ impl<T: EthSpec> OperationPool<T> {
             hash_map::Entry::Occupied(entry) => entry.into_mut(),
         };
 
-        let mut aggregated = false;
-        for existing_attestation in existing_attestations.iter_mut() {
-            if existing_attestation.signers_disjoint_from(&attestation) {
-                existing_attestation.aggregate(&attestation);
-                aggregated = true;
-            } else if *existing_attestation == attestation {
-                aggregated = true;
-            }
+        if existing_attestations
+            .iter()
+            .any(|existing| attestation.signers_subset_of(existing))
+        {
+            return Ok(());
         }
 
-        if !aggregated {
-            existing_attestations.push(attestation);
-        }
+        existing_attestations.retain(|existing| !existing.signers_subset_of(&attestation));
+        existing_attestations.push(attestation);
 
         Ok(())
     }
 
-    /// Total number of attestations in the pool, including attestations for the same data.
+    /// Total number of attestations in the pool, including partial aggregates for the same data.
     pub fn num_attestations(&self) -> usize {
         self.attestations.read().values().map(Vec::len).sum()
     }
 
-    /// Get a list of attestations for inclusion in a block.
+    /// Get a list of attestations for inclusion in a block, using the default packing strategy.
+    ///
+    /// See `Self::get_attestations_with_strategy`.
+    pub fn get_attestations(
+        &self,
+        state: &BeaconState<T>,
+        validity_filter: impl FnMut(&&Attestation<T>) -> bool,
+        spec: &ChainSpec,
+    ) -> Result<Vec<Attestation<T>>, OpPoolError> {
+        self.get_attestations_with_strategy(
+            state,
+            validity_filter,
+            PackingStrategy::default(),
+            spec,
+        )
+        .map(|packed| packed.attestations)
+    }
+
+    /// Get a list of attestations for inclusion in a block, chosen by the `packing_strategy`.
+    ///
+    /// The partial aggregates for each `AttestationData` are first combined into as few
+    /// aggregates as possible, so that a block can include more validators' votes.
     ///
     /// The `validity_filter` is a closure that provides extra filtering of the attestations
     /// before an approximately optimal bundle is constructed. We use it to provide access
     /// to the fork choice data from the `BeaconChain` struct that doesn't logically belong
     /// in the operation pool.
-    pub fn get_attestations(
+    pub fn get_attestations_with_strategy(
         &self,
         state: &BeaconState<T>,
         validity_filter: impl FnMut(&&Attestation<T>) -> bool,
+        packing_strategy: PackingStrategy,
         spec: &ChainSpec,
-    ) -> Result<Vec<Attestation<T>>, OpPoolError> {
+    ) -> Result<PackedAttestations<T>, OpPoolError> {
         // Attestations for the current fork, which may be from the current or previous epoch.
         let prev_epoch = state.previous_epoch();
         let current_epoch = state.current_epoch();
@@ -130,13 +155,18 @@ impl<T: EthSpec> OperationPool<T> {
         let total_active_balance = state
             .get_total_balance(&active_indices, spec)
             .map_err(OpPoolError::GetAttestationsTotalBalanceError)?;
-        let valid_attestations = reader
+        let aggregates = reader
             .iter()
             .filter(|(key, _)| {
                 key.domain_bytes_match(&prev_domain_bytes)
                     || key.domain_bytes_match(&curr_domain_bytes)
             })
-            .flat_map(|(_, attestations)| attestations)
+            .flat_map(|(_, attestations)| aggregate_partials(attestations))
+            .collect::<Vec<_>>();
+        drop(reader);
+
+        let valid_attestations = aggregates
+            .iter()
             // That are valid...
             .filter(|attestation| {
                 verify_attestation_for_block_inclusion(
@@ -148,11 +178,13 @@ impl<T: EthSpec> OperationPool<T> {
                 .is_ok()
             })
             .filter(validity_filter)
-            .flat_map(|att| AttMaxCover::new(att, state, total_active_balance, spec));
+            .flat_map(|att| AttMaxCover::new(att, state, total_active_balance, spec))
+            .collect::<Vec<_>>();
 
-        Ok(maximum_cover(
+        Ok(packing::pack(
             valid_attestations,
             T::MaxAttestations::to_usize(),
+            packing_strategy,
         ))
     }
 
@@ -453,6 +485,7 @@ mod release_tests {
     };
     use std::collections::BTreeSet;
     use std::iter::FromIterator;
+    use std::time::Duration;
     use types::test_utils::*;
     use types::*;
 
@@ -593,8 +626,8 @@ mod release_tests {
             "we expect just one committee with this many validators"
         );
 
+        let step_size = 2;
         for bc in &committees {
-            let step_size = 2;
             for i in (0..bc.committee.len()).step_by(step_size) {
                 let att = signed_attestation(
                     &bc.committee,
@@ -612,8 +645,10 @@ mod release_tests {
             }
         }
 
+        // The partial aggregates are kept separately until they're packed into a block.
+        let num_partials = spec.target_committee_size as usize / step_size;
         assert_eq!(op_pool.attestations.read().len(), committees.len());
-        assert_eq!(op_pool.num_attestations(), committees.len());
+        assert_eq!(op_pool.num_attestations(), num_partials * committees.len());
 
         // Before the min attestation inclusion delay, get_attestations shouldn't return anything.
         state.slot -= 1;
@@ -641,7 +676,7 @@ mod release_tests {
 
         // Prune attestations shouldn't do anything at this point.
         op_pool.prune_attestations(state.current_epoch());
-        assert_eq!(op_pool.num_attestations(), committees.len());
+        assert_eq!(op_pool.num_attestations(), num_partials * committees.len());
 
         // But once we advance to more than an epoch after the attestation, it should prune it
         // out of existence.
@@ -692,8 +727,8 @@ mod release_tests {
         assert_eq!(op_pool.num_attestations(), committees.len());
     }
 
-    /// Adding lots of attestations that only intersect pairwise should lead to two aggregate
-    /// attestations.
+    /// Adding lots of attestations that only intersect pairwise should lead to a single aggregate
+    /// attestation comprising all validators in the block.
     #[test]
     fn attestation_pairwise_overlapping() {
         let (ref mut state, ref keypairs, ref spec) = attestation_test_state::<MainnetEthSpec>(1);
@@ -709,6 +744,7 @@ mod release_tests {
             .collect::<Vec<_>>();
 
         let step_size = 2;
+        let num_partials = spec.target_committee_size as usize / step_size - 1;
         for bc in &committees {
             // Create attestations that overlap on `step_size` validators, like:
             // {0,1,2,3}, {2,3,4,5}, {4,5,6,7}, ...
@@ -729,10 +765,72 @@ mod release_tests {
             }
         }
 
-        // The attestations should get aggregated into two attestations that comprise all
-        // validators.
+        // None of the attestations cover each other, so they should all be kept.
         assert_eq!(op_pool.attestations.read().len(), committees.len());
-        assert_eq!(op_pool.num_attestations(), 2 * committees.len());
+        assert_eq!(op_pool.num_attestations(), num_partials * committees.len());
+
+        // Every other attestation is disjoint, so they should get aggregated into a single
+        // attestation that comprises all validators.
+        state.slot += spec.min_attestation_inclusion_delay;
+        let block_attestations = op_pool
+            .get_attestations(state, |_| true, spec)
+            .expect("should have block attestations");
+        assert_eq!(block_attestations.len(), committees.len());
+        for att in &block_attestations {
+            assert_eq!(
+                att.aggregation_bits.num_set_bits(),
+                spec.target_committee_size as usize
+            );
+        }
+    }
+
+    /// Attestations whose signers are covered by another attestation should not be kept.
+    #[test]
+    fn attestation_subsets() {
+        let (ref mut state, ref keypairs, ref spec) = attestation_test_state::<MainnetEthSpec>(1);
+
+        let op_pool = OperationPool::new();
+
+        let slot = state.slot - 1;
+        let bc = state
+            .get_beacon_committees_at_slot(slot)
+            .unwrap()
+            .into_iter()
+            .map(BeaconCommittee::into_owned)
+            .next()
+            .unwrap();
+
+        let insert = |signing_range| {
+            let att = signed_attestation(
+                &bc.committee,
+                bc.index,
+                keypairs,
+                signing_range,
+                slot,
+                state,
+                spec,
+                None,
+            );
+            op_pool
+                .insert_attestation(att, &state.fork, state.genesis_validators_root, spec)
+                .unwrap();
+        };
+
+        insert(0..2);
+        insert(2..4);
+        assert_eq!(op_pool.num_attestations(), 2);
+
+        // A superset of both replaces them.
+        insert(0..4);
+        assert_eq!(op_pool.num_attestations(), 1);
+
+        // A subset of an existing attestation is ignored.
+        insert(1..3);
+        assert_eq!(op_pool.num_attestations(), 1);
+
+        // An attestation that overlaps without covering is kept alongside.
+        insert(3..6);
+        assert_eq!(op_pool.num_attestations(), 2);
     }
 
     /// Create a bunch of attestations signed by a small number of validators, and another
@@ -787,15 +885,12 @@ mod release_tests {
             insert_attestations(committee, big_step_size);
         }
 
-        let num_small = target_committee_size / small_step_size;
+        // Each small attestation is covered by a big one, so only the big ones are kept.
         let num_big = target_committee_size / big_step_size;
 
         assert_eq!(op_pool.attestations.read().len(), committees.len());
-        assert_eq!(
-            op_pool.num_attestations(),
-            (num_small + num_big) * committees.len()
-        );
-        assert!(op_pool.num_attestations() > max_attestations);
+        assert_eq!(op_pool.num_attestations(), num_big * committees.len());
+        assert!(op_pool.num_attestations() >= max_attestations);
 
         state.slot += spec.min_attestation_inclusion_delay;
         let best_attestations = op_pool
@@ -862,15 +957,12 @@ mod release_tests {
             insert_attestations(committee, big_step_size);
         }
 
-        let num_small = target_committee_size / small_step_size;
+        // Each small attestation is covered by a big one, so only the big ones are kept.
         let num_big = target_committee_size / big_step_size;
 
         assert_eq!(op_pool.attestations.read().len(), committees.len());
-        assert_eq!(
-            op_pool.num_attestations(),
-            (num_small + num_big) * committees.len()
-        );
-        assert!(op_pool.num_attestations() > max_attestations);
+        assert_eq!(op_pool.num_attestations(), num_big * committees.len());
+        assert!(op_pool.num_attestations() >= max_attestations);
 
         state.slot += spec.min_attestation_inclusion_delay;
         let best_attestations = op_pool
@@ -921,6 +1013,79 @@ mod release_tests {
         }
     }
 
+    #[test]
+    fn attestation_packing_strategies() {
+        let step_size = 2;
+
+        let (ref mut state, ref keypairs, ref spec) = attestation_test_state::<MainnetEthSpec>(4);
+
+        let op_pool = OperationPool::new();
+
+        let newest_slot = state.slot - 1;
+        let oldest_slot = state.slot - 2;
+        let target_committee_size = spec.target_committee_size as usize;
+        let max_attestations = <MainnetEthSpec as EthSpec>::MaxAttestations::to_usize();
+
+        for slot in &[oldest_slot, newest_slot] {
+            let committees = state
+                .get_beacon_committees_at_slot(*slot)
+                .unwrap()
+                .into_iter()
+                .map(BeaconCommittee::into_owned)
+                .collect::<Vec<_>>();
+
+            for bc in &committees {
+                for i in (0..target_committee_size).step_by(step_size) {
+                    let att = signed_attestation(
+                        &bc.committee,
+                        bc.index,
+                        keypairs,
+                        i..i + step_size,
+                        *slot,
+                        state,
+                        spec,
+                        if i == 0 { None } else { Some(0) },
+                    );
+                    op_pool
+                        .insert_attestation(att, &state.fork, state.genesis_validators_root, spec)
+                        .unwrap();
+                }
+            }
+        }
+        assert!(op_pool.num_attestations() > 2 * max_attestations);
+
+        state.slot += spec.min_attestation_inclusion_delay;
+        let pack = |strategy| {
+            op_pool
+                .get_attestations_with_strategy(state, |_| true, strategy, spec)
+                .expect("should pack attestations")
+        };
+
+        let max_reward = pack(PackingStrategy::MaxReward);
+        assert_eq!(max_reward.attestations.len(), max_attestations);
+        assert!(max_reward.reward > 0);
+        assert_eq!(max_reward.max_reward, max_reward.reward);
+        assert_eq!(
+            max_reward.attestations,
+            op_pool.get_attestations(state, |_| true, spec).unwrap()
+        );
+
+        // The time budgeted search starts from the `MaxReward` packing and only ever improves it.
+        let time_budget = pack(PackingStrategy::TimeBudget(Duration::from_millis(100)));
+        assert_eq!(time_budget.attestations.len(), max_attestations);
+        assert!(time_budget.reward >= max_reward.reward);
+        assert_eq!(time_budget.max_reward, max_reward.reward);
+
+        // There are enough attestations from the newest slot to fill the block on their own.
+        let fresh_first = pack(PackingStrategy::FreshFirst);
+        assert_eq!(fresh_first.attestations.len(), max_attestations);
+        assert!(fresh_first
+            .attestations
+            .iter()
+            .all(|att| att.data.slot == newest_slot));
+        assert_eq!(fresh_first.max_reward, max_reward.reward);
+    }
+
     struct TestContext {
         spec: ChainSpec,
         state: BeaconState<MainnetEthSpec>,
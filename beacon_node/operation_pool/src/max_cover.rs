@@ -25,24 +25,53 @@ pub trait MaxCover {
 /// Helper struct to track which items of the input are still available for inclusion.
 /// Saves removing elements from the work vector.
 struct MaxCoverItem<T> {
+    /// The position of the item in the input.
+    index: usize,
     item: T,
     available: bool,
 }
 
 impl<T> MaxCoverItem<T> {
-    fn new(item: T) -> Self {
+    fn new(index: usize, item: T) -> Self {
         MaxCoverItem {
+            index,
             item,
             available: true,
         }
     }
 }
 
+/// An item chosen for inclusion in a solution by `maximum_cover_indexed`.
+pub struct Chosen<O> {
+    /// The position of the item in the input.
+    pub index: usize,
+    /// The object extracted from the item.
+    pub object: O,
+    /// The score of the item when it was chosen, i.e. what it adds to the quality of the solution.
+    pub score: usize,
+}
+
 /// Compute an approximate maximum cover using a greedy algorithm.
 ///
 /// * Time complexity: `O(limit * items_iter.len())`
 /// * Space complexity: `O(item_iter.len())`
 pub fn maximum_cover<I, T>(items_iter: I, limit: usize) -> Vec<T::Object>
+where
+    I: IntoIterator<Item = T>,
+    T: MaxCover,
+{
+    maximum_cover_indexed(items_iter, limit)
+        .into_iter()
+        .map(|chosen| chosen.object)
+        .collect()
+}
+
+/// Compute an approximate maximum cover using a greedy algorithm, returning the position and
+/// score of each chosen item alongside its object.
+///
+/// * Time complexity: `O(limit * items_iter.len())`
+/// * Space complexity: `O(item_iter.len())`
+pub fn maximum_cover_indexed<I, T>(items_iter: I, limit: usize) -> Vec<Chosen<T::Object>>
 where
     I: IntoIterator<Item = T>,
     T: MaxCover,
@@ -50,7 +79,8 @@ where
     // Construct an initial vec of all items, marked available.
     let mut all_items: Vec<_> = items_iter
         .into_iter()
-        .map(MaxCoverItem::new)
+        .enumerate()
+        .map(|(index, item)| MaxCoverItem::new(index, item))
         .filter(|x| x.item.score() != 0)
         .collect();
 
@@ -58,14 +88,19 @@ where
 
     for _ in 0..limit {
         // Select the item with the maximum score.
-        let (best_item, best_cover) = match all_items
+        let (best_index, best_score, best_item, best_cover) = match all_items
             .iter_mut()
             .filter(|x| x.available && x.item.score() != 0)
             .max_by_key(|x| x.item.score())
         {
             Some(x) => {
                 x.available = false;
-                (x.item.object(), x.item.covering_set().clone())
+                (
+                    x.index,
+                    x.item.score(),
+                    x.item.object(),
+                    x.item.covering_set().clone(),
+                )
             }
             None => return result,
         };
@@ -77,7 +112,11 @@ where
             .filter(|x| x.available && x.item.score() != 0)
             .for_each(|x| x.item.update_covering_set(&best_item, &best_cover));
 
-        result.push(best_item);
+        result.push(Chosen {
+            index: best_index,
+            object: best_item,
+            score: best_score,
+        });
     }
 
     result
@@ -150,6 +189,20 @@ mod test {
         }
     }
 
+    #[test]
+    fn indexed() {
+        let sets = example_system();
+        let cover = maximum_cover_indexed(sets.clone(), 10);
+        assert_eq!(
+            cover
+                .iter()
+                .map(|chosen| (chosen.index, chosen.score))
+                .collect::<Vec<_>>(),
+            vec![(1, 4), (0, 1)]
+        );
+        assert_eq!(cover[0].object, sets[1]);
+    }
+
     fn quality<T: Eq + Hash>(solution: &[HashSet<T>]) -> usize {
         solution.iter().map(HashSet::len).sum()
     }
@@ -0,0 +1,294 @@
+//! Strategies for choosing which attestations to pack into a block.
+//!
+//! Each strategy is given the attestations that are valid for inclusion, along with the proposer
+//! reward that each would pay, and chooses up to `MaxAttestations` of them.
+use crate::attestation::AttMaxCover;
+use crate::max_cover::maximum_cover_indexed;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+use types::{Attestation, CommitteeIndex, EthSpec, Slot};
+
+/// A strategy for choosing which attestations to include in a block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PackingStrategy {
+    /// Greedily include the attestations which pay the greatest proposer reward.
+    MaxReward,
+    /// Include attestations from the most recent slots first, maximising the proposer reward
+    /// within each slot.
+    FreshFirst,
+    /// Start with the `MaxReward` packing and search for packings with a greater proposer reward
+    /// until the given time budget is spent.
+    TimeBudget(Duration),
+}
+
+impl Default for PackingStrategy {
+    fn default() -> Self {
+        PackingStrategy::MaxReward
+    }
+}
+
+impl PackingStrategy {
+    /// A short name for the strategy, for use in logs and metrics.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PackingStrategy::MaxReward => "max_reward",
+            PackingStrategy::FreshFirst => "fresh_first",
+            PackingStrategy::TimeBudget(_) => "time_budget",
+        }
+    }
+}
+
+/// Attestations which have been packed for inclusion in a block.
+#[derive(Debug, PartialEq)]
+pub struct PackedAttestations<T: EthSpec> {
+    pub attestations: Vec<Attestation<T>>,
+    /// The total proposer reward for including `attestations`, in Gwei.
+    pub reward: u64,
+    /// The total proposer reward of the `MaxReward` packing of the same candidates, in Gwei, so
+    /// that other strategies can be compared against it.
+    pub max_reward: u64,
+}
+
+/// Attestations with the same slot and index are for the same committee.
+type CommitteeKey = (Slot, CommitteeIndex);
+
+fn committee_key<T: EthSpec>(item: &AttMaxCover<T>) -> CommitteeKey {
+    let data = &item.attestation().data;
+    (data.slot, data.index)
+}
+
+/// Chooses up to `limit` of the `candidates` according to `strategy`.
+pub fn pack<T: EthSpec>(
+    candidates: Vec<AttMaxCover<T>>,
+    limit: usize,
+    strategy: PackingStrategy,
+) -> PackedAttestations<T> {
+    match strategy {
+        PackingStrategy::MaxReward => {
+            let (attestations, reward) = greedy(candidates, limit);
+            PackedAttestations {
+                attestations,
+                reward,
+                max_reward: reward,
+            }
+        }
+        PackingStrategy::FreshFirst => {
+            let max_reward = maximum_cover_indexed(candidates.iter().cloned(), limit)
+                .iter()
+                .map(|chosen| chosen.score as u64)
+                .sum();
+            let (attestations, reward) = fresh_first(candidates, limit);
+            PackedAttestations {
+                attestations,
+                reward,
+                max_reward,
+            }
+        }
+        PackingStrategy::TimeBudget(budget) => {
+            let deadline = Instant::now() + budget;
+            let initial = maximum_cover_indexed(candidates.iter().cloned(), limit);
+            let max_reward = initial.iter().map(|chosen| chosen.score as u64).sum();
+            let chosen = local_search(
+                &candidates,
+                initial.into_iter().map(|chosen| chosen.index).collect(),
+                limit,
+                deadline,
+            );
+            PackedAttestations {
+                reward: total_reward(&candidates, &chosen),
+                attestations: chosen
+                    .into_iter()
+                    .map(|i| candidates[i].attestation().clone())
+                    .collect(),
+                max_reward,
+            }
+        }
+    }
+}
+
+/// Runs the greedy maximum cover algorithm over the `candidates`, returning the chosen
+/// attestations and their total reward.
+///
+/// The score of each candidate only counts the validators not covered by those chosen before it,
+/// so the total reward is the sum of the scores.
+fn greedy<'a, T: EthSpec>(
+    candidates: impl IntoIterator<Item = AttMaxCover<'a, T>>,
+    limit: usize,
+) -> (Vec<Attestation<T>>, u64) {
+    maximum_cover_indexed(candidates, limit).into_iter().fold(
+        (Vec::with_capacity(limit), 0),
+        |(mut attestations, reward), chosen| {
+            attestations.push(chosen.object.clone());
+            (attestations, reward + chosen.score as u64)
+        },
+    )
+}
+
+fn fresh_first<T: EthSpec>(
+    candidates: Vec<AttMaxCover<T>>,
+    limit: usize,
+) -> (Vec<Attestation<T>>, u64) {
+    let by_slot = candidates
+        .into_iter()
+        .fold(BTreeMap::new(), |mut map, candidate| {
+            map.entry(candidate.attestation().data.slot)
+                .or_insert_with(Vec::new)
+                .push(candidate);
+            map
+        });
+
+    // Attestations from different slots are always for different committees, so packing each
+    // slot separately doesn't affect the rewards of the other slots.
+    by_slot.into_iter().rev().fold(
+        (Vec::with_capacity(limit), 0),
+        |(mut attestations, reward), (_, candidates)| {
+            let remaining = limit.saturating_sub(attestations.len());
+            let (chosen, chosen_reward) = greedy(candidates, remaining);
+            attestations.extend(chosen);
+            (attestations, reward + chosen_reward)
+        },
+    )
+}
+
+/// Improves upon the `chosen` candidates by repeatedly swapping a chosen candidate for one that
+/// increases the total reward, until no such swap is found or the `deadline` passes.
+///
+/// Swaps are considered in order of the reward gained by adding a candidate and the reward lost
+/// by removing one, so this finds a local optimum rather than an exact one.
+fn local_search<T: EthSpec>(
+    candidates: &[AttMaxCover<T>],
+    mut chosen: Vec<usize>,
+    limit: usize,
+    deadline: Instant,
+) -> Vec<usize> {
+    // If there is room for more attestations then the greedy packing already includes every
+    // candidate that would increase the reward.
+    if chosen.len() < limit {
+        return chosen;
+    }
+
+    let mut members: HashMap<CommitteeKey, Vec<usize>> = HashMap::new();
+    for i in &chosen {
+        members
+            .entry(committee_key(&candidates[*i]))
+            .or_default()
+            .push(*i);
+    }
+    // The reward of the chosen members of each committee, which only changes for the committees
+    // involved in a swap.
+    let mut rewards: HashMap<CommitteeKey, u64> = members
+        .iter()
+        .map(|(key, members)| (*key, committee_reward(candidates, members.iter().copied())))
+        .collect();
+
+    // The deadline is checked for each candidate as well as for each swap, since a single pass
+    // over the candidates may be slow.
+    'search: while Instant::now() < deadline {
+        let members_of = |key: &CommitteeKey| members.get(key).map_or(&[][..], Vec::as_slice);
+        let reward_of = |key: &CommitteeKey| rewards.get(key).copied().unwrap_or(0);
+
+        // The reward lost by removing each chosen candidate, in ascending order.
+        let mut losses = Vec::with_capacity(chosen.len());
+        for (position, i) in chosen.iter().copied().enumerate() {
+            if Instant::now() >= deadline {
+                break 'search;
+            }
+            let key = committee_key(&candidates[i]);
+            let loss = reward_of(&key)
+                - committee_reward(
+                    candidates,
+                    members_of(&key).iter().copied().filter(|j| *j != i),
+                );
+            losses.push((loss, position));
+        }
+        losses.sort_unstable();
+
+        // The reward gained by adding each other candidate, in descending order.
+        let mut gains = vec![];
+        for i in (0..candidates.len()).filter(|i| !chosen.contains(i)) {
+            if Instant::now() >= deadline {
+                break 'search;
+            }
+            let key = committee_key(&candidates[i]);
+            let gain =
+                committee_reward(candidates, members_of(&key).iter().copied().chain(Some(i)))
+                    - reward_of(&key);
+            if gain > 0 {
+                gains.push((gain, i));
+            }
+        }
+        gains.sort_unstable_by(|a, b| b.cmp(a));
+
+        let swap = gains.iter().find_map(|(gain, incoming)| {
+            losses
+                .iter()
+                .take_while(|(loss, _)| loss < gain)
+                .find(|(_, position)| {
+                    let outgoing = chosen[*position];
+                    let key = committee_key(&candidates[outgoing]);
+                    // Swapping within a committee changes the reward of the committee as a whole,
+                    // so the gain and loss can't simply be added together.
+                    key != committee_key(&candidates[*incoming]) || {
+                        let swapped = members_of(&key)
+                            .iter()
+                            .copied()
+                            .filter(|j| *j != outgoing)
+                            .chain(Some(*incoming));
+                        committee_reward(candidates, swapped) > reward_of(&key)
+                    }
+                })
+                .map(|(_, position)| (*position, *incoming))
+        });
+
+        let (position, incoming) = match swap {
+            Some(swap) => swap,
+            None => break,
+        };
+        let outgoing = std::mem::replace(&mut chosen[position], incoming);
+
+        let outgoing_key = committee_key(&candidates[outgoing]);
+        let incoming_key = committee_key(&candidates[incoming]);
+        if let Some(members) = members.get_mut(&outgoing_key) {
+            members.retain(|j| *j != outgoing);
+        }
+        members.entry(incoming_key).or_default().push(incoming);
+        for key in &[outgoing_key, incoming_key] {
+            let reward =
+                committee_reward(candidates, members.get(key).into_iter().flatten().copied());
+            rewards.insert(*key, reward);
+        }
+    }
+
+    chosen
+}
+
+/// Returns the reward for including all of the `candidates` at `indices`, which must all be for
+/// the same committee.
+fn committee_reward<T: EthSpec>(
+    candidates: &[AttMaxCover<T>],
+    indices: impl Iterator<Item = usize>,
+) -> u64 {
+    indices
+        .fold(HashMap::<u64, u64>::new(), |mut rewards, i| {
+            rewards.extend(candidates[i].fresh_validators_rewards());
+            rewards
+        })
+        .values()
+        .sum()
+}
+
+/// Returns the reward for including all of the `candidates` at `indices`.
+fn total_reward<T: EthSpec>(candidates: &[AttMaxCover<T>], indices: &[usize]) -> u64 {
+    indices
+        .iter()
+        .fold(HashMap::<_, Vec<usize>>::new(), |mut by_committee, i| {
+            by_committee
+                .entry(committee_key(&candidates[*i]))
+                .or_default()
+                .push(*i);
+            by_committee
+        })
+        .values()
+        .map(|indices| committee_reward(candidates, indices.iter().copied()))
+        .sum()
+}
@@ -75,6 +75,24 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .default_value("50")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("max-peers-per-ip")
+                .long("max-peers-per-ip")
+                .value_name("COUNT")
+                .help("The maximum number of peers that may be connected from a single IP \
+                       address. Trusted peers are exempt. Defaults to no limit.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("muxer")
+                .long("muxer")
+                .value_name("MUXER")
+                .help("The stream multiplexers to support on libp2p connections. When `both` \
+                       is selected, yamux is preferred over mplex.")
+                .possible_values(&["yamux", "mplex", "both"])
+                .default_value("both")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("boot-nodes")
                 .long("boot-nodes")
@@ -185,6 +203,14 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     address of this server (e.g., http://localhost:5052).")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("http-allow-peer-management")
+                .long("http-allow-peer-management")
+                .help("Enable the HTTP API endpoints which dial, disconnect, ban, unban and \
+                    change the trusted status of peers. The HTTP API has no authentication, so \
+                    anyone who can reach it can manage the peers of this node. Disabled by default.")
+                .takes_value(false),
+        )
         /* Prometheus metrics HTTP server related arguments */
         .arg(
             Arg::with_name("metrics")
@@ -361,4 +387,15 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("WSS_CHECKPOINT")
                 .takes_value(true)
         )
+        .arg(
+            Arg::with_name("proposer-score-boost")
+                .long("proposer-score-boost")
+                .help(
+                    "Add this percentage of the committee weight to blocks which arrive within \
+                     the first third of their slot when running fork choice. This helps to prevent \
+                     balancing attacks. Disabled by default."
+                )
+                .value_name("PERCENTAGE")
+                .takes_value(true)
+        )
 }
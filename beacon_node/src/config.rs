@@ -115,6 +115,10 @@ pub fn get_config<E: EthSpec>(
         client_config.http_api.allow_origin = Some(allow_origin.to_string());
     }
 
+    if cli_args.is_present("http-allow-peer-management") {
+        client_config.http_api.allow_peer_management = true;
+    }
+
     /*
      * Prometheus metrics HTTP server
      */
@@ -350,6 +354,9 @@ pub fn get_config<E: EthSpec>(
         };
     }
 
+    client_config.chain.proposer_score_boost =
+        clap_utils::parse_optional(cli_args, "proposer-score-boost")?;
+
     Ok(client_config)
 }
 
@@ -385,6 +392,19 @@ pub fn set_network_config(
             .map_err(|_| format!("Invalid number of target peers: {}", target_peers_str))?;
     }
 
+    if let Some(max_peers_per_ip_str) = cli_args.value_of("max-peers-per-ip") {
+        config.max_peers_per_ip = Some(max_peers_per_ip_str.parse::<usize>().map_err(|_| {
+            format!(
+                "Invalid maximum number of peers per IP: {}",
+                max_peers_per_ip_str
+            )
+        })?);
+    }
+
+    if let Some(muxer_str) = cli_args.value_of("muxer") {
+        config.muxer = muxer_str.parse()?;
+    }
+
     if let Some(port_str) = cli_args.value_of("port") {
         let port = port_str
             .parse::<u16>()
@@ -153,6 +153,8 @@ pub enum DBColumn {
     OpPool,
     Eth1Cache,
     ForkChoice,
+    /// For persisting the caches used to process gossip, such as the naive aggregation pool.
+    GossipCaches,
     /// For the table mapping restore point numbers to state roots.
     BeaconRestorePoint,
     /// For the mapping from state roots to their slots or summaries.
@@ -178,6 +180,7 @@ impl Into<&'static str> for DBColumn {
             DBColumn::OpPool => "opo",
             DBColumn::Eth1Cache => "etc",
             DBColumn::ForkChoice => "frk",
+            DBColumn::GossipCaches => "gsc",
             DBColumn::BeaconRestorePoint => "brp",
             DBColumn::BeaconStateSummary => "bss",
             DBColumn::BeaconStateTemporary => "bst",
@@ -1,16 +1,33 @@
 //! A timer service for the beacon node.
 //!
 //! This service allows task execution on the beacon node for various functionality.
+//!
+//! In addition to the `BeaconChain::per_slot_task`, which runs at the start of each slot, the
+//! `SlotHook`s registered by `BeaconChain::slot_hooks` run at some other point within each slot
+//! (e.g., 2/3 of the way through the slot, or 500ms before the next slot). Hooks run on the
+//! blocking thread pool, so they may perform disk I/O without stalling the async executor.
+
+#[macro_use]
+extern crate lazy_static;
+
+mod metrics;
+mod slot_hook;
 
-use beacon_chain::{BeaconChain, BeaconChainTypes};
+use beacon_chain::{BeaconChain, BeaconChainTypes, SlotHook};
 use futures::stream::StreamExt;
-use slog::info;
+use slog::{debug, error, info, warn};
 use slot_clock::SlotClock;
+use slot_hook::next_run;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::time::{interval_at, Instant};
+use task_executor::TaskExecutor;
+use tokio::sync::oneshot;
+use tokio::time::{delay_for, interval_at, Instant};
 
-/// Spawns a timer service which periodically executes tasks for the beacon chain
+/// Spawns a timer service which periodically executes tasks for the beacon chain.
+///
+/// Each of the beacon chain's slot hooks is run once per slot, at its configured offset into the
+/// slot.
 pub fn spawn_timer<T: BeaconChainTypes>(
     executor: task_executor::TaskExecutor,
     beacon_chain: Arc<BeaconChain<T>>,
@@ -23,8 +40,16 @@ pub fn spawn_timer<T: BeaconChainTypes>(
             .duration_to_next_slot()
             .ok_or_else(|| "slot_notifier unable to determine time to next slot")?;
 
+    let slot_duration = Duration::from_millis(milliseconds_per_slot);
+
+    for hook in beacon_chain.slot_hooks() {
+        let hook_future =
+            run_slot_hook(executor.clone(), beacon_chain.clone(), hook, slot_duration);
+        executor.spawn(hook_future, "timer_slot_hook");
+    }
+
     // Warning: `interval_at` panics if `milliseconds_per_slot` = 0.
-    let mut interval = interval_at(start_instant, Duration::from_millis(milliseconds_per_slot));
+    let mut interval = interval_at(start_instant, slot_duration);
     let timer_future = async move {
         while interval.next().await.is_some() {
             beacon_chain.per_slot_task();
@@ -36,3 +61,87 @@ pub fn spawn_timer<T: BeaconChainTypes>(
 
     Ok(())
 }
+
+/// Runs `hook` at its offset into every slot, forever.
+async fn run_slot_hook<T: BeaconChainTypes>(
+    executor: TaskExecutor,
+    beacon_chain: Arc<BeaconChain<T>>,
+    hook: SlotHook,
+    slot_duration: Duration,
+) {
+    let log = executor.log().clone();
+    let slot_clock = &beacon_chain.slot_clock;
+    let name = hook.name();
+    let offset = hook.offset().duration_into_slot(slot_duration);
+    // The earliest slot in which the hook may next run.
+    let mut next_slot = None;
+
+    debug!(
+        log,
+        "Starting slot hook";
+        "hook" => name,
+        "offset_ms" => offset.as_millis()
+    );
+
+    loop {
+        let (slot, delay) = match next_run(slot_clock, next_slot, offset) {
+            Some(next) => next,
+            None => {
+                warn!(
+                    log,
+                    "Unable to read slot clock";
+                    "hook" => name
+                );
+                delay_for(slot_duration).await;
+                continue;
+            }
+        };
+
+        if let Some(expected_slot) = next_slot {
+            if slot > expected_slot {
+                warn!(
+                    log,
+                    "Slot hook skipped slots";
+                    "hook" => name,
+                    "skipped" => (slot - expected_slot).as_u64(),
+                );
+            }
+        }
+        next_slot = Some(slot + 1);
+
+        delay_for(delay).await;
+
+        if let (Some(now), Some(slot_start)) =
+            (slot_clock.now_duration(), slot_clock.start_of(slot))
+        {
+            if let Some(since_slot_start) = now.checked_sub(slot_start) {
+                metrics::observe_timer_vec(
+                    &metrics::SLOT_HOOK_START_TIMES,
+                    &[name],
+                    since_slot_start,
+                );
+            }
+        }
+
+        // Hooks may block (e.g., writing to disk), so keep them off the async executor. Wait for
+        // the hook to complete so that runs never overlap.
+        let (done_tx, done_rx) = oneshot::channel();
+        let task = hook.clone();
+        let timer = metrics::start_timer_vec(&metrics::SLOT_HOOK_TIMES, &[name]);
+        executor.spawn_blocking(
+            move || {
+                task.run();
+                let _ = done_tx.send(());
+            },
+            "timer_slot_hook_task",
+        );
+        if done_rx.await.is_err() {
+            error!(
+                log,
+                "Slot hook failed";
+                "hook" => name,
+            );
+        }
+        metrics::stop_timer(timer);
+    }
+}
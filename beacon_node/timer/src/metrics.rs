@@ -0,0 +1,14 @@
+pub use lighthouse_metrics::*;
+
+lazy_static! {
+    pub static ref SLOT_HOOK_TIMES: Result<HistogramVec> = try_create_histogram_vec(
+        "timer_slot_hook_seconds",
+        "Time taken to run each slot hook",
+        &["hook"]
+    );
+    pub static ref SLOT_HOOK_START_TIMES: Result<HistogramVec> = try_create_histogram_vec(
+        "timer_slot_hook_start_seconds",
+        "Duration since the start of the slot at which each slot hook started running",
+        &["hook"]
+    );
+}
@@ -0,0 +1,135 @@
+//! Determines when slot hooks run.
+
+use slot_clock::SlotClock;
+use std::time::Duration;
+use types::Slot;
+
+/// Returns the next slot in which a hook at `offset` should run, along with the duration until it
+/// should run.
+///
+/// The slot is never earlier than `earliest`, so a hook which has already run in a slot is not run
+/// again in that slot, even if the timer fires marginally early. If the run time in `earliest` has
+/// passed the hook runs late rather than skipping the slot, unless the run time in the following
+/// slot has also passed.
+///
+/// If `earliest` is `None` the hook has not run before and runs at the next occurrence of
+/// `offset`.
+pub(crate) fn next_run<S: SlotClock>(
+    slot_clock: &S,
+    earliest: Option<Slot>,
+    offset: Duration,
+) -> Option<(Slot, Duration)> {
+    let now = slot_clock.now_duration()?;
+    // Prior to genesis the first run is in the genesis slot.
+    let current_slot = slot_clock
+        .now()
+        .unwrap_or_else(|| slot_clock.genesis_slot());
+    let run_time = |slot: Slot| slot_clock.start_of(slot).map(|start| start + offset);
+
+    let slot = match earliest {
+        Some(earliest) => {
+            let mut slot = std::cmp::max(earliest, current_slot.saturating_sub(1_u64));
+            while run_time(slot + 1)? <= now {
+                slot += 1;
+            }
+            slot
+        }
+        // Since `offset` is never greater than the slot duration, the run time in the following
+        // slot is always in the future.
+        None if run_time(current_slot)? < now => current_slot + 1,
+        None => current_slot,
+    };
+
+    Some((slot, run_time(slot)?.checked_sub(now).unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slot_clock::ManualSlotClock;
+
+    const SLOT: Duration = Duration::from_secs(12);
+
+    fn slot_clock() -> ManualSlotClock {
+        ManualSlotClock::new(Slot::new(0), Duration::from_secs(SLOT.as_secs() * 10), SLOT)
+    }
+
+    fn at(slot: u64, into_slot: Duration) -> Duration {
+        Duration::from_secs(SLOT.as_secs() * (10 + slot)) + into_slot
+    }
+
+    #[test]
+    fn next_run_in_current_slot() {
+        let clock = slot_clock();
+
+        // 3 seconds into the slot, with an offset of 4 seconds.
+        clock.set_current_time(at(5, Duration::from_secs(3)));
+        assert_eq!(
+            next_run(&clock, None, Duration::from_secs(4)),
+            Some((Slot::new(5), Duration::from_secs(1)))
+        );
+    }
+
+    #[test]
+    fn next_run_in_next_slot() {
+        let clock = slot_clock();
+
+        // 5 seconds into the slot, with an offset of 4 seconds.
+        clock.set_current_time(at(5, Duration::from_secs(5)));
+        assert_eq!(
+            next_run(&clock, None, Duration::from_secs(4)),
+            Some((Slot::new(6), Duration::from_secs(11)))
+        );
+
+        // Exactly at the offset.
+        clock.set_current_time(at(5, Duration::from_secs(4)));
+        assert_eq!(
+            next_run(&clock, None, Duration::from_secs(4)),
+            Some((Slot::new(5), Duration::from_secs(0)))
+        );
+    }
+
+    #[test]
+    fn next_run_prior_to_genesis() {
+        let clock = slot_clock();
+
+        clock.set_current_time(at(0, Duration::from_secs(0)) - Duration::from_secs(2));
+        assert_eq!(
+            next_run(&clock, None, Duration::from_secs(4)),
+            Some((Slot::new(0), Duration::from_secs(6)))
+        );
+    }
+
+    #[test]
+    fn next_run_does_not_repeat_or_skip_slots() {
+        let clock = slot_clock();
+
+        // The hook ran in slot 5 but the timer fires marginally early for slot 6.
+        clock.set_current_time(at(6, Duration::from_secs(0)) - Duration::from_millis(1));
+        assert_eq!(
+            next_run(&clock, Some(Slot::new(6)), Duration::from_secs(0)),
+            Some((Slot::new(6), Duration::from_millis(1)))
+        );
+
+        // The hook runs at the end of the slot and the timer fires marginally late for slot 5.
+        clock.set_current_time(at(6, Duration::from_millis(1)));
+        assert_eq!(
+            next_run(&clock, Some(Slot::new(5)), SLOT),
+            Some((Slot::new(5), Duration::from_secs(0)))
+        );
+
+        // The hook ran in slot 4 and is late for slot 5, but not yet due in slot 6.
+        clock.set_current_time(at(5, Duration::from_secs(5)));
+        assert_eq!(
+            next_run(&clock, Some(Slot::new(5)), Duration::from_secs(4)),
+            Some((Slot::new(5), Duration::from_secs(0)))
+        );
+
+        // The hook is due in slot 6 before it could run in slot 5, so slot 5 is skipped.
+        clock.set_current_time(at(6, Duration::from_secs(5)));
+        assert_eq!(
+            next_run(&clock, Some(Slot::new(5)), Duration::from_secs(4)),
+            Some((Slot::new(6), Duration::from_secs(0)))
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::http;
 use beacon_node::{get_data_dir, get_eth2_testnet_config, set_network_config};
 use clap::ArgMatches;
 use eth2_libp2p::discv5::{enr::CombinedKey, Enr};
@@ -8,8 +9,9 @@ use eth2_libp2p::{
 use ssz::Encode;
 use std::convert::TryFrom;
 use std::marker::PhantomData;
-use std::net::SocketAddr;
-use types::EthSpec;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use types::{EnrForkId, EthSpec};
 
 /// A set of configuration parameters for the bootnode, established from CLI arguments.
 pub struct BootNodeConfig<T: EthSpec> {
@@ -19,6 +21,9 @@ pub struct BootNodeConfig<T: EthSpec> {
     pub local_enr: Enr,
     pub local_key: CombinedKey,
     pub auto_update: bool,
+    /// The directory in which the ENR and routing table are persisted.
+    pub network_dir: PathBuf,
+    pub http_config: http::Config,
     phantom: PhantomData<T>,
 }
 
@@ -79,14 +84,8 @@ impl<T: EthSpec> TryFrom<&ArgMatches<'_>> for BootNodeConfig<T> {
         let private_key = load_private_key(&network_config, &logger);
         let local_key = CombinedKey::from_libp2p(&private_key)?;
 
-        let mut local_enr = create_enr_builder_from_config(&network_config)
-            .build(&local_key)
-            .map_err(|e| format!("Failed to build ENR: {:?}", e))?;
-
-        use_or_load_enr(&local_key, &mut local_enr, &network_config, &logger)?;
-
-        // build the enr_fork_id and add it to the local_enr if it exists
-        if let Some(config) = eth2_testnet_config.as_ref() {
+        // build the enr_fork_id so that it is included in the local_enr, if it exists
+        let enr_fork_id = if let Some(config) = eth2_testnet_config.as_ref() {
             let spec = config
                 .yaml_config
                 .as_ref()
@@ -98,27 +97,26 @@ impl<T: EthSpec> TryFrom<&ArgMatches<'_>> for BootNodeConfig<T> {
                 let genesis_state = config.beacon_state::<T>()?;
 
                 slog::info!(logger, "Genesis state found"; "root" => genesis_state.canonical_root().to_string());
-                let enr_fork = spec.enr_fork_id(
+                Some(spec.enr_fork_id(
                     types::Slot::from(0u64),
                     genesis_state.genesis_validators_root,
-                );
-
-                // add to the local_enr
-                if let Err(e) = local_enr.insert("eth2", &enr_fork.as_ssz_bytes(), &local_key) {
-                    slog::warn!(logger, "Could not update eth2 field"; "error" => ?e);
-                }
+                ))
             } else {
                 slog::warn!(
                     logger,
                     "No genesis state provided. No Eth2 field added to the ENR"
                 );
+                None
             }
         } else {
             slog::warn!(
                 logger,
                 "No testnet config provided. Not setting an eth2 field"
             );
-        }
+            None
+        };
+
+        let local_enr = build_local_enr(&local_key, &network_config, enr_fork_id, &logger)?;
 
         let auto_update = matches.is_present("enable-enr_auto_update");
 
@@ -126,13 +124,101 @@ impl<T: EthSpec> TryFrom<&ArgMatches<'_>> for BootNodeConfig<T> {
         let listen_socket =
             SocketAddr::new(network_config.listen_address, network_config.discovery_port);
 
+        let mut http_config = http::Config::default();
+
+        if matches.is_present("http") {
+            http_config.enabled = true;
+        }
+
+        if let Some(address) = matches.value_of("http-address") {
+            http_config.listen_addr = address
+                .parse::<Ipv4Addr>()
+                .map_err(|_| "http-address is not a valid IPv4 address.")?;
+        }
+
+        if let Some(port) = matches.value_of("http-port") {
+            http_config.listen_port = port
+                .parse::<u16>()
+                .map_err(|_| "http-port is not a valid u16.")?;
+        }
+
+        if let Some(allow_origin) = matches.value_of("http-allow-origin") {
+            http_config.allow_origin = Some(allow_origin.to_string());
+        }
+
         Ok(BootNodeConfig {
             listen_socket,
             boot_nodes,
             local_enr,
             local_key,
             auto_update,
+            network_dir: network_config.network_dir,
+            http_config,
             phantom: PhantomData,
         })
     }
 }
+
+/// Builds the local ENR, including the `eth2` field if `enr_fork_id` is known.
+///
+/// The ENR persisted in the network directory is reused if nothing has changed, so that the
+/// sequence number is only incremented when the contents of the ENR change.
+fn build_local_enr(
+    local_key: &CombinedKey,
+    network_config: &NetworkConfig,
+    enr_fork_id: Option<EnrForkId>,
+    log: &slog::Logger,
+) -> Result<Enr, String> {
+    let mut builder = create_enr_builder_from_config(network_config);
+
+    // the `eth2` field must be set before comparing against the ENR on disk
+    if let Some(enr_fork_id) = enr_fork_id {
+        builder.add_value("eth2", &enr_fork_id.as_ssz_bytes());
+    }
+
+    let mut local_enr = builder
+        .build(local_key)
+        .map_err(|e| format!("Failed to build ENR: {:?}", e))?;
+
+    use_or_load_enr(local_key, &mut local_enr, network_config, log)?;
+
+    Ok(local_enr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+    use types::{ChainSpec, Hash256, Slot};
+
+    #[test]
+    fn enr_seq_is_stable_across_restarts() {
+        let log = slog::Logger::root(slog::Discard, slog::o!());
+        let dir = TempDir::new("boot_node_enr").unwrap();
+
+        let mut network_config = NetworkConfig::default();
+        network_config.network_dir = dir.path().into();
+        network_config.enr_udp_port = Some(9000);
+
+        let local_key = CombinedKey::generate_secp256k1();
+        let enr_fork_id = ChainSpec::mainnet().enr_fork_id(Slot::new(0), Hash256::repeat_byte(1));
+        let start = || {
+            build_local_enr(&local_key, &network_config, Some(enr_fork_id.clone()), &log).unwrap()
+        };
+
+        let first = start();
+        let second = start();
+        let third = start();
+
+        assert_eq!(second.seq(), first.seq());
+        assert_eq!(third.seq(), first.seq());
+        assert_eq!(third.to_base64(), first.to_base64());
+        assert!(third.get("eth2").is_some());
+
+        // changing the contents of the ENR still bumps the sequence number
+        let other_fork_id = ChainSpec::mainnet().enr_fork_id(Slot::new(0), Hash256::repeat_byte(2));
+        let updated =
+            build_local_enr(&local_key, &network_config, Some(other_fork_id), &log).unwrap();
+        assert_eq!(updated.seq(), first.seq() + 1);
+    }
+}
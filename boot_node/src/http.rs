@@ -0,0 +1,209 @@
+//! An optional HTTP server which exposes the status of the boot node and its Prometheus metrics.
+//!
+//! Endpoints:
+//!
+//! - `GET /enr`: the local ENR and the identities derived from it.
+//! - `GET /routing_table`: the size of the routing table and the number of entries per bucket.
+//! - `GET /enrs`: all ENRs in the routing table.
+//! - `GET /metrics`: Prometheus metrics.
+
+use crate::metrics;
+use eth2::types::GenericResponse;
+use eth2_libp2p::{discv5::Discv5, EnrExt, Eth2Enr};
+use lighthouse_version::version_with_platform;
+use serde::{Deserialize, Serialize};
+use slog::{info, Logger};
+use std::future::Future;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::Arc;
+use warp::{http::Response, Filter};
+
+/// Configuration for the HTTP server.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    pub enabled: bool,
+    pub listen_addr: Ipv4Addr,
+    pub listen_port: u16,
+    pub allow_origin: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen_addr: Ipv4Addr::new(127, 0, 0, 1),
+            listen_port: 5058,
+            allow_origin: None,
+        }
+    }
+}
+
+/// Describes an ENR and the identities derived from it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnrData {
+    pub enr: String,
+    pub node_id: String,
+    pub peer_id: String,
+    pub udp_socket: Option<SocketAddr>,
+    pub tcp_socket: Option<SocketAddr>,
+    /// The hex-encoded fork digest of the `eth2` field, if present.
+    pub fork_digest: Option<String>,
+}
+
+impl From<&eth2_libp2p::Enr> for EnrData {
+    fn from(enr: &eth2_libp2p::Enr) -> Self {
+        Self {
+            enr: enr.to_base64(),
+            node_id: enr.node_id().to_string(),
+            peer_id: enr.peer_id().to_string(),
+            udp_socket: enr.udp_socket().map(Into::into),
+            tcp_socket: enr.tcp_socket().map(Into::into),
+            fork_digest: enr
+                .eth2()
+                .ok()
+                .map(|fork_id| hex::encode(fork_id.fork_digest)),
+        }
+    }
+}
+
+/// The number of entries in a single bucket of the routing table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketData {
+    /// The log2 distance of the bucket from the local node id.
+    pub distance: u64,
+    pub entries: usize,
+}
+
+/// A summary of the routing table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingTableData {
+    pub entries: usize,
+    pub connected_peers: usize,
+    /// Non-empty buckets, ordered by distance.
+    pub buckets: Vec<BucketData>,
+}
+
+/// Creates a server that will serve requests using information from `discv5`.
+///
+/// The server will shut down gracefully when the `shutdown` future resolves.
+///
+/// ## Returns
+///
+/// This function will bind the server to the provided address and then return a tuple of:
+///
+/// - `SocketAddr`: the address that the HTTP server will listen on.
+/// - `Future`: the actual server future that will need to be awaited.
+///
+/// ## Errors
+///
+/// Returns an error if the server is unable to bind or there is another error during
+/// configuration.
+pub fn serve(
+    config: &Config,
+    discv5: Arc<Discv5>,
+    log: Logger,
+    shutdown: impl Future<Output = ()> + Send + Sync + 'static,
+) -> Result<(SocketAddr, impl Future<Output = ()>), String> {
+    // Configure CORS.
+    let cors_builder = {
+        let builder = warp::cors()
+            .allow_method("GET")
+            .allow_headers(vec!["Content-Type"]);
+
+        warp_utils::cors::set_builder_origins(
+            builder,
+            config.allow_origin.as_deref(),
+            (config.listen_addr, config.listen_port),
+        )
+        .map_err(|e| format!("Invalid CORS config: {:?}", e))?
+    };
+
+    let discv5_filter = warp::any().map(move || discv5.clone());
+
+    // GET enr
+    let get_enr = warp::path("enr")
+        .and(warp::path::end())
+        .and(discv5_filter.clone())
+        .and_then(|discv5: Arc<Discv5>| {
+            warp_utils::task::blocking_json_task(move || {
+                Ok(GenericResponse::from(EnrData::from(&discv5.local_enr())))
+            })
+        });
+
+    // GET routing_table
+    let get_routing_table = warp::path("routing_table")
+        .and(warp::path::end())
+        .and(discv5_filter.clone())
+        .and_then(|discv5: Arc<Discv5>| {
+            warp_utils::task::blocking_json_task(move || {
+                let mut buckets = metrics::bucket_sizes(&discv5)
+                    .into_iter()
+                    .map(|(distance, entries)| BucketData { distance, entries })
+                    .collect::<Vec<_>>();
+                buckets.sort_by_key(|bucket| bucket.distance);
+
+                Ok(GenericResponse::from(RoutingTableData {
+                    entries: discv5.table_entries_id().len(),
+                    connected_peers: discv5.connected_peers(),
+                    buckets,
+                }))
+            })
+        });
+
+    // GET enrs
+    let get_enrs = warp::path("enrs")
+        .and(warp::path::end())
+        .and(discv5_filter.clone())
+        .and_then(|discv5: Arc<Discv5>| {
+            warp_utils::task::blocking_json_task(move || {
+                Ok(GenericResponse::from(
+                    discv5
+                        .table_entries_enr()
+                        .iter()
+                        .map(EnrData::from)
+                        .collect::<Vec<_>>(),
+                ))
+            })
+        });
+
+    // GET metrics
+    let get_metrics = warp::path("metrics")
+        .and(warp::path::end())
+        .and(discv5_filter)
+        .and_then(|discv5: Arc<Discv5>| async move {
+            Ok::<_, warp::Rejection>(
+                metrics::gather_prometheus_metrics(&discv5)
+                    .map(|body| Response::builder().status(200).body(body).unwrap())
+                    .unwrap_or_else(|e| {
+                        Response::builder()
+                            .status(500)
+                            .body(format!("Unable to gather metrics: {:?}", e))
+                            .unwrap()
+                    }),
+            )
+        });
+
+    let routes = warp::get()
+        .and(get_enr.or(get_routing_table).or(get_enrs).or(get_metrics))
+        .recover(warp_utils::reject::handle_rejection)
+        // Add a `Server` header.
+        .map(|reply| warp::reply::with_header(reply, "Server", &version_with_platform()))
+        .with(cors_builder.build());
+
+    let (listening_socket, server) = warp::serve(routes)
+        .try_bind_with_graceful_shutdown(
+            SocketAddrV4::new(config.listen_addr, config.listen_port),
+            async {
+                shutdown.await;
+            },
+        )
+        .map_err(|e| format!("Unable to start HTTP server: {:?}", e))?;
+
+    info!(
+        log,
+        "HTTP server started";
+        "listen_address" => listening_socket.to_string(),
+    );
+
+    Ok((listening_socket, server))
+}
@@ -1,10 +1,16 @@
 //! Creates a simple DISCV5 server which can be used to bootstrap an Eth2 network.
+#[macro_use]
+extern crate lazy_static;
+
 use clap::ArgMatches;
 use slog::{o, Drain, Level, Logger};
 
 use std::convert::TryFrom;
 mod cli;
 mod config;
+mod http;
+mod metrics;
+mod persisted_dht;
 mod server;
 pub use cli::cli_app;
 use config::BootNodeConfig;
@@ -0,0 +1,140 @@
+use eth2_libp2p::{
+    discv5::{enr::NodeId, Discv5},
+    Eth2Enr,
+};
+use lighthouse_metrics::{Encoder, TextEncoder};
+use std::collections::HashMap;
+
+pub use lighthouse_metrics::*;
+
+lazy_static! {
+    pub static ref ROUTING_TABLE_ENTRIES: Result<IntGauge> = try_create_int_gauge(
+        "boot_node_routing_table_entries",
+        "Count of ENRs in the discovery routing table"
+    );
+    pub static ref ROUTING_TABLE_BUCKET_ENTRIES: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "boot_node_routing_table_bucket_entries",
+        "Count of ENRs in each bucket of the discovery routing table",
+        &["distance"]
+    );
+    pub static ref CONNECTED_PEERS: Result<IntGauge> = try_create_int_gauge(
+        "boot_node_connected_peers",
+        "Count of peers with which the boot node has an active discovery session"
+    );
+    pub static ref KNOWN_ENRS_PER_FORK_DIGEST: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "boot_node_known_enrs_per_fork_digest",
+        "Count of ENRs in the discovery routing table for each fork digest",
+        &["fork_digest"]
+    );
+}
+
+/// Returns the logarithmic (base 2) XOR distance between two node ids, which is the index of the
+/// routing table bucket that `b` would occupy in the routing table of `a`.
+///
+/// Returns `None` if the two ids are equal.
+pub fn log2_distance(a: &NodeId, b: &NodeId) -> Option<u64> {
+    let (a, b) = (a.raw(), b.raw());
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| a ^ b)
+        .enumerate()
+        .find(|(_, xor)| *xor != 0)
+        .map(|(i, xor)| (a.len() - i) as u64 * 8 - u64::from(xor.leading_zeros()))
+}
+
+/// Returns the number of entries in each non-empty bucket of the routing table, keyed by bucket
+/// distance.
+pub fn bucket_sizes(discv5: &Discv5) -> HashMap<u64, usize> {
+    let local_id = discv5.local_enr().node_id();
+    let mut buckets = HashMap::new();
+    for node_id in discv5.table_entries_id() {
+        if let Some(distance) = log2_distance(&local_id, &node_id) {
+            *buckets.entry(distance).or_insert(0) += 1;
+        }
+    }
+    buckets
+}
+
+/// Returns the number of known ENRs for each fork digest, using `"none"` for ENRs without a valid
+/// `eth2` field.
+pub fn fork_digest_counts(discv5: &Discv5) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for enr in discv5.table_entries_enr() {
+        let fork_digest = enr
+            .eth2()
+            .map(|fork_id| hex::encode(fork_id.fork_digest))
+            .unwrap_or_else(|_| "none".to_string());
+        *counts.entry(fork_digest).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Updates the statically scraped metrics and returns all metrics in the Prometheus text format.
+pub fn gather_prometheus_metrics(discv5: &Discv5) -> std::result::Result<String, String> {
+    let mut buffer = vec![];
+    let encoder = TextEncoder::new();
+
+    // Discovery sessions and requests per second.
+    eth2_libp2p::scrape_discovery_metrics();
+
+    set_gauge(
+        &ROUTING_TABLE_ENTRIES,
+        discv5.table_entries_id().len() as i64,
+    );
+    set_gauge(&CONNECTED_PEERS, discv5.connected_peers() as i64);
+
+    // Reset the vectors so that buckets and fork digests which are no longer present are removed.
+    if let Ok(gauge_vec) = ROUTING_TABLE_BUCKET_ENTRIES.as_ref() {
+        gauge_vec.reset();
+    }
+    for (distance, count) in bucket_sizes(discv5) {
+        set_int_gauge(
+            &ROUTING_TABLE_BUCKET_ENTRIES,
+            &[&distance.to_string()],
+            count as i64,
+        );
+    }
+
+    if let Ok(gauge_vec) = KNOWN_ENRS_PER_FORK_DIGEST.as_ref() {
+        gauge_vec.reset();
+    }
+    for (fork_digest, count) in fork_digest_counts(discv5) {
+        set_int_gauge(&KNOWN_ENRS_PER_FORK_DIGEST, &[&fork_digest], count as i64);
+    }
+
+    encoder
+        .encode(&lighthouse_metrics::gather(), &mut buffer)
+        .unwrap();
+
+    String::from_utf8(buffer).map_err(|e| format!("Failed to encode prometheus info: {:?}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node_id(bytes: &[(usize, u8)]) -> NodeId {
+        let mut raw = [0; 32];
+        for (i, byte) in bytes {
+            raw[*i] = *byte;
+        }
+        NodeId::new(&raw)
+    }
+
+    #[test]
+    fn log2_distance_matches_bucket_index() {
+        let zero = node_id(&[]);
+        assert_eq!(log2_distance(&zero, &zero), None);
+        assert_eq!(log2_distance(&zero, &node_id(&[(31, 1)])), Some(1));
+        assert_eq!(
+            log2_distance(&zero, &node_id(&[(31, 0b1000_0000)])),
+            Some(8)
+        );
+        assert_eq!(log2_distance(&zero, &node_id(&[(30, 1)])), Some(9));
+        assert_eq!(log2_distance(&zero, &node_id(&[(0, 0xff)])), Some(256));
+        assert_eq!(
+            log2_distance(&node_id(&[(0, 0xff)]), &node_id(&[(0, 0x7f)])),
+            Some(256)
+        );
+    }
+}
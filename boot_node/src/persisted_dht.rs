@@ -0,0 +1,83 @@
+//! Persists the boot node's routing table to its network directory, in the same format that
+//! `network::persisted_dht` uses to persist the routing table of a beacon node to its database.
+
+use eth2_libp2p::Enr;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// The file in the network directory in which the routing table is stored.
+pub const DHT_FILENAME: &str = "dht.dat";
+
+/// Load the persisted routing table from `network_dir`, returning an empty list if there is none.
+pub fn load_dht(network_dir: &Path, log: &slog::Logger) -> Vec<Enr> {
+    let path = network_dir.join(DHT_FILENAME);
+    if !path.exists() {
+        return vec![];
+    }
+
+    let mut bytes = vec![];
+    match File::open(&path)
+        .and_then(|mut f| f.read_to_end(&mut bytes))
+        .map_err(|e| format!("{}", e))
+        .and_then(|_| PersistedDht::from_bytes(&bytes))
+    {
+        Ok(dht) => dht.enrs,
+        Err(e) => {
+            slog::warn!(
+                log,
+                "Could not load persisted DHT";
+                "file" => format!("{:?}", path),
+                "error" => e
+            );
+            vec![]
+        }
+    }
+}
+
+/// Attempt to persist the ENRs in the routing table to `network_dir`.
+pub fn persist_dht(network_dir: &Path, enrs: Vec<Enr>) -> Result<(), String> {
+    std::fs::create_dir_all(network_dir)
+        .map_err(|e| format!("Unable to create {:?}: {}", network_dir, e))?;
+
+    let path = network_dir.join(DHT_FILENAME);
+    File::create(&path)
+        .and_then(|mut f| f.write_all(&PersistedDht { enrs }.as_bytes()))
+        .map_err(|e| format!("Unable to write {:?}: {}", path, e))
+}
+
+/// Wrapper around DHT for persistence to disk.
+pub struct PersistedDht {
+    pub enrs: Vec<Enr>,
+}
+
+impl PersistedDht {
+    fn as_bytes(&self) -> Vec<u8> {
+        rlp::encode_list(&self.enrs)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let rlp = rlp::Rlp::new(bytes);
+        let enrs: Vec<Enr> = rlp.as_list().map_err(|e| format!("{}", e))?;
+        Ok(PersistedDht { enrs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_persisted_dht() {
+        let log = slog::Logger::root(slog::Discard, slog::o!());
+        let dir = TempDir::new("boot_node_dht").unwrap();
+
+        assert!(load_dht(dir.path(), &log).is_empty());
+
+        let enrs = vec![Enr::from_str("enr:-IS4QHCYrYZbAKWCBRlAy5zzaDZXJBGkcnh4MHcBFZntXNFrdvJjX04jRzjzCBOonrkTfj499SZuOh8R33Ls8RRcy5wBgmlkgnY0gmlwhH8AAAGJc2VjcDI1NmsxoQPKY0yuDUmstAHYpMa2_oxVtw0RW_QAdpzBQA8yWM0xOIN1ZHCCdl8").unwrap()];
+        persist_dht(dir.path(), enrs.clone()).unwrap();
+        assert_eq!(load_dht(dir.path(), &log), enrs);
+    }
+}
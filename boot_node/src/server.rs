@@ -1,14 +1,23 @@
 //! The main bootnode server execution.
 
 use super::BootNodeConfig;
+use crate::http;
+use crate::persisted_dht::{load_dht, persist_dht};
 use eth2_libp2p::{
+    discovery::save_enr_to_disk,
     discv5::{enr::NodeId, Discv5, Discv5ConfigBuilder, Discv5Event},
     EnrExt, Eth2Enr,
 };
 use futures::prelude::*;
-use slog::info;
+use slog::{info, warn};
+use std::path::Path;
+use std::sync::Arc;
 use types::EthSpec;
 
+/// The interval at which the ENR and routing table are persisted to disk, in addition to
+/// persisting them on shutdown.
+const PERSIST_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(300);
+
 pub async fn run<T: EthSpec>(config: BootNodeConfig<T>, log: slog::Logger) {
     // Print out useful information about the generated ENR
 
@@ -51,6 +60,15 @@ pub async fn run<T: EthSpec>(config: BootNodeConfig<T>, log: slog::Logger) {
         }
     }
 
+    // Repopulate the routing table with the ENRs persisted by a previous run
+    let enrs_to_load = load_dht(&config.network_dir, &log);
+    info!(log, "Loading peers into the routing table"; "peers" => enrs_to_load.len());
+    for enr in enrs_to_load {
+        if let Err(e) = discv5.add_enr(enr) {
+            slog::debug!(log, "Failed adding persisted ENR"; "error" => e.to_string());
+        }
+    }
+
     // start the server
     if let Err(e) = discv5.start(config.listen_socket) {
         slog::crit!(log, "Could not start discv5 server"; "error" => e.to_string());
@@ -75,9 +93,46 @@ pub async fn run<T: EthSpec>(config: BootNodeConfig<T>, log: slog::Logger) {
         }
     };
 
+    // the discv5 server is shared with the HTTP server, if any
+    let discv5 = Arc::new(discv5);
+
+    if config.http_config.enabled {
+        match http::serve(
+            &config.http_config,
+            discv5.clone(),
+            log.clone(),
+            future::pending(),
+        ) {
+            Ok((_, server)) => {
+                tokio::spawn(server);
+            }
+            Err(e) => {
+                slog::crit!(log, "Could not start HTTP server"; "error" => e);
+                return;
+            }
+        }
+    }
+
+    // persist the ENR and routing table periodically, so they survive an unclean shutdown
+    let mut persist_interval = tokio::time::interval_at(
+        tokio::time::Instant::now() + PERSIST_INTERVAL,
+        PERSIST_INTERVAL,
+    );
+
+    let shutdown = shutdown_signal(&log);
+    futures::pin_mut!(shutdown);
+
     // listen for events
     loop {
         tokio::select! {
+            _ = &mut shutdown => {
+                info!(log, "Shutting down boot node");
+                persist(&discv5, &config.network_dir, &log);
+                return;
+            }
+            _ = persist_interval.next() => {
+                persist(&discv5, &config.network_dir, &log);
+            }
             _ = metric_interval.next() => {
                 // display server metrics
                 let metrics = discv5.metrics();
@@ -93,9 +148,49 @@ pub async fn run<T: EthSpec>(config: BootNodeConfig<T>, log: slog::Logger) {
                     Discv5Event::NodeInserted { .. } => {} // Ignore
                     Discv5Event::SocketUpdated(socket_addr) => {
                         info!(log, "External socket address updated"; "socket_addr" => format!("{:?}", socket_addr));
+                        // the ENR sequence number has been incremented, ensure it is not reused
+                        save_enr_to_disk(&config.network_dir, &discv5.local_enr(), &log);
                     }
                 }
             }
         }
     }
 }
+
+/// Resolves once the process receives SIGINT or, on unix, SIGTERM.
+async fn shutdown_signal(log: &slog::Logger) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = sigterm.recv() => {}
+                }
+                return;
+            }
+            Err(e) => {
+                warn!(log, "Could not register SIGTERM handler"; "error" => format!("{}", e));
+            }
+        }
+    }
+
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        warn!(log, "Could not listen for SIGINT"; "error" => format!("{}", e));
+        futures::future::pending::<()>().await;
+    }
+}
+
+/// Persists the local ENR, including its sequence number, and the routing table to `network_dir`.
+fn persist(discv5: &Discv5, network_dir: &Path, log: &slog::Logger) {
+    save_enr_to_disk(network_dir, &discv5.local_enr(), log);
+
+    let enrs = discv5.table_entries_enr();
+    let peers = enrs.len();
+    match persist_dht(network_dir, enrs) {
+        Ok(()) => slog::debug!(log, "Saved DHT state"; "peers" => peers),
+        Err(e) => slog::error!(log, "Failed to persist DHT"; "error" => e),
+    }
+}
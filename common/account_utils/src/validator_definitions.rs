@@ -251,6 +251,11 @@ impl ValidatorDefinitions {
         self.0.push(def)
     }
 
+    /// Removes all `ValidatorDefinition` from `self` for which `f` returns `false`.
+    pub fn retain<F: FnMut(&ValidatorDefinition) -> bool>(&mut self, f: F) {
+        self.0.retain(f)
+    }
+
     /// Returns a slice of all `ValidatorDefinition` in `self`.
     pub fn as_slice(&self) -> &[ValidatorDefinition] {
         self.0.as_slice()
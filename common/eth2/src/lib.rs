@@ -78,6 +78,12 @@ pub struct BeaconNodeHttpClient {
     server: Url,
 }
 
+impl fmt::Display for BeaconNodeHttpClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.server.fmt(f)
+    }
+}
+
 impl BeaconNodeHttpClient {
     pub fn new(server: Url) -> Self {
         Self {
@@ -826,7 +832,7 @@ impl BeaconNodeHttpClient {
     pub async fn get_validator_duties_proposer(
         &self,
         epoch: Epoch,
-    ) -> Result<GenericResponse<Vec<ProposerData>>, Error> {
+    ) -> Result<DutiesResponse<Vec<ProposerData>>, Error> {
         let mut path = self.eth_path()?;
 
         path.path_segments_mut()
@@ -913,7 +919,7 @@ impl BeaconNodeHttpClient {
         &self,
         epoch: Epoch,
         indices: &[u64],
-    ) -> Result<GenericResponse<Vec<AttesterData>>, Error> {
+    ) -> Result<DutiesResponse<Vec<AttesterData>>, Error> {
         let mut path = self.eth_path()?;
 
         path.path_segments_mut()
@@ -2,7 +2,10 @@
 
 use crate::{
     ok_or_error,
-    types::{BeaconState, Epoch, EthSpec, GenericResponse, ValidatorId},
+    types::{
+        BeaconBlock, BeaconState, Epoch, EthSpec, GenericResponse, Graffiti, SignatureBytes, Slot,
+        ValidatorId,
+    },
     BeaconNodeHttpClient, DepositData, Error, Eth1Data, Hash256, StateId, StatusCode,
 };
 use proto_array::core::ProtoArray;
@@ -10,8 +13,13 @@ use reqwest::IntoUrl;
 use serde::{Deserialize, Serialize};
 use ssz::Decode;
 use ssz_derive::{Decode, Encode};
+use std::fmt;
+use std::str::FromStr;
 
-pub use eth2_libp2p::{types::SyncState, PeerInfo};
+pub use eth2_libp2p::{
+    types::SyncState, AttestationSubnetsSummary, PeerInfo, SubnetSubscription,
+    SubnetSubscriptionKind,
+};
 
 /// Information returned by `peers` and `connected_peers`.
 // TODO: this should be deserializable..
@@ -24,6 +32,87 @@ pub struct Peer<T: EthSpec> {
     pub peer_info: PeerInfo<T>,
 }
 
+/// An action performed on a peer by `POST lighthouse/peers/{peer_id}/{action}`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PeerManagementAction {
+    /// Mark the peer as trusted, so that it is never disconnected or banned due to its score.
+    Trust,
+    /// Remove the trusted status of the peer.
+    Untrust,
+    /// Disconnect from the peer without banning it.
+    Disconnect,
+    /// Disconnect from and ban the peer.
+    Ban,
+    /// Reset the score of a banned peer and unban it.
+    Unban,
+}
+
+impl FromStr for PeerManagementAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "trust" => Ok(PeerManagementAction::Trust),
+            "untrust" => Ok(PeerManagementAction::Untrust),
+            "disconnect" => Ok(PeerManagementAction::Disconnect),
+            "ban" => Ok(PeerManagementAction::Ban),
+            "unban" => Ok(PeerManagementAction::Unban),
+            _ => Err("peer management action cannot be parsed.".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for PeerManagementAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PeerManagementAction::Trust => write!(f, "trust"),
+            PeerManagementAction::Untrust => write!(f, "untrust"),
+            PeerManagementAction::Disconnect => write!(f, "disconnect"),
+            PeerManagementAction::Ban => write!(f, "ban"),
+            PeerManagementAction::Unban => write!(f, "unban"),
+        }
+    }
+}
+
+/// The body of a `POST lighthouse/peers/dial` request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerDialRequest {
+    /// Either a multiaddr or a base64 encoded ENR (beginning with `enr:`).
+    pub address: String,
+}
+
+/// The strategy used to choose attestations by `GET lighthouse/validator/blocks/{slot}`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockPackingStrategy {
+    /// Greedily include the attestations which pay the greatest proposer reward.
+    MaxReward,
+    /// Include attestations from the most recent slots first.
+    FreshFirst,
+    /// Search for the packing with the greatest proposer reward for up to `time_budget_ms`.
+    TimeBudget,
+}
+
+impl fmt::Display for BlockPackingStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockPackingStrategy::MaxReward => write!(f, "max_reward"),
+            BlockPackingStrategy::FreshFirst => write!(f, "fresh_first"),
+            BlockPackingStrategy::TimeBudget => write!(f, "time_budget"),
+        }
+    }
+}
+
+/// The query parameters of `GET lighthouse/validator/blocks/{slot}`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LighthouseValidatorBlocksQuery {
+    pub randao_reveal: SignatureBytes,
+    pub graffiti: Option<Graffiti>,
+    pub packing_strategy: Option<BlockPackingStrategy>,
+    /// Only used by the `time_budget` strategy.
+    pub time_budget_ms: Option<u64>,
+}
+
 /// The results of validators voting during an epoch.
 ///
 /// Provides information about the current and previous epochs.
@@ -236,6 +325,20 @@ impl BeaconNodeHttpClient {
         self.get(path).await
     }
 
+    /// `GET lighthouse/subnets`
+    pub async fn get_lighthouse_subnets(
+        &self,
+    ) -> Result<GenericResponse<AttestationSubnetsSummary>, Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("subnets");
+
+        self.get(path).await
+    }
+
     /*
      * Note:
      *
@@ -244,6 +347,37 @@ impl BeaconNodeHttpClient {
      * fairly simply achieved, if desired.
      */
 
+    /// `POST lighthouse/peers/{peer_id}/{action}`
+    pub async fn post_lighthouse_peers_action(
+        &self,
+        peer_id: &str,
+        action: PeerManagementAction,
+    ) -> Result<(), Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("peers")
+            .push(peer_id)
+            .push(&action.to_string());
+
+        self.post(path, &()).await
+    }
+
+    /// `POST lighthouse/peers/dial`
+    pub async fn post_lighthouse_peers_dial(&self, address: String) -> Result<(), Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("peers")
+            .push("dial");
+
+        self.post(path, &PeerDialRequest { address }).await
+    }
+
     /// `GET lighthouse/proto_array`
     pub async fn get_lighthouse_proto_array(&self) -> Result<GenericResponse<ProtoArray>, Error> {
         let mut path = self.server.clone();
@@ -336,6 +470,45 @@ impl BeaconNodeHttpClient {
         self.get(path).await
     }
 
+    /// `GET lighthouse/validator/blocks/{slot}`
+    pub async fn get_lighthouse_validator_blocks<T: EthSpec>(
+        &self,
+        slot: Slot,
+        randao_reveal: SignatureBytes,
+        graffiti: Option<&Graffiti>,
+        packing_strategy: Option<BlockPackingStrategy>,
+        time_budget_ms: Option<u64>,
+    ) -> Result<GenericResponse<BeaconBlock<T>>, Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("validator")
+            .push("blocks")
+            .push(&slot.to_string());
+
+        path.query_pairs_mut()
+            .append_pair("randao_reveal", &randao_reveal.to_string());
+
+        if let Some(graffiti) = graffiti {
+            path.query_pairs_mut()
+                .append_pair("graffiti", &graffiti.to_string());
+        }
+
+        if let Some(packing_strategy) = packing_strategy {
+            path.query_pairs_mut()
+                .append_pair("packing_strategy", &packing_strategy.to_string());
+        }
+
+        if let Some(time_budget_ms) = time_budget_ms {
+            path.query_pairs_mut()
+                .append_pair("time_budget_ms", &time_budget_ms.to_string());
+        }
+
+        self.get(path).await
+    }
+
     /// `GET lighthouse/beacon/states/{state_id}/ssz`
     pub async fn get_lighthouse_beacon_states_ssz<E: EthSpec>(
         &self,
@@ -185,6 +185,24 @@ impl ValidatorClientHttpClient {
         Ok(())
     }
 
+    /// Perform a HTTP DELETE request with a JSON body.
+    async fn delete_with_body<T: Serialize, U: IntoUrl, V: DeserializeOwned>(
+        &self,
+        url: U,
+        body: &T,
+    ) -> Result<V, Error> {
+        let response = self
+            .client
+            .delete(url)
+            .headers(self.headers()?)
+            .json(body)
+            .send()
+            .await
+            .map_err(Error::Reqwest)?;
+        let response = ok_or_error(response).await?;
+        self.signed_json(response).await
+    }
+
     /// `GET lighthouse/version`
     pub async fn get_lighthouse_version(&self) -> Result<GenericResponse<VersionData>, Error> {
         let mut path = self.server.clone();
@@ -314,6 +332,87 @@ impl ValidatorClientHttpClient {
 
         self.patch(path, &ValidatorPatchRequest { enabled }).await
     }
+
+    /// `GET lighthouse/slashing_protection`
+    ///
+    /// Returns the EIP-3076 slashing protection interchange of the validators in `pubkeys`, or of
+    /// all validators if `pubkeys` is `None`. `T` is typically
+    /// `slashing_protection::interchange::Interchange`.
+    pub async fn get_lighthouse_slashing_protection<T: DeserializeOwned>(
+        &self,
+        pubkeys: Option<&[PublicKeyBytes]>,
+    ) -> Result<T, Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("slashing_protection");
+
+        if let Some(pubkeys) = pubkeys {
+            let pubkeys_string = pubkeys
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            path.query_pairs_mut()
+                .append_pair("pubkeys", &pubkeys_string);
+        }
+
+        self.get(path).await
+    }
+
+    /// `POST lighthouse/slashing_protection`
+    ///
+    /// Imports an EIP-3076 slashing protection interchange. `T` is typically
+    /// `slashing_protection::interchange::Interchange`.
+    pub async fn post_lighthouse_slashing_protection<T: Serialize>(
+        &self,
+        interchange: &T,
+    ) -> Result<(), Error> {
+        let mut path = self.server.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("slashing_protection");
+
+        self.post(path, interchange).await
+    }
+
+    fn make_keystores_url(&self) -> Result<Url, Error> {
+        let mut url = self.server.clone();
+        url.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("eth")
+            .push("v1")
+            .push("keystores");
+        Ok(url)
+    }
+
+    /// `GET eth/v1/keystores`
+    pub async fn get_keystores(&self) -> Result<GenericResponse<Vec<KeystoreData>>, Error> {
+        let url = self.make_keystores_url()?;
+        self.get(url).await
+    }
+
+    /// `POST eth/v1/keystores`
+    pub async fn post_keystores(
+        &self,
+        request: &ImportKeystoresRequest,
+    ) -> Result<GenericResponse<Vec<KeystoreStatus<ImportKeystoreStatus>>>, Error> {
+        let url = self.make_keystores_url()?;
+        self.post(url, &request).await
+    }
+
+    /// `DELETE eth/v1/keystores`
+    pub async fn delete_keystores(
+        &self,
+        request: &DeleteKeystoresRequest,
+    ) -> Result<DeleteKeystoresResponse, Error> {
+        let url = self.make_keystores_url()?;
+        self.delete_with_body(url, &request).await
+    }
 }
 
 /// Returns `Ok(response)` if the response is a `200 OK` response. Otherwise, creates an
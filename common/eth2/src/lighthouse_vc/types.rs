@@ -1,9 +1,9 @@
 use account_utils::ZeroizeString;
 use eth2_keystore::Keystore;
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, ser::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 
 pub use crate::lighthouse::Health;
-pub use crate::types::{GenericResponse, VersionData};
+pub use crate::types::{GenericResponse, QueryVec, VersionData};
 pub use types::*;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -56,3 +56,99 @@ pub struct KeystoreValidatorsPostRequest {
     pub enable: bool,
     pub keystore: Keystore,
 }
+
+#[derive(Clone, Deserialize)]
+pub struct SlashingProtectionQuery {
+    pub pubkeys: Option<QueryVec<PublicKey>>,
+}
+
+/// An EIP-2335 keystore which is (de)serialized as a JSON-encoded string, as required by the
+/// `eth/v1/keystores` endpoints.
+#[derive(Clone, PartialEq)]
+pub struct KeystoreJsonStr(pub Keystore);
+
+impl Serialize for KeystoreJsonStr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let json = self
+            .0
+            .to_json_string()
+            .map_err(|e| S::Error::custom(format!("{:?}", e)))?;
+        serializer.serialize_str(&json)
+    }
+}
+
+impl<'de> Deserialize<'de> for KeystoreJsonStr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = String::deserialize(deserializer)?;
+        Keystore::from_json_str(&json)
+            .map(Self)
+            .map_err(|e| D::Error::custom(format!("{:?}", e)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeystoreData {
+    pub validating_pubkey: PublicKeyBytes,
+    pub readonly: bool,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportKeystoresRequest {
+    pub keystores: Vec<KeystoreJsonStr>,
+    pub passwords: Vec<ZeroizeString>,
+    /// An EIP-3076 slashing protection interchange, encoded as a JSON string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slashing_protection: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportKeystoreStatus {
+    Imported,
+    Duplicate,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteKeystoreStatus {
+    Deleted,
+    NotActive,
+    NotFound,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeystoreStatus<T> {
+    pub status: T,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl<T> KeystoreStatus<T> {
+    pub fn ok(status: T) -> Self {
+        Self {
+            status,
+            message: None,
+        }
+    }
+
+    pub fn error(status: T, message: String) -> Self {
+        Self {
+            status,
+            message: Some(message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeleteKeystoresRequest {
+    pub pubkeys: Vec<PublicKeyBytes>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeleteKeystoresResponse {
+    pub data: Vec<KeystoreStatus<DeleteKeystoreStatus>>,
+    /// An EIP-3076 slashing protection interchange, encoded as a JSON string.
+    pub slashing_protection: String,
+}
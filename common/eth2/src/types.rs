@@ -171,6 +171,17 @@ impl<'a, T: Serialize> From<&'a T> for GenericResponseRef<'a, T> {
     }
 }
 
+/// A response to a request for validator duties.
+///
+/// The `dependent_root` is the root of the block on which the duties depend. If that block is
+/// re-orged out of the canonical chain then the duties may have changed and should be refreshed.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(bound = "T: Serialize + serde::de::DeserializeOwned")]
+pub struct DutiesResponse<T: Serialize + serde::de::DeserializeOwned> {
+    pub dependent_root: Hash256,
+    pub data: T,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct RootData {
     pub root: Hash256,
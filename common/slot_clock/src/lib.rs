@@ -53,6 +53,9 @@ pub trait SlotClock: Send + Sync + Sized {
     /// Returns the duration from now until `slot`.
     fn duration_to_slot(&self, slot: Slot) -> Option<Duration>;
 
+    /// Returns the duration between the UNIX epoch and the start of `slot`.
+    fn start_of(&self, slot: Slot) -> Option<Duration>;
+
     /// Returns the duration until the next slot.
     fn duration_to_next_slot(&self) -> Option<Duration>;
 
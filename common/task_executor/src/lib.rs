@@ -50,6 +50,20 @@ impl TaskExecutor {
         }
     }
 
+    /// Clones the task executor adding a service name, replacing the exit future with `exit`.
+    ///
+    /// Tasks spawned on the returned executor are shut down when `exit` fires, rather than when
+    /// the exit future of `self` fires. This allows a single service to be shut down without
+    /// affecting the others.
+    pub fn clone_with_name_and_exit(&self, service_name: String, exit: exit_future::Exit) -> Self {
+        TaskExecutor {
+            handle: self.handle.clone(),
+            exit,
+            signal_tx: self.signal_tx.clone(),
+            log: self.log.new(o!("service" => service_name)),
+        }
+    }
+
     /// Spawn a future on the tokio runtime wrapped in an `exit_future::Exit`. The task is canceled
     /// when the corresponding exit_future `Signal` is fired/dropped.
     ///
@@ -0,0 +1,340 @@
+//! Provides `StandardDepositDataJson`, the deposit data format produced by the
+//! `eth2.0-deposit-cli` and consumed by the Eth2 launchpad.
+//!
+//! The format is a JSON array of objects, one per validator, where all byte values are hex
+//! encoded *without* a `0x` prefix.
+
+use serde_derive::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tree_hash::TreeHash;
+use types::{
+    ChainSpec, DepositData, DepositMessage, Hash256, PublicKeyBytes, SignatureBytes, SignedRoot,
+};
+
+/// The prefix of files created by `write_standard_deposit_data_json`.
+pub const DEPOSIT_DATA_JSON_PREFIX: &str = "deposit_data-";
+
+#[derive(Debug)]
+pub enum Error {
+    UnableToCreateFile(io::Error),
+    UnableToWriteFile(serde_json::Error),
+    UnableToReadFile(io::Error),
+    UnableToParseFile(serde_json::Error),
+    SystemTimeBeforeUnixEpoch,
+}
+
+/// A single deposit, in the format used by the `eth2.0-deposit-cli`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(try_from = "HexDepositDataJson", into = "HexDepositDataJson")]
+pub struct StandardDepositDataJson {
+    pub pubkey: PublicKeyBytes,
+    pub withdrawal_credentials: Hash256,
+    pub amount: u64,
+    pub signature: SignatureBytes,
+    pub deposit_message_root: Hash256,
+    pub deposit_data_root: Hash256,
+    pub fork_version: [u8; 4],
+}
+
+impl StandardDepositDataJson {
+    /// Produce the JSON representation of a signed `deposit_data` for the network described by
+    /// `spec`.
+    pub fn new(deposit_data: &DepositData, spec: &ChainSpec) -> Self {
+        Self {
+            pubkey: deposit_data.pubkey.clone(),
+            withdrawal_credentials: deposit_data.withdrawal_credentials,
+            amount: deposit_data.amount,
+            signature: deposit_data.signature.clone(),
+            deposit_message_root: deposit_data.as_deposit_message().tree_hash_root(),
+            deposit_data_root: deposit_data.tree_hash_root(),
+            fork_version: spec.genesis_fork_version,
+        }
+    }
+
+    /// Returns the `DepositData` described by `self`.
+    pub fn deposit_data(&self) -> DepositData {
+        DepositData {
+            pubkey: self.pubkey.clone(),
+            withdrawal_credentials: self.withdrawal_credentials,
+            amount: self.amount,
+            signature: self.signature.clone(),
+        }
+    }
+
+    /// Checks that `self` is internally consistent and valid for the network described by `spec`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a description of the first failed check:
+    ///
+    /// - The `fork_version` is not the `genesis_fork_version` of `spec`.
+    /// - Either of the roots do not match those computed from the other fields.
+    /// - The signature is not a valid signature from `pubkey` across the deposit message.
+    pub fn verify(&self, spec: &ChainSpec) -> Result<(), String> {
+        if self.fork_version != spec.genesis_fork_version {
+            return Err(format!(
+                "Fork version 0x{} does not match the genesis fork version 0x{}",
+                hex::encode(self.fork_version),
+                hex::encode(spec.genesis_fork_version)
+            ));
+        }
+
+        let deposit_data = self.deposit_data();
+        let deposit_message = deposit_data.as_deposit_message();
+
+        let deposit_message_root = deposit_message.tree_hash_root();
+        if self.deposit_message_root != deposit_message_root {
+            return Err(format!(
+                "Deposit message root is invalid. Expected {:?}, but got {:?}",
+                deposit_message_root, self.deposit_message_root
+            ));
+        }
+
+        let deposit_data_root = deposit_data.tree_hash_root();
+        if self.deposit_data_root != deposit_data_root {
+            return Err(format!(
+                "Deposit data root is invalid. Expected {:?}, but got {:?}",
+                deposit_data_root, self.deposit_data_root
+            ));
+        }
+
+        if !verify_deposit_message_signature(&deposit_message, &self.signature, spec) {
+            return Err("Deposit signature is invalid".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `true` if `signature` is a valid signature from `deposit_message.pubkey` across
+/// `deposit_message`.
+fn verify_deposit_message_signature(
+    deposit_message: &DepositMessage,
+    signature: &SignatureBytes,
+    spec: &ChainSpec,
+) -> bool {
+    let pubkey = match deposit_message.pubkey.decompress() {
+        Ok(pubkey) => pubkey,
+        Err(_) => return false,
+    };
+    let signature = match signature.decompress() {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    let message = deposit_message.signing_root(spec.get_deposit_domain());
+
+    signature.verify(&pubkey, message)
+}
+
+/// The representation of `StandardDepositDataJson` on disk.
+#[derive(Clone, Serialize, Deserialize)]
+struct HexDepositDataJson {
+    pubkey: String,
+    withdrawal_credentials: String,
+    amount: u64,
+    signature: String,
+    deposit_message_root: String,
+    deposit_data_root: String,
+    fork_version: String,
+}
+
+impl From<StandardDepositDataJson> for HexDepositDataJson {
+    fn from(json: StandardDepositDataJson) -> Self {
+        Self {
+            pubkey: hex::encode(json.pubkey.as_serialized()),
+            withdrawal_credentials: hex::encode(json.withdrawal_credentials),
+            amount: json.amount,
+            signature: hex::encode(json.signature.serialize()),
+            deposit_message_root: hex::encode(json.deposit_message_root),
+            deposit_data_root: hex::encode(json.deposit_data_root),
+            fork_version: hex::encode(json.fork_version),
+        }
+    }
+}
+
+impl TryFrom<HexDepositDataJson> for StandardDepositDataJson {
+    type Error = String;
+
+    fn try_from(json: HexDepositDataJson) -> Result<Self, Self::Error> {
+        let fork_version_bytes = decode_hex(&json.fork_version, "fork_version")?;
+        if fork_version_bytes.len() != 4 {
+            return Err(format!(
+                "fork_version must be 4 bytes, not {}",
+                fork_version_bytes.len()
+            ));
+        }
+        let mut fork_version = [0; 4];
+        fork_version.copy_from_slice(&fork_version_bytes);
+
+        Ok(Self {
+            pubkey: PublicKeyBytes::deserialize(&decode_hex(&json.pubkey, "pubkey")?)
+                .map_err(|e| format!("Invalid pubkey: {:?}", e))?,
+            withdrawal_credentials: decode_hash256(
+                &json.withdrawal_credentials,
+                "withdrawal_credentials",
+            )?,
+            amount: json.amount,
+            signature: SignatureBytes::deserialize(&decode_hex(&json.signature, "signature")?)
+                .map_err(|e| format!("Invalid signature: {:?}", e))?,
+            deposit_message_root: decode_hash256(
+                &json.deposit_message_root,
+                "deposit_message_root",
+            )?,
+            deposit_data_root: decode_hash256(&json.deposit_data_root, "deposit_data_root")?,
+            fork_version,
+        })
+    }
+}
+
+/// Decode a hex string which may or may not have a `0x` prefix.
+fn decode_hex(hex: &str, field: &str) -> Result<Vec<u8>, String> {
+    let hex = hex.trim_start_matches("0x");
+    hex::decode(hex).map_err(|e| format!("Invalid hex for {}: {:?}", field, e))
+}
+
+fn decode_hash256(hex: &str, field: &str) -> Result<Hash256, String> {
+    let bytes = decode_hex(hex, field)?;
+    if bytes.len() == 32 {
+        Ok(Hash256::from_slice(&bytes))
+    } else {
+        Err(format!("{} must be 32 bytes, not {}", field, bytes.len()))
+    }
+}
+
+/// Writes `deposits` to a new `deposit_data-<unix timestamp>.json` file in `dir`, returning the
+/// path of the new file.
+///
+/// An existing file is never overwritten. If a file with the same timestamp already exists (e.g.,
+/// one written earlier in the same second), a `-<n>` suffix is added to the timestamp.
+pub fn write_standard_deposit_data_json<P: AsRef<Path>>(
+    dir: P,
+    deposits: &[StandardDepositDataJson],
+) -> Result<PathBuf, Error> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| Error::SystemTimeBeforeUnixEpoch)?
+        .as_secs();
+
+    let mut suffix = 0;
+    loop {
+        let file_name = if suffix == 0 {
+            format!("{}{}.json", DEPOSIT_DATA_JSON_PREFIX, timestamp)
+        } else {
+            format!("{}{}-{}.json", DEPOSIT_DATA_JSON_PREFIX, timestamp, suffix)
+        };
+        let path = dir.as_ref().join(file_name);
+
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => {
+                serde_json::to_writer(file, deposits).map_err(Error::UnableToWriteFile)?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => suffix += 1,
+            Err(e) => return Err(Error::UnableToCreateFile(e)),
+        }
+    }
+}
+
+/// Reads a list of deposits from a file in the `eth2.0-deposit-cli` format.
+pub fn read_standard_deposit_data_json<P: AsRef<Path>>(
+    path: P,
+) -> Result<Vec<StandardDepositDataJson>, Error> {
+    let file = File::open(path).map_err(Error::UnableToReadFile)?;
+    serde_json::from_reader(file).map_err(Error::UnableToParseFile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use types::{EthSpec, Keypair, MainnetEthSpec};
+
+    fn deposit_data(spec: &ChainSpec) -> DepositData {
+        let keypair = Keypair::random();
+        let mut deposit_data = DepositData {
+            pubkey: keypair.pk.into(),
+            withdrawal_credentials: Hash256::repeat_byte(42),
+            amount: spec.max_effective_balance,
+            signature: SignatureBytes::empty(),
+        };
+        deposit_data.signature = deposit_data.create_signature(&keypair.sk, spec);
+        deposit_data
+    }
+
+    #[test]
+    fn valid_deposit() {
+        let spec = MainnetEthSpec::default_spec();
+        let json = StandardDepositDataJson::new(&deposit_data(&spec), &spec);
+
+        assert_eq!(json.verify(&spec), Ok(()));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let spec = MainnetEthSpec::default_spec();
+        let json = StandardDepositDataJson::new(&deposit_data(&spec), &spec);
+
+        let string = serde_json::to_string(&vec![json.clone()]).unwrap();
+        assert!(!string.contains("0x"), "should not use 0x prefixes");
+
+        let decoded: Vec<StandardDepositDataJson> = serde_json::from_str(&string).unwrap();
+        assert_eq!(decoded, vec![json]);
+    }
+
+    #[test]
+    fn invalid_fork_version() {
+        let spec = MainnetEthSpec::default_spec();
+        let mut json = StandardDepositDataJson::new(&deposit_data(&spec), &spec);
+        json.fork_version = [1, 2, 3, 4];
+
+        assert!(json.verify(&spec).is_err());
+    }
+
+    #[test]
+    fn invalid_roots() {
+        let spec = MainnetEthSpec::default_spec();
+        let json = StandardDepositDataJson::new(&deposit_data(&spec), &spec);
+
+        let mut bad_message_root = json.clone();
+        bad_message_root.deposit_message_root = Hash256::zero();
+        assert!(bad_message_root.verify(&spec).is_err());
+
+        let mut bad_data_root = json;
+        bad_data_root.deposit_data_root = Hash256::zero();
+        assert!(bad_data_root.verify(&spec).is_err());
+    }
+
+    #[test]
+    fn invalid_signature() {
+        let spec = MainnetEthSpec::default_spec();
+        let mut json = StandardDepositDataJson::new(&deposit_data(&spec), &spec);
+        json.amount -= 1;
+        json.deposit_message_root = json.deposit_data().as_deposit_message().tree_hash_root();
+        json.deposit_data_root = json.deposit_data().tree_hash_root();
+
+        assert!(json.verify(&spec).is_err());
+    }
+
+    #[test]
+    fn write_does_not_overwrite() {
+        let spec = MainnetEthSpec::default_spec();
+        let dir = tempdir().unwrap();
+        let first = vec![StandardDepositDataJson::new(&deposit_data(&spec), &spec)];
+        let second = vec![StandardDepositDataJson::new(&deposit_data(&spec), &spec)];
+
+        // Both writes are very likely to happen within the same second.
+        let first_path = write_standard_deposit_data_json(dir.path(), &first).unwrap();
+        let second_path = write_standard_deposit_data_json(dir.path(), &second).unwrap();
+
+        assert_ne!(first_path, second_path);
+        assert_eq!(read_standard_deposit_data_json(first_path).unwrap(), first);
+        assert_eq!(
+            read_standard_deposit_data_json(second_path).unwrap(),
+            second
+        );
+    }
+}
@@ -3,11 +3,13 @@
 //! - `ValidatorDir`: manages a directory containing validator keypairs, deposit info and other
 //! things.
 //! - `Manager`: manages a directory that contains multiple `ValidatorDir`.
+//! - `StandardDepositDataJson`: the `deposit_data-*.json` format used by the Eth2 launchpad.
 //!
 //! This crate is intended to be used by the account manager to create validators and the validator
 //! client to load those validators.
 
 mod builder;
+mod deposit_data_json;
 pub mod insecure_keys;
 mod manager;
 mod validator_dir;
@@ -20,4 +22,8 @@ pub use builder::{
     Builder, Error as BuilderError, ETH1_DEPOSIT_DATA_FILE, VOTING_KEYSTORE_FILE,
     WITHDRAWAL_KEYSTORE_FILE,
 };
+pub use deposit_data_json::{
+    read_standard_deposit_data_json, write_standard_deposit_data_json,
+    Error as DepositDataJsonError, StandardDepositDataJson, DEPOSIT_DATA_JSON_PREFIX,
+};
 pub use manager::{Error as ManagerError, Manager};
@@ -1,9 +1,10 @@
 use std::marker::PhantomData;
+use std::time::Duration;
 
 use proto_array::{Block as ProtoBlock, ProtoArrayForkChoice};
 use ssz_derive::{Decode, Encode};
 use types::{
-    BeaconBlock, BeaconState, BeaconStateError, Checkpoint, Epoch, EthSpec, Hash256,
+    BeaconBlock, BeaconState, BeaconStateError, ChainSpec, Checkpoint, Epoch, EthSpec, Hash256,
     IndexedAttestation, RelativeEpoch, ShufflingId, Slot,
 };
 
@@ -15,6 +16,10 @@ use std::cmp::Ordering;
 /// https://github.com/ethereum/eth2.0-specs/blob/v0.12.1/specs/phase0/fork-choice.md#configuration
 pub const SAFE_SLOTS_TO_UPDATE_JUSTIFIED: u64 = 8;
 
+/// The number of intervals in each slot. A block that arrives within the first interval of its
+/// slot is considered timely and is eligible for the proposer score boost.
+pub const INTERVALS_PER_SLOT: u64 = 3;
+
 #[derive(Debug)]
 pub enum Error<T> {
     InvalidAttestation(InvalidAttestation),
@@ -210,6 +215,7 @@ fn dequeue_attestations(
 ///
 /// - Management of the justified state and caching of balances.
 /// - Queuing of attestations from the current slot.
+/// - Tracking of the timely block from the current slot, for the proposer score boost.
 pub struct ForkChoice<T, E> {
     /// Storage for `ForkChoice`, modelled off the spec `Store` object.
     fc_store: T,
@@ -217,6 +223,10 @@ pub struct ForkChoice<T, E> {
     proto_array: ProtoArrayForkChoice,
     /// Attestations that arrived at the current slot and must be queued for later processing.
     queued_attestations: Vec<QueuedAttestation>,
+    /// The root of a timely block from the current slot, or `Hash256::zero()` if there is none.
+    ///
+    /// This value is only relevant for the current slot, so it is not persisted.
+    proposer_boost_root: Hash256,
     _phantom: PhantomData<E>,
 }
 
@@ -267,6 +277,7 @@ where
             fc_store,
             proto_array,
             queued_attestations: vec![],
+            proposer_boost_root: Hash256::zero(),
             _phantom: PhantomData,
         })
     }
@@ -284,6 +295,7 @@ where
             fc_store,
             proto_array,
             queued_attestations,
+            proposer_boost_root: Hash256::zero(),
             _phantom: PhantomData,
         }
     }
@@ -335,22 +347,32 @@ where
 
     /// Run the fork choice rule to determine the head.
     ///
+    /// If `proposer_score_boost` is `Some`, a timely block from the current slot has its weight
+    /// boosted by that percentage of the committee weight. If it is `None`, only LMD votes are
+    /// considered.
+    ///
     /// ## Specification
     ///
     /// Is equivalent to:
     ///
     /// https://github.com/ethereum/eth2.0-specs/blob/v0.12.1/specs/phase0/fork-choice.md#get_head
-    pub fn get_head(&mut self, current_slot: Slot) -> Result<Hash256, Error<T::Error>> {
+    pub fn get_head(
+        &mut self,
+        current_slot: Slot,
+        proposer_score_boost: Option<u64>,
+    ) -> Result<Hash256, Error<T::Error>> {
         self.update_time(current_slot)?;
 
         let store = &mut self.fc_store;
 
         self.proto_array
-            .find_head(
+            .find_head::<E>(
                 store.justified_checkpoint().epoch,
                 store.justified_checkpoint().root,
                 store.finalized_checkpoint().epoch,
                 store.justified_balances(),
+                self.proposer_boost_root,
+                proposer_score_boost,
             )
             .map_err(Into::into)
     }
@@ -409,6 +431,7 @@ where
     /// Add `block` to the fork choice DAG.
     ///
     /// - `block_root` is the root of `block.
+    /// - `block_delay` is the time between the start of `block.slot` and the arrival of `block`.
     /// - The root of `state` matches `block.state_root`.
     ///
     /// ## Specification
@@ -429,7 +452,9 @@ where
         current_slot: Slot,
         block: &BeaconBlock<E>,
         block_root: Hash256,
+        block_delay: Duration,
         state: &BeaconState<E>,
+        spec: &ChainSpec,
     ) -> Result<(), Error<T::Error>> {
         let current_slot = self.update_time(current_slot)?;
 
@@ -536,6 +561,14 @@ where
             .on_verified_block(block, block_root, state)
             .map_err(Error::AfterBlockFailed)?;
 
+        // Blocks from the current slot which arrive within the first interval of the slot are
+        // eligible for the proposer score boost.
+        let attesting_interval_start =
+            Duration::from_millis(spec.milliseconds_per_slot / INTERVALS_PER_SLOT);
+        if block.slot == current_slot && block_delay < attesting_interval_start {
+            self.proposer_boost_root = block_root;
+        }
+
         // This does not apply a vote to the block, it just makes fork choice aware of the block so
         // it can still be identified as the head even if it doesn't have any votes.
         self.proto_array.process_block(ProtoBlock {
@@ -724,7 +757,10 @@ where
             let previous_slot = self.fc_store.get_current_slot();
             // Note: we are relying upon `on_tick` to update `fc_store.time` to ensure we don't
             // get stuck in a loop.
-            on_tick(&mut self.fc_store, previous_slot + 1)?
+            on_tick(&mut self.fc_store, previous_slot + 1)?;
+
+            // The proposer boost only applies to blocks from the current slot.
+            self.proposer_boost_root = Hash256::zero();
         }
 
         // Process any attestations that might now be eligible.
@@ -799,6 +835,15 @@ where
         &self.fc_store
     }
 
+    /// Returns the root of the block which currently receives the proposer score boost, if any.
+    pub fn proposer_boost_root(&self) -> Option<Hash256> {
+        if self.proposer_boost_root == Hash256::zero() {
+            None
+        } else {
+            Some(self.proposer_boost_root)
+        }
+    }
+
     /// Returns a reference to the currently queued attestations.
     pub fn queued_attestations(&self) -> &[QueuedAttestation] {
         &self.queued_attestations
@@ -826,6 +871,7 @@ where
             fc_store,
             proto_array,
             queued_attestations: persisted.queued_attestations,
+            proposer_boost_root: Hash256::zero(),
             _phantom: PhantomData,
         })
     }
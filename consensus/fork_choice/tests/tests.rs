@@ -11,6 +11,7 @@ use fork_choice::{
 };
 use std::fmt;
 use std::sync::Mutex;
+use std::time::Duration;
 use store::{MemoryStore, StoreConfig};
 use types::{
     test_utils::{generate_deterministic_keypair, generate_deterministic_keypairs},
@@ -246,7 +247,22 @@ impl ForkChoiceTest {
     /// Applies a block directly to fork choice, bypassing the beacon chain.
     ///
     /// Asserts the block was applied successfully.
-    pub fn apply_block_directly_to_fork_choice<F>(self, mut func: F) -> Self
+    pub fn apply_block_directly_to_fork_choice<F>(self, func: F) -> Self
+    where
+        F: FnMut(&mut BeaconBlock<E>, &mut BeaconState<E>),
+    {
+        self.apply_block_directly_to_fork_choice_with_delay(Duration::from_secs(0), func)
+    }
+
+    /// Applies a block directly to fork choice, bypassing the beacon chain, as if it arrived
+    /// `block_delay` after the start of its slot.
+    ///
+    /// Asserts the block was applied successfully.
+    pub fn apply_block_directly_to_fork_choice_with_delay<F>(
+        self,
+        block_delay: Duration,
+        mut func: F,
+    ) -> Self
     where
         F: FnMut(&mut BeaconBlock<E>, &mut BeaconState<E>),
     {
@@ -266,7 +282,14 @@ impl ForkChoiceTest {
             .chain
             .fork_choice
             .write()
-            .on_block(current_slot, &block.message, block.canonical_root(), &state)
+            .on_block(
+                current_slot,
+                &block.message,
+                block.canonical_root(),
+                block_delay,
+                &state,
+                &self.harness.spec,
+            )
             .unwrap();
         self
     }
@@ -300,13 +323,35 @@ impl ForkChoiceTest {
             .chain
             .fork_choice
             .write()
-            .on_block(current_slot, &block.message, block.canonical_root(), &state)
+            .on_block(
+                current_slot,
+                &block.message,
+                block.canonical_root(),
+                Duration::from_secs(0),
+                &state,
+                &self.harness.spec,
+            )
             .err()
             .expect("on_block did not return an error");
         comparison_func(err);
         self
     }
 
+    /// Assert that a block is (or is not) receiving the proposer score boost, after updating the
+    /// fork choice time to the current slot.
+    pub fn assert_proposer_boost(self, expect_boost: bool) -> Self {
+        let current_slot = self.harness.get_current_slot();
+        let mut fork_choice = self.harness.chain.fork_choice.write();
+        fork_choice.update_time(current_slot).unwrap();
+        assert_eq!(
+            fork_choice.proposer_boost_root().is_some(),
+            expect_boost,
+            "proposer_boost_root"
+        );
+        drop(fork_choice);
+        self
+    }
+
     /// Compares the justified balances in the `ForkChoiceStore` verses a direct lookup from the
     /// database.
     fn check_justified_balances(&self) {
@@ -963,6 +1008,37 @@ fn valid_attestation_skip_across_epoch() {
         );
 }
 
+/// A block which arrives early in the current slot receives the proposer boost.
+#[test]
+fn proposer_boost_timely_block() {
+    ForkChoiceTest::new()
+        .apply_blocks(2)
+        .apply_block_directly_to_fork_choice_with_delay(Duration::from_secs(0), |_, _| {})
+        .assert_proposer_boost(true);
+}
+
+/// A block which arrives late in the current slot does not receive the proposer boost.
+#[test]
+fn proposer_boost_late_block() {
+    let slot_duration = Duration::from_millis(E::default_spec().milliseconds_per_slot);
+
+    ForkChoiceTest::new()
+        .apply_blocks(2)
+        .apply_block_directly_to_fork_choice_with_delay(slot_duration / 2, |_, _| {})
+        .assert_proposer_boost(false);
+}
+
+/// The proposer boost is removed once the slot of the boosted block has passed.
+#[test]
+fn proposer_boost_removed_after_slot() {
+    ForkChoiceTest::new()
+        .apply_blocks(2)
+        .apply_block_directly_to_fork_choice(|_, _| {})
+        .assert_proposer_boost(true)
+        .skip_slot()
+        .assert_proposer_boost(false);
+}
+
 #[test]
 fn can_read_finalized_block() {
     ForkChoiceTest::new()
@@ -981,6 +1057,7 @@ fn weak_subjectivity_fail_on_startup() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(Checkpoint { epoch, root }),
         import_max_skip_slots: None,
+        proposer_score_boost: None,
     };
 
     ForkChoiceTest::new_with_chain_config(chain_config);
@@ -994,6 +1071,7 @@ fn weak_subjectivity_pass_on_startup() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(Checkpoint { epoch, root }),
         import_max_skip_slots: None,
+        proposer_score_boost: None,
     };
 
     ForkChoiceTest::new_with_chain_config(chain_config)
@@ -1019,6 +1097,7 @@ fn weak_subjectivity_check_passes() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(checkpoint),
         import_max_skip_slots: None,
+        proposer_score_boost: None,
     };
 
     ForkChoiceTest::new_with_chain_config(chain_config.clone())
@@ -1049,6 +1128,7 @@ fn weak_subjectivity_check_fails_early_epoch() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(checkpoint),
         import_max_skip_slots: None,
+        proposer_score_boost: None,
     };
 
     ForkChoiceTest::new_with_chain_config(chain_config.clone())
@@ -1078,6 +1158,7 @@ fn weak_subjectivity_check_fails_late_epoch() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(checkpoint),
         import_max_skip_slots: None,
+        proposer_score_boost: None,
     };
 
     ForkChoiceTest::new_with_chain_config(chain_config.clone())
@@ -1107,6 +1188,7 @@ fn weak_subjectivity_check_fails_incorrect_root() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(checkpoint),
         import_max_skip_slots: None,
+        proposer_score_boost: None,
     };
 
     ForkChoiceTest::new_with_chain_config(chain_config.clone())
@@ -1143,6 +1225,7 @@ fn weak_subjectivity_check_epoch_boundary_is_skip_slot() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(checkpoint),
         import_max_skip_slots: None,
+        proposer_score_boost: None,
     };
 
     // recreate the chain exactly
@@ -1184,6 +1267,7 @@ fn weak_subjectivity_check_epoch_boundary_is_skip_slot_failure() {
     let chain_config = ChainConfig {
         weak_subjectivity_checkpoint: Some(checkpoint),
         import_max_skip_slots: None,
+        proposer_score_boost: None,
     };
 
     // recreate the chain exactly
@@ -1,13 +1,15 @@
 mod ffg_updates;
 mod no_votes;
+mod proposer_boost;
 mod votes;
 
 use crate::proto_array_fork_choice::{Block, ProtoArrayForkChoice};
 use serde_derive::{Deserialize, Serialize};
-use types::{Epoch, Hash256, ShufflingId, Slot};
+use types::{Epoch, Hash256, MainnetEthSpec, ShufflingId, Slot};
 
 pub use ffg_updates::*;
 pub use no_votes::*;
+pub use proposer_boost::*;
 pub use votes::*;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +27,15 @@ pub enum Operation {
         finalized_epoch: Epoch,
         justified_state_balances: Vec<u64>,
     },
+    ProposerBoostFindHead {
+        justified_epoch: Epoch,
+        justified_root: Hash256,
+        finalized_epoch: Epoch,
+        justified_state_balances: Vec<u64>,
+        proposer_boost_root: Hash256,
+        proposer_score_boost: u64,
+        expected_head: Hash256,
+    },
     ProcessBlock {
         slot: Slot,
         root: Hash256,
@@ -77,11 +88,13 @@ impl ForkChoiceTestDefinition {
                     expected_head,
                 } => {
                     let head = fork_choice
-                        .find_head(
+                        .find_head::<MainnetEthSpec>(
                             justified_epoch,
                             justified_root,
                             finalized_epoch,
                             &justified_state_balances,
+                            Hash256::zero(),
+                            None,
                         )
                         .unwrap_or_else(|_| {
                             panic!("find_head op at index {} returned error", op_index)
@@ -100,11 +113,13 @@ impl ForkChoiceTestDefinition {
                     finalized_epoch,
                     justified_state_balances,
                 } => {
-                    let result = fork_choice.find_head(
+                    let result = fork_choice.find_head::<MainnetEthSpec>(
                         justified_epoch,
                         justified_root,
                         finalized_epoch,
                         &justified_state_balances,
+                        Hash256::zero(),
+                        None,
                     );
 
                     assert!(
@@ -115,6 +130,38 @@ impl ForkChoiceTestDefinition {
                     );
                     check_bytes_round_trip(&fork_choice);
                 }
+                Operation::ProposerBoostFindHead {
+                    justified_epoch,
+                    justified_root,
+                    finalized_epoch,
+                    justified_state_balances,
+                    proposer_boost_root,
+                    proposer_score_boost,
+                    expected_head,
+                } => {
+                    let head = fork_choice
+                        .find_head::<MainnetEthSpec>(
+                            justified_epoch,
+                            justified_root,
+                            finalized_epoch,
+                            &justified_state_balances,
+                            proposer_boost_root,
+                            Some(proposer_score_boost),
+                        )
+                        .unwrap_or_else(|_| {
+                            panic!(
+                                "proposer_boost_find_head op at index {} returned error",
+                                op_index
+                            )
+                        });
+
+                    assert_eq!(
+                        head, expected_head,
+                        "Operation at index {} failed checks. Operation: {:?}",
+                        op_index, op
+                    );
+                    check_bytes_round_trip(&fork_choice);
+                }
                 Operation::ProcessBlock {
                     slot,
                     root,
@@ -0,0 +1,172 @@
+use super::*;
+
+pub fn get_proposer_boost_test_definition() -> ForkChoiceTestDefinition {
+    // With 32 slots per epoch, the committee weight of these balances is 10.
+    let balances = vec![10; 32];
+
+    let operations = vec![
+        // Add blocks 1 and 2
+        //
+        //         0
+        //        / \
+        //        1  2
+        Operation::ProcessBlock {
+            slot: Slot::new(1),
+            root: get_hash(1),
+            parent_root: get_hash(0),
+            justified_epoch: Epoch::new(1),
+            finalized_epoch: Epoch::new(1),
+        },
+        Operation::ProcessBlock {
+            slot: Slot::new(1),
+            root: get_hash(2),
+            parent_root: get_hash(0),
+            justified_epoch: Epoch::new(1),
+            finalized_epoch: Epoch::new(1),
+        },
+        // Ensure the head is 2, via the tie-breaker.
+        //
+        //         0
+        //        / \
+        //        1  2 <- head
+        Operation::FindHead {
+            justified_epoch: Epoch::new(1),
+            justified_root: get_hash(0),
+            finalized_epoch: Epoch::new(1),
+            justified_state_balances: balances.clone(),
+            expected_head: get_hash(2),
+        },
+        // Boost block 1 by 40% of the committee weight (4), with no votes.
+        //
+        //         0
+        //        / \
+        // head-> 1  2
+        Operation::ProposerBoostFindHead {
+            justified_epoch: Epoch::new(1),
+            justified_root: get_hash(0),
+            finalized_epoch: Epoch::new(1),
+            justified_state_balances: balances.clone(),
+            proposer_boost_root: get_hash(1),
+            proposer_score_boost: 40,
+            expected_head: get_hash(1),
+        },
+        // Ensure the boost is not retained once it is no longer supplied.
+        //
+        //         0
+        //        / \
+        //        1  2 <- head
+        Operation::FindHead {
+            justified_epoch: Epoch::new(1),
+            justified_root: get_hash(0),
+            finalized_epoch: Epoch::new(1),
+            justified_state_balances: balances.clone(),
+            expected_head: get_hash(2),
+        },
+        // Add a vote for block 2 (weight 10).
+        //
+        //         0
+        //        / \
+        //        1  2
+        //           ^
+        //           +1 vote
+        Operation::ProcessAttestation {
+            validator_index: 0,
+            block_root: get_hash(2),
+            target_epoch: Epoch::new(2),
+        },
+        // Ensure that a 40% boost (4) on block 1 does not outweigh the vote for block 2.
+        //
+        //         0
+        //        / \
+        //        1  2 <- head
+        Operation::ProposerBoostFindHead {
+            justified_epoch: Epoch::new(1),
+            justified_root: get_hash(0),
+            finalized_epoch: Epoch::new(1),
+            justified_state_balances: balances.clone(),
+            proposer_boost_root: get_hash(1),
+            proposer_score_boost: 40,
+            expected_head: get_hash(2),
+        },
+        // Add block 3 on top of 1.
+        //
+        //         0
+        //        / \
+        //        1  2
+        //        |
+        //        3
+        Operation::ProcessBlock {
+            slot: Slot::new(2),
+            root: get_hash(3),
+            parent_root: get_hash(1),
+            justified_epoch: Epoch::new(1),
+            finalized_epoch: Epoch::new(1),
+        },
+        // Ensure that a 200% boost (20) on block 3 outweighs the vote for block 2, since the
+        // boost is applied to the ancestors of block 3.
+        //
+        //         0
+        //        / \
+        //        1  2
+        //        |
+        //        3 <- head
+        Operation::ProposerBoostFindHead {
+            justified_epoch: Epoch::new(1),
+            justified_root: get_hash(0),
+            finalized_epoch: Epoch::new(1),
+            justified_state_balances: balances.clone(),
+            proposer_boost_root: get_hash(3),
+            proposer_score_boost: 200,
+            expected_head: get_hash(3),
+        },
+        // Ensure that an unknown boost root is ignored.
+        //
+        //         0
+        //        / \
+        //        1  2 <- head
+        //        |
+        //        3
+        Operation::ProposerBoostFindHead {
+            justified_epoch: Epoch::new(1),
+            justified_root: get_hash(0),
+            finalized_epoch: Epoch::new(1),
+            justified_state_balances: balances.clone(),
+            proposer_boost_root: get_hash(4),
+            proposer_score_boost: 200,
+            expected_head: get_hash(2),
+        },
+        // Ensure the head is 2 once the boost is removed.
+        //
+        //         0
+        //        / \
+        //        1  2 <- head
+        //        |
+        //        3
+        Operation::FindHead {
+            justified_epoch: Epoch::new(1),
+            justified_root: get_hash(0),
+            finalized_epoch: Epoch::new(1),
+            justified_state_balances: balances,
+            expected_head: get_hash(2),
+        },
+    ];
+
+    ForkChoiceTestDefinition {
+        finalized_block_slot: Slot::new(0),
+        justified_epoch: Epoch::new(1),
+        finalized_epoch: Epoch::new(1),
+        finalized_root: get_hash(0),
+        operations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let test = get_proposer_boost_test_definition();
+        test.run();
+    }
+}
@@ -4,7 +4,7 @@ use crate::ssz_container::SszContainer;
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
 use std::collections::HashMap;
-use types::{Epoch, Hash256, ShufflingId, Slot};
+use types::{Epoch, EthSpec, Hash256, ShufflingId, Slot};
 
 pub const DEFAULT_PRUNE_THRESHOLD: usize = 256;
 
@@ -134,12 +134,20 @@ impl ProtoArrayForkChoice {
             .map_err(|e| format!("process_block_error: {:?}", e))
     }
 
-    pub fn find_head(
+    /// Run the fork choice rule to determine the head.
+    ///
+    /// If `proposer_score_boost` is `Some` and `proposer_boost_root` is a known, non-zero block
+    /// root, that block receives additional weight equal to `proposer_score_boost` percent of the
+    /// average committee weight. The boost only applies to this call; it is removed from the tree
+    /// before returning and therefore never persisted.
+    pub fn find_head<E: EthSpec>(
         &mut self,
         justified_epoch: Epoch,
         justified_root: Hash256,
         finalized_epoch: Epoch,
         justified_state_balances: &[u64],
+        proposer_boost_root: Hash256,
+        proposer_score_boost: Option<u64>,
     ) -> Result<Hash256, String> {
         let old_balances = &mut self.balances;
 
@@ -159,9 +167,58 @@ impl ProtoArrayForkChoice {
 
         *old_balances = new_balances.to_vec();
 
-        self.proto_array
+        let proposer_boost = match proposer_score_boost {
+            Some(percentage) if proposer_boost_root != Hash256::zero() => {
+                match self.proto_array.indices.get(&proposer_boost_root).copied() {
+                    Some(index) => {
+                        let score =
+                            calculate_proposer_boost::<E>(justified_state_balances, percentage)
+                                .map_err(|e| {
+                                    format!("find_head calculate_proposer_boost failed: {:?}", e)
+                                })?;
+                        Some((index, score))
+                    }
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+
+        if let Some((index, score)) = proposer_boost {
+            self.apply_proposer_boost(index, score as i64, justified_epoch, finalized_epoch)?;
+        }
+
+        let head = self
+            .proto_array
             .find_head(&justified_root)
-            .map_err(|e| format!("find_head failed: {:?}", e))
+            .map_err(|e| format!("find_head failed: {:?}", e));
+
+        // Always remove the boost, even if `find_head` failed, so the weights in the tree only
+        // ever reflect attestations.
+        if let Some((index, score)) = proposer_boost {
+            self.apply_proposer_boost(index, -(score as i64), justified_epoch, finalized_epoch)?;
+        }
+
+        head
+    }
+
+    /// Adds `score` to the weight of the node at `index` (and all its ancestors), updating the
+    /// best-child and best-descendant links accordingly.
+    fn apply_proposer_boost(
+        &mut self,
+        index: usize,
+        score: i64,
+        justified_epoch: Epoch,
+        finalized_epoch: Epoch,
+    ) -> Result<(), String> {
+        let mut deltas = vec![0_i64; self.proto_array.indices.len()];
+        *deltas
+            .get_mut(index)
+            .ok_or_else(|| format!("find_head invalid proposer boost index: {}", index))? = score;
+
+        self.proto_array
+            .apply_score_changes(deltas, justified_epoch, finalized_epoch)
+            .map_err(|e| format!("find_head apply_proposer_boost failed: {:?}", e))
     }
 
     pub fn maybe_prune(&mut self, finalized_root: Hash256) -> Result<(), String> {
@@ -260,6 +317,25 @@ impl ProtoArrayForkChoice {
     }
 }
 
+/// Returns the weight that should be added to a timely block, expressed as `percentage` percent of
+/// the average weight of a single slot's committees.
+///
+/// ## Errors
+///
+/// - If the sum of `balances` overflows a `u64`.
+fn calculate_proposer_boost<E: EthSpec>(balances: &[u64], percentage: u64) -> Result<u64, Error> {
+    let total_balance = balances
+        .iter()
+        .try_fold(0_u64, |total, balance| total.checked_add(*balance))
+        .ok_or(Error::ProposerBoostOverflow)?;
+    let committee_weight = total_balance / E::slots_per_epoch();
+
+    committee_weight
+        .checked_mul(percentage)
+        .map(|boost| boost / 100)
+        .ok_or(Error::ProposerBoostOverflow)
+}
+
 /// Returns a list of `deltas`, where there is one delta for each of the indices in
 /// `0..indices.len()`.
 ///
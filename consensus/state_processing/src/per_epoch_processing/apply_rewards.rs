@@ -8,8 +8,8 @@ use types::*;
 /// Use to track the changes to a validators balance.
 #[derive(Default, Clone)]
 pub struct Delta {
-    rewards: u64,
-    penalties: u64,
+    pub rewards: u64,
+    pub penalties: u64,
 }
 
 impl Delta {
@@ -32,6 +32,32 @@ impl Delta {
     }
 }
 
+/// Combination of several deltas for different components of an attestation reward.
+///
+/// The components are flattened into a single `Delta` when applied to the state, and are kept
+/// separate so that they can be checked individually by the EF rewards tests.
+#[derive(Default, Clone)]
+pub struct AttestationDelta {
+    pub source_delta: Delta,
+    pub target_delta: Delta,
+    pub head_delta: Delta,
+    pub inclusion_delay_delta: Delta,
+    pub inactivity_penalty_delta: Delta,
+}
+
+impl AttestationDelta {
+    /// Flatten into a single delta.
+    pub fn flatten(self) -> Result<Delta, Error> {
+        let mut result = Delta::default();
+        result.combine(self.source_delta)?;
+        result.combine(self.target_delta)?;
+        result.combine(self.head_delta)?;
+        result.combine(self.inclusion_delay_delta)?;
+        result.combine(self.inactivity_penalty_delta)?;
+        Ok(result)
+    }
+}
+
 /// Apply attester and proposer rewards.
 ///
 /// Spec v0.12.1
@@ -55,7 +81,8 @@ pub fn process_rewards_and_penalties<T: EthSpec>(
 
     // Apply the deltas, erroring on overflow above but not on overflow below (saturating at 0
     // instead).
-    for (i, delta) in deltas.iter().enumerate() {
+    for (i, delta) in deltas.into_iter().enumerate() {
+        let delta = delta.flatten()?;
         state.balances[i] = state.balances[i].safe_add(delta.rewards)?;
         state.balances[i] = state.balances[i].saturating_sub(delta.penalties);
     }
@@ -65,18 +92,21 @@ pub fn process_rewards_and_penalties<T: EthSpec>(
 
 /// Apply rewards for participation in attestations during the previous epoch.
 ///
+/// The deltas are returned per-component (source, target, head, etc.) so that they may be
+/// compared individually against the EF rewards tests.
+///
 /// Spec v0.12.1
-fn get_attestation_deltas<T: EthSpec>(
+pub fn get_attestation_deltas<T: EthSpec>(
     state: &BeaconState<T>,
     validator_statuses: &ValidatorStatuses,
     spec: &ChainSpec,
-) -> Result<Vec<Delta>, Error> {
+) -> Result<Vec<AttestationDelta>, Error> {
     let finality_delay = state
         .previous_epoch()
         .safe_sub(state.finalized_checkpoint.epoch)?
         .as_u64();
 
-    let mut deltas = vec![Delta::default(); state.validators.len()];
+    let mut deltas = vec![AttestationDelta::default(); state.validators.len()];
 
     let total_balances = &validator_statuses.total_balances;
 
@@ -102,18 +132,23 @@ fn get_attestation_deltas<T: EthSpec>(
         let inactivity_penalty_delta =
             get_inactivity_penalty_delta(validator, base_reward, finality_delay, spec)?;
 
-        deltas[index].combine(source_delta)?;
-        deltas[index].combine(target_delta)?;
-        deltas[index].combine(head_delta)?;
-        deltas[index].combine(inclusion_delay_delta)?;
-        deltas[index].combine(inactivity_penalty_delta)?;
+        let delta = deltas
+            .get_mut(index)
+            .ok_or(Error::ValidatorStatusesInconsistent)?;
+        delta.source_delta.combine(source_delta)?;
+        delta.target_delta.combine(target_delta)?;
+        delta.head_delta.combine(head_delta)?;
+        delta.inclusion_delay_delta.combine(inclusion_delay_delta)?;
+        delta
+            .inactivity_penalty_delta
+            .combine(inactivity_penalty_delta)?;
 
         if let Some((proposer_index, proposer_delta)) = proposer_delta {
-            if proposer_index >= deltas.len() {
-                return Err(Error::ValidatorStatusesInconsistent);
-            }
-
-            deltas[proposer_index].combine(proposer_delta)?;
+            deltas
+                .get_mut(proposer_index)
+                .ok_or(Error::ValidatorStatusesInconsistent)?
+                .inclusion_delay_delta
+                .combine(proposer_delta)?;
         }
     }
 
@@ -42,7 +42,11 @@ pub fn per_slot_processing<T: EthSpec>(
     Ok(summary)
 }
 
-fn cache_state<T: EthSpec>(
+/// Stores the root of `state` (and of its latest block) in its historical roots, the first step of
+/// `per_slot_processing`.
+///
+/// Exposed so that tools may time per-slot processing separately from per-epoch processing.
+pub fn cache_state<T: EthSpec>(
     state: &mut BeaconState<T>,
     state_root: Option<Hash256>,
 ) -> Result<(), Error> {
@@ -36,6 +36,13 @@ impl<T: EthSpec> Attestation<T> {
             .is_zero()
     }
 
+    /// Is every signer of this attestation also a signer of `other`?
+    pub fn signers_subset_of(&self, other: &Self) -> bool {
+        self.aggregation_bits
+            .difference(&other.aggregation_bits)
+            .is_zero()
+    }
+
     /// Aggregate another Attestation into this one.
     ///
     /// The aggregation bitfields must be disjoint, and the data must be the same.
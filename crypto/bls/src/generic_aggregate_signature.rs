@@ -115,6 +115,17 @@ where
         self.point.as_ref()
     }
 
+    /// Instantiates `Self` from a `point`, which is `None` for the "empty" value.
+    pub(crate) fn from_point(point: Option<AggSig>, is_infinity: bool) -> Self {
+        Self {
+            point,
+            is_infinity,
+            _phantom_pub: PhantomData,
+            _phantom_agg_pub: PhantomData,
+            _phantom_sig: PhantomData,
+        }
+    }
+
     /// Aggregates a signature onto `self`.
     pub fn add_assign(&mut self, other: &GenericSignature<Pub, Sig>) {
         if let Some(other_point) = other.point() {
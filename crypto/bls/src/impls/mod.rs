@@ -1,3 +1,4 @@
 pub mod blst;
 pub mod fake_crypto;
 pub mod milagro;
+pub mod runtime;
@@ -0,0 +1,462 @@
+//! A backend which defers to either `milagro` or `blst`, as selected at runtime with
+//! `crate::select_backend`.
+//!
+//! The selection is fixed the first time a point is created (defaulting to `blst` if no backend
+//! was selected), so all points in a process are from the same library. Operations on points
+//! from different libraries are therefore unreachable; they are treated as invalid.
+
+use crate::{
+    generic_aggregate_public_key::TAggregatePublicKey,
+    generic_aggregate_signature::{GenericAggregateSignature, TAggregateSignature},
+    generic_public_key::{GenericPublicKey, TPublicKey, PUBLIC_KEY_BYTES_LEN},
+    generic_secret_key::TSecretKey,
+    generic_signature::{TSignature, SIGNATURE_BYTES_LEN},
+    generic_signature_set::GenericSignatureSet,
+    impls::blst::{self as blst_impl, blst_core, BlstAggregatePublicKey, BlstAggregateSignature},
+    impls::milagro::{self as milagro_impl, milagro as milagro_core},
+    Backend, Error, Hash256, ZeroizeHash,
+};
+use std::borrow::Cow;
+use std::iter::ExactSizeIterator;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+const UNSELECTED: u8 = 0;
+const MILAGRO: u8 = 1;
+const SUPRANATIONAL: u8 = 2;
+
+/// The backend in use by this process, one of the constants above.
+static SELECTED_BACKEND: AtomicU8 = AtomicU8::new(UNSELECTED);
+
+/// Provides the externally-facing, core BLS types.
+pub mod types {
+    pub use super::verify_signature_sets;
+    pub use super::AggregatePublicKey;
+    pub use super::AggregateSignature;
+    pub use super::PublicKey;
+    pub use super::SecretKey;
+    pub use super::Signature;
+    pub use super::SignatureSet;
+}
+
+/// Selects the library which will perform all BLS operations in this process.
+///
+/// Returns an error if `backend` cannot be selected at runtime, or if a different backend is
+/// already in use.
+pub fn select_backend(backend: Backend) -> Result<(), String> {
+    let selection = match backend {
+        Backend::Milagro => MILAGRO,
+        Backend::Supranational => SUPRANATIONAL,
+        Backend::FakeCrypto => {
+            return Err("The fake_crypto BLS backend cannot be selected at runtime".to_string())
+        }
+    };
+
+    match SELECTED_BACKEND.compare_exchange(
+        UNSELECTED,
+        selection,
+        Ordering::AcqRel,
+        Ordering::Acquire,
+    ) {
+        Ok(_) => Ok(()),
+        Err(current) if current == selection => Ok(()),
+        Err(_) => Err(format!(
+            "Unable to select the {} BLS backend, {} is already in use",
+            backend,
+            selected_backend()
+        )),
+    }
+}
+
+/// Returns the backend in use by this process, fixing it to `blst` if none has been selected.
+pub fn selected_backend() -> Backend {
+    if SELECTED_BACKEND.load(Ordering::Acquire) == MILAGRO {
+        Backend::Milagro
+    } else if select_backend(Backend::Supranational).is_ok() {
+        Backend::Supranational
+    } else {
+        // Another thread selected milagro after the load above.
+        Backend::Milagro
+    }
+}
+
+pub type SignatureSet<'a> =
+    GenericSignatureSet<'a, PublicKey, AggregatePublicKey, Signature, AggregateSignature>;
+
+pub fn verify_signature_sets<'a>(
+    signature_sets: impl ExactSizeIterator<Item = &'a SignatureSet<'a>>,
+) -> bool {
+    match selected_backend() {
+        Backend::Milagro => {
+            let sets = signature_sets
+                .map(|set| {
+                    convert_signature_set(
+                        set,
+                        PublicKey::as_milagro,
+                        AggregateSignature::as_milagro,
+                    )
+                })
+                .collect::<Option<Vec<MilagroSignatureSet>>>();
+            sets.map_or(false, |sets| {
+                let sets = sets
+                    .iter()
+                    .map(|(signature, signing_keys, message)| {
+                        milagro_impl::SignatureSet::multiple_pubkeys(
+                            signature,
+                            signing_keys.iter().map(Cow::Borrowed).collect(),
+                            *message,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                milagro_impl::verify_signature_sets(sets.iter())
+            })
+        }
+        _ => {
+            let sets = signature_sets
+                .map(|set| {
+                    convert_signature_set(set, PublicKey::as_blst, AggregateSignature::as_blst)
+                })
+                .collect::<Option<Vec<BlstSignatureSet>>>();
+            sets.map_or(false, |sets| {
+                let sets = sets
+                    .iter()
+                    .map(|(signature, signing_keys, message)| {
+                        blst_impl::SignatureSet::multiple_pubkeys(
+                            signature,
+                            signing_keys.iter().map(Cow::Borrowed).collect(),
+                            *message,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                blst_impl::verify_signature_sets(sets.iter())
+            })
+        }
+    }
+}
+
+/// The signature, signing keys and message of a `SignatureSet`, converted to the points of a
+/// single library.
+type ConvertedSignatureSet<Pub, AggPub, Sig, AggSig> = (
+    GenericAggregateSignature<Pub, AggPub, Sig, AggSig>,
+    Vec<GenericPublicKey<Pub>>,
+    Hash256,
+);
+
+type MilagroSignatureSet = ConvertedSignatureSet<
+    milagro_core::PublicKey,
+    milagro_core::AggregatePublicKey,
+    milagro_core::Signature,
+    milagro_core::AggregateSignature,
+>;
+
+type BlstSignatureSet = ConvertedSignatureSet<
+    blst_core::PublicKey,
+    BlstAggregatePublicKey,
+    blst_core::Signature,
+    BlstAggregateSignature,
+>;
+
+/// Converts `set` to the points of a single library, returning `None` if any point is from another
+/// library.
+fn convert_signature_set<Pub, AggPub, Sig, AggSig>(
+    set: &SignatureSet,
+    pubkey: impl Fn(&PublicKey) -> Option<&Pub>,
+    signature: impl Fn(&AggregateSignature) -> Option<&AggSig>,
+) -> Option<ConvertedSignatureSet<Pub, AggPub, Sig, AggSig>>
+where
+    Pub: TPublicKey,
+    Sig: TSignature<Pub>,
+    AggSig: TAggregateSignature<Pub, AggPub, Sig>,
+{
+    let point = match set.signature.point() {
+        Some(point) => Some(signature(point)?.clone()),
+        None => None,
+    };
+    let signing_keys = convert_pubkeys(set.signing_keys.iter().map(|pk| pk.as_ref()), pubkey)?;
+
+    Some((
+        GenericAggregateSignature::from_point(point, set.signature.is_infinity),
+        signing_keys,
+        set.message,
+    ))
+}
+
+/// Converts `pubkeys` to the points of a single library, returning `None` if any point is from
+/// another library.
+fn convert_pubkeys<'a, Pub: TPublicKey>(
+    pubkeys: impl Iterator<Item = &'a GenericPublicKey<PublicKey>>,
+    pubkey: impl Fn(&PublicKey) -> Option<&Pub>,
+) -> Option<Vec<GenericPublicKey<Pub>>> {
+    pubkeys
+        .map(|pk| {
+            pubkey(pk.point())
+                .cloned()
+                .map(GenericPublicKey::from_point)
+        })
+        .collect()
+}
+
+#[derive(Clone, PartialEq)]
+pub enum PublicKey {
+    Milagro(milagro_core::PublicKey),
+    Supranational(blst_core::PublicKey),
+}
+
+impl PublicKey {
+    fn as_milagro(&self) -> Option<&milagro_core::PublicKey> {
+        match self {
+            PublicKey::Milagro(point) => Some(point),
+            PublicKey::Supranational(_) => None,
+        }
+    }
+
+    fn as_blst(&self) -> Option<&blst_core::PublicKey> {
+        match self {
+            PublicKey::Supranational(point) => Some(point),
+            PublicKey::Milagro(_) => None,
+        }
+    }
+}
+
+impl TPublicKey for PublicKey {
+    fn serialize(&self) -> [u8; PUBLIC_KEY_BYTES_LEN] {
+        match self {
+            PublicKey::Milagro(point) => TPublicKey::serialize(point),
+            PublicKey::Supranational(point) => TPublicKey::serialize(point),
+        }
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        match selected_backend() {
+            Backend::Milagro => {
+                <milagro_core::PublicKey as TPublicKey>::deserialize(bytes).map(PublicKey::Milagro)
+            }
+            _ => <blst_core::PublicKey as TPublicKey>::deserialize(bytes)
+                .map(PublicKey::Supranational),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum AggregatePublicKey {
+    Milagro(milagro_core::AggregatePublicKey),
+    Supranational(BlstAggregatePublicKey),
+}
+
+impl TAggregatePublicKey for AggregatePublicKey {}
+
+#[derive(Clone, PartialEq)]
+pub enum Signature {
+    Milagro(milagro_core::Signature),
+    Supranational(blst_core::Signature),
+}
+
+impl TSignature<PublicKey> for Signature {
+    fn serialize(&self) -> [u8; SIGNATURE_BYTES_LEN] {
+        match self {
+            Signature::Milagro(point) => TSignature::serialize(point),
+            Signature::Supranational(point) => TSignature::serialize(point),
+        }
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        match selected_backend() {
+            Backend::Milagro => <milagro_core::Signature as TSignature<_>>::deserialize(bytes)
+                .map(Signature::Milagro),
+            _ => <blst_core::Signature as TSignature<_>>::deserialize(bytes)
+                .map(Signature::Supranational),
+        }
+    }
+
+    fn verify(&self, pubkey: &PublicKey, msg: Hash256) -> bool {
+        match (self, pubkey) {
+            (Signature::Milagro(point), PublicKey::Milagro(pubkey)) => {
+                TSignature::verify(point, pubkey, msg)
+            }
+            (Signature::Supranational(point), PublicKey::Supranational(pubkey)) => {
+                TSignature::verify(point, pubkey, msg)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum AggregateSignature {
+    Milagro(milagro_core::AggregateSignature),
+    Supranational(BlstAggregateSignature),
+}
+
+impl AggregateSignature {
+    fn as_milagro(&self) -> Option<&milagro_core::AggregateSignature> {
+        match self {
+            AggregateSignature::Milagro(point) => Some(point),
+            AggregateSignature::Supranational(_) => None,
+        }
+    }
+
+    fn as_blst(&self) -> Option<&BlstAggregateSignature> {
+        match self {
+            AggregateSignature::Supranational(point) => Some(point),
+            AggregateSignature::Milagro(_) => None,
+        }
+    }
+}
+
+impl TAggregateSignature<PublicKey, AggregatePublicKey, Signature> for AggregateSignature {
+    fn infinity() -> Self {
+        match selected_backend() {
+            Backend::Milagro => AggregateSignature::Milagro(
+                <milagro_core::AggregateSignature as TAggregateSignature<_, _, _>>::infinity(),
+            ),
+            _ => {
+                AggregateSignature::Supranational(<BlstAggregateSignature as TAggregateSignature<
+                    _,
+                    _,
+                    _,
+                >>::infinity())
+            }
+        }
+    }
+
+    fn add_assign(&mut self, other: &Signature) {
+        match (self, other) {
+            (AggregateSignature::Milagro(point), Signature::Milagro(other)) => {
+                TAggregateSignature::add_assign(point, other)
+            }
+            (AggregateSignature::Supranational(point), Signature::Supranational(other)) => {
+                TAggregateSignature::add_assign(point, other)
+            }
+            _ => (),
+        }
+    }
+
+    fn add_assign_aggregate(&mut self, other: &Self) {
+        match (self, other) {
+            (AggregateSignature::Milagro(point), AggregateSignature::Milagro(other)) => {
+                TAggregateSignature::add_assign_aggregate(point, other)
+            }
+            (
+                AggregateSignature::Supranational(point),
+                AggregateSignature::Supranational(other),
+            ) => TAggregateSignature::add_assign_aggregate(point, other),
+            _ => (),
+        }
+    }
+
+    fn serialize(&self) -> [u8; SIGNATURE_BYTES_LEN] {
+        match self {
+            AggregateSignature::Milagro(point) => TAggregateSignature::serialize(point),
+            AggregateSignature::Supranational(point) => TAggregateSignature::serialize(point),
+        }
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        match selected_backend() {
+            Backend::Milagro => {
+                <milagro_core::AggregateSignature as TAggregateSignature<_, _, _>>::deserialize(
+                    bytes,
+                )
+                .map(AggregateSignature::Milagro)
+            }
+            _ => <BlstAggregateSignature as TAggregateSignature<_, _, _>>::deserialize(bytes)
+                .map(AggregateSignature::Supranational),
+        }
+    }
+
+    fn fast_aggregate_verify(
+        &self,
+        msg: Hash256,
+        pubkeys: &[&GenericPublicKey<PublicKey>],
+    ) -> bool {
+        match self {
+            AggregateSignature::Milagro(point) => convert_pubkeys(
+                pubkeys.iter().copied(),
+                PublicKey::as_milagro,
+            )
+            .map_or(false, |pubkeys| {
+                let pubkeys = pubkeys.iter().collect::<Vec<_>>();
+                TAggregateSignature::fast_aggregate_verify(point, msg, &pubkeys)
+            }),
+            AggregateSignature::Supranational(point) => convert_pubkeys(
+                pubkeys.iter().copied(),
+                PublicKey::as_blst,
+            )
+            .map_or(false, |pubkeys| {
+                let pubkeys = pubkeys.iter().collect::<Vec<_>>();
+                TAggregateSignature::fast_aggregate_verify(point, msg, &pubkeys)
+            }),
+        }
+    }
+
+    fn aggregate_verify(&self, msgs: &[Hash256], pubkeys: &[&GenericPublicKey<PublicKey>]) -> bool {
+        match self {
+            AggregateSignature::Milagro(point) => convert_pubkeys(
+                pubkeys.iter().copied(),
+                PublicKey::as_milagro,
+            )
+            .map_or(false, |pubkeys| {
+                let pubkeys = pubkeys.iter().collect::<Vec<_>>();
+                TAggregateSignature::aggregate_verify(point, msgs, &pubkeys)
+            }),
+            AggregateSignature::Supranational(point) => convert_pubkeys(
+                pubkeys.iter().copied(),
+                PublicKey::as_blst,
+            )
+            .map_or(false, |pubkeys| {
+                let pubkeys = pubkeys.iter().collect::<Vec<_>>();
+                TAggregateSignature::aggregate_verify(point, msgs, &pubkeys)
+            }),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum SecretKey {
+    Milagro(milagro_core::SecretKey),
+    Supranational(blst_core::SecretKey),
+}
+
+impl TSecretKey<Signature, PublicKey> for SecretKey {
+    fn random() -> Self {
+        match selected_backend() {
+            Backend::Milagro => {
+                SecretKey::Milagro(<milagro_core::SecretKey as TSecretKey<_, _>>::random())
+            }
+            _ => SecretKey::Supranational(<blst_core::SecretKey as TSecretKey<_, _>>::random()),
+        }
+    }
+
+    fn sign(&self, msg: Hash256) -> Signature {
+        match self {
+            SecretKey::Milagro(point) => Signature::Milagro(TSecretKey::sign(point, msg)),
+            SecretKey::Supranational(point) => {
+                Signature::Supranational(TSecretKey::sign(point, msg))
+            }
+        }
+    }
+
+    fn public_key(&self) -> PublicKey {
+        match self {
+            SecretKey::Milagro(point) => PublicKey::Milagro(TSecretKey::public_key(point)),
+            SecretKey::Supranational(point) => {
+                PublicKey::Supranational(TSecretKey::public_key(point))
+            }
+        }
+    }
+
+    fn serialize(&self) -> ZeroizeHash {
+        match self {
+            SecretKey::Milagro(point) => TSecretKey::serialize(point),
+            SecretKey::Supranational(point) => TSecretKey::serialize(point),
+        }
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        match selected_backend() {
+            Backend::Milagro => <milagro_core::SecretKey as TSecretKey<_, _>>::deserialize(bytes)
+                .map(SecretKey::Milagro),
+            _ => <blst_core::SecretKey as TSecretKey<_, _>>::deserialize(bytes)
+                .map(SecretKey::Supranational),
+        }
+    }
+}
@@ -13,6 +13,9 @@
 //! - `fake_crypto`: an always-returns-valid implementation that is only useful for testing
 //!     scenarios which intend to *ignore* real cryptography.
 //!
+//! Additionally, the `runtime_backend` feature exports a backend which defers to either
+//! `supranational` or `milagro`, as chosen at startup with `select_backend`.
+//!
 //! This crate uses traits to reduce code-duplication between the two implementations. For example,
 //! the `GenericPublicKey` struct exported from this crate is generic across the `TPublicKey` trait
 //! (i.e., `PublicKey<TPublicKey>`). `TPublicKey` is implemented by all three backends (see the
@@ -46,6 +49,123 @@ use milagro_bls::AmclError;
 
 pub type Hash256 = ethereum_types::H256;
 
+/// Identifies one of the BLS backends supported by this crate.
+///
+/// The types exported at the root of this crate (e.g., `PublicKey`) are those of the backend
+/// selected at compile-time, unless the `runtime_backend` feature is enabled, in which case the
+/// backend is chosen at startup with `select_backend` (see `active_backend`). Additionally, the
+/// `milagro` and `supranational` backends are always compiled in and can be accessed directly via
+/// the `milagro_implementations` and `blst_implementations` modules (e.g., for benchmarking).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    Milagro,
+    Supranational,
+    FakeCrypto,
+}
+
+impl Backend {
+    /// Returns all backends which are available in this build.
+    pub fn available() -> Vec<Backend> {
+        let mut backends = vec![Backend::Supranational, Backend::Milagro];
+        if cfg!(feature = "fake_crypto") {
+            backends.push(Backend::FakeCrypto);
+        }
+        backends
+    }
+
+    /// Returns `true` if this backend is compiled into the current binary.
+    pub fn is_available(&self) -> bool {
+        match self {
+            Backend::Milagro | Backend::Supranational => true,
+            Backend::FakeCrypto => cfg!(feature = "fake_crypto"),
+        }
+    }
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Backend::Milagro => write!(f, "milagro"),
+            Backend::Supranational => write!(f, "blst"),
+            Backend::FakeCrypto => write!(f, "fake_crypto"),
+        }
+    }
+}
+
+impl std::str::FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "milagro" => Ok(Backend::Milagro),
+            "blst" | "supranational" => Ok(Backend::Supranational),
+            "fake_crypto" => Ok(Backend::FakeCrypto),
+            other => Err(format!("Unknown BLS backend: {}", other)),
+        }
+    }
+}
+
+/// The backend selected by the features this crate was compiled with.
+#[cfg(feature = "fake_crypto")]
+const COMPILED_BACKEND: Backend = Backend::FakeCrypto;
+#[cfg(all(feature = "milagro", not(feature = "fake_crypto")))]
+const COMPILED_BACKEND: Backend = Backend::Milagro;
+#[cfg(not(any(feature = "milagro", feature = "fake_crypto")))]
+const COMPILED_BACKEND: Backend = Backend::Supranational;
+
+/// Returns the backend which provides the types exported at the root of this crate.
+#[cfg(all(
+    feature = "runtime_backend",
+    not(feature = "fake_crypto"),
+    not(feature = "milagro")
+))]
+pub fn active_backend() -> Backend {
+    impls::runtime::selected_backend()
+}
+
+/// Returns the backend which provides the types exported at the root of this crate.
+#[cfg(not(all(
+    feature = "runtime_backend",
+    not(feature = "fake_crypto"),
+    not(feature = "milagro")
+)))]
+pub fn active_backend() -> Backend {
+    COMPILED_BACKEND
+}
+
+/// Selects `backend` to provide the types exported at the root of this crate.
+///
+/// This must be called before any BLS points are created, since the backend is fixed once it is in
+/// use.
+#[cfg(all(
+    feature = "runtime_backend",
+    not(feature = "fake_crypto"),
+    not(feature = "milagro")
+))]
+pub fn select_backend(backend: Backend) -> Result<(), String> {
+    impls::runtime::select_backend(backend)
+}
+
+/// Selects `backend` to provide the types exported at the root of this crate.
+///
+/// Without the `runtime_backend` feature, only the backend chosen at compile-time may be selected.
+#[cfg(not(all(
+    feature = "runtime_backend",
+    not(feature = "fake_crypto"),
+    not(feature = "milagro")
+)))]
+pub fn select_backend(backend: Backend) -> Result<(), String> {
+    if backend == COMPILED_BACKEND {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unable to select the {} BLS backend, this binary was compiled to use {} and without \
+             the runtime_backend feature",
+            backend, COMPILED_BACKEND
+        ))
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Error {
     /// An error was raised from the Milagro BLS library.
@@ -124,6 +244,7 @@ macro_rules! define_mod {
 
 define_mod!(milagro_implementations, crate::impls::milagro::types);
 define_mod!(blst_implementations, crate::impls::blst::types);
+define_mod!(runtime_implementations, crate::impls::runtime::types);
 #[cfg(feature = "fake_crypto")]
 define_mod!(
     fake_crypto_implementations,
@@ -134,10 +255,18 @@ define_mod!(
 pub use milagro_implementations::*;
 
 #[cfg(all(
-    feature = "supranational",
+    feature = "runtime_backend",
     not(feature = "fake_crypto"),
     not(feature = "milagro")
 ))]
+pub use runtime_implementations::*;
+
+#[cfg(all(
+    feature = "supranational",
+    not(feature = "fake_crypto"),
+    not(feature = "milagro"),
+    not(feature = "runtime_backend")
+))]
 pub use blst_implementations::*;
 
 #[cfg(feature = "fake_crypto")]
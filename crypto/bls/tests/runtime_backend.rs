@@ -0,0 +1,57 @@
+//! The runtime backend is selected once per process, so these tests have their own test binary in
+//! which the `milagro` backend is selected before any points are created.
+
+use bls::impls::runtime::{select_backend, selected_backend};
+use bls::runtime_implementations::{
+    verify_signature_sets, AggregateSignature, SecretKey, SignatureSet,
+};
+use bls::{Backend, Hash256};
+use std::borrow::Cow;
+
+#[test]
+fn milagro_selected_at_runtime() {
+    assert!(select_backend(Backend::FakeCrypto).is_err());
+
+    select_backend(Backend::Milagro).unwrap();
+    // The backend may be selected again, but it may not be changed.
+    select_backend(Backend::Milagro).unwrap();
+    assert!(select_backend(Backend::Supranational).is_err());
+    assert_eq!(selected_backend(), Backend::Milagro);
+
+    let message = Hash256::from_low_u64_be(42);
+    let secret_keys = (0..4).map(|_| SecretKey::random()).collect::<Vec<_>>();
+    let public_keys = secret_keys
+        .iter()
+        .map(|sk| sk.public_key())
+        .collect::<Vec<_>>();
+    let signatures = secret_keys
+        .iter()
+        .map(|sk| sk.sign(message))
+        .collect::<Vec<_>>();
+
+    assert!(signatures[0].verify(&public_keys[0], message));
+    assert!(!signatures[0].verify(&public_keys[1], message));
+
+    let mut aggregate = AggregateSignature::infinity();
+    for signature in &signatures {
+        aggregate.add_assign(signature);
+    }
+    let public_key_refs = public_keys.iter().collect::<Vec<_>>();
+    assert!(aggregate.fast_aggregate_verify(message, &public_key_refs));
+
+    let mut sets = signatures
+        .iter()
+        .zip(public_keys.iter())
+        .map(|(signature, pubkey)| {
+            SignatureSet::single_pubkey(signature, Cow::Borrowed(pubkey), message)
+        })
+        .collect::<Vec<_>>();
+    assert!(verify_signature_sets(sets.iter()));
+
+    sets.push(SignatureSet::single_pubkey(
+        &signatures[0],
+        Cow::Borrowed(&public_keys[1]),
+        message,
+    ));
+    assert!(!verify_signature_sets(sets.iter()));
+}
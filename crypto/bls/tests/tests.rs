@@ -502,3 +502,7 @@ mod blst {
 mod milagro {
     test_suite!(milagro_implementations);
 }
+
+mod runtime {
+    test_suite!(runtime_implementations);
+}
@@ -0,0 +1,247 @@
+use bls::Backend;
+use clap::ArgMatches;
+use clap_utils::parse_required;
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+
+/// Parameters shared across all backends for a single benchmark run.
+struct BenchConfig {
+    /// The number of signature sets verified in each batch.
+    sets: usize,
+    /// The number of public keys contributing to each aggregate signature.
+    keys_per_aggregate: usize,
+    /// The number of times each measurement is repeated.
+    iterations: u32,
+}
+
+/// The results of benchmarking a single backend with a fixed thread count.
+struct BenchReport {
+    backend: Backend,
+    threads: usize,
+    single: Duration,
+    batch: Duration,
+    fast_aggregate: Duration,
+    aggregate: Duration,
+}
+
+impl BenchReport {
+    fn print(&self, config: &BenchConfig) {
+        let per_set = |d: Duration| format!("{:?}", d / config.sets as u32);
+        println!(
+            "{:>12} {:>8} {:>14} {:>14} {:>14} {:>14}",
+            self.backend.to_string(),
+            self.threads,
+            per_set(self.single),
+            per_set(self.batch),
+            format!("{:?}", self.fast_aggregate),
+            format!("{:?}", self.aggregate),
+        );
+    }
+}
+
+/// Defines a function which benchmarks the backend found at `$module`.
+///
+/// A macro is used (rather than a generic function) since each backend is exposed as a module of
+/// concrete types by the `bls` crate.
+macro_rules! define_bench {
+    ($fn_name: ident, $backend: expr, $module: path) => {
+        fn $fn_name(config: &BenchConfig, threads: usize) -> Result<BenchReport, String> {
+            use $module as bls_variant;
+
+            use bls::Hash256;
+            use bls_variant::{
+                verify_signature_sets, AggregateSignature, PublicKey, SecretKey, Signature,
+                SignatureSet,
+            };
+
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .map_err(|e| format!("Unable to build thread pool: {:?}", e))?;
+
+            let secret_keys = (0..config.sets.max(config.keys_per_aggregate))
+                .map(|_| SecretKey::random())
+                .collect::<Vec<_>>();
+            let public_keys = secret_keys
+                .iter()
+                .map(|sk| sk.public_key())
+                .collect::<Vec<PublicKey>>();
+            let messages = (0..secret_keys.len())
+                .map(|i| Hash256::from_low_u64_be(i as u64))
+                .collect::<Vec<_>>();
+            let signatures = secret_keys
+                .iter()
+                .zip(messages.iter())
+                .map(|(sk, msg)| sk.sign(*msg))
+                .collect::<Vec<Signature>>();
+
+            let sets = (0..config.sets)
+                .map(|i| {
+                    SignatureSet::single_pubkey(
+                        &signatures[i],
+                        Cow::Borrowed(&public_keys[i]),
+                        messages[i],
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            // Individual verification of each set, spread across the thread pool.
+            let single = time(config.iterations, || {
+                pool.install(|| sets.par_iter().all(|set| set.clone().verify()))
+            })?;
+
+            // Batch verification, with one batch per thread.
+            let chunk_size = std::cmp::max(1, sets.len() / threads);
+            let batch = time(config.iterations, || {
+                pool.install(|| {
+                    sets.par_chunks(chunk_size)
+                        .all(|chunk| verify_signature_sets(chunk.iter()))
+                })
+            })?;
+
+            // Many keys signing the same message.
+            let common_message = Hash256::from_low_u64_be(u64::max_value());
+            let mut fast_aggregate_signature = AggregateSignature::infinity();
+            for sk in secret_keys.iter().take(config.keys_per_aggregate) {
+                fast_aggregate_signature.add_assign(&sk.sign(common_message));
+            }
+            let aggregate_keys = public_keys
+                .iter()
+                .take(config.keys_per_aggregate)
+                .collect::<Vec<_>>();
+            let fast_aggregate = time(config.iterations, || {
+                fast_aggregate_signature.fast_aggregate_verify(common_message, &aggregate_keys)
+            })?;
+
+            // Many keys signing distinct messages.
+            let mut aggregate_signature = AggregateSignature::infinity();
+            for signature in signatures.iter().take(config.keys_per_aggregate) {
+                aggregate_signature.add_assign(signature);
+            }
+            let aggregate = time(config.iterations, || {
+                aggregate_signature
+                    .aggregate_verify(&messages[0..config.keys_per_aggregate], &aggregate_keys)
+            })?;
+
+            Ok(BenchReport {
+                backend: $backend,
+                threads,
+                single,
+                batch,
+                fast_aggregate,
+                aggregate,
+            })
+        }
+    };
+}
+
+define_bench!(
+    bench_supranational,
+    Backend::Supranational,
+    bls::blst_implementations
+);
+define_bench!(
+    bench_milagro,
+    Backend::Milagro,
+    bls::milagro_implementations
+);
+#[cfg(feature = "fake_crypto")]
+define_bench!(
+    bench_fake_crypto,
+    Backend::FakeCrypto,
+    bls::fake_crypto_implementations
+);
+
+/// Runs `f` `iterations` times, returning the mean duration of each run.
+///
+/// Returns an error if `f` ever returns `false` (i.e., a signature failed to verify).
+fn time<F: FnMut() -> bool>(iterations: u32, mut f: F) -> Result<Duration, String> {
+    let iterations = std::cmp::max(1, iterations);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        if !f() {
+            return Err("Signature verification failed".to_string());
+        }
+    }
+    Ok(start.elapsed() / iterations)
+}
+
+fn run_backend(
+    backend: Backend,
+    config: &BenchConfig,
+    threads: usize,
+) -> Result<BenchReport, String> {
+    match backend {
+        Backend::Supranational => bench_supranational(config, threads),
+        Backend::Milagro => bench_milagro(config, threads),
+        #[cfg(feature = "fake_crypto")]
+        Backend::FakeCrypto => bench_fake_crypto(config, threads),
+        #[cfg(not(feature = "fake_crypto"))]
+        Backend::FakeCrypto => Err("lcli was not compiled with fake_crypto".to_string()),
+    }
+}
+
+/// Parses a comma-separated list of values (e.g., `1,2,4`).
+fn parse_list<T: std::str::FromStr>(matches: &ArgMatches, name: &str) -> Result<Vec<T>, String>
+where
+    T::Err: std::fmt::Debug,
+{
+    let string: String = parse_required(matches, name)?;
+    string
+        .split(',')
+        .map(|s| {
+            s.trim()
+                .parse()
+                .map_err(|e| format!("Unable to parse {} from {}: {:?}", name, s, e))
+        })
+        .collect()
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), String> {
+    let backends = match matches.value_of("backends") {
+        Some("all") | None => Backend::available(),
+        Some(_) => parse_list::<Backend>(matches, "backends")?,
+    };
+    let threads = parse_list::<usize>(matches, "threads")?;
+    let config = BenchConfig {
+        sets: parse_required(matches, "sets")?,
+        keys_per_aggregate: parse_required(matches, "keys-per-aggregate")?,
+        iterations: parse_required(matches, "iterations")?,
+    };
+
+    if config.sets == 0 || config.keys_per_aggregate == 0 {
+        return Err("--sets and --keys-per-aggregate must be greater than zero".to_string());
+    }
+
+    if let Some(backend) = backends.iter().find(|backend| !backend.is_available()) {
+        return Err(format!(
+            "The {} backend is not available in this build",
+            backend
+        ));
+    }
+
+    info!("Default backend for this build: {}", bls::active_backend());
+    info!(
+        "Benchmarking {} sets, {} keys per aggregate, {} iterations",
+        config.sets, config.keys_per_aggregate, config.iterations
+    );
+
+    println!(
+        "{:>12} {:>8} {:>14} {:>14} {:>14} {:>14}",
+        "backend", "threads", "single/set", "batch/set", "fast_agg", "agg"
+    );
+
+    for backend in backends {
+        for &num_threads in &threads {
+            if num_threads == 0 {
+                return Err("Thread count must be greater than zero".to_string());
+            }
+
+            run_backend(backend, &config, num_threads)?.print(&config);
+        }
+    }
+
+    Ok(())
+}
@@ -1,10 +1,19 @@
 use clap::ArgMatches;
-use clap_utils::{parse_required, parse_ssz_required};
+use clap_utils::{parse_optional, parse_required, parse_ssz_required};
 use deposit_contract::{decode_eth1_tx_data, DEPOSIT_DATA_LEN};
+use environment::Environment;
+use eth2_testnet_config::Eth2TestnetConfig;
+use std::path::PathBuf;
 use tree_hash::TreeHash;
-use types::EthSpec;
+use types::{ChainSpec, EthSpec};
+use validator_dir::read_standard_deposit_data_json;
+
+pub fn run<T: EthSpec>(mut env: Environment<T>, matches: &ArgMatches) -> Result<(), String> {
+    if let Some(path) = parse_optional::<PathBuf>(matches, "file")? {
+        let spec = spec_from_testnet_dir(&mut env, matches)?;
+        return check_deposit_data_json(path, &spec);
+    }
 
-pub fn run<T: EthSpec>(matches: &ArgMatches) -> Result<(), String> {
     let rlp_bytes = parse_ssz_required::<Vec<u8>>(matches, "deposit-data")?;
     let amount = parse_required(matches, "deposit-amount")?;
 
@@ -29,3 +38,49 @@ pub fn run<T: EthSpec>(matches: &ArgMatches) -> Result<(), String> {
 
     Ok(())
 }
+
+/// Verifies each of the deposits in a `deposit_data-*.json` file against `spec`.
+fn check_deposit_data_json(path: PathBuf, spec: &ChainSpec) -> Result<(), String> {
+    let deposits = read_standard_deposit_data_json(&path)
+        .map_err(|e| format!("Unable to read {:?}: {:?}", path, e))?;
+
+    if deposits.is_empty() {
+        return Err(format!("{:?} does not contain any deposits", path));
+    }
+
+    for (i, deposit) in deposits.iter().enumerate() {
+        deposit
+            .verify(spec)
+            .map_err(|e| format!("Deposit {} ({}) is invalid: {}", i, deposit.pubkey, e))?;
+    }
+
+    println!("{} valid deposit(s) in {:?}", deposits.len(), path);
+
+    Ok(())
+}
+
+/// Returns the spec of the testnet in `--testnet-dir`, if supplied. Otherwise, returns the spec
+/// of the environment.
+fn spec_from_testnet_dir<T: EthSpec>(
+    env: &mut Environment<T>,
+    matches: &ArgMatches,
+) -> Result<ChainSpec, String> {
+    let testnet_dir = match parse_optional::<PathBuf>(matches, "testnet-dir")? {
+        Some(dir) => dir,
+        None => return Ok(env.core_context().eth2_config.spec),
+    };
+
+    let eth2_testnet_config = Eth2TestnetConfig::load(testnet_dir)?;
+
+    eth2_testnet_config
+        .yaml_config
+        .as_ref()
+        .ok_or_else(|| "The testnet directory must contain a spec config".to_string())?
+        .apply_to_chain_spec::<T>(&env.core_context().eth2_config.spec)
+        .ok_or_else(|| {
+            format!(
+                "The loaded config is not compatible with the {} spec",
+                &env.core_context().eth2_config.eth_spec_id
+            )
+        })
+}
@@ -0,0 +1,434 @@
+//! Reports the field-level differences between two `BeaconState`s, to assist in debugging
+//! consensus splits.
+//!
+//! The states are either read from SSZ files or downloaded from the debug API of one or two beacon
+//! nodes.
+
+use crate::transition_blocks::load_from_ssz;
+use clap::ArgMatches;
+use compare_fields::{CompareFields, Comparison};
+use environment::Environment;
+use eth2::{types::StateId, BeaconNodeHttpClient, Url};
+use serde::Serialize;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use types::{BeaconState, ChainSpec, EthSpec, RelativeEpoch};
+
+/// `BeaconState` fields which are caches rather than part of the consensus state. The committee
+/// caches are compared separately, after they have been built for both states.
+const CACHE_FIELDS: &[&str] = &[
+    "committee_caches",
+    "pubkey_cache",
+    "exit_cache",
+    "tree_hash_cache",
+];
+
+/// `BeaconState` list fields which `CompareFields` compares as a whole. They are compared
+/// element-wise instead, so that only the differing indices are reported rather than the entire
+/// list.
+const ELEMENT_WISE_FIELDS: &[&str] = &[
+    "historical_roots",
+    "eth1_data_votes",
+    "randao_mixes",
+    "slashings",
+    "previous_epoch_attestations",
+    "current_epoch_attestations",
+];
+
+/// A difference in a field which is compared as a whole.
+#[derive(Debug, Serialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub a: String,
+    pub b: String,
+}
+
+/// A difference at a single index of a list field (e.g., the validator registry).
+///
+/// An index which is only present in one of the states is shown as `None` in the other.
+#[derive(Debug, Serialize)]
+pub struct IndexDiff {
+    pub index: String,
+    pub a: String,
+    pub b: String,
+}
+
+/// The differences within a list field, such as `validators` or `balances`.
+#[derive(Debug, Serialize)]
+pub struct ListDiff {
+    pub field: String,
+    pub diffs: Vec<IndexDiff>,
+}
+
+impl ListDiff {
+    /// Compares the lists `a` and `b` element-wise, returning `None` if they are equal.
+    fn new<T: Debug + PartialEq>(field: &str, a: &[T], b: &[T]) -> Option<Self> {
+        if a == b {
+            None
+        } else {
+            Self::from_comparison(Comparison::from_slice(field.to_string(), a, b))
+        }
+    }
+
+    /// Returns the differing children of a `Comparison::Parent`, or `None` if there are none.
+    fn from_comparison(comparison: Comparison) -> Option<Self> {
+        match comparison {
+            Comparison::Parent {
+                field_name,
+                equal: false,
+                children,
+            } => Some(ListDiff {
+                field: field_name,
+                diffs: children
+                    .into_iter()
+                    .filter(|fc| fc.not_equal())
+                    .map(|fc| IndexDiff {
+                        index: fc.field_name,
+                        a: fc.a,
+                        b: fc.b,
+                    })
+                    .collect(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// A difference in the committee cache of some epoch, relative to each state.
+#[derive(Debug, Serialize)]
+pub struct CommitteeCacheDiff {
+    pub relative_epoch: String,
+    pub committee_count_a: u64,
+    pub committee_count_b: u64,
+    /// The number of positions at which the shufflings differ, including positions only present
+    /// in one of the shufflings.
+    pub shuffling_diffs: usize,
+}
+
+/// All the differences between two states.
+#[derive(Debug, Serialize)]
+pub struct StateDiff {
+    pub tree_hash_root_a: String,
+    pub tree_hash_root_b: String,
+    pub fields: Vec<FieldDiff>,
+    pub lists: Vec<ListDiff>,
+    pub committee_caches: Vec<CommitteeCacheDiff>,
+}
+
+impl StateDiff {
+    /// Compares `a` and `b`, building their committee caches if they have not been built already.
+    pub fn new<T: EthSpec>(
+        a: &mut BeaconState<T>,
+        b: &mut BeaconState<T>,
+        spec: &ChainSpec,
+    ) -> Result<Self, String> {
+        let mut fields = vec![];
+        let mut lists = vec![];
+
+        for comparison in a.compare_fields(b) {
+            match comparison {
+                Comparison::Child(fc) => {
+                    let field = fc.field_name.as_str();
+                    if fc.not_equal()
+                        && !CACHE_FIELDS.contains(&field)
+                        && !ELEMENT_WISE_FIELDS.contains(&field)
+                    {
+                        fields.push(FieldDiff {
+                            field: fc.field_name,
+                            a: fc.a,
+                            b: fc.b,
+                        })
+                    }
+                }
+                parent => lists.extend(ListDiff::from_comparison(parent)),
+            }
+        }
+
+        lists.extend(
+            vec![
+                ListDiff::new(
+                    "historical_roots",
+                    &a.historical_roots[..],
+                    &b.historical_roots[..],
+                ),
+                ListDiff::new(
+                    "eth1_data_votes",
+                    &a.eth1_data_votes[..],
+                    &b.eth1_data_votes[..],
+                ),
+                ListDiff::new("randao_mixes", &a.randao_mixes[..], &b.randao_mixes[..]),
+                ListDiff::new("slashings", &a.slashings[..], &b.slashings[..]),
+                ListDiff::new(
+                    "previous_epoch_attestations",
+                    &a.previous_epoch_attestations[..],
+                    &b.previous_epoch_attestations[..],
+                ),
+                ListDiff::new(
+                    "current_epoch_attestations",
+                    &a.current_epoch_attestations[..],
+                    &b.current_epoch_attestations[..],
+                ),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+
+        let mut committee_caches = vec![];
+        for relative_epoch in &[
+            RelativeEpoch::Previous,
+            RelativeEpoch::Current,
+            RelativeEpoch::Next,
+        ] {
+            let relative_epoch = *relative_epoch;
+            for state in &mut [&mut *a, &mut *b] {
+                state
+                    .build_committee_cache(relative_epoch, spec)
+                    .map_err(|e| format!("Unable to build committee cache: {:?}", e))?;
+            }
+
+            let committee_count = |state: &BeaconState<T>| {
+                state
+                    .get_epoch_committee_count(relative_epoch)
+                    .map_err(|e| format!("Unable to get committee count: {:?}", e))
+            };
+            let shuffling = |state: &BeaconState<T>| -> Result<Vec<usize>, String> {
+                state
+                    .get_shuffling(relative_epoch)
+                    .map(|shuffling| shuffling.to_vec())
+                    .map_err(|e| format!("Unable to get shuffling: {:?}", e))
+            };
+
+            let (committee_count_a, committee_count_b) =
+                (committee_count(&*a)?, committee_count(&*b)?);
+            let (shuffling_a, shuffling_b) = (shuffling(&*a)?, shuffling(&*b)?);
+            let shuffling_diffs = (0..std::cmp::max(shuffling_a.len(), shuffling_b.len()))
+                .filter(|i| shuffling_a.get(*i) != shuffling_b.get(*i))
+                .count();
+
+            if committee_count_a != committee_count_b || shuffling_diffs > 0 {
+                committee_caches.push(CommitteeCacheDiff {
+                    relative_epoch: format!("{:?}", relative_epoch),
+                    committee_count_a,
+                    committee_count_b,
+                    shuffling_diffs,
+                })
+            }
+        }
+
+        Ok(Self {
+            tree_hash_root_a: format!("{:?}", a.canonical_root()),
+            tree_hash_root_b: format!("{:?}", b.canonical_root()),
+            fields,
+            lists,
+            committee_caches,
+        })
+    }
+
+    /// Returns `true` if no differences were found.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.lists.is_empty() && self.committee_caches.is_empty()
+    }
+
+    /// Returns a human-readable description of the differences.
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("state a: {}", self.tree_hash_root_a),
+            format!("state b: {}", self.tree_hash_root_b),
+        ];
+
+        if self.is_empty() {
+            lines.push("The states are identical".to_string());
+        }
+
+        for diff in &self.fields {
+            lines.push(format!("{}:", diff.field));
+            lines.push(format!("  a: {}", diff.a));
+            lines.push(format!("  b: {}", diff.b));
+        }
+
+        for list in &self.lists {
+            lines.push(format!(
+                "{} ({} differences):",
+                list.field,
+                list.diffs.len()
+            ));
+            for diff in &list.diffs {
+                lines.push(format!("  [{}]", diff.index));
+                lines.push(format!("    a: {}", diff.a));
+                lines.push(format!("    b: {}", diff.b));
+            }
+        }
+
+        for diff in &self.committee_caches {
+            lines.push(format!("committee cache ({} epoch):", diff.relative_epoch));
+            lines.push(format!(
+                "  committee count a: {}, b: {}",
+                diff.committee_count_a, diff.committee_count_b
+            ));
+            lines.push(format!("  shuffling differences: {}", diff.shuffling_diffs));
+        }
+
+        lines.join("\n")
+    }
+}
+
+pub fn run<T: EthSpec>(mut env: Environment<T>, matches: &ArgMatches) -> Result<(), String> {
+    let state_a = matches
+        .value_of("state-a")
+        .ok_or_else(|| "No state-a supplied".to_string())?;
+    let state_b = matches
+        .value_of("state-b")
+        .ok_or_else(|| "No state-b supplied".to_string())?;
+    let format = matches
+        .value_of("format")
+        .ok_or_else(|| "No format supplied".to_string())?;
+    let spec = env.eth2_config.spec.clone();
+
+    let (mut state_a, mut state_b) = if let Some(beacon_node) = matches.value_of("beacon-node") {
+        let client_a = client(beacon_node)?;
+        let client_b = matches
+            .value_of("other-beacon-node")
+            .map(client)
+            .transpose()?
+            .unwrap_or_else(|| client_a.clone());
+        let state_id_a = StateId::from_str(state_a)?;
+        let state_id_b = StateId::from_str(state_b)?;
+
+        env.runtime().block_on(async {
+            Ok::<_, String>((
+                download_state::<T>(&client_a, state_id_a).await?,
+                download_state::<T>(&client_b, state_id_b).await?,
+            ))
+        })?
+    } else {
+        let path = |s: &str| {
+            s.parse::<PathBuf>()
+                .map_err(|e| format!("Failed to parse state path: {}", e))
+        };
+        (
+            load_from_ssz::<BeaconState<T>>(path(state_a)?)?,
+            load_from_ssz::<BeaconState<T>>(path(state_b)?)?,
+        )
+    };
+
+    if state_a.slot != state_b.slot {
+        warn!(
+            "Comparing states at different slots: {} and {}",
+            state_a.slot, state_b.slot
+        );
+    }
+
+    let diff = StateDiff::new(&mut state_a, &mut state_b, &spec)?;
+
+    let output = match format {
+        "json" => serde_json::to_string_pretty(&diff)
+            .map_err(|e| format!("Unable to serialize diff: {:?}", e))?,
+        "text" => diff.to_text(),
+        other => return Err(format!("Unknown format: {}", other)),
+    };
+
+    if let Some(output_path) = matches.value_of("output") {
+        let mut file = File::create(output_path)
+            .map_err(|e| format!("Unable to create {}: {:?}", output_path, e))?;
+        file.write_all(output.as_bytes())
+            .map_err(|e| format!("Unable to write to {}: {:?}", output_path, e))?;
+    } else {
+        println!("{}", output);
+    }
+
+    Ok(())
+}
+
+fn client(url: &str) -> Result<BeaconNodeHttpClient, String> {
+    Ok(BeaconNodeHttpClient::new(Url::parse(url).map_err(|e| {
+        format!("Failed to parse beacon node URL: {:?}", e)
+    })?))
+}
+
+async fn download_state<T: EthSpec>(
+    client: &BeaconNodeHttpClient,
+    state_id: StateId,
+) -> Result<BeaconState<T>, String> {
+    client
+        .get_debug_beacon_states::<T>(state_id)
+        .await
+        .map_err(|e| format!("Failed to download state {}: {:?}", state_id, e))?
+        .map(|response| response.data)
+        .ok_or_else(|| format!("State {} not found", state_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{test_utils::TestingBeaconStateBuilder, Hash256, MinimalEthSpec};
+
+    type E = MinimalEthSpec;
+
+    fn states() -> (BeaconState<E>, BeaconState<E>, ChainSpec) {
+        let spec = E::default_spec();
+        let (state, _) = TestingBeaconStateBuilder::from_deterministic_keypairs(16, &spec).build();
+        (state.clone(), state, spec)
+    }
+
+    fn list<'a>(diff: &'a StateDiff, field: &str) -> &'a ListDiff {
+        diff.lists
+            .iter()
+            .find(|list| list.field == field)
+            .unwrap_or_else(|| panic!("{} should differ", field))
+    }
+
+    #[test]
+    fn identical_states() {
+        let (mut a, mut b, spec) = states();
+        let diff = StateDiff::new(&mut a, &mut b, &spec).unwrap();
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.tree_hash_root_a, diff.tree_hash_root_b);
+    }
+
+    #[test]
+    fn fields_and_registry() {
+        let (mut a, mut b, spec) = states();
+        b.eth1_deposit_index += 1;
+        b.balances[5] += 1;
+        let diff = StateDiff::new(&mut a, &mut b, &spec).unwrap();
+
+        assert_eq!(diff.fields.len(), 1);
+        assert_eq!(diff.fields[0].field, "eth1_deposit_index");
+
+        assert_eq!(diff.lists.len(), 1);
+        let balances = list(&diff, "balances");
+        assert_eq!(balances.diffs.len(), 1);
+        assert_eq!(balances.diffs[0].index, "5");
+        assert_ne!(diff.tree_hash_root_a, diff.tree_hash_root_b);
+    }
+
+    #[test]
+    fn element_wise_lists() {
+        let (mut a, mut b, spec) = states();
+        b.randao_mixes[3] = Hash256::repeat_byte(1);
+        b.slashings[2] = 7;
+        b.historical_roots.push(Hash256::repeat_byte(2)).unwrap();
+        let diff = StateDiff::new(&mut a, &mut b, &spec).unwrap();
+
+        // None of the lists are reported as a whole.
+        assert!(diff.fields.is_empty());
+
+        let randao_mixes = list(&diff, "randao_mixes");
+        assert_eq!(randao_mixes.diffs.len(), 1);
+        assert_eq!(randao_mixes.diffs[0].index, "3");
+
+        let slashings = list(&diff, "slashings");
+        assert_eq!(slashings.diffs.len(), 1);
+        assert_eq!(slashings.diffs[0].index, "2");
+        assert_eq!(slashings.diffs[0].b, "Some(7)");
+
+        let historical_roots = list(&diff, "historical_roots");
+        assert_eq!(historical_roots.diffs.len(), 1);
+        assert_eq!(historical_roots.diffs[0].index, "0");
+        assert_eq!(historical_roots.diffs[0].a, "None");
+    }
+}
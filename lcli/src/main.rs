@@ -1,8 +1,10 @@
 #[macro_use]
 extern crate log;
+mod bls_bench;
 mod change_genesis_time;
 mod check_deposit_data;
 mod deploy_deposit_contract;
+mod diff_states;
 mod eth1_genesis;
 mod generate_bootnode_enr;
 mod insecure_validators;
@@ -10,6 +12,7 @@ mod interop_genesis;
 mod new_testnet;
 mod parse_hex;
 mod refund_deposit_contract;
+mod replay;
 mod skip_slots;
 mod transition_blocks;
 
@@ -137,6 +140,126 @@ fn main() {
                         .help("Path to output a SSZ file."),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("diff-states")
+                .about(
+                    "Reports the field-level differences between two BeaconStates, either read \
+                    from SSZ files or downloaded from a beacon node.",
+                )
+                .arg(
+                    Arg::with_name("state-a")
+                        .value_name("STATE_A")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Path to a SSZ file of the first state or, if --beacon-node is \
+                            supplied, its state id (e.g., head, finalized, a slot or a root)."),
+                )
+                .arg(
+                    Arg::with_name("state-b")
+                        .value_name("STATE_B")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Path to a SSZ file of the second state or, if --beacon-node is \
+                            supplied, its state id."),
+                )
+                .arg(
+                    Arg::with_name("beacon-node")
+                        .long("beacon-node")
+                        .value_name("URL")
+                        .takes_value(true)
+                        .help("Download the states from the HTTP API of this beacon node, \
+                            instead of reading them from files."),
+                )
+                .arg(
+                    Arg::with_name("other-beacon-node")
+                        .long("other-beacon-node")
+                        .value_name("URL")
+                        .takes_value(true)
+                        .requires("beacon-node")
+                        .help("Download the second state from this beacon node instead of \
+                            --beacon-node, e.g. to compare two nodes which have split."),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .takes_value(true)
+                        .possible_values(&["text", "json"])
+                        .default_value("text")
+                        .help("The format in which to report the differences."),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .help("Write the differences to this file instead of stdout."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("replay")
+                .about(
+                    "Replays a sequence of blocks on top of a pre-state and reports the time \
+                    spent in each phase of the state transition.",
+                )
+                .arg(
+                    Arg::with_name("pre-state")
+                        .long("pre-state")
+                        .value_name("BEACON_STATE")
+                        .takes_value(true)
+                        .required_unless("beacon-node")
+                        .help("Path to a SSZ file of the pre-state. Defaults to the state at the \
+                            slot prior to --start-slot if --beacon-node is supplied."),
+                )
+                .arg(
+                    Arg::with_name("blocks-dir")
+                        .long("blocks-dir")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .required_unless("beacon-node")
+                        .conflicts_with("beacon-node")
+                        .help("Path to a directory of SSZ files of blocks. The blocks are \
+                            applied in order of slot."),
+                )
+                .arg(
+                    Arg::with_name("beacon-node")
+                        .long("beacon-node")
+                        .value_name("URL")
+                        .takes_value(true)
+                        .requires_all(&["start-slot", "end-slot"])
+                        .help("Download the blocks from the HTTP API of this beacon node."),
+                )
+                .arg(
+                    Arg::with_name("start-slot")
+                        .long("start-slot")
+                        .value_name("SLOT")
+                        .takes_value(true)
+                        .requires("beacon-node")
+                        .help("The slot of the first block to download."),
+                )
+                .arg(
+                    Arg::with_name("end-slot")
+                        .long("end-slot")
+                        .value_name("SLOT")
+                        .takes_value(true)
+                        .requires("beacon-node")
+                        .help("The slot of the last block to download (inclusive)."),
+                )
+                .arg(
+                    Arg::with_name("no-signature-verification")
+                        .long("no-signature-verification")
+                        .takes_value(false)
+                        .help("Do not verify the signatures in each block."),
+                )
+                .arg(
+                    Arg::with_name("trace")
+                        .long("trace")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .help("Write the timings to this file as folded stacks, suitable for \
+                            rendering as a flamegraph."),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("pretty-hex")
                 .about("Parses SSZ encoded as ASCII 0x-prefixed hex")
@@ -396,7 +519,7 @@ fn main() {
                         .index(1)
                         .value_name("GWEI")
                         .takes_value(true)
-                        .required(true)
+                        .required_unless("file")
                         .help("The amount (in Gwei) that was deposited"),
                 )
                 .arg(
@@ -404,10 +527,20 @@ fn main() {
                         .index(2)
                         .value_name("HEX")
                         .takes_value(true)
-                        .required(true)
+                        .required_unless("file")
                         .help("A 0x-prefixed hex string of the deposit data. Should include the
                             function signature."),
                 )
+                .arg(
+                    Arg::with_name("file")
+                        .long("file")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .conflicts_with_all(&["deposit-amount", "deposit-data"])
+                        .help("Path to a launchpad-compatible deposit_data-*.json file. Each \
+                            deposit is verified against the spec of --testnet-dir, if supplied, \
+                            or the --spec otherwise."),
+                )
         )
         .subcommand(
             SubCommand::with_name("generate-bootnode-enr")
@@ -483,6 +616,55 @@ fn main() {
                         .help("The directory for storing secrets."),
                 )
         )
+        .subcommand(
+            SubCommand::with_name("bls-bench")
+                .about(
+                    "Benchmarks signature verification across the available BLS backends.",
+                )
+                .arg(
+                    Arg::with_name("backends")
+                        .long("backends")
+                        .value_name("BACKENDS")
+                        .takes_value(true)
+                        .default_value("all")
+                        .help("A comma-separated list of backends to benchmark (blst, milagro, \
+                              fake_crypto) or \"all\" for every backend in this build."),
+                )
+                .arg(
+                    Arg::with_name("threads")
+                        .long("threads")
+                        .value_name("THREADS")
+                        .takes_value(true)
+                        .default_value("1")
+                        .help("A comma-separated list of thread counts to benchmark (e.g., 1,2,4)."),
+                )
+                .arg(
+                    Arg::with_name("sets")
+                        .long("sets")
+                        .value_name("COUNT")
+                        .takes_value(true)
+                        .default_value("128")
+                        .help("The number of signature sets to verify, both individually and \
+                              in batches."),
+                )
+                .arg(
+                    Arg::with_name("keys-per-aggregate")
+                        .long("keys-per-aggregate")
+                        .value_name("COUNT")
+                        .takes_value(true)
+                        .default_value("128")
+                        .help("The number of public keys contributing to each aggregate \
+                              signature."),
+                )
+                .arg(
+                    Arg::with_name("iterations")
+                        .long("iterations")
+                        .value_name("COUNT")
+                        .takes_value(true)
+                        .default_value("10")
+                        .help("The number of times to repeat each measurement."),
+                )
+        )
         .get_matches();
 
     let result = matches
@@ -560,6 +742,10 @@ fn run<T: EthSpec>(
         ("skip-slots", Some(matches)) => {
             skip_slots::run::<T>(matches).map_err(|e| format!("Failed to skip slots: {}", e))
         }
+        ("diff-states", Some(matches)) => diff_states::run::<T>(env, matches)
+            .map_err(|e| format!("Failed to run diff-states command: {}", e)),
+        ("replay", Some(matches)) => replay::run::<T>(env, matches)
+            .map_err(|e| format!("Failed to run replay command: {}", e)),
         ("pretty-hex", Some(matches)) => {
             run_parse_hex::<T>(matches).map_err(|e| format!("Failed to pretty print hex: {}", e))
         }
@@ -579,12 +765,15 @@ fn run<T: EthSpec>(
             .map_err(|e| format!("Failed to run change-genesis-time command: {}", e)),
         ("new-testnet", Some(matches)) => new_testnet::run::<T>(matches)
             .map_err(|e| format!("Failed to run new_testnet command: {}", e)),
-        ("check-deposit-data", Some(matches)) => check_deposit_data::run::<T>(matches)
+        ("check-deposit-data", Some(matches)) => check_deposit_data::run::<T>(env, matches)
             .map_err(|e| format!("Failed to run check-deposit-data command: {}", e)),
         ("generate-bootnode-enr", Some(matches)) => generate_bootnode_enr::run::<T>(matches)
             .map_err(|e| format!("Failed to run generate-bootnode-enr command: {}", e)),
         ("insecure-validators", Some(matches)) => insecure_validators::run(matches)
             .map_err(|e| format!("Failed to run insecure-validators command: {}", e)),
+        ("bls-bench", Some(matches)) => {
+            bls_bench::run(matches).map_err(|e| format!("Failed to run bls-bench command: {}", e))
+        }
         (other, _) => Err(format!("Unknown subcommand {}. See --help.", other)),
     }
 }
@@ -0,0 +1,355 @@
+//! Replays a sequence of blocks on top of a pre-state, timing each phase of the state transition.
+//!
+//! Blocks are either read from a directory of SSZ files or downloaded from a beacon node. The time
+//! spent in each phase is reported per block and in total. Optionally, the timings are also written
+//! as "folded stacks" (one `frame;frame;frame microseconds` line per measurement), which can be
+//! rendered with tools such as `flamegraph.pl` or `inferno-flamegraph`.
+
+use crate::transition_blocks::load_from_ssz;
+use clap::ArgMatches;
+use environment::Environment;
+use eth2::{
+    types::{BlockId, StateId},
+    BeaconNodeHttpClient, Url,
+};
+use state_processing::{
+    per_block_processing, per_block_processing::signature_sets::get_pubkey_from_state,
+    per_epoch_processing, per_slot_processing::cache_state, BlockSignatureStrategy,
+    BlockSignatureVerifier,
+};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use types::{BeaconState, ChainSpec, EthSpec, SignedBeaconBlock, Slot};
+
+/// The number of slowest blocks to list in the summary.
+const SLOWEST_BLOCKS: usize = 10;
+
+/// A distinct phase of the state transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    /// Caching the state and block roots of each slot that the state is advanced through.
+    SlotProcessing,
+    /// Processing each epoch boundary that the state is advanced through.
+    EpochProcessing,
+    /// Building the committee, pubkey, exit and tree hash caches of the state.
+    Caches,
+    /// Computing state and block roots.
+    TreeHashing,
+    /// Verifying all the signatures in a block.
+    SignatureVerification,
+    /// Applying a block to the state, without verifying signatures.
+    BlockProcessing,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Phase::SlotProcessing => "slot_processing",
+            Phase::EpochProcessing => "epoch_processing",
+            Phase::Caches => "caches",
+            Phase::TreeHashing => "tree_hashing",
+            Phase::SignatureVerification => "signature_verification",
+            Phase::BlockProcessing => "block_processing",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The time spent in each phase whilst importing a single block, including advancing the state to
+/// the slot of the block.
+#[derive(Debug, Default)]
+pub struct BlockTimings {
+    pub slot: Slot,
+    pub phases: BTreeMap<Phase, Duration>,
+}
+
+impl BlockTimings {
+    fn time<T, F: FnOnce() -> T>(&mut self, phase: Phase, func: F) -> T {
+        let timer = Instant::now();
+        let result = func();
+        *self.phases.entry(phase).or_default() += timer.elapsed();
+        result
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases.values().sum()
+    }
+}
+
+pub fn run<T: EthSpec>(mut env: Environment<T>, matches: &ArgMatches) -> Result<(), String> {
+    let spec = env.eth2_config.spec.clone();
+    let verify_signatures = !matches.is_present("no-signature-verification");
+
+    let (pre_state, blocks) = if let Some(beacon_node) = matches.value_of("beacon-node") {
+        let client = BeaconNodeHttpClient::new(
+            Url::parse(beacon_node)
+                .map_err(|e| format!("Failed to parse beacon node URL: {:?}", e))?,
+        );
+        let start_slot = Slot::new(clap_utils::parse_required(matches, "start-slot")?);
+        let end_slot = Slot::new(clap_utils::parse_required(matches, "end-slot")?);
+        let pre_state_path: Option<PathBuf> = clap_utils::parse_optional(matches, "pre-state")?;
+
+        env.runtime().block_on(async {
+            let pre_state = if let Some(path) = pre_state_path {
+                load_from_ssz(path)?
+            } else {
+                let state_id = StateId::Slot(start_slot.saturating_sub(1_u64));
+                client
+                    .get_debug_beacon_states::<T>(state_id)
+                    .await
+                    .map_err(|e| format!("Failed to download pre-state: {:?}", e))?
+                    .ok_or_else(|| format!("Pre-state {} not found", state_id))?
+                    .data
+            };
+            let blocks = download_blocks::<T>(&client, start_slot, end_slot).await?;
+            Ok::<_, String>((pre_state, blocks))
+        })?
+    } else {
+        let pre_state: PathBuf = clap_utils::parse_required(matches, "pre-state")?;
+        let blocks_dir: PathBuf = clap_utils::parse_required(matches, "blocks-dir")?;
+        (load_from_ssz(pre_state)?, load_blocks::<T>(blocks_dir)?)
+    };
+
+    info!("Using {} spec", T::spec_name());
+    info!("Pre-state slot: {}", pre_state.slot);
+    info!("Blocks: {}", blocks.len());
+
+    let timings = replay(pre_state, &blocks, verify_signatures, &spec)?;
+
+    println!("{}", summary(&timings));
+
+    if let Some(trace_path) = matches.value_of("trace") {
+        let mut file = File::create(trace_path)
+            .map_err(|e| format!("Unable to create {}: {:?}", trace_path, e))?;
+        file.write_all(folded_stacks(&timings).as_bytes())
+            .map_err(|e| format!("Unable to write to {}: {:?}", trace_path, e))?;
+        info!("Wrote trace to {}", trace_path);
+    }
+
+    Ok(())
+}
+
+/// Applies `blocks` to `state` in order, returning the timings of each block.
+///
+/// The post-state root of each block is checked against the `state_root` in the block.
+pub fn replay<T: EthSpec>(
+    mut state: BeaconState<T>,
+    blocks: &[SignedBeaconBlock<T>],
+    verify_signatures: bool,
+    spec: &ChainSpec,
+) -> Result<Vec<BlockTimings>, String> {
+    let mut all_timings = Vec::with_capacity(blocks.len());
+
+    for block in blocks {
+        let mut timings = BlockTimings {
+            slot: block.slot(),
+            ..BlockTimings::default()
+        };
+
+        if block.slot() <= state.slot {
+            return Err(format!(
+                "Block at slot {} is not later than the state at slot {}",
+                block.slot(),
+                state.slot
+            ));
+        }
+
+        // Build the caches of the pre-state. Only the first block does any work here, since the
+        // caches are retained between blocks.
+        timings
+            .time(Phase::Caches, || state.build_all_caches(spec))
+            .map_err(|e| format!("Unable to build caches: {:?}", e))?;
+
+        // Transition the parent state to the block slot. This mirrors `per_slot_processing`, so
+        // that per-slot and per-epoch processing can be timed separately.
+        while state.slot < block.slot() {
+            let state_root = timings
+                .time(Phase::TreeHashing, || state.update_tree_hash_cache())
+                .map_err(|e| format!("Unable to compute state root: {:?}", e))?;
+
+            timings
+                .time(Phase::SlotProcessing, || {
+                    cache_state(&mut state, Some(state_root))
+                })
+                .map_err(|e| format!("Failed to process slot {}: {:?}", state.slot, e))?;
+
+            if state.slot > spec.genesis_slot && (state.slot + 1) % T::slots_per_epoch() == 0 {
+                timings
+                    .time(Phase::EpochProcessing, || {
+                        per_epoch_processing(&mut state, spec)
+                    })
+                    .map_err(|e| {
+                        format!("Failed to process epoch at slot {}: {:?}", state.slot, e)
+                    })?;
+            }
+
+            state.slot += 1;
+        }
+
+        // Epoch processing leaves the caches for the next epoch unbuilt.
+        timings
+            .time(Phase::Caches, || state.build_all_caches(spec))
+            .map_err(|e| format!("Unable to build caches: {:?}", e))?;
+
+        let block_root = timings.time(Phase::TreeHashing, || block.canonical_root());
+
+        if verify_signatures {
+            timings
+                .time(Phase::SignatureVerification, || {
+                    BlockSignatureVerifier::verify_entire_block(
+                        &state,
+                        |i| get_pubkey_from_state(&state, i),
+                        block,
+                        Some(block_root),
+                        spec,
+                    )
+                })
+                .map_err(|e| {
+                    format!(
+                        "Invalid signature in block at slot {}: {:?}",
+                        block.slot(),
+                        e
+                    )
+                })?;
+        }
+
+        timings
+            .time(Phase::BlockProcessing, || {
+                per_block_processing(
+                    &mut state,
+                    block,
+                    Some(block_root),
+                    BlockSignatureStrategy::NoVerification,
+                    spec,
+                )
+            })
+            .map_err(|e| format!("Invalid block at slot {}: {:?}", block.slot(), e))?;
+
+        let state_root = timings
+            .time(Phase::TreeHashing, || state.update_tree_hash_cache())
+            .map_err(|e| format!("Unable to compute state root: {:?}", e))?;
+        if state_root != block.state_root() {
+            return Err(format!(
+                "State root mismatch at slot {}. Block: {:?}, computed: {:?}",
+                block.slot(),
+                block.state_root(),
+                state_root
+            ));
+        }
+
+        all_timings.push(timings);
+    }
+
+    Ok(all_timings)
+}
+
+/// Returns a human-readable report of the time spent in each phase, in total and for the slowest
+/// blocks.
+fn summary(timings: &[BlockTimings]) -> String {
+    let mut totals: BTreeMap<Phase, Duration> = BTreeMap::new();
+    for block in timings {
+        for (phase, duration) in &block.phases {
+            *totals.entry(*phase).or_default() += *duration;
+        }
+    }
+    let total: Duration = totals.values().sum();
+
+    let mut lines = vec![format!("Replayed {} blocks in {:?}", timings.len(), total)];
+    for (phase, duration) in &totals {
+        let percentage = if total.as_nanos() > 0 {
+            duration.as_nanos() as f64 * 100.0 / total.as_nanos() as f64
+        } else {
+            0.0
+        };
+        lines.push(format!(
+            "  {:<24}{:>14?}{:>8.1}%",
+            phase, duration, percentage
+        ));
+    }
+
+    let mut slowest = timings.iter().collect::<Vec<_>>();
+    slowest.sort_by_key(|block| std::cmp::Reverse(block.total()));
+    lines.push(format!(
+        "Slowest {} blocks:",
+        SLOWEST_BLOCKS.min(slowest.len())
+    ));
+    for block in slowest.into_iter().take(SLOWEST_BLOCKS) {
+        let phases = block
+            .phases
+            .iter()
+            .map(|(phase, duration)| format!("{}: {:?}", phase, duration))
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(format!(
+            "  slot {}: {:?} ({})",
+            block.slot,
+            block.total(),
+            phases
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// Returns the timings in the "folded stacks" format, with durations in microseconds.
+fn folded_stacks(timings: &[BlockTimings]) -> String {
+    let mut lines = vec![];
+    for block in timings {
+        for (phase, duration) in &block.phases {
+            lines.push(format!(
+                "replay;slot_{};{} {}",
+                block.slot,
+                phase,
+                duration.as_micros()
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Loads all the SSZ blocks (i.e., files with an `.ssz` extension) in `dir`, ordered by slot.
+fn load_blocks<T: EthSpec>(dir: PathBuf) -> Result<Vec<SignedBeaconBlock<T>>, String> {
+    let paths = std::fs::read_dir(&dir)
+        .map_err(|e| format!("Unable to read {:?}: {:?}", dir, e))?
+        .map(|entry| {
+            entry
+                .map(|entry| entry.path())
+                .map_err(|e| format!("Unable to read {:?}: {:?}", dir, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut blocks = paths
+        .into_iter()
+        .filter(|path| path.is_file() && path.extension().map_or(false, |ext| ext == "ssz"))
+        .map(load_from_ssz::<SignedBeaconBlock<T>>)
+        .collect::<Result<Vec<_>, _>>()?;
+    blocks.sort_by_key(|block| block.slot());
+    Ok(blocks)
+}
+
+/// Downloads the blocks from `start_slot` to `end_slot` (inclusive), ignoring skipped slots.
+async fn download_blocks<T: EthSpec>(
+    client: &BeaconNodeHttpClient,
+    start_slot: Slot,
+    end_slot: Slot,
+) -> Result<Vec<SignedBeaconBlock<T>>, String> {
+    let mut blocks = vec![];
+    for slot in start_slot.as_u64()..=end_slot.as_u64() {
+        let slot = Slot::new(slot);
+        let block = client
+            .get_beacon_blocks::<T>(BlockId::Slot(slot))
+            .await
+            .map_err(|e| format!("Failed to download block at slot {}: {:?}", slot, e))?
+            .map(|response| response.data);
+
+        // A skipped slot may either be absent or resolve to the prior block.
+        if let Some(block) = block.filter(|block| block.slot() == slot) {
+            blocks.push(block);
+        }
+    }
+    Ok(blocks)
+}
@@ -315,6 +315,18 @@ impl<E: EthSpec> RuntimeContext<E> {
         }
     }
 
+    /// Returns a sub-context of this context which is shut down when `exit` fires, rather than
+    /// when the rest of the environment is shut down.
+    ///
+    /// The generated service will have the `service_name` in all it's logs.
+    pub fn service_context_with_exit(&self, service_name: String, exit: exit_future::Exit) -> Self {
+        Self {
+            executor: self.executor.clone_with_name_and_exit(service_name, exit),
+            eth_spec_instance: self.eth_spec_instance.clone(),
+            eth2_config: self.eth2_config.clone(),
+        }
+    }
+
     /// Returns the `eth2_config` for this service.
     pub fn eth2_config(&self) -> &Eth2Config {
         &self.eth2_config
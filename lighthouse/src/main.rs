@@ -13,7 +13,9 @@ use validator_client::ProductionValidatorClient;
 pub const ETH2_CONFIG_FILENAME: &str = "eth2-spec.toml";
 
 fn bls_library_name() -> &'static str {
-    if cfg!(feature = "portable") {
+    if cfg!(feature = "runtime-bls") && !cfg!(feature = "milagro") {
+        "blst or milagro (see --bls-backend)"
+    } else if cfg!(feature = "portable") {
         "blst-portable"
     } else if cfg!(feature = "modern") {
         "blst-modern"
@@ -121,6 +123,17 @@ fn main() {
                 .global(true)
 
         )
+        .arg(
+            Arg::with_name("bls-backend")
+                .long("bls-backend")
+                .value_name("LIBRARY")
+                .help("The BLS library used for all cryptographic operations. A library other than \
+                    the compiled-in default may only be used if Lighthouse was built with the \
+                    runtime-bls feature.")
+                .possible_values(&["blst", "milagro"])
+                .takes_value(true)
+                .global(true)
+        )
         .subcommand(beacon_node::cli_app())
         .subcommand(boot_node::cli_app())
         .subcommand(validator_client::cli_app())
@@ -133,6 +146,11 @@ fn main() {
         Builder::from_env(Env::default()).init();
     }
 
+    if let Err(e) = select_bls_backend(&matches) {
+        eprintln!("{}", e);
+        exit(1)
+    }
+
     let result = load_testnet_config(&matches).and_then(|testnet_config| {
         let eth_spec_id = testnet_config.eth_spec_id()?;
 
@@ -172,6 +190,15 @@ fn main() {
     }
 }
 
+/// Selects the BLS library given by `--bls-backend`. This must happen before any BLS points are
+/// created.
+fn select_bls_backend(matches: &ArgMatches) -> Result<(), String> {
+    if let Some(backend) = clap_utils::parse_optional::<bls::Backend>(matches, "bls-backend")? {
+        bls::select_backend(backend)?;
+    }
+    Ok(())
+}
+
 fn load_testnet_config(matches: &ArgMatches) -> Result<Eth2TestnetConfig, String> {
     if matches.is_present("testnet-dir") {
         clap_utils::parse_testnet_dir(matches, "testnet-dir")?
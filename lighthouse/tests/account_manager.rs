@@ -27,7 +27,7 @@ use std::process::{Command, Output, Stdio};
 use std::str::from_utf8;
 use tempfile::{tempdir, TempDir};
 use types::{Keypair, PublicKey};
-use validator_dir::ValidatorDir;
+use validator_dir::{read_standard_deposit_data_json, ValidatorDir, DEPOSIT_DATA_JSON_PREFIX};
 
 // TODO: create tests for the `lighthouse account validator deposit` command. This involves getting
 // access to an IPC endpoint during testing or adding support for deposit submission via HTTP and
@@ -366,9 +366,11 @@ fn validator_create() {
     );
     drop(created_validators);
 
-    // Number of dir entries should be #validators + 1 for the slashing protection DB
+    // Number of dir entries should be #validators + 1 for the slashing protection DB + 1 for the
+    // deposit data JSON.
     assert_eq!(dir_validator_count(validator_dir.path()), 1);
-    assert_eq!(dir_child_count(validator_dir.path()), 2);
+    assert_eq!(dir_child_count(validator_dir.path()), 3);
+    check_deposit_data_json(&validator_dir, 1);
 
     // Create a validator storing the withdraw key.
     validator.create_expect_success(COUNT_FLAG, 1, true);
@@ -500,6 +502,34 @@ fn validator_import_launchpad() {
     );
 }
 
+/// Check that a single deposit data JSON file with `count` deposits for validators in
+/// `validator_dir` has been created.
+fn check_deposit_data_json(validator_dir: &TempDir, count: usize) {
+    let paths = fs::read_dir(validator_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with(DEPOSIT_DATA_JSON_PREFIX)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(paths.len(), 1, "should have one deposit data JSON file");
+
+    let deposits = read_standard_deposit_data_json(&paths[0]).unwrap();
+    assert_eq!(deposits.len(), count);
+    for deposit in deposits {
+        assert!(
+            validator_dir
+                .path()
+                .join(deposit.pubkey.to_string())
+                .exists(),
+            "deposit should be for a known validator"
+        );
+    }
+}
+
 /// Check that all of the given pubkeys have been registered with slashing protection.
 fn check_slashing_protection(validator_dir: &TempDir, pubkeys: impl Iterator<Item = PublicKey>) {
     let slashing_db_path = validator_dir.path().join(SLASHING_PROTECTION_FILENAME);
@@ -13,6 +13,7 @@ mod epoch_processing;
 mod genesis_initialization;
 mod genesis_validity;
 mod operations;
+mod rewards;
 mod sanity_blocks;
 mod sanity_slots;
 mod shuffling;
@@ -29,6 +30,7 @@ pub use epoch_processing::*;
 pub use genesis_initialization::*;
 pub use genesis_validity::*;
 pub use operations::*;
+pub use rewards::*;
 pub use sanity_blocks::*;
 pub use sanity_slots::*;
 pub use shuffling::*;
@@ -226,6 +226,24 @@ impl<E: EthSpec + TypeName> Handler for FinalityHandler<E> {
     }
 }
 
+pub struct RewardsHandler<E, H>(PhantomData<(E, H)>);
+
+impl<E: EthSpec + TypeName, H: TypeName> Handler for RewardsHandler<E, H> {
+    type Case = cases::RewardsTest<E>;
+
+    fn config_name() -> &'static str {
+        E::name()
+    }
+
+    fn runner_name() -> &'static str {
+        "rewards"
+    }
+
+    fn handler_name() -> String {
+        H::name().into()
+    }
+}
+
 pub struct GenesisValidityHandler<E>(PhantomData<E>);
 
 impl<E: EthSpec + TypeName> Handler for GenesisValidityHandler<E> {
@@ -311,3 +329,11 @@ pub struct Uints;
 type_name!(Uints, "uints");
 pub struct Containers;
 type_name!(Containers, "containers");
+
+// Supported rewards handlers
+pub struct RewardsBasic;
+type_name!(RewardsBasic, "basic");
+pub struct RewardsLeak;
+type_name!(RewardsLeak, "leak");
+pub struct RewardsRandom;
+type_name!(RewardsRandom, "random");
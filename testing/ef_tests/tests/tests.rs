@@ -257,6 +257,16 @@ fn finality() {
     FinalityHandler::<MainnetEthSpec>::run();
 }
 
+#[test]
+fn rewards() {
+    RewardsHandler::<MinimalEthSpec, RewardsBasic>::run();
+    RewardsHandler::<MainnetEthSpec, RewardsBasic>::run();
+    RewardsHandler::<MinimalEthSpec, RewardsLeak>::run();
+    RewardsHandler::<MainnetEthSpec, RewardsLeak>::run();
+    RewardsHandler::<MinimalEthSpec, RewardsRandom>::run();
+    RewardsHandler::<MainnetEthSpec, RewardsRandom>::run();
+}
+
 #[test]
 fn genesis_initialization() {
     GenesisInitializationHandler::<MinimalEthSpec>::run();
@@ -19,6 +19,7 @@ use validator_dir::insecure_keys::build_deterministic_validator_dirs;
 pub use beacon_node::{ClientConfig, ClientGenesis, ProductionClient};
 pub use environment;
 pub use eth2;
+pub use tempdir;
 pub use validator_client::Config as ValidatorConfig;
 
 /// The global timeout for HTTP requests to the beacon node.
@@ -39,12 +40,24 @@ impl<E: EthSpec> LocalBeaconNode<E> {
     /// The node created is using the same types as the node we use in production.
     pub async fn production(
         context: RuntimeContext<E>,
-        mut client_config: ClientConfig,
+        client_config: ClientConfig,
     ) -> Result<Self, String> {
         // Creates a temporary directory that will be deleted once this `TempDir` is dropped.
         let datadir = TempDir::new("lighthouse_node_test_rig")
             .expect("should create temp directory for client datadir");
 
+        Self::production_with_datadir(context, client_config, datadir).await
+    }
+
+    /// Starts a new, production beacon node which stores its data in `datadir`.
+    ///
+    /// The `datadir` may have been used by a previous `LocalBeaconNode`, in which case the node
+    /// resumes from the database in that directory.
+    pub async fn production_with_datadir(
+        context: RuntimeContext<E>,
+        mut client_config: ClientConfig,
+        datadir: TempDir,
+    ) -> Result<Self, String> {
         client_config.data_dir = datadir.path().into();
         client_config.network.network_dir = PathBuf::from(datadir.path()).join("network");
 
@@ -1,8 +1,11 @@
 use crate::local_network::LocalNetwork;
-use node_test_rig::eth2::types::StateId;
+use node_test_rig::eth2::types::{BlockId, StateId};
 use std::time::Duration;
 use types::{Epoch, EthSpec, Slot, Unsigned};
 
+/// The number of times to check whether the heads of all nodes are the same before failing.
+const HEAD_CONVERGENCE_ATTEMPTS: usize = 3;
+
 /// Checks that all of the validators have on-boarded by the start of the second eth1 voting
 /// period.
 pub async fn verify_initial_validator_count<E: EthSpec>(
@@ -61,19 +64,7 @@ pub async fn verify_all_finalized_at<E: EthSpec>(
     network: LocalNetwork<E>,
     epoch: Epoch,
 ) -> Result<(), String> {
-    let epochs = {
-        let mut epochs = Vec::new();
-        for remote_node in network.remote_nodes()? {
-            epochs.push(
-                remote_node
-                    .get_beacon_states_finality_checkpoints(StateId::Head)
-                    .await
-                    .map(|body| body.unwrap().data.finalized.epoch)
-                    .map_err(|e| format!("Get head via http failed: {:?}", e))?,
-            );
-        }
-        epochs
-    };
+    let epochs = finalized_epochs(&network).await?;
 
     if epochs.iter().any(|node_epoch| *node_epoch != epoch) {
         Err(format!(
@@ -85,6 +76,96 @@ pub async fn verify_all_finalized_at<E: EthSpec>(
     }
 }
 
+/// Verifies that all beacon nodes in the given network have a head state that has a finalized
+/// epoch of at least `epoch`.
+pub async fn verify_all_finalized_at_least<E: EthSpec>(
+    network: LocalNetwork<E>,
+    epoch: Epoch,
+) -> Result<(), String> {
+    let epochs = finalized_epochs(&network).await?;
+
+    if epochs.iter().any(|node_epoch| *node_epoch < epoch) {
+        Err(format!(
+            "Nodes are not finalized at or after epoch {}. Finalized epochs: {:?}",
+            epoch, epochs
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the finalized epoch of the head state of each running beacon node in the network.
+pub async fn finalized_epochs<E: EthSpec>(network: &LocalNetwork<E>) -> Result<Vec<Epoch>, String> {
+    let mut epochs = Vec::new();
+    for remote_node in network.remote_nodes()? {
+        epochs.push(
+            remote_node
+                .get_beacon_states_finality_checkpoints(StateId::Head)
+                .await
+                .map(|body| body.unwrap().data.finalized.epoch)
+                .map_err(|e| format!("Get head via http failed: {:?}", e))?,
+        );
+    }
+    Ok(epochs)
+}
+
+/// Verifies that no validator has been slashed in the head state of any beacon node in the
+/// network.
+pub async fn verify_no_slashings<E: EthSpec>(network: LocalNetwork<E>) -> Result<(), String> {
+    for remote_node in network.remote_nodes()? {
+        let slashed = remote_node
+            .get_beacon_states_validators(StateId::Head, None, None)
+            .await
+            .map_err(|e| format!("Get validators via http failed: {:?}", e))?
+            .ok_or_else(|| "Head state not found".to_string())?
+            .data
+            .into_iter()
+            .filter(|validator| validator.validator.slashed)
+            .map(|validator| validator.index)
+            .collect::<Vec<_>>();
+
+        if !slashed.is_empty() {
+            return Err(format!("Validators have been slashed: {:?}", slashed));
+        }
+    }
+    Ok(())
+}
+
+/// Verifies that all beacon nodes in the network have the same head block.
+///
+/// Since nodes may briefly disagree whilst a new block propagates, the check is attempted up to
+/// `HEAD_CONVERGENCE_ATTEMPTS` times, one slot apart.
+pub async fn verify_heads_converged<E: EthSpec>(
+    network: LocalNetwork<E>,
+    slot_duration: Duration,
+) -> Result<(), String> {
+    let mut heads = vec![];
+    for attempt in 0..HEAD_CONVERGENCE_ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::delay_for(slot_duration).await;
+        }
+
+        heads.clear();
+        for remote_node in network.remote_nodes()? {
+            heads.push(
+                remote_node
+                    .get_beacon_blocks_root(BlockId::Head)
+                    .await
+                    .map_err(|e| format!("Get head root via http failed: {:?}", e))?
+                    .ok_or_else(|| "Head block not found".to_string())?
+                    .data
+                    .root,
+            );
+        }
+
+        if heads.windows(2).all(|pair| pair[0] == pair[1]) {
+            return Ok(());
+        }
+    }
+
+    Err(format!("Nodes have not converged on a head: {:?}", heads))
+}
+
 /// Verifies that all beacon nodes in the given `network` have a head state that contains
 /// `expected_count` validators.
 async fn verify_validator_count<E: EthSpec>(
@@ -127,7 +208,12 @@ pub async fn verify_full_block_production_up_to<E: EthSpec>(
 ) -> Result<(), String> {
     slot_delay(slot, slot_duration).await;
     let beacon_nodes = network.beacon_nodes.read();
-    let beacon_chain = beacon_nodes[0].client.beacon_chain().unwrap();
+    let beacon_chain = beacon_nodes[0]
+        .as_ref()
+        .ok_or_else(|| "Boot node is not running".to_string())?
+        .client
+        .beacon_chain()
+        .unwrap();
     let num_blocks = beacon_chain
         .chain_dump()
         .unwrap()
@@ -105,4 +105,79 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                         .help("Sync verification strategy to run."),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("fault-sim")
+                .about("Runs a simulator that injects faults into the network and checks that it \
+                    recovers from them")
+                .arg(
+                    Arg::with_name("nodes")
+                        .short("n")
+                        .long("nodes")
+                        .takes_value(true)
+                        .default_value("4")
+                        .help("Number of beacon nodes"),
+                )
+                .arg(
+                    Arg::with_name("validators_per_node")
+                        .short("v")
+                        .long("validators_per_node")
+                        .takes_value(true)
+                        .default_value("8")
+                        .help("Number of validators"),
+                )
+                .arg(
+                    Arg::with_name("speed_up_factor")
+                        .short("s")
+                        .long("speed_up_factor")
+                        .takes_value(true)
+                        .default_value("3")
+                        .help("Speed up factor"),
+                )
+                .arg(
+                    Arg::with_name("faulty_nodes")
+                        .short("f")
+                        .long("faulty_nodes")
+                        .takes_value(true)
+                        .default_value("1")
+                        .help("Number of nodes to inject faults into. The boot node is never \
+                            faulty, so this must be less than the number of nodes."),
+                )
+                .arg(
+                    Arg::with_name("fault_duration")
+                        .long("fault_duration")
+                        .takes_value(true)
+                        .default_value("2")
+                        .help("Number of epochs for which each fault is held"),
+                )
+                .arg(
+                    Arg::with_name("recovery_epochs")
+                        .long("recovery_epochs")
+                        .takes_value(true)
+                        .default_value("5")
+                        .help("Number of epochs after healing a fault within which the network \
+                            must recover"),
+                )
+                .arg(
+                    Arg::with_name("latency_ms")
+                        .long("latency_ms")
+                        .takes_value(true)
+                        .default_value("500")
+                        .help("Milliseconds by which each message sent by the faulty nodes is \
+                            delayed in the latency scenario"),
+                )
+                .arg(
+                    Arg::with_name("scenario")
+                        .long("scenario")
+                        .takes_value(true)
+                        .default_value("all")
+                        .possible_values(&[
+                            "restart",
+                            "validators-offline",
+                            "partition",
+                            "latency",
+                            "all",
+                        ])
+                        .help("Fault scenario to run."),
+                ),
+        )
 }
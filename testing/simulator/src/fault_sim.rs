@@ -0,0 +1,305 @@
+//! Runs a network of beacon nodes and validator clients, injects faults into it and then checks
+//! that the network recovers.
+//!
+//! After the chain has reached its first finalization, each fault in the chosen scenario is
+//! injected into the last `faulty_nodes` nodes, held for `fault_duration` epochs and then healed.
+//! After a further `recovery_epochs` epochs the following recovery properties are checked:
+//!
+//! - Finality has advanced beyond the point at which the fault was healed.
+//! - No validator has been slashed.
+//! - All beacon nodes have converged on the same head.
+
+use crate::{checks, LocalNetwork, E};
+use clap::ArgMatches;
+use node_test_rig::{
+    environment::EnvironmentBuilder, testing_client_config, testing_validator_config,
+    ClientGenesis, ValidatorFiles,
+};
+use rayon::prelude::*;
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::{delay_until, Instant};
+use types::{Epoch, EthSpec};
+
+/// A fault which can be injected into a `LocalNetwork`, and later healed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fault {
+    /// Stops the faulty beacon nodes (leaving their validators without a beacon node), then
+    /// restarts them from their existing databases.
+    RestartBeaconNodes,
+    /// Stops the validator clients of the faulty nodes, then restarts them.
+    ValidatorsOffline,
+    /// Isolates the faulty beacon nodes from the rest of the network, then reconnects them.
+    Partition,
+    /// Delays every message sent by the faulty beacon nodes by the given duration, then removes
+    /// the delay.
+    Latency(Duration),
+}
+
+impl Fault {
+    /// Returns the faults run by `scenario`. The "all" scenario runs every fault in turn.
+    pub fn from_scenario(scenario: &str, latency: Duration) -> Result<Vec<Self>, String> {
+        match scenario {
+            "restart" => Ok(vec![Fault::RestartBeaconNodes]),
+            "validators-offline" => Ok(vec![Fault::ValidatorsOffline]),
+            "partition" => Ok(vec![Fault::Partition]),
+            "latency" => Ok(vec![Fault::Latency(latency)]),
+            "all" => Ok(vec![
+                Fault::RestartBeaconNodes,
+                Fault::ValidatorsOffline,
+                Fault::Partition,
+                Fault::Latency(latency),
+            ]),
+            other => Err(format!("Unknown fault scenario: {}", other)),
+        }
+    }
+
+    /// Injects `self` into the `nodes` of the `network`.
+    async fn inject(self, network: &LocalNetwork<E>, nodes: &[usize]) -> Result<(), String> {
+        match self {
+            Fault::RestartBeaconNodes => {
+                for &node in nodes {
+                    network.stop_beacon_node(node).await?;
+                }
+            }
+            // Each node has exactly one validator client, with the same index.
+            Fault::ValidatorsOffline => {
+                for &node in nodes {
+                    network.stop_validator_client(node)?;
+                }
+            }
+            Fault::Partition => network.partition_beacon_nodes(nodes).await?,
+            Fault::Latency(latency) => network.delay_beacon_nodes(nodes, latency).await?,
+        }
+        Ok(())
+    }
+
+    /// Heals `self` after it was injected into the `nodes` of the `network`.
+    async fn heal(self, network: &LocalNetwork<E>, nodes: &[usize]) -> Result<(), String> {
+        match self {
+            Fault::RestartBeaconNodes => {
+                for &node in nodes {
+                    network.restart_beacon_node(node).await?;
+                }
+            }
+            Fault::ValidatorsOffline => {
+                for &node in nodes {
+                    network.restart_validator_client(node).await?;
+                }
+            }
+            Fault::Partition => network.heal_partition(nodes).await?,
+            Fault::Latency(_) => {
+                for &node in nodes {
+                    network.stop_beacon_node(node).await?;
+                    network.restart_beacon_node(node).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn run_fault_sim(matches: &ArgMatches) -> Result<(), String> {
+    let node_count = value_t!(matches, "nodes", usize).expect("missing nodes default");
+    let validators_per_node = value_t!(matches, "validators_per_node", usize)
+        .expect("missing validators_per_node default");
+    let faulty_node_count =
+        value_t!(matches, "faulty_nodes", usize).expect("missing faulty_nodes default");
+    let speed_up_factor =
+        value_t!(matches, "speed_up_factor", u64).expect("missing speed_up_factor default");
+    let fault_duration =
+        value_t!(matches, "fault_duration", u64).expect("missing fault_duration default");
+    let recovery_epochs =
+        value_t!(matches, "recovery_epochs", u64).expect("missing recovery_epochs default");
+    let latency_ms = value_t!(matches, "latency_ms", u64).expect("missing latency_ms default");
+    let scenario = value_t!(matches, "scenario", String).expect("missing scenario default");
+
+    let faults = Fault::from_scenario(&scenario, Duration::from_millis(latency_ms))?;
+
+    if faulty_node_count == 0 || faulty_node_count >= node_count {
+        return Err(format!(
+            "faulty_nodes must be at least 1 and less than nodes ({})",
+            node_count
+        ));
+    }
+    // The boot node (index 0) is never faulty.
+    let faulty_nodes = (node_count - faulty_node_count..node_count).collect::<Vec<_>>();
+
+    println!("Fault Simulator:");
+    println!(" nodes:{}", node_count);
+    println!(" validators_per_node:{}", validators_per_node);
+    println!(" faulty_nodes:{:?}", faulty_nodes);
+    println!(" fault_duration:{}", fault_duration);
+    println!(" recovery_epochs:{}", recovery_epochs);
+    println!(" faults:{:?}", faults);
+
+    // Generate the directories and keystores required for the validator clients.
+    let validator_files = (0..node_count)
+        .into_par_iter()
+        .map(|i| {
+            println!(
+                "Generating keystores for validator {} of {}",
+                i + 1,
+                node_count
+            );
+
+            let indices =
+                (i * validators_per_node..(i + 1) * validators_per_node).collect::<Vec<_>>();
+            ValidatorFiles::with_keystores(&indices).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let log_level = "debug";
+    let log_format = None;
+
+    let mut env = EnvironmentBuilder::minimal()
+        .async_logger(log_level, log_format)?
+        .multi_threaded_tokio_runtime()?
+        .build()?;
+
+    let spec = &mut env.eth2_config.spec;
+
+    let total_validator_count = validators_per_node * node_count;
+
+    spec.milliseconds_per_slot /= speed_up_factor;
+    spec.min_genesis_time = 0;
+    spec.min_genesis_active_validator_count = total_validator_count as u64;
+
+    let genesis_delay = Duration::from_secs(5);
+    let genesis_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| "should get system time")?
+        + genesis_delay;
+    let genesis_instant = Instant::now() + genesis_delay;
+
+    let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
+
+    let context = env.core_context();
+
+    let mut beacon_config = testing_client_config();
+
+    beacon_config.genesis = ClientGenesis::Interop {
+        validator_count: total_validator_count,
+        genesis_time: genesis_time.as_secs(),
+    };
+    beacon_config.dummy_eth1_backend = true;
+    beacon_config.sync_eth1_chain = true;
+
+    beacon_config.network.enr_address = Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+
+    let main_future = async {
+        let network = LocalNetwork::new(context, beacon_config.clone()).await?;
+
+        for _ in 0..node_count - 1 {
+            network.add_beacon_node(beacon_config.clone()).await?;
+        }
+
+        /*
+         * Create a future that will add validator clients to the network. Each validator client is
+         * attached to a single corresponding beacon node.
+         */
+        let add_validators_fut = async {
+            for (i, files) in validator_files.into_iter().enumerate() {
+                network
+                    .add_validator_client(testing_validator_config(), i, files)
+                    .await?;
+            }
+
+            Ok::<(), String>(())
+        };
+
+        /*
+         * Inject each fault in turn, checking that the network recovers from each.
+         */
+        let faults_fut = async {
+            delay_until(genesis_instant).await;
+
+            // Ensure the network is healthy before injecting any faults.
+            checks::verify_first_finalization(network.clone(), slot_duration).await?;
+
+            for fault in &faults {
+                run_fault(
+                    &network,
+                    *fault,
+                    &faulty_nodes,
+                    Epoch::new(fault_duration),
+                    Epoch::new(recovery_epochs),
+                    slot_duration,
+                )
+                .await?;
+            }
+
+            Ok::<(), String>(())
+        };
+
+        let (add_validators, faults) = futures::join!(add_validators_fut, faults_fut);
+
+        add_validators?;
+        faults?;
+
+        /*
+         * End the simulation by dropping the network. This will kill all running beacon nodes and
+         * validator clients.
+         */
+        println!(
+            "Simulation complete. Finished with {} beacon nodes and {} validator clients",
+            network.beacon_node_count(),
+            network.validator_client_count()
+        );
+
+        // Be explicit about dropping the network, as this kills all the nodes. This ensures
+        // all the checks have adequate time to pass.
+        drop(network);
+        Ok::<(), String>(())
+    };
+
+    env.runtime().block_on(main_future)
+}
+
+/// Injects `fault` into the `faulty_nodes` of the `network` for `fault_duration`, heals it and then
+/// checks that the network has recovered within `recovery_epochs`.
+async fn run_fault(
+    network: &LocalNetwork<E>,
+    fault: Fault,
+    faulty_nodes: &[usize],
+    fault_duration: Epoch,
+    recovery_epochs: Epoch,
+    slot_duration: Duration,
+) -> Result<(), String> {
+    println!("Injecting fault {:?} into nodes {:?}", fault, faulty_nodes);
+    fault.inject(network, faulty_nodes).await?;
+
+    checks::epoch_delay(fault_duration, slot_duration, E::slots_per_epoch()).await;
+
+    println!("Healing fault {:?}", fault);
+    fault.heal(network, faulty_nodes).await?;
+
+    let healed_finalized_epoch = checks::finalized_epochs(network)
+        .await?
+        .into_iter()
+        .max()
+        .unwrap_or_else(|| Epoch::new(0));
+
+    checks::epoch_delay(recovery_epochs, slot_duration, E::slots_per_epoch()).await;
+
+    if network.remote_nodes()?.len() != network.beacon_node_count() {
+        return Err(format!(
+            "Not all beacon nodes are running after healing {:?}",
+            fault
+        ));
+    }
+
+    // Check that the network has recovered.
+    let (finality, slashings, heads) = futures::join!(
+        checks::verify_all_finalized_at_least(network.clone(), healed_finalized_epoch + 1),
+        checks::verify_no_slashings(network.clone()),
+        checks::verify_heads_converged(network.clone(), slot_duration),
+    );
+    finality.map_err(|e| format!("Finality did not resume after {:?}: {}", fault, e))?;
+    slashings.map_err(|e| format!("Slashing occurred during {:?}: {}", fault, e))?;
+    heads.map_err(|e| format!("Heads did not converge after {:?}: {}", fault, e))?;
+
+    println!("Network recovered from fault {:?}", fault);
+
+    Ok(())
+}
@@ -1,25 +1,61 @@
 use node_test_rig::{
     environment::RuntimeContext,
     eth2::{types::StateId, BeaconNodeHttpClient},
+    tempdir::TempDir,
     ClientConfig, LocalBeaconNode, LocalValidatorClient, ValidatorConfig, ValidatorFiles,
 };
 use parking_lot::RwLock;
 use std::ops::Deref;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 use types::{Epoch, EthSpec};
 
 const BOOTNODE_PORT: u16 = 42424;
 
+/// Added to the libp2p ports of partitioned beacon nodes, so that nodes outside of the partition
+/// are unable to dial them.
+const PARTITION_PORT_OFFSET: u16 = 1_000;
+
+/// The maximum time to wait for a stopped beacon node to release its database.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Retains the information required to restart a beacon node after it has been stopped.
+struct BeaconNodeHandle {
+    /// The config that the beacon node was first started with.
+    config: ClientConfig,
+    /// Shuts down all of the tasks of the beacon node when fired or dropped.
+    exit_signal: Option<exit_future::Signal>,
+    /// Holds the data directory of the beacon node whilst it is stopped.
+    stopped_datadir: Option<TempDir>,
+}
+
+/// Retains the information required to restart a validator client after it has been stopped.
+struct ValidatorClientHandle {
+    /// The config that the validator client was first started with.
+    config: ValidatorConfig,
+    /// Shuts down all of the tasks of the validator client when fired or dropped.
+    exit_signal: Option<exit_future::Signal>,
+    /// Holds the keystores and slashing protection database of the validator client whilst it is
+    /// stopped.
+    stopped_files: Option<ValidatorFiles>,
+}
+
 /// Helper struct to reduce `Arc` usage.
 pub struct Inner<E: EthSpec> {
     pub context: RuntimeContext<E>,
-    pub beacon_nodes: RwLock<Vec<LocalBeaconNode<E>>>,
-    pub validator_clients: RwLock<Vec<LocalValidatorClient<E>>>,
+    /// The beacon nodes in the network, or `None` if the node at that index has been stopped.
+    pub beacon_nodes: RwLock<Vec<Option<LocalBeaconNode<E>>>>,
+    /// The validator clients in the network, or `None` if the client at that index has been
+    /// stopped.
+    pub validator_clients: RwLock<Vec<Option<LocalValidatorClient<E>>>>,
+    beacon_node_handles: RwLock<Vec<BeaconNodeHandle>>,
+    validator_client_handles: RwLock<Vec<ValidatorClientHandle>>,
 }
 
 /// Represents a set of interconnected `LocalBeaconNode` and `LocalValidatorClient`.
 ///
-/// Provides functions to allow adding new beacon nodes and validators.
+/// Provides functions to allow adding new beacon nodes and validators, as well as stopping and
+/// restarting them to simulate faults.
 pub struct LocalNetwork<E: EthSpec> {
     inner: Arc<Inner<E>>,
 }
@@ -50,19 +86,22 @@ impl<E: EthSpec> LocalNetwork<E> {
         beacon_config.network.libp2p_port = BOOTNODE_PORT;
         beacon_config.network.enr_udp_port = Some(BOOTNODE_PORT);
         beacon_config.network.enr_tcp_port = Some(BOOTNODE_PORT);
-        let beacon_node =
-            LocalBeaconNode::production(context.service_context("boot_node".into()), beacon_config)
-                .await?;
-        Ok(Self {
+
+        let network = Self {
             inner: Arc::new(Inner {
                 context,
-                beacon_nodes: RwLock::new(vec![beacon_node]),
+                beacon_nodes: RwLock::new(vec![]),
                 validator_clients: RwLock::new(vec![]),
+                beacon_node_handles: RwLock::new(vec![]),
+                validator_client_handles: RwLock::new(vec![]),
             }),
-        })
+        };
+        network.start_new_beacon_node(beacon_config).await?;
+
+        Ok(network)
     }
 
-    /// Returns the number of beacon nodes in the network.
+    /// Returns the number of beacon nodes in the network, including those which are stopped.
     ///
     /// Note: does not count nodes that are external to this `LocalNetwork` that may have connected
     /// (e.g., another Lighthouse process on the same machine.)
@@ -70,7 +109,7 @@ impl<E: EthSpec> LocalNetwork<E> {
         self.beacon_nodes.read().len()
     }
 
-    /// Returns the number of validator clients in the network.
+    /// Returns the number of validator clients in the network, including those which are stopped.
     ///
     /// Note: does not count nodes that are external to this `LocalNetwork` that may have connected
     /// (e.g., another Lighthouse process on the same machine.)
@@ -80,12 +119,14 @@ impl<E: EthSpec> LocalNetwork<E> {
 
     /// Adds a beacon node to the network, connecting to the 0'th beacon node via ENR.
     pub async fn add_beacon_node(&self, mut beacon_config: ClientConfig) -> Result<(), String> {
-        let self_1 = self.clone();
         println!("Adding beacon node..");
         {
             let read_lock = self.beacon_nodes.read();
 
-            let boot_node = read_lock.first().expect("should have at least one node");
+            let boot_node = read_lock
+                .first()
+                .and_then(Option::as_ref)
+                .expect("should have a running boot node");
 
             beacon_config.network.boot_nodes_enr.push(
                 boot_node
@@ -93,24 +134,53 @@ impl<E: EthSpec> LocalNetwork<E> {
                     .enr()
                     .expect("bootnode must have a network"),
             );
-            let count = self.beacon_node_count() as u16;
+            let count = read_lock.len() as u16;
             beacon_config.network.discovery_port = BOOTNODE_PORT + count;
             beacon_config.network.libp2p_port = BOOTNODE_PORT + count;
             beacon_config.network.enr_udp_port = Some(BOOTNODE_PORT + count);
             beacon_config.network.enr_tcp_port = Some(BOOTNODE_PORT + count);
         }
 
+        self.start_new_beacon_node(beacon_config).await
+    }
+
+    /// Starts a beacon node with a new data directory and adds it to the network.
+    async fn start_new_beacon_node(&self, mut beacon_config: ClientConfig) -> Result<(), String> {
         let index = self.beacon_nodes.read().len();
+        let (exit_signal, exit) = exit_future::signal();
 
         let beacon_node = LocalBeaconNode::production(
-            self.context.service_context(format!("node_{}", index)),
-            beacon_config,
+            self.beacon_node_context(index, exit),
+            beacon_config.clone(),
         )
         .await?;
-        self_1.beacon_nodes.write().push(beacon_node);
+
+        // Always use the same HTTP port for this node, so that validator clients are able to
+        // reconnect after it is restarted.
+        if let Some(listen_addr) = beacon_node.client.http_api_listen_addr() {
+            beacon_config.http_api.listen_port = listen_addr.port();
+        }
+
+        self.beacon_nodes.write().push(Some(beacon_node));
+        self.beacon_node_handles.write().push(BeaconNodeHandle {
+            config: beacon_config,
+            exit_signal: Some(exit_signal),
+            stopped_datadir: None,
+        });
         Ok(())
     }
 
+    /// Returns the context for the beacon node with the given `index`, which is shut down when
+    /// `exit` fires.
+    fn beacon_node_context(&self, index: usize, exit: exit_future::Exit) -> RuntimeContext<E> {
+        let service_name = if index == 0 {
+            "boot_node".to_string()
+        } else {
+            format!("node_{}", index)
+        };
+        self.context.service_context_with_exit(service_name, exit)
+    }
+
     /// Adds a validator client to the network, connecting it to the beacon node with index
     /// `beacon_node`.
     pub async fn add_validator_client(
@@ -120,13 +190,12 @@ impl<E: EthSpec> LocalNetwork<E> {
         validator_files: ValidatorFiles,
     ) -> Result<(), String> {
         let index = self.validator_clients.read().len();
-        let context = self.context.service_context(format!("validator_{}", index));
-        let self_1 = self.clone();
         let socket_addr = {
             let read_lock = self.beacon_nodes.read();
             let beacon_node = read_lock
                 .get(beacon_node)
-                .ok_or_else(|| format!("No beacon node for index {}", beacon_node))?;
+                .and_then(Option::as_ref)
+                .ok_or_else(|| format!("No running beacon node for index {}", beacon_node))?;
             beacon_node
                 .client
                 .http_api_listen_addr()
@@ -135,22 +204,233 @@ impl<E: EthSpec> LocalNetwork<E> {
 
         validator_config.beacon_node =
             format!("http://{}:{}", socket_addr.ip(), socket_addr.port());
+
+        let (exit_signal, exit) = exit_future::signal();
+        let context = self
+            .context
+            .service_context_with_exit(format!("validator_{}", index), exit);
         let validator_client = LocalValidatorClient::production_with_insecure_keypairs(
             context,
-            validator_config,
+            validator_config.clone(),
             validator_files,
         )
         .await?;
-        self_1.validator_clients.write().push(validator_client);
+
+        self.validator_clients.write().push(Some(validator_client));
+        self.validator_client_handles
+            .write()
+            .push(ValidatorClientHandle {
+                config: validator_config,
+                exit_signal: Some(exit_signal),
+                stopped_files: None,
+            });
+        Ok(())
+    }
+
+    /// Stops the beacon node with the given `index`, retaining its database so that it may be
+    /// restarted with `Self::restart_beacon_node`.
+    pub async fn stop_beacon_node(&self, index: usize) -> Result<(), String> {
+        println!("Stopping beacon node {}..", index);
+
+        let beacon_node = self
+            .beacon_nodes
+            .write()
+            .get_mut(index)
+            .and_then(Option::take)
+            .ok_or_else(|| format!("No running beacon node for index {}", index))?;
+
+        // Firing the exit signal shuts down all of the tasks of the node.
+        drop(self.beacon_node_handles.write()[index].exit_signal.take());
+
+        let LocalBeaconNode { client, datadir } = beacon_node;
+        let beacon_chain = client.beacon_chain().map(|chain| Arc::downgrade(&chain));
+        drop(client);
+
+        // The database can only be opened by a single node at once, so wait for the old node to
+        // release it before allowing a restart.
+        if let Some(beacon_chain) = beacon_chain {
+            wait_for_drop(beacon_chain).await?;
+        }
+
+        self.beacon_node_handles.write()[index].stopped_datadir = Some(datadir);
+        Ok(())
+    }
+
+    /// Restarts a beacon node previously stopped with `Self::stop_beacon_node`, using the same
+    /// config it was first started with.
+    pub async fn restart_beacon_node(&self, index: usize) -> Result<(), String> {
+        println!("Restarting beacon node {}..", index);
+
+        let config = self
+            .beacon_node_handles
+            .read()
+            .get(index)
+            .map(|handle| handle.config.clone())
+            .ok_or_else(|| format!("No beacon node for index {}", index))?;
+
+        self.start_stopped_beacon_node(index, config).await
+    }
+
+    /// Restarts each of the beacon nodes in `indices` such that they are only able to connect to
+    /// each other, partitioning them from the rest of the network.
+    ///
+    /// The partition can be healed with `Self::heal_partition`.
+    pub async fn partition_beacon_nodes(&self, indices: &[usize]) -> Result<(), String> {
+        if indices.contains(&0) {
+            return Err("The boot node cannot be partitioned".to_string());
+        }
+
+        println!("Partitioning beacon nodes {:?}..", indices);
+
+        let mut partition_addrs = vec![];
+        for &index in indices {
+            self.stop_beacon_node(index).await?;
+
+            let mut config = self.beacon_node_handles.read()[index].config.clone();
+            let port = config.network.libp2p_port + PARTITION_PORT_OFFSET;
+
+            // Discovery is disabled so the node is unable to find (or be found by) nodes outside
+            // of the partition. Instead, it dials the nodes already in the partition directly.
+            config.network.disable_discovery = true;
+            config.network.boot_nodes_enr.clear();
+            config.network.boot_nodes_multiaddr = partition_addrs.clone();
+            config.network.libp2p_port = port;
+            config.network.discovery_port = port;
+            config.network.enr_udp_port = Some(port);
+            config.network.enr_tcp_port = Some(port);
+
+            partition_addrs.push(
+                format!("/ip4/127.0.0.1/tcp/{}", port)
+                    .parse()
+                    .map_err(|e| format!("Unable to parse multiaddr: {:?}", e))?,
+            );
+
+            self.start_stopped_beacon_node(index, config).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Restarts each of the beacon nodes in `indices` such that every message they send to their
+    /// peers is delayed by `latency`.
+    ///
+    /// The delay can be removed by stopping and restarting the nodes with
+    /// `Self::restart_beacon_node`.
+    pub async fn delay_beacon_nodes(
+        &self,
+        indices: &[usize],
+        latency: Duration,
+    ) -> Result<(), String> {
+        println!(
+            "Adding {:?} of latency to beacon nodes {:?}..",
+            latency, indices
+        );
+
+        for &index in indices {
+            self.stop_beacon_node(index).await?;
+
+            let mut config = self.beacon_node_handles.read()[index].config.clone();
+            config.network.transport_latency = Some(latency);
+
+            self.start_stopped_beacon_node(index, config).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reconnects beacon nodes which were partitioned with `Self::partition_beacon_nodes` to the
+    /// rest of the network.
+    pub async fn heal_partition(&self, indices: &[usize]) -> Result<(), String> {
+        println!("Healing partition of beacon nodes {:?}..", indices);
+
+        for &index in indices {
+            self.stop_beacon_node(index).await?;
+            self.restart_beacon_node(index).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Starts the stopped beacon node with the given `index` using `config`.
+    async fn start_stopped_beacon_node(
+        &self,
+        index: usize,
+        config: ClientConfig,
+    ) -> Result<(), String> {
+        let datadir = self
+            .beacon_node_handles
+            .write()
+            .get_mut(index)
+            .and_then(|handle| handle.stopped_datadir.take())
+            .ok_or_else(|| format!("No stopped beacon node for index {}", index))?;
+
+        let (exit_signal, exit) = exit_future::signal();
+        let beacon_node = LocalBeaconNode::production_with_datadir(
+            self.beacon_node_context(index, exit),
+            config,
+            datadir,
+        )
+        .await?;
+
+        self.beacon_nodes.write()[index] = Some(beacon_node);
+        self.beacon_node_handles.write()[index].exit_signal = Some(exit_signal);
+        Ok(())
+    }
+
+    /// Stops the validator client with the given `index`, retaining its keystores and slashing
+    /// protection database so that it may be restarted with `Self::restart_validator_client`.
+    pub fn stop_validator_client(&self, index: usize) -> Result<(), String> {
+        println!("Stopping validator client {}..", index);
+
+        let validator_client = self
+            .validator_clients
+            .write()
+            .get_mut(index)
+            .and_then(Option::take)
+            .ok_or_else(|| format!("No running validator client for index {}", index))?;
+
+        let mut handles = self.validator_client_handles.write();
+        drop(handles[index].exit_signal.take());
+        handles[index].stopped_files = Some(validator_client.files);
+        Ok(())
+    }
+
+    /// Restarts a validator client previously stopped with `Self::stop_validator_client`.
+    pub async fn restart_validator_client(&self, index: usize) -> Result<(), String> {
+        println!("Restarting validator client {}..", index);
+
+        let (config, files) = {
+            let mut handles = self.validator_client_handles.write();
+            let handle = handles
+                .get_mut(index)
+                .ok_or_else(|| format!("No validator client for index {}", index))?;
+            let files = handle
+                .stopped_files
+                .take()
+                .ok_or_else(|| format!("No stopped validator client for index {}", index))?;
+            (handle.config.clone(), files)
+        };
+
+        let (exit_signal, exit) = exit_future::signal();
+        let context = self
+            .context
+            .service_context_with_exit(format!("validator_{}", index), exit);
+        let validator_client =
+            LocalValidatorClient::production_with_insecure_keypairs(context, config, files).await?;
+
+        self.validator_clients.write()[index] = Some(validator_client);
+        self.validator_client_handles.write()[index].exit_signal = Some(exit_signal);
         Ok(())
     }
 
-    /// For all beacon nodes in `Self`, return a HTTP client to access each nodes HTTP API.
+    /// For all running beacon nodes in `Self`, return a HTTP client to access each nodes HTTP
+    /// API.
     pub fn remote_nodes(&self) -> Result<Vec<BeaconNodeHttpClient>, String> {
         let beacon_nodes = self.beacon_nodes.read();
 
         beacon_nodes
             .iter()
+            .flatten()
             .map(|beacon_node| beacon_node.remote_node())
             .collect()
     }
@@ -166,3 +446,19 @@ impl<E: EthSpec> LocalNetwork<E> {
             .map(|body| body.unwrap().data.finalized.epoch)
     }
 }
+
+/// Waits until all strong references to `item` have been dropped, or `SHUTDOWN_TIMEOUT` elapses.
+async fn wait_for_drop<T>(item: Weak<T>) -> Result<(), String> {
+    let poll_interval = Duration::from_millis(100);
+    let mut waited = Duration::from_secs(0);
+
+    while item.upgrade().is_some() {
+        if waited >= SHUTDOWN_TIMEOUT {
+            return Err("Timed out waiting for beacon node to shut down".to_string());
+        }
+        tokio::time::delay_for(poll_interval).await;
+        waited += poll_interval;
+    }
+
+    Ok(())
+}
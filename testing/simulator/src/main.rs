@@ -12,6 +12,9 @@
 //! simulation uses `println` to communicate some info. It might be nice if the nodes logged to
 //! easy-to-find files and stdout only contained info from the simulation.
 //!
+//! The `fault-sim` can stop, restart, partition and delay nodes, but it cannot yet inject packet
+//! loss between nodes.
+//!
 
 #[macro_use]
 extern crate clap;
@@ -19,6 +22,7 @@ extern crate clap;
 mod checks;
 mod cli;
 mod eth1_sim;
+mod fault_sim;
 mod local_network;
 mod no_eth1_sim;
 mod sync_sim;
@@ -50,6 +54,13 @@ fn main() {
                 std::process::exit(1)
             }
         },
+        ("fault-sim", Some(matches)) => match fault_sim::run_fault_sim(matches) {
+            Ok(()) => println!("Simulation exited successfully"),
+            Err(e) => {
+                eprintln!("Simulation exited with error: {}", e);
+                std::process::exit(1)
+            }
+        },
         ("syncing-sim", Some(matches)) => match sync_sim::run_syncing_sim(matches) {
             Ok(()) => println!("Simulation exited successfully"),
             Err(e) => {
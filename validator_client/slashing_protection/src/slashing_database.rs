@@ -691,6 +691,41 @@ impl SlashingDatabase {
         Ok(Interchange { metadata, data })
     }
 
+    /// Export the slashing protection history of the validators in `public_keys`.
+    ///
+    /// Registered validators that have not signed any messages are included with an empty
+    /// history, whilst unregistered validators are omitted.
+    pub fn export_interchange_info_for_validators(
+        &self,
+        genesis_validators_root: Hash256,
+        public_keys: &[PublicKey],
+    ) -> Result<Interchange, InterchangeError> {
+        let mut interchange = self.export_interchange_info(genesis_validators_root)?;
+        interchange
+            .data
+            .retain(|record| public_keys.contains(&record.pubkey));
+
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction()?;
+
+        for pubkey in public_keys {
+            let exported = interchange
+                .data
+                .iter()
+                .any(|record| record.pubkey == *pubkey);
+
+            if !exported && self.get_validator_id_opt(&txn, pubkey)?.is_some() {
+                interchange.data.push(InterchangeData {
+                    pubkey: pubkey.clone(),
+                    signed_blocks: vec![],
+                    signed_attestations: vec![],
+                });
+            }
+        }
+
+        Ok(interchange)
+    }
+
     pub fn num_validator_rows(&self) -> Result<u32, NotSafe> {
         let mut conn = self.conn_pool.get()?;
         let txn = conn.transaction()?;
@@ -803,4 +838,37 @@ mod tests {
         let db2 = SlashingDatabase::open(&file).unwrap();
         check(&db2);
     }
+
+    // Only the requested, registered validators should be exported.
+    #[test]
+    fn export_for_validators() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("db.sqlite");
+        let db = SlashingDatabase::create(&file).unwrap();
+
+        db.register_validators([pubkey(0), pubkey(1), pubkey(2)].iter())
+            .unwrap();
+        for i in 0..2 {
+            db.check_and_insert_block_signing_root(&pubkey(i), Slot::new(1), Hash256::zero())
+                .unwrap();
+        }
+
+        let interchange = db
+            .export_interchange_info_for_validators(
+                Hash256::zero(),
+                &[pubkey(1), pubkey(2), pubkey(3)],
+            )
+            .unwrap();
+
+        assert_eq!(interchange.len(), 2);
+        let record = |pk: PublicKey| {
+            interchange
+                .data
+                .iter()
+                .find(|record| record.pubkey == pk)
+                .unwrap()
+        };
+        assert_eq!(record(pubkey(1)).signed_blocks.len(), 1);
+        assert!(record(pubkey(2)).signed_blocks.is_empty());
+    }
 }
@@ -1,22 +1,33 @@
 use crate::{
     duties_service::{DutiesService, DutyAndProof},
+    http_metrics::metrics,
     validator_store::ValidatorStore,
 };
 use environment::RuntimeContext;
-use eth2::BeaconNodeHttpClient;
-use futures::StreamExt;
-use slog::{crit, error, info, trace};
+use eth2::{types::BlockId, BeaconNodeHttpClient};
+use futures::{FutureExt, StreamExt, TryFutureExt};
+use slog::{crit, debug, error, info, trace, Logger};
 use slot_clock::SlotClock;
 use std::collections::HashMap;
+use std::fmt::Debug;
+use std::future::Future;
 use std::ops::Deref;
 use std::sync::Arc;
-use tokio::time::{delay_until, interval_at, Duration, Instant};
+use tokio::time::{delay_until, interval_at, timeout_at, Duration, Instant};
 use tree_hash::TreeHash;
 use types::{
     AggregateSignature, Attestation, AttestationData, BitList, ChainSpec, CommitteeIndex, EthSpec,
     Slot,
 };
 
+/// The interval at which the beacon node is polled for a head block in the current slot, whilst
+/// waiting to attest.
+const HEAD_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The service is woken at the start of each slot, but the slot clock may still read the previous
+/// slot. This tolerance ensures the upcoming slot is read instead.
+const SLOT_START_TOLERANCE: Duration = Duration::from_millis(500);
+
 /// Builds an `AttestationService`.
 pub struct AttestationServiceBuilder<T, E: EthSpec> {
     duties_service: Option<DutiesService<T, E>>,
@@ -24,6 +35,9 @@ pub struct AttestationServiceBuilder<T, E: EthSpec> {
     slot_clock: Option<T>,
     beacon_node: Option<BeaconNodeHttpClient>,
     context: Option<RuntimeContext<E>>,
+    attestation_delay: Option<Duration>,
+    aggregation_delay: Option<Duration>,
+    early_attestations: bool,
 }
 
 impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
@@ -34,6 +48,9 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
             slot_clock: None,
             beacon_node: None,
             context: None,
+            attestation_delay: None,
+            aggregation_delay: None,
+            early_attestations: true,
         }
     }
 
@@ -62,6 +79,27 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
         self
     }
 
+    /// Set the delay after the start of the slot at which attestations are produced, if they
+    /// haven't already been triggered by a head block. Defaults to 1/3rd of the slot.
+    pub fn attestation_delay(mut self, delay: Option<Duration>) -> Self {
+        self.attestation_delay = delay;
+        self
+    }
+
+    /// Set the delay after the start of the slot at which aggregates are produced. Defaults to
+    /// 2/3rds of the slot.
+    pub fn aggregation_delay(mut self, delay: Option<Duration>) -> Self {
+        self.aggregation_delay = delay;
+        self
+    }
+
+    /// Set to `true` to produce attestations as soon as the beacon node has a head block in the
+    /// current slot.
+    pub fn early_attestations(mut self, early_attestations: bool) -> Self {
+        self.early_attestations = early_attestations;
+        self
+    }
+
     pub fn build(self) -> Result<AttestationService<T, E>, String> {
         Ok(AttestationService {
             inner: Arc::new(Inner {
@@ -80,6 +118,9 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
                 context: self
                     .context
                     .ok_or_else(|| "Cannot build AttestationService without runtime_context")?,
+                attestation_delay: self.attestation_delay,
+                aggregation_delay: self.aggregation_delay,
+                early_attestations: self.early_attestations,
             }),
         })
     }
@@ -92,9 +133,14 @@ pub struct Inner<T, E: EthSpec> {
     slot_clock: T,
     beacon_node: BeaconNodeHttpClient,
     context: RuntimeContext<E>,
+    attestation_delay: Option<Duration>,
+    aggregation_delay: Option<Duration>,
+    early_attestations: bool,
 }
 
-/// Attempts to produce attestations for all known validators 1/3rd of the way through each slot.
+/// Attempts to produce attestations for all known validators 1/3rd of the way through each slot,
+/// or as soon as the beacon node has a head block in the slot, whichever comes first. Aggregates
+/// are produced 2/3rds of the way through each slot. Both delays are configurable.
 ///
 /// If any validators are on the same committee, a single attestation will be downloaded and
 /// returned to the beacon node. This attestation will have a signature from each of the
@@ -125,6 +171,12 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
         let log = self.context.log().clone();
 
         let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
+        let (attestation_delay, aggregation_delay) = attestation_delays(
+            slot_duration,
+            self.attestation_delay,
+            self.aggregation_delay,
+        )?;
+
         let duration_to_next_slot = self
             .slot_clock
             .duration_to_next_slot()
@@ -133,15 +185,15 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
         info!(
             log,
             "Attestation production service started";
-            "next_update_millis" => duration_to_next_slot.as_millis()
+            "next_update_millis" => duration_to_next_slot.as_millis(),
+            "attestation_delay_millis" => attestation_delay.as_millis(),
+            "aggregation_delay_millis" => aggregation_delay.as_millis(),
+            "early_attestations" => self.early_attestations,
         );
 
         let mut interval = {
             // Note: `interval_at` panics if `slot_duration` is 0
-            interval_at(
-                Instant::now() + duration_to_next_slot + slot_duration / 3,
-                slot_duration,
-            )
+            interval_at(Instant::now() + duration_to_next_slot, slot_duration)
         };
 
         let executor = self.context.executor.clone();
@@ -150,17 +202,17 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
             while interval.next().await.is_some() {
                 let log = self.context.log();
 
-                if let Err(e) = self.spawn_attestation_tasks(slot_duration) {
-                    crit!(
-                        log,
-                        "Failed to spawn attestation tasks";
-                        "error" => e
-                    )
+                if let Some(slot) = self
+                    .slot_clock
+                    .now_with_future_tolerance(SLOT_START_TOLERANCE)
+                {
+                    self.inner.context.executor.spawn(
+                        self.clone()
+                            .attest_at_slot(slot, attestation_delay, aggregation_delay),
+                        "attestation_service",
+                    );
                 } else {
-                    trace!(
-                        log,
-                        "Spawned attestation tasks";
-                    )
+                    crit!(log, "Failed to read slot clock");
                 }
             }
         };
@@ -169,25 +221,79 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
         Ok(())
     }
 
-    /// For each each required attestation, spawn a new task that downloads, signs and uploads the
-    /// attestation to the beacon node.
-    fn spawn_attestation_tasks(&self, slot_duration: Duration) -> Result<(), String> {
-        let slot = self
-            .slot_clock
-            .now()
-            .ok_or_else(|| "Failed to read slot clock".to_string())?;
-        let duration_to_next_slot = self
-            .slot_clock
-            .duration_to_next_slot()
-            .ok_or_else(|| "Unable to determine duration to next slot".to_string())?;
+    /// Waits until it is time to attest in `slot`, then spawns the attestation tasks.
+    async fn attest_at_slot(
+        self,
+        slot: Slot,
+        attestation_delay: Duration,
+        aggregation_delay: Duration,
+    ) {
+        let log = self.context.log();
+
+        let slot_start = if let Some(instant) = self.slot_start_instant(slot) {
+            instant
+        } else {
+            crit!(log, "Failed to read slot clock");
+            return;
+        };
+        let attestation_instant = slot_start + attestation_delay;
+
+        // There's no need to poll the beacon node if we have no validators to attest with. Duties
+        // may still be learned before the deadline, so wait for it regardless.
+        let trigger = if self.early_attestations && !self.duties_service.attesters(slot).is_empty()
+        {
+            self.wait_for_head_block(slot, attestation_instant).await
+        } else {
+            delay_until(attestation_instant).await;
+            metrics::DEADLINE
+        };
+        metrics::inc_counter_vec(&metrics::ATTESTATION_TRIGGERS, &[trigger]);
+
+        if let Err(e) = self.spawn_attestation_tasks(slot, slot_start + aggregation_delay) {
+            crit!(
+                log,
+                "Failed to spawn attestation tasks";
+                "error" => e
+            )
+        } else {
+            trace!(
+                log,
+                "Spawned attestation tasks";
+                "trigger" => trigger,
+            )
+        }
+    }
 
-        // If a validator needs to publish an aggregate attestation, they must do so at 2/3
-        // through the slot. This delay triggers at this time
-        let aggregate_production_instant = Instant::now()
-            + duration_to_next_slot
-                .checked_sub(slot_duration / 3)
-                .unwrap_or_else(|| Duration::from_secs(0));
+    /// Polls the beacon node until its head block is in `slot`, or until `deadline` is reached.
+    ///
+    /// Returns the metrics label for the event that ended the wait.
+    async fn wait_for_head_block(&self, slot: Slot, deadline: Instant) -> &'static str {
+        wait_for_head_slot(slot, deadline, self.context.log(), || {
+            self.beacon_node
+                .get_beacon_headers_block_id(BlockId::Head)
+                .map_ok(|response| response.map(|response| response.data.header.message.slot))
+        })
+        .await
+    }
+
+    /// Returns the `Instant` at which `slot` starts, according to the slot clock.
+    fn slot_start_instant(&self, slot: Slot) -> Option<Instant> {
+        let now = self.slot_clock.now_duration()?;
+        let slot_start = self.slot_clock.start_of(slot)?;
+
+        Some(match now.checked_sub(slot_start) {
+            Some(since_start) => Instant::now() - since_start,
+            None => Instant::now() + (slot_start - now),
+        })
+    }
 
+    /// For each each required attestation, spawn a new task that downloads, signs and uploads the
+    /// attestation to the beacon node.
+    fn spawn_attestation_tasks(
+        &self,
+        slot: Slot,
+        aggregate_production_instant: Instant,
+    ) -> Result<(), String> {
         let duties_by_committee_index: HashMap<CommitteeIndex, Vec<DutyAndProof>> = self
             .duties_service
             .attesters(slot)
@@ -210,13 +316,16 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
             .into_iter()
             .for_each(|(committee_index, validator_duties)| {
                 // Spawn a separate task for each attestation.
-                self.inner.context.executor.runtime_handle().spawn(
-                    self.clone().publish_attestations_and_aggregates(
-                        slot,
-                        committee_index,
-                        validator_duties,
-                        aggregate_production_instant,
-                    ),
+                self.inner.context.executor.spawn(
+                    self.clone()
+                        .publish_attestations_and_aggregates(
+                            slot,
+                            committee_index,
+                            validator_duties,
+                            aggregate_production_instant,
+                        )
+                        .map(|_| ()),
+                    "attestation_service",
                 );
             });
 
@@ -394,20 +503,34 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
             }
         }
 
-        match self
+        let result = self
             .beacon_node
             .post_beacon_pool_attestations(attestations.as_slice())
-            .await
-        {
-            Ok(()) => info!(
-                log,
-                "Successfully published attestations";
-                "count" => attestations.len(),
-                "head_block" => ?attestation_data.beacon_block_root,
-                "committee_index" => attestation_data.index,
-                "slot" => attestation_data.slot.as_u64(),
-                "type" => "unaggregated",
-            ),
+            .await;
+        metrics::observe_published_messages(
+            &self.beacon_node,
+            metrics::ATTESTATION,
+            attestations.len(),
+            result.is_ok(),
+        );
+
+        match result {
+            Ok(()) => {
+                metrics::observe_publication_delay(
+                    &self.slot_clock,
+                    attestation_data.slot,
+                    metrics::ATTESTATION,
+                );
+                info!(
+                    log,
+                    "Successfully published attestations";
+                    "count" => attestations.len(),
+                    "head_block" => ?attestation_data.beacon_block_root,
+                    "committee_index" => attestation_data.index,
+                    "slot" => attestation_data.slot.as_u64(),
+                    "type" => "unaggregated",
+                )
+            }
             Err(e) => error!(
                 log,
                 "Unable to publish attestations";
@@ -493,12 +616,24 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
         }
 
         if !signed_aggregate_and_proofs.is_empty() {
-            match self
+            let result = self
                 .beacon_node
                 .post_validator_aggregate_and_proof(signed_aggregate_and_proofs.as_slice())
-                .await
-            {
+                .await;
+            metrics::observe_published_messages(
+                &self.beacon_node,
+                metrics::AGGREGATE,
+                signed_aggregate_and_proofs.len(),
+                result.is_ok(),
+            );
+
+            match result {
                 Ok(()) => {
+                    metrics::observe_publication_delay(
+                        &self.slot_clock,
+                        attestation_data.slot,
+                        metrics::AGGREGATE,
+                    );
                     for signed_aggregate_and_proof in signed_aggregate_and_proofs {
                         let attestation = &signed_aggregate_and_proof.message.aggregate;
                         info!(
@@ -533,12 +668,74 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
     }
 }
 
+/// Returns the delays after the start of the slot at which attestations and aggregates are
+/// produced, using 1/3rd and 2/3rds of `slot_duration` unless they are configured.
+fn attestation_delays(
+    slot_duration: Duration,
+    attestation_delay: Option<Duration>,
+    aggregation_delay: Option<Duration>,
+) -> Result<(Duration, Duration), String> {
+    let attestation_delay = attestation_delay.unwrap_or(slot_duration / 3);
+    let aggregation_delay = aggregation_delay.unwrap_or(slot_duration * 2 / 3);
+
+    if attestation_delay > aggregation_delay || aggregation_delay >= slot_duration {
+        return Err(format!(
+            "Invalid attestation timing: the attestation delay ({:?}) must not exceed the \
+             aggregation delay ({:?}), which must be less than the slot duration ({:?})",
+            attestation_delay, aggregation_delay, slot_duration
+        ));
+    }
+
+    Ok((attestation_delay, aggregation_delay))
+}
+
+/// Calls `poll_head_slot` every `HEAD_POLL_INTERVAL` until it returns a slot that is at least
+/// `slot`, or until `deadline` is reached.
+///
+/// Returns the metrics label for the event that ended the wait.
+async fn wait_for_head_slot<F, R, Err>(
+    slot: Slot,
+    deadline: Instant,
+    log: &Logger,
+    mut poll_head_slot: F,
+) -> &'static str
+where
+    F: FnMut() -> R,
+    R: Future<Output = Result<Option<Slot>, Err>>,
+    Err: Debug,
+{
+    loop {
+        match timeout_at(deadline, poll_head_slot()).await {
+            Ok(Ok(Some(head_slot))) if head_slot >= slot => return metrics::HEAD_BLOCK,
+            Ok(Ok(_)) => (),
+            Ok(Err(e)) => debug!(
+                log,
+                "Failed to poll for head block";
+                "error" => ?e,
+                "slot" => slot.as_u64(),
+            ),
+            Err(_) => return metrics::DEADLINE,
+        }
+
+        let next_poll = Instant::now() + HEAD_POLL_INTERVAL;
+        if next_poll >= deadline {
+            delay_until(deadline).await;
+            return metrics::DEADLINE;
+        }
+        delay_until(next_poll).await;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use futures::future::FutureExt;
+    use futures::future;
     use parking_lot::RwLock;
 
+    fn null_logger() -> Logger {
+        Logger::root(slog::Discard, slog::o!())
+    }
+
     /// This test is to ensure that a `tokio_timer::Delay` with an instant in the past will still
     /// trigger.
     #[tokio::test]
@@ -556,4 +753,79 @@ mod tests {
             "state should have been updated"
         );
     }
+
+    #[test]
+    fn attestation_delays_default_to_fractions_of_the_slot() {
+        let slot_duration = Duration::from_secs(12);
+
+        assert_eq!(
+            attestation_delays(slot_duration, None, None),
+            Ok((Duration::from_secs(4), Duration::from_secs(8)))
+        );
+    }
+
+    #[test]
+    fn attestation_delays_are_configurable() {
+        let slot_duration = Duration::from_secs(12);
+
+        assert_eq!(
+            attestation_delays(slot_duration, Some(Duration::from_secs(2)), None),
+            Ok((Duration::from_secs(2), Duration::from_secs(8)))
+        );
+        assert_eq!(
+            attestation_delays(
+                slot_duration,
+                Some(Duration::from_secs(6)),
+                Some(Duration::from_secs(6))
+            ),
+            Ok((Duration::from_secs(6), Duration::from_secs(6)))
+        );
+
+        // Attestations must not be produced after aggregates.
+        assert!(attestation_delays(slot_duration, Some(Duration::from_secs(9)), None).is_err());
+        // Aggregates must be produced within the slot.
+        assert!(attestation_delays(slot_duration, None, Some(slot_duration)).is_err());
+    }
+
+    #[tokio::test]
+    async fn wait_for_head_slot_returns_on_head_block() {
+        let slot = Slot::new(10);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut responses =
+            vec![Ok(None), Err("offline"), Ok(Some(slot - 1)), Ok(Some(slot))].into_iter();
+        let mut polls = 0;
+
+        let trigger = wait_for_head_slot(slot, deadline, &null_logger(), || {
+            polls += 1;
+            future::ready(responses.next().expect("should not poll after head block"))
+        })
+        .await;
+
+        assert_eq!(trigger, metrics::HEAD_BLOCK);
+        assert_eq!(polls, 4);
+        assert!(Instant::now() < deadline);
+    }
+
+    #[tokio::test]
+    async fn wait_for_head_slot_returns_at_deadline() {
+        let slot = Slot::new(10);
+
+        // The head block never reaches `slot`.
+        let deadline = Instant::now() + HEAD_POLL_INTERVAL * 2 + HEAD_POLL_INTERVAL / 2;
+        let trigger = wait_for_head_slot(slot, deadline, &null_logger(), || {
+            future::ready(Ok::<_, ()>(Some(slot - 1)))
+        })
+        .await;
+        assert_eq!(trigger, metrics::DEADLINE);
+        assert!(Instant::now() >= deadline);
+
+        // The beacon node never responds.
+        let deadline = Instant::now() + HEAD_POLL_INTERVAL;
+        let trigger = wait_for_head_slot(slot, deadline, &null_logger(), || {
+            future::pending::<Result<Option<Slot>, ()>>()
+        })
+        .await;
+        assert_eq!(trigger, metrics::DEADLINE);
+        assert!(Instant::now() >= deadline);
+    }
 }
@@ -1,4 +1,4 @@
-use crate::validator_store::ValidatorStore;
+use crate::{http_metrics::metrics, validator_store::ValidatorStore};
 use environment::RuntimeContext;
 use eth2::{types::Graffiti, BeaconNodeHttpClient};
 use futures::channel::mpsc::Receiver;
@@ -231,10 +231,19 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockService<T, E> {
             .sign_block(&validator_pubkey, block, current_slot)
             .ok_or_else(|| "Unable to sign block".to_string())?;
 
-        self.beacon_node
-            .post_beacon_blocks(&signed_block)
-            .await
-            .map_err(|e| format!("Error from beacon node when publishing block: {:?}", e))?;
+        let result = self.beacon_node.post_beacon_blocks(&signed_block).await;
+        metrics::observe_published_messages(
+            &self.beacon_node,
+            metrics::BEACON_BLOCK,
+            1,
+            result.is_ok(),
+        );
+        result.map_err(|e| format!("Error from beacon node when publishing block: {:?}", e))?;
+        metrics::observe_publication_delay(
+            &*self.slot_clock,
+            signed_block.slot(),
+            metrics::BEACON_BLOCK,
+        );
 
         info!(
             log,
@@ -100,6 +100,37 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("GRAFFITI")
                 .takes_value(true)
         )
+        /* Attestation timing arguments */
+        .arg(
+            Arg::with_name("attestation-delay-ms")
+                .long("attestation-delay-ms")
+                .value_name("MILLISECONDS")
+                .help(
+                    "The delay after the start of a slot at which attestations are produced, if \
+                     the beacon node has not already imported a block in that slot. Defaults to \
+                     one third of a slot."
+                )
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("aggregation-delay-ms")
+                .long("aggregation-delay-ms")
+                .value_name("MILLISECONDS")
+                .help(
+                    "The delay after the start of a slot at which aggregate attestations are \
+                     produced. Defaults to two thirds of a slot."
+                )
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("disable-early-attestations")
+                .long("disable-early-attestations")
+                .help(
+                    "If present, always wait until the attestation delay before attesting, \
+                     rather than attesting as soon as the beacon node imports a block in the \
+                     current slot."
+                )
+        )
         /* REST API related arguments */
         .arg(
             Arg::with_name("http")
@@ -138,4 +169,37 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     address of this server (e.g., http://localhost:5062).")
                 .takes_value(true),
         )
+        /* Prometheus metrics HTTP server related arguments */
+        .arg(
+            Arg::with_name("metrics")
+                .long("metrics")
+                .help("Enable the Prometheus metrics HTTP server. Disabled by default.")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("metrics-address")
+                .long("metrics-address")
+                .value_name("ADDRESS")
+                .help("Set the listen address for the Prometheus metrics HTTP server.")
+                .default_value("127.0.0.1")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("metrics-port")
+                .long("metrics-port")
+                .value_name("PORT")
+                .help("Set the listen TCP port for the Prometheus metrics HTTP server.")
+                .default_value("5064")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("metrics-allow-origin")
+                .long("metrics-allow-origin")
+                .value_name("ORIGIN")
+                .help("Set the value of the Access-Control-Allow-Origin response HTTP header. \
+                    Use * to allow any origin (not recommended in production). \
+                    If no value is supplied, the CORS allowed origin is set to the listen \
+                    address of this server (e.g., http://localhost:5064).")
+                .takes_value(true),
+        )
 }
@@ -1,4 +1,4 @@
-use crate::http_api;
+use crate::{http_api, http_metrics};
 use clap::ArgMatches;
 use clap_utils::{parse_optional, parse_required};
 use directory::{
@@ -9,6 +9,7 @@ use eth2::types::Graffiti;
 use serde_derive::{Deserialize, Serialize};
 use slog::{warn, Logger};
 use std::fs;
+use std::net::Ipv4Addr;
 use std::path::PathBuf;
 use types::GRAFFITI_BYTES_LEN;
 
@@ -36,8 +37,19 @@ pub struct Config {
     pub init_slashing_protection: bool,
     /// Graffiti to be inserted everytime we create a block.
     pub graffiti: Option<Graffiti>,
+    /// The delay after the start of a slot before attesting, in milliseconds. Defaults to a third
+    /// of a slot.
+    pub attestation_delay_ms: Option<u64>,
+    /// The delay after the start of a slot before aggregating, in milliseconds. Defaults to two
+    /// thirds of a slot.
+    pub aggregation_delay_ms: Option<u64>,
+    /// If true, attest as soon as the beacon node has a head block in the current slot, rather
+    /// than always waiting for the attestation delay.
+    pub early_attestations: bool,
     /// Configuration for the HTTP REST API.
     pub http_api: http_api::Config,
+    /// Configuration for the HTTP metrics server.
+    pub http_metrics: http_metrics::Config,
 }
 
 impl Default for Config {
@@ -60,7 +72,11 @@ impl Default for Config {
             disable_auto_discover: false,
             init_slashing_protection: false,
             graffiti: None,
+            attestation_delay_ms: None,
+            aggregation_delay_ms: None,
+            early_attestations: true,
             http_api: <_>::default(),
+            http_metrics: <_>::default(),
         }
     }
 }
@@ -143,6 +159,14 @@ impl Config {
             }
         }
 
+        /*
+         * Attestation timing
+         */
+
+        config.attestation_delay_ms = parse_optional(cli_args, "attestation-delay-ms")?;
+        config.aggregation_delay_ms = parse_optional(cli_args, "aggregation-delay-ms")?;
+        config.early_attestations = !cli_args.is_present("disable-early-attestations");
+
         /*
          * Http API server
          */
@@ -166,6 +190,35 @@ impl Config {
             config.http_api.allow_origin = Some(allow_origin.to_string());
         }
 
+        /*
+         * Prometheus metrics HTTP server
+         */
+
+        if cli_args.is_present("metrics") {
+            config.http_metrics.enabled = true;
+        }
+
+        if let Some(address) = cli_args.value_of("metrics-address") {
+            config.http_metrics.listen_addr = address
+                .parse::<Ipv4Addr>()
+                .map_err(|_| "metrics-address is not a valid IPv4 address.")?;
+        }
+
+        if let Some(port) = cli_args.value_of("metrics-port") {
+            config.http_metrics.listen_port = port
+                .parse::<u16>()
+                .map_err(|_| "metrics-port is not a valid u16.")?;
+        }
+
+        if let Some(allow_origin) = cli_args.value_of("metrics-allow-origin") {
+            // Pre-validate the config value to give feedback to the user on node startup, instead of
+            // as late as when the first API response is produced.
+            hyper::header::HeaderValue::from_str(allow_origin)
+                .map_err(|_| "Invalid allow-origin value")?;
+
+            config.http_metrics.allow_origin = Some(allow_origin.to_string());
+        }
+
         Ok(config)
     }
 }
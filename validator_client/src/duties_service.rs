@@ -1,5 +1,7 @@
 use crate::{
-    block_service::BlockServiceNotification, is_synced::is_synced, validator_duty::ValidatorDuty,
+    block_service::BlockServiceNotification,
+    is_synced::is_synced,
+    validator_duty::{DependentRoots, ValidatorDuty},
     validator_store::ValidatorStore,
 };
 use environment::RuntimeContext;
@@ -7,6 +9,7 @@ use eth2::BeaconNodeHttpClient;
 use futures::channel::mpsc::Sender;
 use futures::{SinkExt, StreamExt};
 use parking_lot::RwLock;
+use rayon::prelude::*;
 use slog::{debug, error, trace, warn};
 use slot_clock::SlotClock;
 use std::collections::HashMap;
@@ -78,6 +81,7 @@ impl DutyAndProof {
         self.selection_proof_eq(other)
             && self.duty.validator_index == other.duty.validator_index
             && self.duty.attestation_committee_index == other.duty.attestation_committee_index
+            && self.duty.committee_count_at_slot == other.duty.committee_count_at_slot
             && self.duty.attestation_slot == other.duty.attestation_slot
     }
 
@@ -86,7 +90,8 @@ impl DutyAndProof {
     /// It's important to note that this doesn't actually check `self.selection_proof`, instead it
     /// checks to see if the inputs to computing the selection proof are equal.
     fn selection_proof_eq(&self, other: &Self) -> bool {
-        self.duty.committee_count_at_slot == other.duty.committee_count_at_slot
+        self.duty.validator_pubkey == other.duty.validator_pubkey
+            && self.duty.committee_length == other.duty.committee_length
             && self.duty.attestation_slot == other.duty.attestation_slot
     }
 
@@ -241,24 +246,31 @@ impl DutiesStore {
             .validator_index()
     }
 
-    fn is_aggregator(&self, validator_pubkey: &PublicKey, epoch: Epoch) -> Option<bool> {
-        Some(
-            self.store
-                .read()
-                .get(validator_pubkey)?
-                .get(&epoch)?
-                .selection_proof
-                .is_some(),
-        )
+    /// Copies the selection proof from any known duties for the same validator and `epoch` into
+    /// `duties`, if the inputs to the selection proof are unchanged.
+    ///
+    /// Returns `false` if the selection proof for `duties` still needs to be computed.
+    fn reuse_selection_proof(&self, epoch: Epoch, duties: &mut DutyAndProof) -> bool {
+        let store = self.store.read();
+        let known_duties = store
+            .get(&duties.duty.validator_pubkey)
+            .and_then(|validator_map| validator_map.get(&epoch));
+
+        match known_duties {
+            Some(known_duties) if known_duties.selection_proof_eq(duties) => {
+                duties.selection_proof = known_duties.selection_proof.clone();
+                true
+            }
+            _ => false,
+        }
     }
 
-    fn insert<T: SlotClock + 'static, E: EthSpec>(
+    /// Inserts `duties` into the store. The selection proof of `duties` must already be computed.
+    fn insert(
         &self,
         epoch: Epoch,
-        mut duties: DutyAndProof,
+        duties: DutyAndProof,
         slots_per_epoch: u64,
-        validator_store: &ValidatorStore<T, E>,
-        spec: &ChainSpec,
     ) -> Result<InsertOutcome, String> {
         let mut store = self.store.write();
 
@@ -280,9 +292,6 @@ impl DutiesStore {
                         Ok(InsertOutcome::Invalid)
                     }
                 } else {
-                    // Compute the selection proof.
-                    duties.compute_selection_proof(validator_store, spec)?;
-
                     // Determine if a re-subscription is required.
                     let should_resubscribe = !duties.subscription_eq(known_duties);
 
@@ -292,17 +301,11 @@ impl DutiesStore {
                     Ok(InsertOutcome::Replaced { should_resubscribe })
                 }
             } else {
-                // Compute the selection proof.
-                duties.compute_selection_proof(validator_store, spec)?;
-
                 validator_map.insert(epoch, duties);
 
                 Ok(InsertOutcome::NewEpoch)
             }
         } else {
-            // Compute the selection proof.
-            duties.compute_selection_proof(validator_store, spec)?;
-
             let validator_pubkey = duties.duty.validator_pubkey.clone();
 
             let mut validator_map = HashMap::new();
@@ -386,6 +389,8 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
                     .context
                     .ok_or_else(|| "Cannot build DutiesService without runtime_context")?,
                 allow_unsynced_beacon_node: self.allow_unsynced_beacon_node,
+                last_update_slot: RwLock::new(None),
+                dependent_roots: RwLock::new(HashMap::new()),
             }),
         })
     }
@@ -401,13 +406,21 @@ pub struct Inner<T, E: EthSpec> {
     /// If true, the duties service will poll for duties from the beacon node even if it is not
     /// synced.
     allow_unsynced_beacon_node: bool,
+    /// The slot at which the duties for the current epoch were last successfully downloaded.
+    last_update_slot: RwLock<Option<Slot>>,
+    /// The roots of the blocks on which the most recently downloaded duties for each epoch depend.
+    dependent_roots: RwLock<HashMap<Epoch, DependentRoots>>,
 }
 
 /// Maintains a store of the duties for all voting validators in the `validator_store`.
 ///
 /// Polls the beacon node at the start of each slot, collecting duties for the current and next
-/// epoch. The duties service notifies the block production service to run each time it completes,
-/// so it *must* be run every slot.
+/// epoch. Selection proofs are computed as soon as the duties are known, so they are ready well
+/// before they're required. A change in the roots on which the duties depend indicates a re-org,
+/// in which case any changed duties are replaced.
+///
+/// The duties service notifies the block production service to run each time it completes, so it
+/// *must* be run every slot.
 pub struct DutiesService<T, E: EthSpec> {
     inner: Arc<Inner<T, E>>,
 }
@@ -457,6 +470,14 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
         self.store.attesters(slot, E::slots_per_epoch())
     }
 
+    /// Returns the number of slots since the duties for the current epoch were last successfully
+    /// downloaded from the beacon node, or `None` if they have never been downloaded.
+    pub fn slots_since_update(&self) -> Option<u64> {
+        let last_update_slot = (*self.last_update_slot.read())?;
+        let current_slot = self.slot_clock.now()?;
+        Some(current_slot.saturating_sub(last_update_slot).as_u64())
+    }
+
     /// Start the service that periodically polls the beacon node for validator duties.
     pub fn start_update_service(
         self,
@@ -508,7 +529,7 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
     async fn do_update(
         self,
         block_service_tx: &mut Sender<BlockServiceNotification>,
-        spec: &ChainSpec,
+        spec: &Arc<ChainSpec>,
     ) {
         let log = self.context.log();
 
@@ -538,20 +559,24 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
             );
 
             self.store.prune(prune_below);
+            self.dependent_roots
+                .write()
+                .retain(|epoch, _| *epoch >= prune_below);
         }
 
         // Update duties for the current epoch, but keep running if there's an error:
         // block production or the next epoch update could still succeed.
-        if let Err(e) = self
+        match self
             .clone()
             .update_epoch(current_epoch, current_epoch, spec)
             .await
         {
-            error!(
+            Ok(()) => *self.last_update_slot.write() = Some(slot),
+            Err(e) => error!(
                 log,
                 "Failed to get current epoch duties";
                 "http_error" => format!("{:?}", e)
-            );
+            ),
         }
 
         // Notify the block service to produce a block.
@@ -585,12 +610,41 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
 
     /// Attempt to download the duties of all managed validators for the given `request_epoch`. The
     /// `current_epoch` should be a local reading of the slot clock.
+    ///
+    /// If the downloaded duties reveal a re-org, they are downloaded again so that the proposer
+    /// and attester duties are both consistent with the new head.
     async fn update_epoch(
         self,
         current_epoch: Epoch,
         request_epoch: Epoch,
-        spec: &ChainSpec,
+        spec: &Arc<ChainSpec>,
     ) -> Result<(), String> {
+        let reorged = self
+            .clone()
+            .download_and_store_duties(current_epoch, request_epoch, spec)
+            .await?;
+
+        if reorged {
+            debug!(
+                self.context.log(),
+                "Refreshing duties after re-org";
+                "epoch" => request_epoch.as_u64(),
+            );
+            self.download_and_store_duties(current_epoch, request_epoch, spec)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Downloads the duties for `request_epoch` and stores them, returning `true` if the roots on
+    /// which the duties depend indicate a re-org since they were last downloaded.
+    async fn download_and_store_duties(
+        self,
+        current_epoch: Epoch,
+        request_epoch: Epoch,
+        spec: &Arc<ChainSpec>,
+    ) -> Result<bool, String> {
         let log = self.context.log();
 
         let mut new_validator = 0;
@@ -613,7 +667,7 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
             .collect();
 
         let mut validator_subscriptions = vec![];
-        let remote_duties: Vec<ValidatorDuty> = match ValidatorDuty::download(
+        let (remote_duties, dependent_roots) = match ValidatorDuty::download(
             &self.beacon_node,
             current_epoch,
             request_epoch,
@@ -623,30 +677,63 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
         .await
         {
             Ok(duties) => duties,
-            Err(e) => {
-                error!(
-                    log,
-                    "Failed to download validator duties";
-                    "error" => e
-                );
-                vec![]
-            }
+            Err(e) => return Err(format!("Failed to download validator duties: {}", e)),
         };
 
-        remote_duties.iter().for_each(|remote_duty| {
+        let reorged = self.update_dependent_roots(request_epoch, dependent_roots);
+
+        // Reuse any selection proofs that are already known, since computing them requires
+        // signing.
+        let mut new_duties = Vec::with_capacity(remote_duties.len());
+        let mut pending_duties = vec![];
+        for remote_duty in remote_duties {
             // Convert the remote duties into our local representation.
-            let duties: DutyAndProof = remote_duty.clone().into();
+            let mut duties: DutyAndProof = remote_duty.into();
 
-            let validator_pubkey = duties.duty.validator_pubkey.clone();
+            if self.store.reuse_selection_proof(request_epoch, &mut duties) {
+                new_duties.push(duties);
+            } else {
+                pending_duties.push(duties);
+            }
+        }
+
+        // Compute the remaining selection proofs in parallel, so they are ready well before the
+        // validators are required to aggregate.
+        let validator_store = self.validator_store.clone();
+        let inner_spec = spec.clone();
+        let computed_duties = tokio::task::spawn_blocking(move || {
+            pending_duties
+                .into_par_iter()
+                .map(|mut duties| {
+                    duties
+                        .compute_selection_proof(&validator_store, &inner_spec)
+                        .map(|()| duties)
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .map_err(|e| format!("Failed to compute selection proofs: {:?}", e))?;
+
+        for result in computed_duties {
+            match result {
+                Ok(duties) => new_duties.push(duties),
+                Err(e) => error!(
+                    log,
+                    "Unable to compute selection proof";
+                    "error" => e
+                ),
+            }
+        }
+
+        new_duties.into_iter().for_each(|duties| {
+            let remote_duty = duties.duty.clone();
+            let is_aggregator = duties.selection_proof.is_some();
 
             // Attempt to update our local store.
-            match self.store.insert(
-                request_epoch,
-                duties,
-                E::slots_per_epoch(),
-                &self.validator_store,
-                spec,
-            ) {
+            match self
+                .store
+                .insert(request_epoch, duties, E::slots_per_epoch())
+            {
                 Ok(outcome) => {
                     match &outcome {
                         InsertOutcome::NewValidator => {
@@ -666,13 +753,9 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
                         InsertOutcome::Invalid => invalid += 1,
                     }
 
-                    if let Some(is_aggregator) =
-                    self.store.is_aggregator(&validator_pubkey, request_epoch)
-                    {
-                        if outcome.is_subscription_candidate() {
-                            if let Some(subscription) = remote_duty.subscription(is_aggregator) {
-                                validator_subscriptions.push(subscription)
-                            }
+                    if outcome.is_subscription_candidate() {
+                        if let Some(subscription) = remote_duty.subscription(is_aggregator) {
+                            validator_subscriptions.push(subscription)
                         }
                     }
                 }
@@ -704,15 +787,6 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
             "epoch" => format!("{}", request_epoch)
         );
 
-        if replaced > 0 {
-            warn!(
-                log,
-                "Duties changed during routine update";
-                "info" => "Chain re-org likely occurred",
-                "replaced" => replaced,
-            )
-        }
-
         let log = self.context.log().clone();
         let count = validator_subscriptions.len();
 
@@ -730,7 +804,63 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
             );
         }
 
-        Ok(())
+        Ok(reorged)
+    }
+
+    /// Records the `dependent_roots` of the duties downloaded for `epoch`, returning `true` if
+    /// they indicate a re-org.
+    ///
+    /// If a dependent root differs from the one previously recorded for `epoch` then the block it
+    /// points to was re-orged out and the duties may have changed. A root is only compared once
+    /// its decision slot has passed: before then the beacon node returns its head block root,
+    /// which changes with every new block.
+    fn update_dependent_roots(&self, epoch: Epoch, dependent_roots: DependentRoots) -> bool {
+        let log = self.context.log();
+        let mut known_roots = self.dependent_roots.write();
+        let mut reorged = false;
+
+        let current_slot = self.slot_clock.now();
+        let decision_slot_passed = |shuffling_epoch: Epoch| {
+            let decision_slot = shuffling_epoch
+                .start_slot(E::slots_per_epoch())
+                .saturating_sub(1_u64);
+            current_slot.map_or(false, |slot| slot > decision_slot)
+        };
+
+        if let Some(prior_roots) = known_roots.get(&epoch) {
+            if prior_roots.attester != dependent_roots.attester
+                && decision_slot_passed(epoch.saturating_sub(1_u64))
+            {
+                warn!(
+                    log,
+                    "Attester duties re-org";
+                    "info" => "duties will be updated",
+                    "dependent_root" => format!("{:?}", dependent_roots.attester),
+                    "prior_dependent_root" => format!("{:?}", prior_roots.attester),
+                    "epoch" => epoch.as_u64(),
+                );
+                reorged = true;
+            }
+
+            if let (Some(prior_root), Some(root)) = (prior_roots.proposer, dependent_roots.proposer)
+            {
+                if prior_root != root && decision_slot_passed(epoch) {
+                    warn!(
+                        log,
+                        "Proposer duties re-org";
+                        "info" => "duties will be updated",
+                        "dependent_root" => format!("{:?}", root),
+                        "prior_dependent_root" => format!("{:?}", prior_root),
+                        "epoch" => epoch.as_u64(),
+                    );
+                    reorged = true;
+                }
+            }
+        }
+
+        known_roots.insert(epoch, dependent_roots);
+
+        reorged
     }
 }
 
@@ -8,7 +8,7 @@ use slot_clock::SlotClock;
 use std::ops::Deref;
 use std::sync::Arc;
 use tokio::time::{interval_at, Duration, Instant};
-use types::{EthSpec, Fork};
+use types::{EthSpec, Fork, Slot};
 
 /// Delay this period of time after the slot starts. This allows the node to process the new slot.
 const TIME_DELAY_FROM_SLOT: Duration = Duration::from_millis(80);
@@ -50,6 +50,7 @@ impl<T: SlotClock + 'static> ForkServiceBuilder<T> {
         Ok(ForkService {
             inner: Arc::new(Inner {
                 fork: RwLock::new(self.fork),
+                last_update_slot: RwLock::new(None),
                 slot_clock: self
                     .slot_clock
                     .ok_or_else(|| "Cannot build ForkService without slot_clock")?,
@@ -87,6 +88,8 @@ impl ForkServiceBuilder<slot_clock::TestingSlotClock> {
 /// Helper to minimise `Arc` usage.
 pub struct Inner<T> {
     fork: RwLock<Option<Fork>>,
+    /// The slot at which the `Fork` was last successfully downloaded.
+    last_update_slot: RwLock<Option<Slot>>,
     beacon_node: BeaconNodeHttpClient,
     log: Logger,
     slot_clock: T,
@@ -119,6 +122,14 @@ impl<T: SlotClock + 'static> ForkService<T> {
         *self.fork.read()
     }
 
+    /// Returns the number of slots since the `Fork` was last successfully downloaded from the
+    /// beacon node, or `None` if it has never been downloaded.
+    pub fn slots_since_update(&self) -> Option<u64> {
+        let last_update_slot = (*self.last_update_slot.read())?;
+        let current_slot = self.slot_clock.now()?;
+        Some(current_slot.saturating_sub(last_update_slot).as_u64())
+    }
+
     /// Starts the service that periodically polls for the `Fork`.
     pub fn start_update_service<E: EthSpec>(
         self,
@@ -186,6 +197,8 @@ impl<T: SlotClock + 'static> ForkService<T> {
             *(self.fork.write()) = Some(fork);
         }
 
+        *self.last_update_slot.write() = self.slot_clock.now();
+
         debug!(self.log, "Fork update success");
 
         // Returning an error will stop the interval. This is not desired, a single failure
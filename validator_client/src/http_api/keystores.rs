@@ -0,0 +1,183 @@
+//! Implementation of the standard `eth/v1/keystores` key manager endpoints.
+use crate::ValidatorStore;
+use account_utils::ZeroizeString;
+use eth2::lighthouse_vc::types::{
+    DeleteKeystoreStatus, DeleteKeystoresRequest, DeleteKeystoresResponse, GenericResponse,
+    ImportKeystoreStatus, ImportKeystoresRequest, KeystoreData, KeystoreStatus, PublicKeyBytes,
+};
+use eth2_keystore::Keystore;
+use slashing_protection::interchange::Interchange;
+use slot_clock::SlotClock;
+use std::path::PathBuf;
+use types::{EthSpec, PublicKey};
+use validator_dir::Builder as ValidatorDirBuilder;
+use warp::Rejection;
+
+/// Lists the voting public keys of all validators known to `validator_store`.
+pub fn list<T: SlotClock + 'static, E: EthSpec>(
+    validator_store: &ValidatorStore<T, E>,
+) -> GenericResponse<Vec<KeystoreData>> {
+    let keystores = validator_store
+        .initialized_validators()
+        .read()
+        .validator_definitions()
+        .iter()
+        .map(|def| KeystoreData {
+            validating_pubkey: PublicKeyBytes::from(&def.voting_public_key),
+            readonly: false,
+        })
+        .collect();
+
+    GenericResponse::from(keystores)
+}
+
+/// Imports the keystores in `request`, storing them in `validator_dir` and enabling them in
+/// `validator_store`.
+///
+/// Any slashing protection data in `request` is imported before any of the keystores, so that it
+/// is always in place before a key can be used for signing. If the slashing protection data is
+/// invalid then the entire request is rejected without importing any keystores. Otherwise, each
+/// keystore is imported independently and a status is returned for each.
+pub fn import<T: SlotClock + 'static, E: EthSpec>(
+    request: ImportKeystoresRequest,
+    validator_dir: PathBuf,
+    validator_store: &ValidatorStore<T, E>,
+) -> Result<Vec<KeystoreStatus<ImportKeystoreStatus>>, Rejection> {
+    if request.keystores.len() != request.passwords.len() {
+        return Err(warp_utils::reject::custom_bad_request(format!(
+            "mismatched numbers of keystores ({}) and passwords ({})",
+            request.keystores.len(),
+            request.passwords.len()
+        )));
+    }
+
+    if let Some(slashing_protection) = &request.slashing_protection {
+        let interchange = Interchange::from_json_str(slashing_protection).map_err(|e| {
+            warp_utils::reject::custom_bad_request(format!(
+                "invalid slashing protection data: {:?}",
+                e
+            ))
+        })?;
+
+        validator_store
+            .import_slashing_protection(&interchange)
+            .map_err(warp_utils::reject::custom_bad_request)?;
+    }
+
+    let statuses = request
+        .keystores
+        .into_iter()
+        .zip(request.passwords.into_iter())
+        .map(|(keystore, password)| {
+            match import_single_keystore(keystore.0, password, &validator_dir, validator_store) {
+                Ok(status) => KeystoreStatus::ok(status),
+                Err(e) => KeystoreStatus::error(ImportKeystoreStatus::Error, e),
+            }
+        })
+        .collect();
+
+    Ok(statuses)
+}
+
+fn import_single_keystore<T: SlotClock + 'static, E: EthSpec>(
+    keystore: Keystore,
+    password: ZeroizeString,
+    validator_dir: &PathBuf,
+    validator_store: &ValidatorStore<T, E>,
+) -> Result<ImportKeystoreStatus, String> {
+    // Check to ensure the password is correct.
+    let keypair = keystore
+        .decrypt_keypair(password.as_ref())
+        .map_err(|e| format!("invalid keystore: {:?}", e))?;
+
+    if validator_store
+        .initialized_validators()
+        .read()
+        .is_enabled(&keypair.pk)
+        .is_some()
+    {
+        return Ok(ImportKeystoreStatus::Duplicate);
+    }
+
+    let validator_dir = ValidatorDirBuilder::new(validator_dir.clone())
+        .voting_keystore(keystore, password.as_ref())
+        .store_withdrawal_keystore(false)
+        .build()
+        .map_err(|e| format!("failed to build validator directory: {:?}", e))?;
+
+    tokio::runtime::Handle::current()
+        .block_on(validator_store.add_validator_keystore(
+            validator_dir.voting_keystore_path(),
+            password,
+            true,
+        ))
+        .map_err(|e| format!("failed to initialize validator: {:?}", e))?;
+
+    Ok(ImportKeystoreStatus::Imported)
+}
+
+/// Deletes the keystores for the validators in `request` from `validator_store`, returning the
+/// slashing protection history of those validators.
+///
+/// Each validator is removed before the slashing protection data is exported, so the returned
+/// history includes every message that was signed with the deleted keys.
+pub fn delete<T: SlotClock + 'static, E: EthSpec>(
+    request: DeleteKeystoresRequest,
+    validator_store: &ValidatorStore<T, E>,
+) -> Result<DeleteKeystoresResponse, Rejection> {
+    let pubkeys = request
+        .pubkeys
+        .iter()
+        .map(|pubkey| {
+            pubkey.decompress().map_err(|e| {
+                warp_utils::reject::custom_bad_request(format!(
+                    "invalid pubkey {:?}: {:?}",
+                    pubkey, e
+                ))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let results = pubkeys
+        .iter()
+        .map(|pubkey| {
+            tokio::runtime::Handle::current()
+                .block_on(validator_store.delete_validator_keystore(pubkey))
+        })
+        .collect::<Vec<_>>();
+
+    let interchange = validator_store
+        .export_slashing_protection(Some(&pubkeys))
+        .map_err(warp_utils::reject::custom_server_error)?;
+
+    let has_history = |pubkey: &PublicKey| {
+        interchange
+            .data
+            .iter()
+            .any(|record| record.pubkey == *pubkey)
+    };
+
+    let data = pubkeys
+        .iter()
+        .zip(results)
+        .map(|(pubkey, result)| match result {
+            Ok(true) => KeystoreStatus::ok(DeleteKeystoreStatus::Deleted),
+            // The key is unknown, but we have slashing protection data for it.
+            Ok(false) if has_history(pubkey) => KeystoreStatus::ok(DeleteKeystoreStatus::NotActive),
+            Ok(false) => KeystoreStatus::ok(DeleteKeystoreStatus::NotFound),
+            Err(e) => KeystoreStatus::error(DeleteKeystoreStatus::Error, e),
+        })
+        .collect();
+
+    let slashing_protection = serde_json::to_string(&interchange).map_err(|e| {
+        warp_utils::reject::custom_server_error(format!(
+            "unable to serialize slashing protection data: {:?}",
+            e
+        ))
+    })?;
+
+    Ok(DeleteKeystoresResponse {
+        data,
+        slashing_protection,
+    })
+}
@@ -1,5 +1,6 @@
 mod api_secret;
 mod create_validator;
+mod keystores;
 mod tests;
 
 use crate::ValidatorStore;
@@ -8,6 +9,7 @@ use create_validator::create_validators;
 use eth2::lighthouse_vc::types::{self as api_types, PublicKey, PublicKeyBytes};
 use lighthouse_version::version_with_platform;
 use serde::{Deserialize, Serialize};
+use slashing_protection::interchange::Interchange;
 use slog::{crit, info, Logger};
 use slot_clock::SlotClock;
 use std::future::Future;
@@ -104,7 +106,7 @@ pub fn serve<T: 'static + SlotClock + Clone, E: EthSpec>(
     // Configure CORS.
     let cors_builder = {
         let builder = warp::cors()
-            .allow_methods(vec!["GET", "POST", "PATCH"])
+            .allow_methods(vec!["GET", "POST", "PATCH", "DELETE"])
             .allow_headers(vec!["Content-Type", "Authorization"]);
 
         warp_utils::cors::set_builder_origins(
@@ -321,7 +323,7 @@ pub fn serve<T: 'static + SlotClock + Clone, E: EthSpec>(
         .and(warp::path("keystore"))
         .and(warp::path::end())
         .and(warp::body::json())
-        .and(validator_dir_filter)
+        .and(validator_dir_filter.clone())
         .and(validator_store_filter.clone())
         .and(signer.clone())
         .and_then(
@@ -382,8 +384,8 @@ pub fn serve<T: 'static + SlotClock + Clone, E: EthSpec>(
         .and(warp::path::param::<PublicKey>())
         .and(warp::path::end())
         .and(warp::body::json())
-        .and(validator_store_filter)
-        .and(signer)
+        .and(validator_store_filter.clone())
+        .and(signer.clone())
         .and_then(
             |validator_pubkey: PublicKey,
              body: api_types::ValidatorPatchRequest,
@@ -419,6 +421,95 @@ pub fn serve<T: 'static + SlotClock + Clone, E: EthSpec>(
             },
         );
 
+    // GET lighthouse/slashing_protection
+    let get_lighthouse_slashing_protection = warp::path("lighthouse")
+        .and(warp::path("slashing_protection"))
+        .and(warp::path::end())
+        .and(warp::query::<api_types::SlashingProtectionQuery>())
+        .and(validator_store_filter.clone())
+        .and(signer.clone())
+        .and_then(
+            |query: api_types::SlashingProtectionQuery,
+             validator_store: ValidatorStore<T, E>,
+             signer| {
+                blocking_signed_json_task(signer, move || {
+                    let pubkeys = query.pubkeys.as_ref().map(|pubkeys| pubkeys.0.as_slice());
+                    validator_store
+                        .export_slashing_protection(pubkeys)
+                        .map_err(warp_utils::reject::custom_server_error)
+                })
+            },
+        );
+
+    // POST lighthouse/slashing_protection
+    let post_lighthouse_slashing_protection = warp::path("lighthouse")
+        .and(warp::path("slashing_protection"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(validator_store_filter.clone())
+        .and(signer.clone())
+        .and_then(
+            |interchange: Interchange, validator_store: ValidatorStore<T, E>, signer| {
+                blocking_signed_json_task(signer, move || {
+                    validator_store
+                        .import_slashing_protection(&interchange)
+                        .map_err(warp_utils::reject::custom_bad_request)
+                })
+            },
+        );
+
+    // Standard key manager API.
+    let eth_v1 = warp::path("eth").and(warp::path("v1"));
+
+    // GET eth/v1/keystores
+    let get_std_keystores = eth_v1
+        .clone()
+        .and(warp::path("keystores"))
+        .and(warp::path::end())
+        .and(validator_store_filter.clone())
+        .and(signer.clone())
+        .and_then(|validator_store: ValidatorStore<T, E>, signer| {
+            blocking_signed_json_task(signer, move || Ok(keystores::list(&validator_store)))
+        });
+
+    // POST eth/v1/keystores
+    let post_std_keystores = eth_v1
+        .clone()
+        .and(warp::path("keystores"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(validator_dir_filter)
+        .and(validator_store_filter.clone())
+        .and(signer.clone())
+        .and_then(
+            |request: api_types::ImportKeystoresRequest,
+             validator_dir: PathBuf,
+             validator_store: ValidatorStore<T, E>,
+             signer| {
+                blocking_signed_json_task(signer, move || {
+                    keystores::import(request.clone(), validator_dir.clone(), &validator_store)
+                        .map(api_types::GenericResponse::from)
+                })
+            },
+        );
+
+    // DELETE eth/v1/keystores
+    let delete_std_keystores = eth_v1
+        .and(warp::path("keystores"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(validator_store_filter)
+        .and(signer)
+        .and_then(
+            |request: api_types::DeleteKeystoresRequest,
+             validator_store: ValidatorStore<T, E>,
+             signer| {
+                blocking_signed_json_task(signer, move || {
+                    keystores::delete(request.clone(), &validator_store)
+                })
+            },
+        );
+
     let routes = warp::any()
         .and(authorization_header_filter)
         .and(
@@ -427,15 +518,20 @@ pub fn serve<T: 'static + SlotClock + Clone, E: EthSpec>(
                     .or(get_lighthouse_health)
                     .or(get_lighthouse_spec)
                     .or(get_lighthouse_validators)
-                    .or(get_lighthouse_validators_pubkey),
+                    .or(get_lighthouse_validators_pubkey)
+                    .or(get_lighthouse_slashing_protection)
+                    .or(get_std_keystores),
             ),
         )
         .or(warp::post().and(
             post_validators
                 .or(post_validators_keystore)
-                .or(post_validators_mnemonic),
+                .or(post_validators_mnemonic)
+                .or(post_lighthouse_slashing_protection)
+                .or(post_std_keystores),
         ))
         .or(warp::patch().and(patch_validators))
+        .or(warp::delete().and(delete_std_keystores))
         // Maps errors into HTTP responses.
         .recover(warp_utils::reject::handle_rejection)
         // Add a `Server` header.
@@ -6,7 +6,9 @@ use crate::{
     Config, ForkServiceBuilder, InitializedValidators, ValidatorDefinitions, ValidatorStore,
 };
 use account_utils::{
-    eth2_wallet::WalletBuilder, mnemonic_from_phrase, random_mnemonic, random_password,
+    eth2_wallet::WalletBuilder,
+    mnemonic_from_phrase, random_mnemonic, random_password,
+    validator_definitions::{SigningDefinition, ValidatorDefinition},
     ZeroizeString,
 };
 use deposit_contract::decode_eth1_tx_data;
@@ -17,16 +19,25 @@ use eth2::{
 };
 use eth2_keystore::KeystoreBuilder;
 use parking_lot::RwLock;
-use slashing_protection::{SlashingDatabase, SLASHING_PROTECTION_FILENAME};
+use slashing_protection::{
+    interchange::{Interchange, InterchangeData, InterchangeMetadata, SignedBlock},
+    SlashingDatabase, SLASHING_PROTECTION_FILENAME, SUPPORTED_INTERCHANGE_FORMAT_VERSION,
+};
 use slot_clock::TestingSlotClock;
 use std::marker::PhantomData;
 use std::net::Ipv4Addr;
 use std::sync::Arc;
 use tempfile::{tempdir, TempDir};
 use tokio::sync::oneshot;
+use validator_dir::{
+    Builder as ValidatorDirBuilder, ETH1_DEPOSIT_DATA_FILE, VOTING_KEYSTORE_FILE,
+    WITHDRAWAL_KEYSTORE_FILE,
+};
 
 const PASSWORD_BYTES: &[u8] = &[42, 50, 37];
 
+const GENESIS_VALIDATORS_ROOT: Hash256 = Hash256::repeat_byte(42);
+
 type E = MainnetEthSpec;
 
 struct ApiTester {
@@ -34,7 +45,7 @@ struct ApiTester {
     initialized_validators: Arc<RwLock<InitializedValidators>>,
     url: Url,
     _server_shutdown: oneshot::Sender<()>,
-    _validator_dir: TempDir,
+    validator_dir: TempDir,
 }
 
 impl ApiTester {
@@ -72,7 +83,7 @@ impl ApiTester {
         let validator_store: ValidatorStore<TestingSlotClock, E> = ValidatorStore::new(
             initialized_validators,
             slashing_protection,
-            Hash256::repeat_byte(42),
+            GENESIS_VALIDATORS_ROOT,
             E::default_spec(),
             fork_service.clone(),
             log.clone(),
@@ -115,7 +126,7 @@ impl ApiTester {
 
         Self {
             initialized_validators,
-            _validator_dir: validator_dir,
+            validator_dir,
             client,
             url,
             _server_shutdown: shutdown_tx,
@@ -407,6 +418,243 @@ impl ApiTester {
 
         self
     }
+
+    pub async fn manage_std_keystores(self) -> Self {
+        let initial_vals = self.vals_total();
+
+        let password = random_password();
+        let keypair = Keypair::random();
+        let keystore = KeystoreBuilder::new(&keypair, password.as_bytes(), String::new())
+            .unwrap()
+            .build()
+            .unwrap();
+        let password: ZeroizeString = String::from_utf8(password.as_ref().to_vec())
+            .unwrap()
+            .into();
+        let pubkey = PublicKeyBytes::from(&keypair.pk);
+
+        let interchange = Interchange {
+            metadata: InterchangeMetadata {
+                interchange_format_version: SUPPORTED_INTERCHANGE_FORMAT_VERSION,
+                genesis_validators_root: GENESIS_VALIDATORS_ROOT,
+            },
+            data: vec![InterchangeData {
+                pubkey: keypair.pk.clone(),
+                signed_blocks: vec![SignedBlock {
+                    slot: Slot::new(1),
+                    signing_root: Some(Hash256::repeat_byte(1)),
+                }],
+                signed_attestations: vec![],
+            }],
+        };
+
+        /*
+         * Import the keystore along with its slashing protection history.
+         */
+
+        let request = ImportKeystoresRequest {
+            keystores: vec![KeystoreJsonStr(keystore.clone())],
+            passwords: vec![password.clone()],
+            slashing_protection: Some(serde_json::to_string(&interchange).unwrap()),
+        };
+        let statuses = self.client.post_keystores(&request).await.unwrap().data;
+
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].status, ImportKeystoreStatus::Imported);
+        assert_eq!(self.vals_total(), initial_vals + 1);
+        assert_eq!(
+            self.initialized_validators.read().is_enabled(&keypair.pk),
+            Some(true)
+        );
+
+        let keystores = self.client.get_keystores().await.unwrap().data;
+        assert_eq!(keystores.len(), self.vals_total());
+        assert!(keystores.iter().any(|k| k.validating_pubkey == pubkey));
+
+        /*
+         * Importing the same keystore again, or using the wrong password, has no effect.
+         */
+
+        let request = ImportKeystoresRequest {
+            keystores: vec![
+                KeystoreJsonStr(keystore.clone()),
+                KeystoreJsonStr(keystore.clone()),
+            ],
+            passwords: vec![password, random_password_string()],
+            slashing_protection: None,
+        };
+        let statuses = self.client.post_keystores(&request).await.unwrap().data;
+
+        assert_eq!(statuses[0].status, ImportKeystoreStatus::Duplicate);
+        assert_eq!(statuses[1].status, ImportKeystoreStatus::Error);
+        assert_eq!(self.vals_total(), initial_vals + 1);
+
+        /*
+         * Delete the keystore, retrieving its slashing protection history.
+         */
+
+        let unknown_pubkey = PublicKeyBytes::from(&Keypair::random().pk);
+        let request = DeleteKeystoresRequest {
+            pubkeys: vec![pubkey, unknown_pubkey],
+        };
+        let response = self.client.delete_keystores(&request).await.unwrap();
+
+        assert_eq!(response.data[0].status, DeleteKeystoreStatus::Deleted);
+        assert_eq!(response.data[1].status, DeleteKeystoreStatus::NotFound);
+        assert_eq!(self.vals_total(), initial_vals);
+        assert_eq!(
+            self.initialized_validators.read().is_enabled(&keypair.pk),
+            None
+        );
+
+        let exported = Interchange::from_json_str(&response.slashing_protection).unwrap();
+        assert!(exported.equiv(&interchange));
+
+        /*
+         * Deleting the keystore again still returns its slashing protection history.
+         */
+
+        let request = DeleteKeystoresRequest {
+            pubkeys: vec![pubkey],
+        };
+        let response = self.client.delete_keystores(&request).await.unwrap();
+
+        assert_eq!(response.data[0].status, DeleteKeystoreStatus::NotActive);
+
+        let exported = Interchange::from_json_str(&response.slashing_protection).unwrap();
+        assert!(exported.equiv(&interchange));
+
+        self
+    }
+
+    pub async fn delete_keystore_preserves_other_files(self) -> Self {
+        let initial_vals = self.vals_total();
+        let spec = E::default_spec();
+        let secrets_dir = tempdir().unwrap();
+
+        let validator_dir = ValidatorDirBuilder::new(self.validator_dir.path().into())
+            .password_dir(secrets_dir.path())
+            .random_voting_keystore()
+            .unwrap()
+            .random_withdrawal_keystore()
+            .unwrap()
+            .create_eth1_tx_data(spec.max_effective_balance, &spec)
+            .build()
+            .unwrap();
+
+        let voting_keypair = validator_dir.voting_keypair(secrets_dir.path()).unwrap();
+        let withdrawal_keypair = validator_dir
+            .withdrawal_keypair(secrets_dir.path())
+            .unwrap();
+        let voting_keystore_path = validator_dir.voting_keystore_path();
+        let voting_password_path = secrets_dir.path().join(voting_keypair.pk.to_hex_string());
+        let withdrawal_password_path = secrets_dir
+            .path()
+            .join(withdrawal_keypair.pk.to_hex_string());
+
+        let def = ValidatorDefinition {
+            enabled: true,
+            voting_public_key: voting_keypair.pk.clone(),
+            description: String::new(),
+            signing_definition: SigningDefinition::LocalKeystore {
+                voting_keystore_path: voting_keystore_path.clone(),
+                voting_keystore_password_path: Some(voting_password_path.clone()),
+                voting_keystore_password: None,
+            },
+        };
+        self.initialized_validators
+            .write()
+            .add_definition(def)
+            .await
+            .unwrap();
+
+        assert_eq!(self.vals_total(), initial_vals + 1);
+        assert!(voting_keystore_path.exists());
+        assert!(voting_password_path.exists());
+
+        self.initialized_validators
+            .write()
+            .delete_definition_and_keystore(&voting_keypair.pk)
+            .await
+            .unwrap();
+
+        assert_eq!(self.vals_total(), initial_vals);
+
+        // The voting keystore, its lockfile and its password are removed.
+        assert!(!voting_keystore_path.exists());
+        assert!(!validator_dir
+            .dir()
+            .join(format!("{}.lock", VOTING_KEYSTORE_FILE))
+            .exists());
+        assert!(!voting_password_path.exists());
+
+        // Everything else in the validator directory survives.
+        assert!(validator_dir.dir().join(WITHDRAWAL_KEYSTORE_FILE).exists());
+        assert!(validator_dir.dir().join(ETH1_DEPOSIT_DATA_FILE).exists());
+        assert!(withdrawal_password_path.exists());
+        assert!(validator_dir.withdrawal_keypair(secrets_dir.path()).is_ok());
+
+        self
+    }
+
+    pub async fn slashing_protection_import_export(self) -> Self {
+        let keypairs = (0..2).map(|_| Keypair::random()).collect::<Vec<_>>();
+        let interchange = Interchange {
+            metadata: InterchangeMetadata {
+                interchange_format_version: SUPPORTED_INTERCHANGE_FORMAT_VERSION,
+                genesis_validators_root: GENESIS_VALIDATORS_ROOT,
+            },
+            data: keypairs
+                .iter()
+                .enumerate()
+                .map(|(i, keypair)| InterchangeData {
+                    pubkey: keypair.pk.clone(),
+                    signed_blocks: vec![SignedBlock {
+                        slot: Slot::new(i as u64),
+                        signing_root: Some(Hash256::repeat_byte(i as u8)),
+                    }],
+                    signed_attestations: vec![],
+                })
+                .collect(),
+        };
+
+        self.client
+            .post_lighthouse_slashing_protection(&interchange)
+            .await
+            .unwrap();
+
+        let exported: Interchange = self
+            .client
+            .get_lighthouse_slashing_protection(None)
+            .await
+            .unwrap();
+        assert!(exported.equiv(&interchange));
+
+        // Filter the export by public key.
+        let pubkey = PublicKeyBytes::from(&keypairs[1].pk);
+        let exported: Interchange = self
+            .client
+            .get_lighthouse_slashing_protection(Some(&[pubkey][..]))
+            .await
+            .unwrap();
+        assert_eq!(exported.data, vec![interchange.data[1].clone()]);
+
+        // Data for a different chain is rejected.
+        let mut wrong_chain = interchange.clone();
+        wrong_chain.metadata.genesis_validators_root = Hash256::repeat_byte(0);
+        self.client
+            .post_lighthouse_slashing_protection(&wrong_chain)
+            .await
+            .unwrap_err();
+
+        self
+    }
+}
+
+fn random_password_string() -> ZeroizeString {
+    String::from_utf8(random_password().as_ref().to_vec())
+        .unwrap()
+        .into()
 }
 
 struct HdValidatorScenario {
@@ -528,3 +776,37 @@ async fn keystore_validator_creation() {
         .assert_enabled_validators_count(1)
         .assert_validators_count(2);
 }
+
+#[tokio::test(core_threads = 2)]
+async fn std_keystore_management() {
+    ApiTester::new()
+        .await
+        .create_hd_validators(HdValidatorScenario {
+            count: 1,
+            specify_mnemonic: false,
+            key_derivation_path_offset: 0,
+            disabled: vec![],
+        })
+        .await
+        .manage_std_keystores()
+        .await
+        .assert_enabled_validators_count(1)
+        .assert_validators_count(1);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn std_keystore_delete_preserves_other_files() {
+    ApiTester::new()
+        .await
+        .delete_keystore_preserves_other_files()
+        .await
+        .assert_validators_count(0);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn slashing_protection_import_export() {
+    ApiTester::new()
+        .await
+        .slashing_protection_import_export()
+        .await;
+}
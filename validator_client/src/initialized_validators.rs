@@ -65,6 +65,10 @@ pub enum Error {
     UnableToDeleteLockfile(io::Error),
     /// Cannot initialize the same validator twice.
     DuplicatePublicKey,
+    /// The validator is not known to this validator client.
+    UnknownPublicKey,
+    /// There was a filesystem error when deleting a voting keystore or its validator directory.
+    UnableToDeleteKeystore(PathBuf, io::Error),
 }
 
 /// A method used by a validator to sign messages.
@@ -469,6 +473,91 @@ impl InitializedValidators {
         Ok(())
     }
 
+    /// Removes the validator with `voting_public_key` from `self`, deleting its definition from the
+    /// on-disk validator definitions file and its voting keystore from the filesystem.
+    ///
+    /// The validator is disabled before anything is deleted, so it will not be used for signing
+    /// once this function returns (even if an error is returned whilst deleting files).
+    ///
+    /// Only the voting keystore, its lockfile and its password file (if the password is stored in
+    /// a file named after the voting public key) are deleted. Any other files alongside the
+    /// keystore (e.g., the withdrawal keystore or deposit data) are left untouched and the
+    /// validator directory is only removed if it is left empty.
+    pub async fn delete_definition_and_keystore(
+        &mut self,
+        voting_public_key: &PublicKey,
+    ) -> Result<(), Error> {
+        let def = self
+            .definitions
+            .as_slice()
+            .iter()
+            .find(|def| def.voting_public_key == *voting_public_key)
+            .cloned()
+            .ok_or(Error::UnknownPublicKey)?;
+
+        // Disabling the validator drops its `InitializedValidator` (removing the lockfile) and
+        // evicts it from the key cache.
+        self.set_validator_status(voting_public_key, false).await?;
+
+        self.definitions
+            .retain(|existing| existing.voting_public_key != *voting_public_key);
+        self.definitions
+            .save(&self.validators_dir)
+            .map_err(Error::UnableToSaveDefinitions)?;
+
+        match &def.signing_definition {
+            SigningDefinition::LocalKeystore {
+                voting_keystore_path,
+                voting_keystore_password_path,
+                ..
+            } => {
+                fs::remove_file(voting_keystore_path)
+                    .map_err(|e| Error::UnableToDeleteKeystore(voting_keystore_path.clone(), e))?;
+
+                if let Some(lockfile_path) = get_lockfile_path(voting_keystore_path) {
+                    if lockfile_path.exists() {
+                        fs::remove_file(&lockfile_path)
+                            .map_err(|e| Error::UnableToDeleteKeystore(lockfile_path, e))?;
+                    }
+                }
+
+                // Only remove a password file that belongs exclusively to this validator, not one
+                // that might be shared between several keystores.
+                if let Some(password_path) = voting_keystore_password_path {
+                    let is_validator_password = password_path.file_name().map_or(false, |name| {
+                        name == voting_public_key.to_hex_string().as_str()
+                    });
+
+                    if is_validator_password && password_path.exists() {
+                        fs::remove_file(password_path)
+                            .map_err(|e| Error::UnableToDeleteKeystore(password_path.clone(), e))?;
+                    }
+                }
+
+                // Tidy up the validator directory, but only if nothing else is stored in it.
+                let validator_dir = self.validators_dir.join(voting_public_key.to_hex_string());
+                if voting_keystore_path.parent() == Some(validator_dir.as_path()) {
+                    let is_empty = fs::read_dir(&validator_dir)
+                        .map(|mut entries| entries.next().is_none())
+                        .unwrap_or(false);
+
+                    if is_empty {
+                        fs::remove_dir(&validator_dir)
+                            .map_err(|e| Error::UnableToDeleteKeystore(validator_dir, e))?;
+                    }
+                }
+            }
+        }
+
+        info!(
+            self.log,
+            "Deleted validator";
+            "voting_pubkey" => format!("{:?}", voting_public_key)
+        );
+
+        Ok(())
+    }
+
     /// Tries to decrypt the key cache.
     ///
     /// Returns `Ok(true)` if decryption was successful, `Ok(false)` if it couldn't get decrypted
@@ -1,3 +1,6 @@
+#[macro_use]
+extern crate lazy_static;
+
 mod attestation_service;
 mod block_service;
 mod cli;
@@ -12,6 +15,7 @@ mod validator_duty;
 mod validator_store;
 
 pub mod http_api;
+pub mod http_metrics;
 
 pub use cli::cli_app;
 pub use config::Config;
@@ -54,6 +58,7 @@ pub struct ProductionValidatorClient<T: EthSpec> {
     attestation_service: AttestationService<SystemTimeSlotClock, T>,
     validator_store: ValidatorStore<SystemTimeSlotClock, T>,
     http_api_listen_addr: Option<SocketAddr>,
+    http_metrics_listen_addr: Option<SocketAddr>,
     config: Config,
 }
 
@@ -245,6 +250,9 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             .validator_store(validator_store.clone())
             .beacon_node(beacon_node)
             .runtime_context(context.service_context("attestation".into()))
+            .attestation_delay(config.attestation_delay_ms.map(Duration::from_millis))
+            .aggregation_delay(config.aggregation_delay_ms.map(Duration::from_millis))
+            .early_attestations(config.early_attestations)
             .build()?;
 
         Ok(Self {
@@ -256,6 +264,7 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             validator_store,
             config,
             http_api_listen_addr: None,
+            http_metrics_listen_addr: None,
         })
     }
 
@@ -321,6 +330,32 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             None
         };
 
+        self.http_metrics_listen_addr = if self.config.http_metrics.enabled {
+            let ctx: Arc<http_metrics::Context<SystemTimeSlotClock, T>> =
+                Arc::new(http_metrics::Context {
+                    config: self.config.http_metrics.clone(),
+                    validator_store: Some(self.validator_store.clone()),
+                    duties_service: Some(self.duties_service.clone()),
+                    fork_service: Some(self.fork_service.clone()),
+                    log: log.clone(),
+                });
+
+            let exit = self.context.executor.exit();
+
+            let (listen_addr, server) = http_metrics::serve(ctx, exit)
+                .map_err(|e| format!("Unable to start metrics HTTP server: {:?}", e))?;
+
+            self.context
+                .clone()
+                .executor
+                .spawn_without_exit(async move { server.await }, "http-metrics");
+
+            Some(listen_addr)
+        } else {
+            info!(log, "Metrics HTTP server is disabled");
+            None
+        };
+
         Ok(())
     }
 }
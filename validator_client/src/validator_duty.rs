@@ -5,7 +5,7 @@ use eth2::{
 use serde::{Deserialize, Serialize};
 use slog::{error, Logger};
 use std::collections::HashMap;
-use types::{CommitteeIndex, Epoch, PublicKey, PublicKeyBytes, Slot};
+use types::{CommitteeIndex, Epoch, Hash256, PublicKey, PublicKeyBytes, Slot};
 
 /// This struct is being used as a shim since we deprecated the `rest_api` in favour of `http_api`.
 ///
@@ -29,10 +29,21 @@ pub struct ValidatorDuty {
     pub committee_length: Option<u64>,
     /// The slots in which a validator must propose a block (can be empty).
     ///
-    /// Should be set to `None` when duties are not yet known (before the current epoch).
+    /// Should be set to `None` when proposer duties are not yet known.
     pub block_proposal_slots: Option<Vec<Slot>>,
 }
 
+/// The roots of the blocks on which a set of downloaded duties depend.
+///
+/// If either block is re-orged out of the canonical chain, the corresponding duties may change.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct DependentRoots {
+    /// The block on which the attester shuffling depends.
+    pub attester: Hash256,
+    /// The block on which the proposer shuffling depends, if proposer duties were downloaded.
+    pub proposer: Option<Hash256>,
+}
+
 impl ValidatorDuty {
     /// Instantiate `Self` as if there are no known dutes for `validator_pubkey`.
     fn no_duties(validator_pubkey: PublicKey, validator_index: Option<u64>) -> Self {
@@ -48,16 +59,18 @@ impl ValidatorDuty {
         }
     }
 
-    /// Instantiate `Self` by performing requests on the `beacon_node`.
+    /// Instantiate `Self` by performing requests on the `beacon_node`, also returning the roots of
+    /// the blocks on which the duties depend.
     ///
-    /// Will only request proposer duties if `current_epoch == request_epoch`.
+    /// Will only request proposer duties if `request_epoch` is no later than the epoch after
+    /// `current_epoch`, since the beacon node cannot provide them any earlier.
     pub async fn download(
         beacon_node: &BeaconNodeHttpClient,
         current_epoch: Epoch,
         request_epoch: Epoch,
         mut pubkeys: Vec<(PublicKey, Option<u64>)>,
         log: &Logger,
-    ) -> Result<Vec<ValidatorDuty>, String> {
+    ) -> Result<(Vec<ValidatorDuty>, DependentRoots), String> {
         for (pubkey, index_opt) in &mut pubkeys {
             if index_opt.is_none() {
                 *index_opt = beacon_node
@@ -81,46 +94,47 @@ impl ValidatorDuty {
             }
         }
 
-        // Query for all block proposer duties in the current epoch and map the response by index.
-        let proposal_slots_by_index: HashMap<u64, Vec<Slot>> = if current_epoch == request_epoch {
-            beacon_node
-                .get_validator_duties_proposer(current_epoch)
+        // Query for all block proposer duties in the request epoch and map the response by index.
+        let proposers_known = request_epoch <= current_epoch + 1;
+        let (proposer_dependent_root, proposal_slots_by_index) = if proposers_known {
+            let response = beacon_node
+                .get_validator_duties_proposer(request_epoch)
                 .await
-                .map(|resp| resp.data)
                 // Exit early if there's an error.
-                .map_err(|e| format!("Failed to get proposer indices: {:?}", e))?
-                .into_iter()
-                .fold(
-                    HashMap::with_capacity(pubkeys.len()),
-                    |mut map, proposer_data| {
-                        map.entry(proposer_data.validator_index)
-                            .or_insert_with(Vec::new)
-                            .push(proposer_data.slot);
-                        map
-                    },
-                )
+                .map_err(|e| format!("Failed to get proposer indices: {:?}", e))?;
+
+            let proposal_slots_by_index: HashMap<u64, Vec<Slot>> = response.data.into_iter().fold(
+                HashMap::with_capacity(pubkeys.len()),
+                |mut map, proposer_data| {
+                    map.entry(proposer_data.validator_index)
+                        .or_insert_with(Vec::new)
+                        .push(proposer_data.slot);
+                    map
+                },
+            );
+
+            (Some(response.dependent_root), Some(proposal_slots_by_index))
         } else {
-            HashMap::new()
+            (None, None)
         };
 
         let query_indices = pubkeys
             .iter()
             .filter_map(|(_, index_opt)| *index_opt)
             .collect::<Vec<_>>();
-        let attester_data_map = beacon_node
+        let attester_response = beacon_node
             .post_validator_duties_attester(request_epoch, query_indices.as_slice())
             .await
-            .map(|resp| resp.data)
             // Exit early if there's an error.
-            .map_err(|e| format!("Failed to get attester duties: {:?}", e))?
-            .into_iter()
-            .fold(
-                HashMap::with_capacity(pubkeys.len()),
-                |mut map, attester_data| {
-                    map.insert(attester_data.validator_index, attester_data);
-                    map
-                },
-            );
+            .map_err(|e| format!("Failed to get attester duties: {:?}", e))?;
+        let attester_dependent_root = attester_response.dependent_root;
+        let attester_data_map = attester_response.data.into_iter().fold(
+            HashMap::with_capacity(pubkeys.len()),
+            |mut map, attester_data| {
+                map.insert(attester_data.validator_index, attester_data);
+                map
+            },
+        );
 
         let duties = pubkeys
             .into_iter()
@@ -138,9 +152,14 @@ impl ValidatorDuty {
                                 ),
                                 committee_count_at_slot: Some(attester_data.committees_at_slot),
                                 committee_length: Some(attester_data.committee_length),
-                                block_proposal_slots: proposal_slots_by_index
-                                    .get(&attester_data.validator_index)
-                                    .cloned(),
+                                // A validator without any proposals has an empty list of
+                                // slots, so that a re-org which removes their proposals is
+                                // reflected in the duties.
+                                block_proposal_slots: proposal_slots_by_index.as_ref().map(|map| {
+                                    map.get(&attester_data.validator_index)
+                                        .cloned()
+                                        .unwrap_or_default()
+                                }),
                             },
                             Err(e) => {
                                 error!(
@@ -161,7 +180,12 @@ impl ValidatorDuty {
             })
             .collect();
 
-        Ok(duties)
+        let dependent_roots = DependentRoots {
+            attester: attester_dependent_root,
+            proposer: proposer_dependent_root,
+        };
+
+        Ok((duties, dependent_roots))
     }
 
     /// Return `true` if these validator duties are equal, ignoring their `block_proposal_slots`.
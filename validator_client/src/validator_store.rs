@@ -1,7 +1,11 @@
-use crate::{fork_service::ForkService, initialized_validators::InitializedValidators};
+use crate::{
+    fork_service::ForkService,
+    http_metrics::metrics,
+    initialized_validators::{Error as InitializedValidatorsError, InitializedValidators},
+};
 use account_utils::{validator_definitions::ValidatorDefinition, ZeroizeString};
 use parking_lot::RwLock;
-use slashing_protection::{NotSafe, Safe, SlashingDatabase};
+use slashing_protection::{interchange::Interchange, NotSafe, Safe, SlashingDatabase};
 use slog::{crit, error, warn, Logger};
 use slot_clock::SlotClock;
 use std::marker::PhantomData;
@@ -109,6 +113,56 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         Ok(validator_def)
     }
 
+    /// Remove the validator with `voting_public_key` from `self`, deleting its definition and its
+    /// voting keystore from the filesystem.
+    ///
+    /// The validator is removed whilst holding a write-lock on `self.validators`, so its key will
+    /// not be used for signing once this function returns. Since all messages are checked and
+    /// recorded in the slashing protection database *before* the signing key is read, a subsequent
+    /// call to `export_slashing_protection` includes every message signed by the validator.
+    ///
+    /// Returns `Ok(false)` if the validator is not known to `self`.
+    pub async fn delete_validator_keystore(
+        &self,
+        voting_public_key: &PublicKey,
+    ) -> Result<bool, String> {
+        match self
+            .validators
+            .write()
+            .delete_definition_and_keystore(voting_public_key)
+            .await
+        {
+            Ok(()) => Ok(true),
+            Err(InitializedValidatorsError::UnknownPublicKey) => Ok(false),
+            Err(e) => Err(format!("Unable to delete validator: {:?}", e)),
+        }
+    }
+
+    /// Import an EIP-3076 slashing protection interchange into the slashing protection database.
+    ///
+    /// The import is atomic: either all of the records are imported or none of them are.
+    pub fn import_slashing_protection(&self, interchange: &Interchange) -> Result<(), String> {
+        self.slashing_protection
+            .import_interchange_info(interchange, self.genesis_validators_root)
+            .map_err(|e| format!("Unable to import slashing protection data: {:?}", e))
+    }
+
+    /// Export the slashing protection history of the validators in `public_keys` as an EIP-3076
+    /// interchange, or the history of all validators if `public_keys` is `None`.
+    pub fn export_slashing_protection(
+        &self,
+        public_keys: Option<&[PublicKey]>,
+    ) -> Result<Interchange, String> {
+        if let Some(public_keys) = public_keys {
+            self.slashing_protection
+                .export_interchange_info_for_validators(self.genesis_validators_root, public_keys)
+        } else {
+            self.slashing_protection
+                .export_interchange_info(self.genesis_validators_root)
+        }
+        .map_err(|e| format!("Unable to export slashing protection data: {:?}", e))
+    }
+
     pub fn voting_pubkeys(&self) -> Vec<PublicKey> {
         self.validators
             .read()
@@ -132,6 +186,8 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
     }
 
     pub fn randao_reveal(&self, validator_pubkey: &PublicKey, epoch: Epoch) -> Option<Signature> {
+        let _timer = metrics::start_timer_vec(&metrics::SIGNING_TIMES, &[metrics::RANDAO_REVEAL]);
+
         self.validators
             .read()
             .voting_keypair(validator_pubkey)
@@ -154,6 +210,8 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         block: BeaconBlock<E>,
         current_slot: Slot,
     ) -> Option<SignedBeaconBlock<E>> {
+        let _timer = metrics::start_timer_vec(&metrics::SIGNING_TIMES, &[metrics::BEACON_BLOCK]);
+
         // Make sure the block slot is not higher than the current slot to avoid potential attacks.
         if block.slot > current_slot {
             warn!(
@@ -198,9 +256,17 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
                     self.log,
                     "Skipping signing of previously signed block";
                 );
+                metrics::inc_counter_vec(
+                    &metrics::SLASHING_PROTECTION_REFUSALS,
+                    &[metrics::BEACON_BLOCK, metrics::SAME_DATA],
+                );
                 None
             }
             Err(NotSafe::UnregisteredValidator(pk)) => {
+                metrics::inc_counter_vec(
+                    &metrics::SLASHING_PROTECTION_REFUSALS,
+                    &[metrics::BEACON_BLOCK, metrics::UNREGISTERED],
+                );
                 warn!(
                     self.log,
                     "Not signing block for unregistered validator";
@@ -210,6 +276,10 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
                 None
             }
             Err(e) => {
+                metrics::inc_counter_vec(
+                    &metrics::SLASHING_PROTECTION_REFUSALS,
+                    &[metrics::BEACON_BLOCK, metrics::SLASHABLE],
+                );
                 crit!(
                     self.log,
                     "Not signing slashable block";
@@ -227,6 +297,8 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         attestation: &mut Attestation<E>,
         current_epoch: Epoch,
     ) -> Option<()> {
+        let _timer = metrics::start_timer_vec(&metrics::SIGNING_TIMES, &[metrics::ATTESTATION]);
+
         // Make sure the target epoch is not higher than the current epoch to avoid potential attacks.
         if attestation.data.target.epoch > current_epoch {
             return None;
@@ -277,9 +349,17 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
                     self.log,
                     "Skipping signing of previously signed attestation"
                 );
+                metrics::inc_counter_vec(
+                    &metrics::SLASHING_PROTECTION_REFUSALS,
+                    &[metrics::ATTESTATION, metrics::SAME_DATA],
+                );
                 None
             }
             Err(NotSafe::UnregisteredValidator(pk)) => {
+                metrics::inc_counter_vec(
+                    &metrics::SLASHING_PROTECTION_REFUSALS,
+                    &[metrics::ATTESTATION, metrics::UNREGISTERED],
+                );
                 warn!(
                     self.log,
                     "Not signing attestation for unregistered validator";
@@ -289,6 +369,10 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
                 None
             }
             Err(e) => {
+                metrics::inc_counter_vec(
+                    &metrics::SLASHING_PROTECTION_REFUSALS,
+                    &[metrics::ATTESTATION, metrics::SLASHABLE],
+                );
                 crit!(
                     self.log,
                     "Not signing slashable attestation";
@@ -311,6 +395,8 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         aggregate: Attestation<E>,
         selection_proof: SelectionProof,
     ) -> Option<SignedAggregateAndProof<E>> {
+        let _timer = metrics::start_timer_vec(&metrics::SIGNING_TIMES, &[metrics::AGGREGATE]);
+
         let validators = self.validators.read();
         let voting_keypair = &validators.voting_keypair(validator_pubkey)?;
 
@@ -332,6 +418,8 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         validator_pubkey: &PublicKey,
         slot: Slot,
     ) -> Option<SelectionProof> {
+        let _timer = metrics::start_timer_vec(&metrics::SIGNING_TIMES, &[metrics::SELECTION_PROOF]);
+
         let validators = self.validators.read();
         let voting_keypair = &validators.voting_keypair(validator_pubkey)?;
 